@@ -0,0 +1,236 @@
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Notify;
+
+use crate::thermal;
+
+/// How often the signals are sampled during a parallel batch
+pub const SAMPLE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Keyboard or mouse input within this window means someone is working
+const ACTIVE_WITHIN: Duration = Duration::from_secs(60);
+
+/// macOS memory pressure level (`kern.memorystatus_vm_pressure_level`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemoryPressure {
+    Normal,
+    Warning,
+    Critical,
+}
+
+/// What the machine is doing right now
+#[derive(Debug, Clone, PartialEq)]
+pub struct Signals {
+    pub load: Option<f64>,
+    pub cpus: usize,
+    pub memory: MemoryPressure,
+    pub on_battery: bool,
+    /// Time since the last keyboard or mouse input
+    pub user_idle: Option<Duration>,
+}
+
+impl Signals {
+    /// Sample every signal (runs a few short commands)
+    pub fn sample() -> Self {
+        Self {
+            load: thermal::load_average(),
+            cpus: std::thread::available_parallelism().map_or(1, |n| n.get()),
+            memory: memory_pressure(),
+            on_battery: on_battery(),
+            user_idle: user_idle(),
+        }
+    }
+
+    /// Forget input from more than `running` ago, so typing the command that
+    /// started the run does not read as someone working
+    pub fn since(mut self, running: Duration) -> Self {
+        self.user_idle = self.user_idle.filter(|&idle| idle < running);
+        self
+    }
+
+    /// Concurrency to use out of `max`, with the reasons for holding back.
+    /// Each signal halves it; critical memory pressure drops to one task.
+    pub fn target(&self, max: usize) -> (usize, Vec<&'static str>) {
+        let mut target = max;
+        let mut reasons = Vec::new();
+        if self.on_battery {
+            target /= 2;
+            reasons.push("on battery");
+        }
+        if self.user_idle.is_some_and(|idle| idle < ACTIVE_WITHIN) {
+            target /= 2;
+            reasons.push("user active");
+        }
+        if self.load.is_some_and(|load| load > self.cpus as f64) {
+            target /= 2;
+            reasons.push("high load");
+        }
+        match self.memory {
+            MemoryPressure::Normal => {}
+            MemoryPressure::Warning => {
+                target /= 2;
+                reasons.push("memory pressure");
+            }
+            MemoryPressure::Critical => {
+                target = 1;
+                reasons.push("critical memory pressure");
+            }
+        }
+        (target.clamp(1, max.max(1)), reasons)
+    }
+}
+
+fn command_stdout(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn memory_pressure() -> MemoryPressure {
+    match command_stdout("sysctl", &["-n", "kern.memorystatus_vm_pressure_level"])
+        .and_then(|level| level.trim().parse::<u32>().ok())
+    {
+        Some(2) => MemoryPressure::Warning,
+        Some(4) => MemoryPressure::Critical,
+        _ => MemoryPressure::Normal,
+    }
+}
+
+fn on_battery() -> bool {
+    command_stdout("pmset", &["-g", "batt"]).is_some_and(|batt| {
+        batt.lines()
+            .next()
+            .unwrap_or_default()
+            .contains("Battery Power")
+    })
+}
+
+fn user_idle() -> Option<Duration> {
+    command_stdout("ioreg", &["-c", "IOHIDSystem", "-d", "4"])
+        .and_then(|output| parse_hid_idle(&output))
+}
+
+/// `HIDIdleTime` from `ioreg -c IOHIDSystem`, reported in nanoseconds
+fn parse_hid_idle(output: &str) -> Option<Duration> {
+    output
+        .lines()
+        .find(|line| line.contains("\"HIDIdleTime\""))
+        .and_then(|line| line.rsplit('=').next())
+        .and_then(|nanos| nanos.trim().parse::<u64>().ok())
+        .map(Duration::from_nanos)
+}
+
+/// Concurrency gate whose limit can change while tasks are running.
+/// Lowering it never interrupts tasks; new ones wait until enough finished.
+pub struct AdaptiveLimit {
+    /// Running tasks and the current limit
+    state: Mutex<(usize, usize)>,
+    changed: Notify,
+}
+
+/// A running task's place in an [`AdaptiveLimit`], released on drop
+pub struct Slot(Arc<AdaptiveLimit>);
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        self.0.lock().0 -= 1;
+        self.0.changed.notify_waiters();
+    }
+}
+
+impl AdaptiveLimit {
+    pub fn new(limit: usize) -> Arc<Self> {
+        Arc::new(Self {
+            state: Mutex::new((0, limit.max(1))),
+            changed: Notify::new(),
+        })
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, (usize, usize)> {
+        self.state.lock().unwrap_or_else(|p| p.into_inner())
+    }
+
+    pub fn limit(&self) -> usize {
+        self.lock().1
+    }
+
+    pub fn set(&self, limit: usize) {
+        self.lock().1 = limit.max(1);
+        self.changed.notify_waiters();
+    }
+
+    /// Wait until fewer tasks than the limit are running
+    pub async fn acquire(self: &Arc<Self>) -> Slot {
+        loop {
+            // Created before checking so a release in between is not missed
+            let changed = self.changed.notified();
+            {
+                let mut state = self.lock();
+                if state.0 < state.1 {
+                    state.0 += 1;
+                    return Slot(Arc::clone(self));
+                }
+            }
+            changed.await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_target_scales_down() {
+        let idle = Signals {
+            load: Some(2.0),
+            cpus: 8,
+            memory: MemoryPressure::Normal,
+            on_battery: false,
+            user_idle: Some(Duration::from_secs(600)),
+        };
+        assert_eq!(idle.target(8), (8, vec![]));
+
+        let busy = Signals {
+            on_battery: true,
+            user_idle: Some(Duration::from_secs(5)),
+            ..idle.clone()
+        };
+        assert_eq!(busy.target(8), (2, vec!["on battery", "user active"]));
+        assert_eq!(busy.target(2).0, 1);
+        let starting = busy.clone().since(Duration::from_secs(1));
+        assert_eq!(starting.target(8), (4, vec!["on battery"]));
+        assert_eq!(busy.clone().since(Duration::from_secs(30)), busy);
+
+        let swapping = Signals {
+            memory: MemoryPressure::Critical,
+            ..idle
+        };
+        assert_eq!(swapping.target(8).0, 1);
+
+        let ioreg = r#"    | |   "HIDIdleTime" = 2500000000"#;
+        assert_eq!(parse_hid_idle(ioreg), Some(Duration::from_millis(2500)));
+    }
+
+    #[tokio::test]
+    async fn test_adaptive_limit_gates_new_tasks() {
+        let limit = AdaptiveLimit::new(2);
+        let first = limit.acquire().await;
+        let _second = limit.acquire().await;
+        limit.set(1);
+
+        let waiting = tokio::spawn({
+            let limit = Arc::clone(&limit);
+            async move { limit.acquire().await }
+        });
+        drop(first);
+        tokio::task::yield_now().await;
+        assert!(!waiting.is_finished());
+
+        limit.set(3);
+        assert!(waiting.await.is_ok());
+    }
+}
@@ -0,0 +1,108 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::Stdio;
+use tokio::process::Command;
+
+use crate::config::TaskConfig;
+
+/// What to do with `mas` tasks when no Apple ID is signed in to the App Store
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SignInPolicy {
+    /// Offer to open the App Store to sign in, then check again
+    #[default]
+    Prompt,
+    /// Skip the App Store tasks with a message
+    Skip,
+    /// Skip the App Store tasks and send a desktop notification
+    Notify,
+}
+
+/// App Store sign-in state as reported by `mas account`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignIn {
+    SignedIn(String),
+    SignedOut,
+    /// `mas` cannot tell (e.g. `mas account` is unsupported on recent macOS)
+    Unknown,
+}
+
+/// Whether the task runs `mas`, directly or through sudo
+pub fn is_mas_task(task: &TaskConfig) -> bool {
+    task.effective_command()
+        .iter()
+        .find(|arg| *arg != "sudo")
+        .and_then(|program| Path::new(program).file_name())
+        .is_some_and(|name| name == "mas")
+}
+
+/// Ask `mas` for the signed-in Apple ID
+pub async fn check() -> SignIn {
+    let output = Command::new("mas")
+        .arg("account")
+        .stdin(Stdio::null())
+        .output()
+        .await;
+    match output {
+        Ok(output) => parse_account(
+            output.status.success(),
+            &String::from_utf8_lossy(&output.stdout),
+            &String::from_utf8_lossy(&output.stderr),
+        ),
+        Err(_) => SignIn::Unknown,
+    }
+}
+
+fn parse_account(success: bool, stdout: &str, stderr: &str) -> SignIn {
+    let account = stdout.trim();
+    if success && !account.is_empty() {
+        return SignIn::SignedIn(account.to_string());
+    }
+    let message = format!("{}{}", stdout, stderr).to_lowercase();
+    if message.contains("not signed in") {
+        SignIn::SignedOut
+    } else {
+        SignIn::Unknown
+    }
+}
+
+/// Open the App Store so the user can sign in
+pub fn open_app_store() -> std::io::Result<()> {
+    std::process::Command::new("open")
+        .args(["-a", "App Store"])
+        .status()
+        .map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mas_sign_in_detection() {
+        assert_eq!(
+            parse_account(true, "user@example.com\n", ""),
+            SignIn::SignedIn("user@example.com".to_string())
+        );
+        assert_eq!(
+            parse_account(false, "", "Error: Not signed in\n"),
+            SignIn::SignedOut
+        );
+        assert_eq!(
+            parse_account(
+                false,
+                "",
+                "Error: This command is not supported on this macOS version due to changes in macOS"
+            ),
+            SignIn::Unknown
+        );
+
+        let mut task = TaskConfig {
+            command: vec!["/opt/homebrew/bin/mas".to_string(), "upgrade".to_string()],
+            ..TaskConfig::default()
+        };
+        assert!(is_mas_task(&task));
+        task.command = vec!["brew".to_string(), "upgrade".to_string()];
+        assert!(!is_mas_task(&task));
+    }
+}
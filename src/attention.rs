@@ -0,0 +1,65 @@
+use std::io::{IsTerminal, Write};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// Ring the terminal bell and ask the terminal to bounce its Dock icon.
+///
+/// Terminal.app bounces on the bell when it is in the background; iTerm2
+/// additionally honors its `RequestAttention` escape sequence.
+pub fn ring() {
+    let mut stderr = std::io::stderr();
+    if !stderr.is_terminal() {
+        return;
+    }
+    let _ = write!(stderr, "\x07\x1b]1337;RequestAttention=yes\x07");
+    let _ = stderr.flush();
+}
+
+/// Escalating request for the user's attention while Tide waits for input.
+///
+/// Rings the bell and runs `notify` immediately, then repeats both every
+/// `repeat` until the guard is dropped.
+pub struct AttentionGuard {
+    stop: Option<Sender<()>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl AttentionGuard {
+    pub fn request<F>(repeat: Option<Duration>, notify: F) -> Self
+    where
+        F: Fn() + Send + 'static,
+    {
+        ring();
+        notify();
+
+        let Some(interval) = repeat.filter(|interval| !interval.is_zero()) else {
+            return Self {
+                stop: None,
+                handle: None,
+            };
+        };
+
+        let (stop, stopped) = mpsc::channel::<()>();
+        let handle = std::thread::spawn(move || {
+            while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(interval) {
+                ring();
+                notify();
+            }
+        });
+        Self {
+            stop: Some(stop),
+            handle: Some(handle),
+        }
+    }
+}
+
+impl Drop for AttentionGuard {
+    fn drop(&mut self) {
+        // Dropping the sender disconnects the channel and ends the loop
+        drop(self.stop.take());
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
@@ -0,0 +1,249 @@
+use anyhow::{Context, Result};
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::error::TideError;
+use crate::executor::TaskStatus;
+use crate::import;
+
+/// `prev` of the first entry in a log
+const GENESIS: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// What an audit entry attests to; its hash covers every field
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct AuditRecord {
+    pub seq: u64,
+    /// RFC 3339 time the task finished
+    pub timestamp: String,
+    /// Login that invoked tide (the sudo caller when tide itself runs as root)
+    pub user: String,
+    pub group: String,
+    pub task: String,
+    pub command: String,
+    /// Whether the command ran with elevated privileges
+    pub sudo: bool,
+    pub exit_code: Option<i32>,
+    pub status: TaskStatus,
+    /// Hash of the previous entry
+    pub prev: String,
+}
+
+/// One line of the audit log
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AuditEntry {
+    #[serde(flatten)]
+    pub record: AuditRecord,
+    pub hash: String,
+}
+
+impl AuditRecord {
+    fn hash(&self) -> String {
+        import::sha256_hex(&serde_json::to_string(self).unwrap_or_default())
+    }
+}
+
+/// Position of the chain the next entry links to
+struct Chain {
+    file: File,
+    seq: u64,
+    last_hash: String,
+}
+
+/// Append-only log of executed commands where every entry is hash-chained to
+/// the one before it, so edited, reordered or deleted entries are detected
+pub struct AuditLog {
+    path: PathBuf,
+    chain: Mutex<Chain>,
+}
+
+impl AuditLog {
+    /// Open the log, continuing the chain of the entries already in it
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).with_context(|| {
+                format!("Failed to create audit directory {}", parent.display())
+            })?;
+        }
+
+        let (seq, last_hash) = match read_entries(&path)?.pop() {
+            Some(entry) => (entry.record.seq + 1, entry.hash),
+            None => (0, GENESIS.to_string()),
+        };
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("Failed to open audit log {}", path.display()))?;
+
+        Ok(Self {
+            path,
+            chain: Mutex::new(Chain {
+                file,
+                seq,
+                last_hash,
+            }),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append an entry for a finished command
+    pub fn append(
+        &self,
+        group: &str,
+        task: &str,
+        command: &str,
+        sudo: bool,
+        exit_code: Option<i32>,
+        status: TaskStatus,
+    ) -> Result<()> {
+        let mut chain = self
+            .chain
+            .lock()
+            .map_err(|_| anyhow::anyhow!("Failed to lock audit log"))?;
+        let record = AuditRecord {
+            seq: chain.seq,
+            timestamp: Local::now().to_rfc3339(),
+            user: current_user(),
+            group: group.to_string(),
+            task: task.to_string(),
+            command: command.to_string(),
+            sudo,
+            exit_code,
+            status,
+            prev: chain.last_hash.clone(),
+        };
+        let entry = AuditEntry {
+            hash: record.hash(),
+            record,
+        };
+        writeln!(chain.file, "{}", serde_json::to_string(&entry)?)
+            .with_context(|| format!("Failed to write audit log {}", self.path.display()))?;
+        chain.file.sync_data()?;
+        chain.seq += 1;
+        chain.last_hash = entry.hash;
+        Ok(())
+    }
+}
+
+fn current_user() -> String {
+    ["SUDO_USER", "USER"]
+        .iter()
+        .find_map(|var| std::env::var(var).ok().filter(|user| !user.is_empty()))
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+fn read_entries(path: &Path) -> Result<Vec<AuditEntry>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read audit log {}", path.display()))?;
+    contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            serde_json::from_str(line).map_err(|err| {
+                TideError::Config(format!(
+                    "Audit log line {} is malformed: {}",
+                    index + 1,
+                    err
+                ))
+                .into()
+            })
+        })
+        .collect()
+}
+
+/// Check every link of the chain, returning the number of entries
+pub fn verify(path: &Path) -> Result<usize> {
+    let entries = read_entries(path)?;
+    let mut prev = GENESIS.to_string();
+    for (index, entry) in entries.iter().enumerate() {
+        let line = index + 1;
+        if entry.record.seq != index as u64 {
+            return Err(TideError::Config(format!(
+                "Audit log line {}: expected entry #{}, found #{} (entries removed or reordered)",
+                line, index, entry.record.seq
+            ))
+            .into());
+        }
+        if entry.record.prev != prev {
+            return Err(TideError::Config(format!(
+                "Audit log line {}: does not link to the previous entry",
+                line
+            ))
+            .into());
+        }
+        if entry.record.hash() != entry.hash {
+            return Err(TideError::Config(format!(
+                "Audit log line {}: content does not match its hash (entry modified)",
+                line
+            ))
+            .into());
+        }
+        prev = entry.hash.clone();
+    }
+    Ok(entries.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_audit_chain_detects_tampering() {
+        let path = std::env::temp_dir().join(format!("tide-audit-{}.jsonl", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let log = AuditLog::open(&path).unwrap();
+        log.append(
+            "Homebrew",
+            "Update",
+            "brew update",
+            false,
+            Some(0),
+            TaskStatus::Success,
+        )
+        .unwrap();
+        drop(log);
+        // Reopening continues the chain
+        let log = AuditLog::open(&path).unwrap();
+        log.append(
+            "System",
+            "Flush DNS",
+            "sudo dscacheutil -flushcache",
+            true,
+            Some(1),
+            TaskStatus::Failed,
+        )
+        .unwrap();
+        assert_eq!(verify(&path).unwrap(), 2);
+
+        let contents = fs::read_to_string(&path).unwrap();
+        fs::write(
+            &path,
+            contents.replace("\"exit_code\":1", "\"exit_code\":0"),
+        )
+        .unwrap();
+        assert!(verify(&path).unwrap_err().to_string().contains("line 2"));
+
+        let first_removed: String = contents
+            .lines()
+            .skip(1)
+            .map(|l| format!("{}\n", l))
+            .collect();
+        fs::write(&path, first_removed).unwrap();
+        assert!(verify(&path).is_err());
+
+        fs::remove_file(&path).unwrap();
+    }
+}
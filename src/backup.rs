@@ -0,0 +1,148 @@
+use anyhow::{Context, Result};
+use chrono::Local;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::TideError;
+use crate::managed;
+
+/// Number of backups kept per config file; older ones are removed
+pub const RETENTION: usize = 10;
+
+/// Directory holding the backups of a config file: `<config dir>/backups`
+pub fn backup_dir(config_path: &Path) -> PathBuf {
+    config_path
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join("backups")
+}
+
+fn extension(config_path: &Path) -> String {
+    config_path
+        .extension()
+        .map_or_else(|| "toml".to_string(), |s| s.to_string_lossy().into_owned())
+}
+
+fn stem(config_path: &Path) -> String {
+    config_path.file_stem().map_or_else(
+        || "config".to_string(),
+        |s| s.to_string_lossy().into_owned(),
+    )
+}
+
+/// Copy the config into a timestamped backup and prune old backups.
+///
+/// Returns `None` when there is no config file to back up.
+pub fn create(config_path: &Path) -> Result<Option<PathBuf>> {
+    if !config_path.exists() {
+        return Ok(None);
+    }
+
+    let dir = backup_dir(config_path);
+    fs::create_dir_all(&dir)
+        .with_context(|| format!("Failed to create backup directory {}", dir.display()))?;
+
+    let timestamp = Local::now().format("%Y%m%d-%H%M%S-%3f");
+    let backup = dir.join(format!(
+        "{}-{}.{}",
+        stem(config_path),
+        timestamp,
+        extension(config_path)
+    ));
+    fs::copy(config_path, &backup)
+        .with_context(|| format!("Failed to back up config to {}", backup.display()))?;
+
+    for old in list(config_path)?.into_iter().skip(RETENTION) {
+        let _ = fs::remove_file(old);
+    }
+    Ok(Some(backup))
+}
+
+/// Backups of the config file, newest first
+pub fn list(config_path: &Path) -> Result<Vec<PathBuf>> {
+    let dir = backup_dir(config_path);
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let prefix = format!("{}-", stem(config_path));
+    let suffix = format!(".{}", extension(config_path));
+    let mut backups: Vec<PathBuf> = fs::read_dir(&dir)
+        .with_context(|| format!("Failed to read backup directory {}", dir.display()))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.file_name()
+                .map(|name| name.to_string_lossy())
+                .is_some_and(|name| name.starts_with(&prefix) && name.ends_with(&suffix))
+        })
+        .collect();
+    // Timestamps sort lexicographically
+    backups.sort();
+    backups.reverse();
+    Ok(backups)
+}
+
+/// Replace the config with a backup (the newest one if none is given).
+///
+/// The current config is backed up first, so a restore can be undone.
+pub fn restore(config_path: &Path, backup: Option<&Path>) -> Result<PathBuf> {
+    let backup = match backup {
+        Some(path) => path.to_path_buf(),
+        None => list(config_path)?.into_iter().next().ok_or_else(|| {
+            TideError::Config(format!(
+                "No backups found in {}",
+                backup_dir(config_path).display()
+            ))
+        })?,
+    };
+    if !backup.exists() {
+        return Err(TideError::Config(format!("Backup not found: {}", backup.display())).into());
+    }
+
+    managed::ensure_writable(config_path)?;
+
+    // Read first: backing up the current config may prune the oldest backup
+    let contents =
+        fs::read(&backup).with_context(|| format!("Failed to read backup {}", backup.display()))?;
+    create(config_path)?;
+    fs::write(config_path, contents)
+        .with_context(|| format!("Failed to restore {}", backup.display()))?;
+    Ok(backup)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backup_retention_and_restore() {
+        let dir = std::env::temp_dir().join(format!("tide-backup-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let config = dir.join("config.toml");
+
+        assert_eq!(create(&config).unwrap(), None);
+
+        fs::write(&config, "first").unwrap();
+        let first = create(&config).unwrap().unwrap();
+        for _ in 0..RETENTION + 2 {
+            std::thread::sleep(std::time::Duration::from_millis(2));
+            create(&config).unwrap();
+        }
+        let backups = list(&config).unwrap();
+        assert_eq!(backups.len(), RETENTION);
+        assert!(!backups.contains(&first));
+
+        fs::write(&config, "second").unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        let restored = restore(&config, Some(&backups[0])).unwrap();
+        assert_eq!(restored, backups[0]);
+        assert_eq!(fs::read_to_string(&config).unwrap(), "first");
+
+        // The overwritten config was backed up before restoring
+        let newest = list(&config).unwrap().remove(0);
+        assert_eq!(fs::read_to_string(newest).unwrap(), "second");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,240 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::process::Command;
+
+use crate::executor::TaskStatus;
+use crate::history::RunRecord;
+use crate::palette::Paint;
+
+/// A task is flagged when its latest run is this much slower than the median
+pub const REGRESSION_FACTOR: f64 = 1.5;
+/// ...and at least this many seconds slower, so short tasks do not flap
+pub const REGRESSION_MIN_SECS: f64 = 5.0;
+
+/// Duration statistics of one task across the compared runs
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaskBench {
+    pub group: String,
+    pub name: String,
+    pub samples: usize,
+    pub min: f64,
+    pub median: f64,
+    pub max: f64,
+    pub latest: f64,
+    pub regression: bool,
+}
+
+/// Run Tide unattended `runs` times in child processes so each run is
+/// recorded in the history like a regular invocation
+pub fn invoke_runs(
+    runs: usize,
+    config_path: &Path,
+    groups: Option<&[String]>,
+    skip_groups: Option<&[String]>,
+    parallel: usize,
+) -> Result<()> {
+    let tide = std::env::current_exe().context("Could not locate the tide binary")?;
+    for run in 1..=runs {
+        println!("{}", format!("▶ Bench run {}/{}", run, runs).bright_blue());
+
+        let mut command = Command::new(&tide);
+        command
+            .args(["--force", "--quiet", "--parallel"])
+            .arg(parallel.to_string())
+            .arg("--config")
+            .arg(config_path);
+        if let Some(groups) = groups {
+            command.arg("--groups").arg(groups.join(","));
+        }
+        if let Some(skip) = skip_groups {
+            command.arg("--skip-groups").arg(skip.join(","));
+        }
+
+        let status = command.status().context("Failed to start bench run")?;
+        if !status.success() {
+            eprintln!(
+                "{}",
+                format!("⚠️  Bench run {} exited with {}", run, status).warning()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Compute per-task statistics over successful executions, oldest run first
+pub fn analyze(runs: &[RunRecord]) -> Vec<TaskBench> {
+    let mut durations: BTreeMap<(String, String), Vec<f64>> = BTreeMap::new();
+    for run in runs {
+        for task in &run.tasks {
+            if task.status == TaskStatus::Success {
+                durations
+                    .entry((task.group.clone(), task.name.clone()))
+                    .or_default()
+                    .push(task.duration_secs);
+            }
+        }
+    }
+
+    durations
+        .into_iter()
+        .map(|((group, name), samples)| {
+            let latest = *samples.last().unwrap_or(&0.0);
+            let earlier = &samples[..samples.len() - 1];
+            let regression = !earlier.is_empty() && {
+                let baseline = median(earlier);
+                latest > baseline * REGRESSION_FACTOR && latest - baseline >= REGRESSION_MIN_SECS
+            };
+
+            let mut sorted = samples.clone();
+            sorted.sort_by(f64::total_cmp);
+            TaskBench {
+                group,
+                name,
+                samples: sorted.len(),
+                min: sorted[0],
+                median: median(&sorted),
+                max: sorted[sorted.len() - 1],
+                latest,
+                regression,
+            }
+        })
+        .collect()
+}
+
+/// Median of the values, which must not be empty
+pub fn median(values: &[f64]) -> f64 {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let mid = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        (sorted[mid - 1] + sorted[mid]) / 2.0
+    } else {
+        sorted[mid]
+    }
+}
+
+/// Print the benchmark table
+pub fn display(runs: &[RunRecord], benches: &[TaskBench]) {
+    println!("{}", "⏱️  Tide Bench".bright_blue().bold());
+    println!("{}", "═".repeat(60).bright_blue());
+
+    if runs.is_empty() {
+        println!("{}", "No runs recorded yet.".dimmed());
+        return;
+    }
+
+    let totals: Vec<f64> = runs.iter().map(|run| run.duration_secs).collect();
+    let min = totals.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = totals.iter().copied().fold(0.0, f64::max);
+    println!(
+        "Compared {} run(s): total min {:.1}s · median {:.1}s · max {:.1}s\n",
+        runs.len(),
+        min,
+        median(&totals),
+        max
+    );
+
+    println!(
+        "{:<32} {:>8} {:>8} {:>8} {:>8}",
+        "Task".bold(),
+        "min",
+        "median",
+        "max",
+        "latest"
+    );
+    for bench in benches {
+        let label = format!("{} › {}", bench.group, bench.name);
+        let latest = format!("{:.1}s", bench.latest);
+        println!(
+            "{:<32} {:>7.1}s {:>7.1}s {:>7.1}s {:>8}{}",
+            label,
+            bench.min,
+            bench.median,
+            bench.max,
+            if bench.regression {
+                latest.failure().bold()
+            } else {
+                latest.normal()
+            },
+            if bench.regression {
+                " ⚠️ regression".failure().to_string()
+            } else {
+                String::new()
+            }
+        );
+    }
+
+    let regressions = benches.iter().filter(|b| b.regression).count();
+    if regressions > 0 {
+        println!(
+            "\n{}",
+            format!(
+                "{} task(s) ran at least {:.0}% slower than their median",
+                regressions,
+                (REGRESSION_FACTOR - 1.0) * 100.0
+            )
+            .warning()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::TaskRecord;
+    use chrono::Local;
+
+    fn run(durations: &[(&str, f64, TaskStatus)]) -> RunRecord {
+        RunRecord {
+            started_at: Local::now(),
+            resumed: false,
+            duration_secs: durations.iter().map(|(_, d, _)| d).sum(),
+            tasks: durations
+                .iter()
+                .map(|(name, duration, status)| TaskRecord {
+                    group: "Homebrew".to_string(),
+                    name: name.to_string(),
+                    status: *status,
+                    duration_secs: *duration,
+                    reason: None,
+                    started_at: None,
+                    output_hash: None,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_analyze_flags_regressions() {
+        let runs = vec![
+            run(&[
+                ("update", 10.0, TaskStatus::Success),
+                ("upgrade", 60.0, TaskStatus::Success),
+            ]),
+            run(&[
+                ("update", 12.0, TaskStatus::Success),
+                ("upgrade", 1.0, TaskStatus::Failed),
+            ]),
+            run(&[
+                ("update", 30.0, TaskStatus::Success),
+                ("upgrade", 70.0, TaskStatus::Success),
+            ]),
+        ];
+
+        let benches = analyze(&runs);
+        assert_eq!(benches.len(), 2);
+
+        let update = &benches[0];
+        assert_eq!(update.name, "update");
+        assert_eq!(update.samples, 3);
+        assert_eq!((update.min, update.median, update.max), (10.0, 12.0, 30.0));
+        assert!(update.regression);
+
+        let upgrade = &benches[1];
+        assert_eq!(upgrade.samples, 2);
+        assert_eq!(upgrade.median, 65.0);
+        assert!(!upgrade.regression);
+    }
+}
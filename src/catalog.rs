@@ -0,0 +1,165 @@
+use anyhow::{Context, Result};
+
+use crate::config::TaskConfig;
+
+/// A maintained task definition that `tide tasks add` copies into the config
+#[derive(Debug)]
+pub struct CatalogEntry {
+    pub id: &'static str,
+    /// Group the task is added to unless `--group` says otherwise
+    pub group: &'static str,
+    pub summary: &'static str,
+    /// Body of the `[[groups.tasks]]` table
+    pub toml: &'static str,
+}
+
+impl CatalogEntry {
+    pub fn task(&self) -> Result<TaskConfig> {
+        toml::from_str(self.toml).with_context(|| format!("Invalid catalog entry '{}'", self.id))
+    }
+}
+
+/// The curated task library, sorted by id
+pub const CATALOG: &[CatalogEntry] = &[
+    CatalogEntry {
+        id: "brew-cleanup",
+        group: "Homebrew",
+        summary: "Remove old Homebrew versions and the download cache",
+        toml: r#"name = "Cleanup"
+icon = "🧹"
+description = "Remove old versions and clear the download cache"
+command = ["brew", "cleanup", "--prune=all"]
+check_command = "brew"
+timeout = 600
+"#,
+    },
+    CatalogEntry {
+        id: "brew-services",
+        group: "Homebrew",
+        summary: "Restart brew services still running a pre-upgrade binary",
+        toml: r#"name = "Restart Services"
+icon = "🔄"
+description = "Restart brew services still running a pre-upgrade binary"
+type = "brew-services-restart"
+required = false
+"#,
+    },
+    CatalogEntry {
+        id: "dns-flush",
+        group: "System Cleanup",
+        summary: "Flush the DNS cache",
+        toml: r#"name = "Flush DNS Cache"
+icon = "🌐"
+description = "Flush the directory service and mDNSResponder caches"
+command = ["sh", "-c", "dscacheutil -flushcache && killall -HUP mDNSResponder"]
+sudo = true
+check_command = "dscacheutil"
+timeout = 60
+"#,
+    },
+    CatalogEntry {
+        id: "docker-prune",
+        group: "Docker",
+        summary: "Prune unused Docker data and report reclaimed space",
+        toml: r#"name = "Docker Cleanup"
+icon = "🐳"
+description = "Prune unused Docker data and report reclaimed disk space"
+type = "docker-maintenance"
+required = false
+timeout = 600
+"#,
+    },
+    CatalogEntry {
+        id: "node-update",
+        group: "Programming Languages",
+        summary: "Update Node versions managed by nvm, fnm or Volta",
+        toml: r#"name = "Node.js"
+icon = "🟢"
+description = "Update Node versions managed by nvm, fnm or Volta"
+type = "node-update"
+required = false
+timeout = 1200
+"#,
+    },
+    CatalogEntry {
+        id: "python-maintenance",
+        group: "Programming Languages",
+        summary: "Upgrade pipx apps and clean the uv/Poetry caches",
+        toml: r#"name = "Python Tools"
+icon = "🐍"
+description = "Upgrade pipx apps and clean the uv/Poetry caches"
+type = "python-maintenance"
+required = false
+timeout = 600
+"#,
+    },
+    CatalogEntry {
+        id: "rust-toolchain",
+        group: "Programming Languages",
+        summary: "Update the Rust toolchains with rustup",
+        toml: r#"name = "Rust Toolchain"
+icon = "🦀"
+description = "Update installed Rust toolchains"
+command = ["rustup", "update"]
+check_command = "rustup"
+network = true
+timeout = 900
+"#,
+    },
+    CatalogEntry {
+        id: "spotlight-reindex",
+        group: "System Cleanup",
+        summary: "Rebuild the Spotlight index of the startup disk",
+        toml: r#"name = "Rebuild Spotlight Index"
+icon = "🔦"
+description = "Erase and rebuild the Spotlight index of /"
+command = ["mdutil", "-E", "/"]
+sudo = true
+check_command = "mdutil"
+required = false
+timeout = 120
+"#,
+    },
+    CatalogEntry {
+        id: "xcode-update",
+        group: "Xcode",
+        summary: "Update the Command Line Tools and finish Xcode setup",
+        toml: r#"name = "Xcode & Command Line Tools"
+icon = "⬆️"
+description = "Update the CLT, finish first-launch setup, accept the license"
+type = "xcode-update"
+sudo = true
+required = false
+timeout = 3600
+"#,
+    },
+];
+
+/// Look up a catalog entry by id
+pub fn find(id: &str) -> Option<&'static CatalogEntry> {
+    CATALOG.iter().find(|entry| entry.id == id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_catalog_entries_parse() {
+        for entry in CATALOG {
+            let task = entry.task().unwrap();
+            assert!(!task.name.is_empty(), "{} has no name", entry.id);
+            assert!(
+                task.kind.is_some() || !task.command.is_empty(),
+                "{} has no action",
+                entry.id
+            );
+        }
+        let ids: Vec<&str> = CATALOG.iter().map(|entry| entry.id).collect();
+        let mut sorted = ids.clone();
+        sorted.sort_unstable();
+        assert_eq!(ids, sorted);
+        assert!(find("dns-flush").is_some());
+        assert!(find("missing").is_none());
+    }
+}
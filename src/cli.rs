@@ -1,5 +1,10 @@
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use std::path::PathBuf;
+use tide_core::error::ExitCodePolicy;
+use tide_core::export::ExportFormat;
+use tide_core::format::ConfigFormat;
+use tide_core::report::OutputMode;
+use tide_core::status::CheckFormat;
 
 /// CLI Arguments for Tide
 #[derive(Parser, Debug)]
@@ -7,43 +12,333 @@ use std::path::PathBuf;
 #[command(about = "🌊 Tide - Refresh your system with the update wave", long_about = None)]
 #[command(version)]
 pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Commands>,
+
     /// Run in quiet mode (no banner, minimal output)
-    #[arg(short, long)]
+    #[arg(short, long, global = true)]
     pub quiet: bool,
 
-    /// Run in dry-run mode (show what would be executed)
-    #[arg(short = 'n', long)]
-    pub dry_run: bool,
-
     /// Run specific groups only (comma-separated)
-    #[arg(short, long, value_delimiter = ',')]
+    #[arg(short, long, value_delimiter = ',', global = true)]
     pub groups: Option<Vec<String>>,
 
     /// Skip specific groups (comma-separated)
-    #[arg(short = 'x', long, value_delimiter = ',')]
+    #[arg(short = 'x', long, value_delimiter = ',', global = true)]
     pub skip_groups: Option<Vec<String>>,
 
     /// Maximum parallel tasks (default: 4)
-    #[arg(short = 'j', long, default_value = "4")]
+    #[arg(short = 'j', long, default_value = "4", global = true)]
     pub parallel: usize,
 
     /// Config file path (default: ~/.config/tide/config.toml)
-    #[arg(short, long)]
+    #[arg(short, long, global = true)]
     pub config: Option<PathBuf>,
 
-    /// Generate default config and exit
-    #[arg(long)]
+    /// Apply the [profiles.<NAME>] section of the config
+    #[arg(long, value_name = "NAME", global = true)]
+    pub profile: Option<String>,
+
+    /// Same as `tide init`
+    #[arg(long, hide = true)]
     pub init: bool,
 
-    /// List all configured tasks and exit
-    #[arg(short, long)]
+    /// Format of the config written by `--init`
+    #[arg(long, value_enum, requires = "init", hide = true)]
+    pub format: Option<ConfigFormat>,
+
+    /// Same as `tide list`
+    #[arg(short, long, hide = true)]
     pub list: bool,
 
     /// Force run without confirmations
-    #[arg(short, long)]
+    #[arg(short, long, global = true)]
     pub force: bool,
 
     /// Enable verbose output
-    #[arg(short, long)]
+    #[arg(short, long, global = true)]
     pub verbose: bool,
+
+    /// Exit status scheme, overriding `exit_code_policy` in the config
+    #[arg(long, value_enum, global = true)]
+    pub exit_code_policy: Option<ExitCodePolicy>,
+
+    /// Options of `tide run`, also accepted without a subcommand
+    #[command(flatten)]
+    pub run: RunArgs,
+
+    /// Run unattended for Shortcuts.app: print a one-line status and write a JSON report to --json-fd
+    #[arg(long, global = true)]
+    pub shortcuts: bool,
+
+    /// File descriptor receiving the JSON report in --shortcuts mode
+    #[arg(long, default_value = "3", global = true)]
+    pub json_fd: i32,
+}
+
+impl Args {
+    /// Parse the command line, folding the options given to `tide run` into
+    /// [`Args::run`] so they behave like the top-level ones
+    pub fn parse_merged() -> Self {
+        let mut args = Self::parse();
+        if let Some(Commands::Run {
+            options, target, ..
+        }) = &mut args.command
+        {
+            let options = std::mem::take(options);
+            args.run.task = target.take().or(options.task).or(args.run.task.take());
+            args.run.dry_run |= options.dry_run;
+            args.run.skip_sudo |= options.skip_sudo;
+            args.run.interactive |= options.interactive;
+            args.run.resume |= options.resume;
+            args.run.wait |= options.wait;
+            args.run.params.extend(options.params);
+            args.run.record = options.record.or(args.run.record.take());
+            args.run.replay = options.replay.or(args.run.replay.take());
+            if options.output != OutputMode::default() {
+                args.run.output = options.output;
+            }
+        }
+        args
+    }
+}
+
+/// Options that only apply to running tasks
+#[derive(clap::Args, Debug, Default)]
+pub struct RunArgs {
+    /// Run in dry-run mode (show what would be executed)
+    #[arg(short = 'n', long)]
+    pub dry_run: bool,
+
+    /// Leave out every task that needs sudo for this run
+    #[arg(long)]
+    pub skip_sudo: bool,
+
+    /// Value for a task parameter, skipping its prompt (repeatable)
+    #[arg(long = "param", value_name = "NAME=VALUE")]
+    pub params: Vec<String>,
+
+    /// Save each task's outcome, output and exit code to this file
+    #[arg(long, value_name = "FILE")]
+    pub record: Option<PathBuf>,
+
+    /// Run only this task, given as <group>/<task>, even when it is disabled
+    #[arg(long, value_name = "GROUP/TASK")]
+    pub task: Option<String>,
+
+    /// Pick the tasks to run from a checklist, with the enabled ones checked
+    #[arg(short, long, conflicts_with = "task")]
+    pub interactive: bool,
+
+    /// Run only the tasks that failed or never started in the last run
+    #[arg(long, conflicts_with_all = ["task", "interactive", "replay"])]
+    pub resume: bool,
+
+    /// Wait for a tide run already in progress instead of refusing to start
+    #[arg(long)]
+    pub wait: bool,
+
+    /// Replay outcomes from a --record file instead of executing anything
+    #[arg(long, value_name = "FILE", conflicts_with = "dry_run")]
+    pub replay: Option<PathBuf>,
+
+    /// How to report the run: `json` prints only a JSON document with every
+    /// task's status, duration, exit code and output
+    #[arg(long, value_enum, default_value_t, conflicts_with = "shortcuts")]
+    pub output: OutputMode,
+}
+
+/// Tide subcommands
+#[derive(Subcommand, Debug)]
+pub enum Commands {
+    /// Print the computed execution order as sequential/parallel batches
+    Plan,
+    /// Generate the default config
+    Init {
+        /// File format (default: the extension of --config, otherwise TOML)
+        #[arg(long, value_enum)]
+        format: Option<ConfigFormat>,
+    },
+    /// Load the config and report problems without running anything
+    Validate,
+    /// Show the most recent runs from the history
+    History {
+        /// Number of runs to show
+        #[arg(long, default_value = "10")]
+        last: usize,
+    },
+    /// Show per-task durations, failure rates and the slowest tasks
+    Stats {
+        /// Number of recent runs from the history to analyze
+        #[arg(long, default_value = "20")]
+        last: usize,
+    },
+    /// Print the log of recent runs (`log_file`), optionally following it
+    Logs {
+        /// Only the most recent run
+        #[arg(long)]
+        last: bool,
+        /// Keep printing entries as they are written
+        #[arg(short = 'F', long)]
+        follow: bool,
+        /// Only the entries of this task (case-insensitive)
+        #[arg(long, value_name = "NAME")]
+        task: Option<String>,
+    },
+    /// List configured tasks
+    List {
+        /// Print groups and tasks as Raycast/Alfred script-filter JSON
+        #[arg(long)]
+        raycast: bool,
+    },
+    /// Run the configured tasks (the default when no subcommand is given)
+    Run {
+        /// Run only this task, given as <group>/<task>
+        #[arg(value_name = "GROUP/TASK")]
+        target: Option<String>,
+        #[command(flatten)]
+        options: RunArgs,
+        /// Run non-interactively and end with a one-line result for Raycast/Alfred toasts
+        #[arg(long)]
+        raycast_output: bool,
+    },
+    /// Compare task durations across runs and flag regressions
+    Bench {
+        /// Run the configured tasks this many times first, then compare those runs
+        #[arg(long)]
+        runs: Option<usize>,
+        /// Number of recent runs from the history to compare
+        #[arg(long, default_value = "5")]
+        last: usize,
+    },
+    /// Report pending updates and send one notification, installing nothing
+    Check,
+    /// Check the [[tests]] expectations of the config against a simulated machine
+    Test,
+    /// Manage the config file
+    Config {
+        #[command(subcommand)]
+        action: ConfigCommand,
+    },
+    /// Show the outcome of the last recorded run
+    Status {
+        /// Print SwiftBar/xbar menu bar plugin output
+        #[arg(long)]
+        xbar: bool,
+        /// Print a monitoring check line with perfdata and exit 0/1/2/3
+        #[arg(long, value_enum, conflicts_with = "xbar")]
+        check_format: Option<CheckFormat>,
+        /// Hours without a successful run before the check warns
+        #[arg(long, default_value = "48")]
+        warn_hours: f64,
+        /// Hours without a successful run before the check is critical
+        #[arg(long, default_value = "168")]
+        crit_hours: f64,
+    },
+    /// Explain why a task was skipped, failed or left out of a run
+    Why {
+        /// Task name (case-insensitive)
+        task: String,
+    },
+    /// Propose tasks from an existing file and append them to the config after review
+    Import {
+        #[command(subcommand)]
+        source: ImportSource,
+    },
+    /// Browse the built-in task catalog and add its tasks to the config
+    Tasks {
+        #[command(subcommand)]
+        action: TasksCommand,
+    },
+    /// Share a single group with its tasks as a standalone TOML file
+    Group {
+        #[command(subcommand)]
+        action: GroupCommand,
+    },
+    /// Inspect the hash-chained audit log
+    Audit {
+        #[command(subcommand)]
+        action: AuditCommand,
+    },
+    /// Convert the configured groups and tasks to another tool's format
+    Export {
+        /// Output format
+        #[arg(long, value_enum)]
+        format: ExportFormat,
+    },
+}
+
+/// `tide config` actions
+#[derive(Subcommand, Debug)]
+pub enum ConfigCommand {
+    /// List the timestamped config backups, newest first
+    Backups,
+    /// Restore a backup (the newest one when no file is given)
+    Restore {
+        /// Backup file to restore
+        backup: Option<PathBuf>,
+    },
+}
+
+/// `tide tasks` actions
+#[derive(Subcommand, Debug)]
+pub enum TasksCommand {
+    /// List the maintained task definitions that can be added
+    Catalog,
+    /// Append a catalog task to the config
+    Add {
+        /// Catalog id, e.g. brew-cleanup
+        id: String,
+        /// Group to add the task to (default: the entry's suggested group)
+        #[arg(long)]
+        group: Option<String>,
+    },
+}
+
+/// `tide group` actions
+#[derive(Subcommand, Debug)]
+pub enum GroupCommand {
+    /// Write a group and its tasks as a TOML document (stdout when no file is given)
+    Export {
+        /// Group name
+        name: String,
+        /// File to write
+        file: Option<PathBuf>,
+    },
+    /// Append the group(s) of an exported TOML document to the config
+    Import {
+        /// Exported group file
+        file: PathBuf,
+    },
+}
+
+/// `tide audit` actions
+#[derive(Subcommand, Debug)]
+pub enum AuditCommand {
+    /// Check that no entry was modified, removed or reordered
+    Verify {
+        /// Audit log to check (default: the `audit_log` setting)
+        file: Option<PathBuf>,
+    },
+}
+
+/// `tide import` sources
+#[derive(Subcommand, Debug)]
+pub enum ImportSource {
+    /// Split a maintenance shell script into tasks of a new group
+    Script {
+        /// Shell script to import
+        file: PathBuf,
+        /// Name of the new group (default: derived from the file name)
+        #[arg(long)]
+        group: Option<String>,
+    },
+    /// Fetch shared groups/tasks in TOML over HTTPS and merge them into the config
+    Url {
+        /// https:// URL of the TOML snippet (e.g. a raw gist)
+        url: String,
+        /// Expected SHA-256 of the snippet; the import is refused on mismatch
+        #[arg(long)]
+        sha256: Option<String>,
+    },
 }
@@ -1,17 +1,53 @@
-use crate::error::TideError;
+use crate::appstore::SignInPolicy;
+use crate::error::{ExitCodePolicy, TideError};
+use crate::format::ConfigFormat;
+use crate::include;
+use crate::logger::LogFormat;
+use crate::managed;
+use crate::notifications::NotificationsConfig;
+use crate::overlay;
+use crate::palette::ColorScheme;
+use crate::profile::{self, Profile};
+use crate::recipes::RecipeRunner;
+use crate::severity::FailureThreshold;
+use crate::validate;
+use crate::vars;
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
+use std::time::Duration;
 
 /// Main configuration structure
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Config {
+    /// Group files merged into this config, e.g. `["groups/*.toml"]`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<String>,
+    /// Values for `${<name>}` placeholders in task commands, working
+    /// directories and env values
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub vars: HashMap<String, String>,
     #[serde(default)]
     pub settings: Settings,
     #[serde(default)]
     pub groups: Vec<TaskGroup>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tests: Vec<ConfigTest>,
+    #[serde(default, skip_serializing_if = "PostRun::is_empty")]
+    pub post_run: PostRun,
+    #[serde(default, skip_serializing_if = "NotificationsConfig::is_empty")]
+    pub notifications: NotificationsConfig,
+    /// Variations selected with `--profile <name>`
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub profiles: HashMap<String, Profile>,
+    /// Variations applied on the machine with the host name of their key
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub host: HashMap<String, Profile>,
+    /// Changes of the user config that the managed config overrode
+    #[serde(skip)]
+    pub notices: Vec<String>,
 }
 
 /// Global settings
@@ -23,12 +59,25 @@ pub struct Settings {
     pub show_weather: bool,
     #[serde(default = "default_true")]
     pub show_system_info: bool,
+    /// Dashboard blocks shown after the run; replaces `show_weather` and
+    /// `show_system_info` when set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub widgets: Option<Vec<WidgetSpec>>,
+    /// Uptime Kuma status page summarized by the `uptime-kuma` widget
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uptime_kuma_url: Option<String>,
     #[serde(default = "default_false")]
     pub show_progress: bool,
+    #[serde(default = "default_true")]
+    pub show_timeline: bool,
     #[serde(default = "default_false")]
     pub parallel_execution: bool,
     #[serde(default = "default_parallel_limit")]
     pub parallel_limit: usize,
+    /// Lower the parallel limit while on battery, under load or memory
+    /// pressure, or while someone is using the machine
+    #[serde(default = "default_false")]
+    pub adaptive_parallelism: bool,
     #[serde(default = "default_false")]
     pub skip_optional_on_error: bool,
     #[serde(default)]
@@ -39,8 +88,50 @@ pub struct Settings {
     pub verbose: bool,
     #[serde(default)]
     pub log_file: Option<String>,
+    #[serde(default)]
+    pub log_format: LogFormat,
+    /// Directory receiving the complete output of every task in its own file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_dir: Option<String>,
+    #[serde(default)]
+    pub audit_log: Option<String>,
+    /// age identity file that decrypts `env_file_encrypted` files
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub age_identity: Option<String>,
     #[serde(default = "default_true")]
     pub desktop_notifications: bool,
+    /// Which runs end with a desktop notification: a success message, or
+    /// the number and names of the failed tasks
+    #[serde(default = "default_completion_notification")]
+    pub completion_notification: PostRunTrigger,
+    #[serde(default = "default_retry_transient")]
+    pub retry_transient: u32,
+    #[serde(default = "default_true")]
+    pub connectivity_check: bool,
+    #[serde(default)]
+    pub connectivity_check_url: Option<String>,
+    #[serde(default = "default_true")]
+    pub prevent_sleep: bool,
+    #[serde(default = "default_min_free_disk_gb")]
+    pub min_free_disk_gb: u64,
+    #[serde(default = "default_false")]
+    pub defer_when_hot: bool,
+    #[serde(default = "default_attention_repeat_secs")]
+    pub attention_repeat_secs: u64,
+    #[serde(default = "default_sudo_prompt_timeout_secs")]
+    pub sudo_prompt_timeout_secs: u64,
+    #[serde(default = "default_sudo_password_attempts")]
+    pub sudo_password_attempts: u32,
+    #[serde(default)]
+    pub color_scheme: ColorScheme,
+    #[serde(default = "default_output_tail_lines")]
+    pub output_tail_lines: usize,
+    #[serde(default)]
+    pub app_store_sign_in: SignInPolicy,
+    #[serde(default)]
+    pub failure_threshold: Vec<FailureThreshold>,
+    #[serde(default)]
+    pub exit_code_policy: ExitCodePolicy,
 }
 
 impl Default for Settings {
@@ -49,20 +140,54 @@ impl Default for Settings {
             show_banner: true,
             show_weather: true,
             show_system_info: true,
+            widgets: None,
+            uptime_kuma_url: None,
             show_progress: true,
+            show_timeline: true,
             parallel_execution: false,
             parallel_limit: 4,
+            adaptive_parallelism: false,
             skip_optional_on_error: false,
             keychain_label: Some("tide-sudo".to_string()),
             use_colors: true,
             verbose: false,
             log_file: None,
+            log_format: LogFormat::Text,
+            log_dir: None,
+            audit_log: None,
+            age_identity: None,
             desktop_notifications: true,
+            completion_notification: default_completion_notification(),
+            retry_transient: default_retry_transient(),
+            connectivity_check: true,
+            connectivity_check_url: None,
+            prevent_sleep: true,
+            min_free_disk_gb: default_min_free_disk_gb(),
+            defer_when_hot: false,
+            attention_repeat_secs: default_attention_repeat_secs(),
+            sudo_prompt_timeout_secs: default_sudo_prompt_timeout_secs(),
+            sudo_password_attempts: default_sudo_password_attempts(),
+            color_scheme: ColorScheme::Default,
+            output_tail_lines: default_output_tail_lines(),
+            app_store_sign_in: SignInPolicy::Prompt,
+            failure_threshold: Vec::new(),
+            exit_code_policy: ExitCodePolicy::Detailed,
         }
     }
 }
 
 impl Settings {
+    /// Interval for repeating attention requests while input is awaited
+    pub fn attention_repeat(&self) -> Option<Duration> {
+        (self.attention_repeat_secs > 0).then(|| Duration::from_secs(self.attention_repeat_secs))
+    }
+
+    /// How long a sudo password prompt waits for input before giving up
+    pub fn sudo_prompt_timeout(&self) -> Option<Duration> {
+        (self.sudo_prompt_timeout_secs > 0)
+            .then(|| Duration::from_secs(self.sudo_prompt_timeout_secs))
+    }
+
     /// Return the configured log file path, ignoring empty values.
     pub fn log_file_path(&self) -> Option<&str> {
         self.log_file
@@ -70,6 +195,81 @@ impl Settings {
             .map(str::trim)
             .filter(|path| !path.is_empty())
     }
+
+    /// Return the configured per-task log directory, ignoring empty values.
+    pub fn log_dir_path(&self) -> Option<&str> {
+        self.log_dir
+            .as_deref()
+            .map(str::trim)
+            .filter(|path| !path.is_empty())
+    }
+
+    /// The dashboard widgets in display order, derived from `show_system_info`
+    /// and `show_weather` unless `widgets` is set
+    pub fn dashboard_widgets(&self) -> Vec<WidgetSpec> {
+        if let Some(widgets) = &self.widgets {
+            return widgets.clone();
+        }
+        [
+            (self.show_system_info, "system"),
+            (self.show_weather, "weather"),
+        ]
+        .into_iter()
+        .filter(|(enabled, _)| *enabled)
+        .map(|(_, name)| WidgetSpec::Builtin(name.to_string()))
+        .collect()
+    }
+
+    /// Return the configured audit log path, ignoring empty values.
+    pub fn audit_log_path(&self) -> Option<&str> {
+        self.audit_log
+            .as_deref()
+            .map(str::trim)
+            .filter(|path| !path.is_empty())
+    }
+}
+
+/// A dashboard widget: the name of a built-in one, or a command whose
+/// output is shown under a title
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(untagged)]
+pub enum WidgetSpec {
+    Builtin(String),
+    Command {
+        title: String,
+        #[serde(default)]
+        icon: String,
+        command: Vec<String>,
+        /// Seconds before the command is abandoned (default 10)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        timeout: Option<u64>,
+    },
+}
+
+/// Parse an interval such as `"24h"`, `"30m"` or `"1d12h"`: numbers
+/// followed by `s`, `m`, `h`, `d` or `w`
+pub fn parse_interval(text: &str) -> Option<Duration> {
+    let text = text.trim();
+    let mut total = 0u64;
+    let mut number = String::new();
+    for c in text.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        let unit = match c {
+            's' => 1,
+            'm' => 60,
+            'h' => 3600,
+            'd' => 86_400,
+            'w' => 604_800,
+            _ => return None,
+        };
+        let value: u64 = number.parse().ok()?;
+        total = total.checked_add(value.checked_mul(unit)?)?;
+        number.clear();
+    }
+    (number.is_empty() && total > 0).then(|| Duration::from_secs(total))
 }
 
 /// Task group configuration
@@ -84,6 +284,13 @@ pub struct TaskGroup {
     pub description: String,
     #[serde(default)]
     pub parallel: bool,
+    #[serde(default = "default_false")]
+    pub needs_disk_space: bool,
+    #[serde(default = "default_false")]
+    pub heavy: bool,
+    /// `min_interval` of the group's tasks that do not set their own
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_interval: Option<String>,
     #[serde(default)]
     pub tasks: Vec<TaskConfig>,
 }
@@ -94,7 +301,22 @@ pub struct TaskConfig {
     pub name: String,
     #[serde(default)]
     pub icon: String,
+    #[serde(default)]
     pub command: Vec<String>,
+    #[serde(default, rename = "type", skip_serializing_if = "Option::is_none")]
+    pub kind: Option<TaskKind>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cask: Option<CaskOptions>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub services: Option<ServiceOptions>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub docker: Option<DockerOptions>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub xcode: Option<XcodeOptions>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub node: Option<NodeOptions>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub python: Option<PythonOptions>,
     #[serde(default = "default_true")]
     pub required: bool,
     #[serde(default = "default_false")]
@@ -107,12 +329,546 @@ pub struct TaskConfig {
     pub check_path: Option<String>,
     #[serde(default)]
     pub description: String,
+    /// Documentation or troubleshooting page linked from the summary when
+    /// the task fails
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub help_url: Option<String>,
     #[serde(default)]
     pub timeout: Option<u64>,
     #[serde(default)]
     pub env: HashMap<String, String>,
+    /// age-encrypted dotenv file decrypted into the environment at execution
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env_file_encrypted: Option<String>,
     #[serde(default)]
     pub working_dir: Option<String>,
+    #[serde(default = "default_false")]
+    pub allow_dangerous: bool,
+    #[serde(default = "default_false")]
+    pub network: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub osascript: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub applescript_file: Option<String>,
+    #[serde(default = "default_false")]
+    pub jxa: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub just: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub taskfile: Option<String>,
+    /// Run `command` as a script of this shell instead of executing it directly
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shell: Option<Shell>,
+    /// Run the script in an interactive login shell so rc files, aliases and
+    /// functions are loaded (uses `$SHELL` when `shell` is not set)
+    #[serde(default = "default_false")]
+    pub login_shell: bool,
+    /// Run the command as this local user via `sudo -u`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run_as: Option<String>,
+    #[serde(default = "default_true")]
+    pub log_output: bool,
+    /// Tasks that must succeed before this one starts: a task name of the
+    /// same group or `"<group>/<task>"`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<String>,
+    /// Capture the command's stdout under this name for `${outputs.<name>}`
+    /// in the command or env of later tasks
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub register: Option<String>,
+    /// Items the task is expanded over, each run with `${item}` replaced
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub matrix: Option<Matrix>,
+    /// Values supplied per run and interpolated as `${params.<name>}`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub params: Vec<TaskParam>,
+    /// Run the command under `sandbox-exec`: "no-network", "readonly-home"
+    /// or the path of a custom profile
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sandbox: Option<String>,
+    /// Skip the task when it succeeded less than this long ago, e.g. "24h"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_interval: Option<String>,
+    /// Where the task was imported from (`catalog:<id>` or a URL); changes
+    /// to such tasks are confirmed before they run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+}
+
+impl Default for TaskConfig {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            icon: String::new(),
+            command: Vec::new(),
+            kind: None,
+            cask: None,
+            services: None,
+            docker: None,
+            xcode: None,
+            node: None,
+            python: None,
+            required: true,
+            sudo: false,
+            enabled: true,
+            check_command: None,
+            check_path: None,
+            description: String::new(),
+            help_url: None,
+            timeout: None,
+            env: HashMap::new(),
+            env_file_encrypted: None,
+            working_dir: None,
+            allow_dangerous: false,
+            network: false,
+            osascript: None,
+            applescript_file: None,
+            jxa: false,
+            just: None,
+            taskfile: None,
+            shell: None,
+            login_shell: false,
+            run_as: None,
+            depends_on: Vec::new(),
+            log_output: true,
+            register: None,
+            matrix: None,
+            params: Vec::new(),
+            sandbox: None,
+            min_interval: None,
+            source: None,
+        }
+    }
+}
+
+impl TaskConfig {
+    /// `min_interval` as a duration; `None` when unset or not a valid interval
+    pub fn min_interval(&self) -> Option<Duration> {
+        self.min_interval.as_deref().and_then(parse_interval)
+    }
+
+    /// Whether the task needs internet access to succeed
+    pub fn requires_network(&self) -> bool {
+        self.network || self.kind.is_some_and(|kind| kind.requires_network())
+    }
+
+    /// Whether the task runs with elevated privileges, via `sudo = true` or
+    /// a command starting with `sudo`
+    pub fn needs_sudo(&self) -> bool {
+        self.sudo
+            || self.run_as.is_some()
+            || self
+                .command
+                .first()
+                .is_some_and(|program| program == "sudo")
+    }
+
+    /// Whether the task is an inline or file-based AppleScript/JXA script
+    pub fn is_osascript(&self) -> bool {
+        self.osascript.is_some() || self.applescript_file.is_some()
+    }
+
+    /// The shell running `command` as a script, if any
+    pub fn shell_program(&self) -> Option<String> {
+        match self.shell {
+            Some(shell) => Some(shell.program().to_string()),
+            None if self.login_shell => Some(
+                std::env::var("SHELL")
+                    .ok()
+                    .filter(|shell| !shell.trim().is_empty())
+                    .unwrap_or_else(|| "zsh".to_string()),
+            ),
+            None => None,
+        }
+    }
+
+    /// The just/Taskfile recipe the task runs, if any
+    pub fn recipe(&self) -> Option<(RecipeRunner, &str)> {
+        if let Some(recipe) = &self.just {
+            Some((RecipeRunner::Just, recipe))
+        } else {
+            self.taskfile
+                .as_deref()
+                .map(|name| (RecipeRunner::Taskfile, name))
+        }
+    }
+
+    /// Command line to execute: `command`, the `osascript` invocation for
+    /// script tasks (JXA with `jxa = true` or a `.js` script file), or the
+    /// runner invocation for just/Taskfile recipes.
+    pub fn effective_command(&self) -> Vec<String> {
+        if let Some((runner, recipe)) = self.recipe() {
+            return vec![runner.program().to_string(), recipe.to_string()];
+        }
+
+        if let Some(shell) = self.shell_program() {
+            let mut cmd = vec![shell];
+            if self.login_shell {
+                cmd.extend(["-l".to_string(), "-i".to_string()]);
+            }
+            cmd.extend(["-c".to_string(), self.command.join(" ")]);
+            return cmd;
+        }
+
+        let mut cmd = vec!["osascript".to_string()];
+        let jxa_file = self
+            .applescript_file
+            .as_deref()
+            .is_some_and(|path| path.ends_with(".js"));
+        if self.jxa || jxa_file {
+            cmd.extend(["-l".to_string(), "JavaScript".to_string()]);
+        }
+
+        if let Some(script) = &self.osascript {
+            cmd.extend(["-e".to_string(), script.clone()]);
+        } else if let Some(path) = &self.applescript_file {
+            cmd.push(shellexpand::tilde(path).into_owned());
+        } else {
+            return self.command.clone();
+        }
+        cmd
+    }
+}
+
+/// Built-in task types implemented natively instead of via `command`
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum TaskKind {
+    /// `brew upgrade --cask` with detection of running apps
+    BrewCaskUpgrade,
+    /// Restart `brew services` still running a binary from before an upgrade
+    BrewServicesRestart,
+    /// `docker system prune`, dangling volume cleanup and runtime update checks
+    DockerMaintenance,
+    /// Xcode/Command Line Tools updates, first-launch setup and license
+    XcodeUpdate,
+    /// Node updates through nvm, fnm or Volta, moving global packages along
+    NodeUpdate,
+    /// `pipx upgrade-all`, outdated venv packages and uv/Poetry cache cleanup
+    PythonMaintenance,
+}
+
+impl TaskKind {
+    /// Name used for the `type` key in the config file
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaskKind::BrewCaskUpgrade => "brew-cask-upgrade",
+            TaskKind::BrewServicesRestart => "brew-services-restart",
+            TaskKind::DockerMaintenance => "docker-maintenance",
+            TaskKind::XcodeUpdate => "xcode-update",
+            TaskKind::NodeUpdate => "node-update",
+            TaskKind::PythonMaintenance => "python-maintenance",
+        }
+    }
+
+    /// Whether the task type downloads from the internet
+    pub fn requires_network(&self) -> bool {
+        match self {
+            TaskKind::BrewCaskUpgrade => true,
+            TaskKind::BrewServicesRestart => false,
+            TaskKind::DockerMaintenance => false,
+            TaskKind::XcodeUpdate => true,
+            TaskKind::NodeUpdate => true,
+            TaskKind::PythonMaintenance => true,
+        }
+    }
+
+    /// Binary that must be installed for the task type to run
+    pub fn required_command(&self) -> &'static str {
+        match self {
+            TaskKind::BrewCaskUpgrade | TaskKind::BrewServicesRestart => "brew",
+            TaskKind::DockerMaintenance => "docker",
+            TaskKind::XcodeUpdate => "xcode-select",
+            TaskKind::NodeUpdate => "node",
+            TaskKind::PythonMaintenance => "python3",
+        }
+    }
+
+    /// Whether the task's output is a report worth showing in the summary
+    pub fn reports_in_summary(&self) -> bool {
+        matches!(
+            self,
+            TaskKind::BrewServicesRestart
+                | TaskKind::DockerMaintenance
+                | TaskKind::XcodeUpdate
+                | TaskKind::NodeUpdate
+                | TaskKind::PythonMaintenance
+        )
+    }
+}
+
+/// Options for `type = "brew-cask-upgrade"` tasks
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct CaskOptions {
+    /// What to do with casks whose app is currently running
+    #[serde(default)]
+    pub running_apps: RunningAppPolicy,
+    /// Include casks that auto-update themselves (`--greedy`)
+    #[serde(default = "default_false")]
+    pub greedy: bool,
+    /// Casks that are never upgraded by this task
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+/// Options for `type = "brew-services-restart"` tasks
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct ServiceOptions {
+    /// Only restart these services (all running services when empty)
+    #[serde(default)]
+    pub allow: Vec<String>,
+    /// Never restart these services
+    #[serde(default)]
+    pub deny: Vec<String>,
+}
+
+impl ServiceOptions {
+    /// Whether the allow/deny lists permit restarting the service
+    pub fn allows(&self, service: &str) -> bool {
+        (self.allow.is_empty() || self.allow.iter().any(|s| s == service))
+            && !self.deny.iter().any(|s| s == service)
+    }
+}
+
+/// Options for `type = "docker-maintenance"` tasks
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct DockerOptions {
+    /// Offer to remove dangling volumes (always asks for confirmation)
+    #[serde(default = "default_false")]
+    pub prune_volumes: bool,
+    /// Report pending Docker Desktop/OrbStack updates via Homebrew
+    #[serde(default = "default_true")]
+    pub check_updates: bool,
+}
+
+impl Default for DockerOptions {
+    fn default() -> Self {
+        Self {
+            prune_volumes: false,
+            check_updates: true,
+        }
+    }
+}
+
+/// Options for `type = "xcode-update"` tasks
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct XcodeOptions {
+    /// Install pending Command Line Tools updates instead of only reporting them
+    #[serde(default = "default_true")]
+    pub install_updates: bool,
+    /// Run `xcodebuild -runFirstLaunch` when Xcode needs it
+    #[serde(default = "default_true")]
+    pub first_launch: bool,
+    /// Accept the Xcode license when it has not been accepted yet
+    #[serde(default = "default_true")]
+    pub accept_license: bool,
+}
+
+impl Default for XcodeOptions {
+    fn default() -> Self {
+        Self {
+            install_updates: true,
+            first_launch: true,
+            accept_license: true,
+        }
+    }
+}
+
+/// Options for `type = "node-update"` tasks
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct NodeOptions {
+    /// Reinstall the global npm packages of the old default version into the new one
+    #[serde(default = "default_true")]
+    pub reinstall_globals: bool,
+    /// Uninstall versions superseded by a newer release of the same major line
+    #[serde(default = "default_false")]
+    pub remove_old: bool,
+}
+
+impl Default for NodeOptions {
+    fn default() -> Self {
+        Self {
+            reinstall_globals: true,
+            remove_old: false,
+        }
+    }
+}
+
+/// Options for `type = "python-maintenance"` tasks
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct PythonOptions {
+    /// Run `pipx upgrade-all`
+    #[serde(default = "default_true")]
+    pub upgrade_pipx: bool,
+    /// Virtualenvs whose outdated packages are reported (supports `~`)
+    #[serde(default)]
+    pub venvs: Vec<String>,
+    /// Clear the uv and Poetry caches
+    #[serde(default = "default_true")]
+    pub clean_caches: bool,
+}
+
+impl Default for PythonOptions {
+    fn default() -> Self {
+        Self {
+            upgrade_pipx: true,
+            venvs: Vec::new(),
+            clean_caches: true,
+        }
+    }
+}
+
+/// Shells a task's command can run in
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Shell {
+    Zsh,
+    Bash,
+    Fish,
+}
+
+impl Shell {
+    pub fn program(self) -> &'static str {
+        match self {
+            Shell::Zsh => "zsh",
+            Shell::Bash => "bash",
+            Shell::Fish => "fish",
+        }
+    }
+}
+
+/// What a matrix task expands over: a list of items, or the non-empty
+/// lines printed by a command
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(untagged)]
+pub enum Matrix {
+    Items(Vec<String>),
+    Command { command: Vec<String> },
+}
+
+/// A task parameter, supplied with `--param <name>=<value>`, asked for
+/// before the run or taken from its default
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct TaskParam {
+    pub name: String,
+    #[serde(default, rename = "type")]
+    pub kind: ParamType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<toml::Value>,
+    /// Question shown when asking for the value (defaults to the name)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<String>,
+}
+
+/// Values a task parameter accepts
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ParamType {
+    #[default]
+    String,
+    Integer,
+    Boolean,
+}
+
+/// Handling of casks whose application is running during the upgrade
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RunningAppPolicy {
+    /// Leave running apps alone and upgrade them next time
+    #[default]
+    Skip,
+    /// Ask whether to quit, upgrade and relaunch each running app
+    Prompt,
+    /// Quit the app, upgrade it and relaunch it afterwards
+    Restart,
+}
+
+/// Expectation checked by `tide test` against a simulated machine where
+/// every command and path exists except the ones listed as missing
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ConfigTest {
+    pub name: String,
+    /// Group the expectation applies to (narrows `task` lookups)
+    #[serde(default)]
+    pub group: Option<String>,
+    /// Task the `expect` outcome applies to
+    #[serde(default)]
+    pub task: Option<String>,
+    #[serde(default)]
+    pub missing_commands: Vec<String>,
+    #[serde(default)]
+    pub missing_paths: Vec<String>,
+    /// Whether the task should run or be skipped
+    #[serde(default)]
+    pub expect: Option<TaskExpectation>,
+    /// Number of enabled tasks in `group`
+    #[serde(default)]
+    pub enabled_tasks: Option<usize>,
+}
+
+/// Expected outcome of a task under test
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskExpectation {
+    Run,
+    Skip,
+}
+
+/// `[post_run]` actions performed after a run finishes
+#[derive(Debug, Default, Deserialize, Serialize, Clone)]
+pub struct PostRun {
+    /// Open the `log_file` in its default app
+    #[serde(default)]
+    pub open_log: PostRunTrigger,
+    /// Copy the run summary to the clipboard
+    #[serde(default)]
+    pub copy_summary: PostRunTrigger,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub commands: Vec<PostRunCommand>,
+}
+
+impl PostRun {
+    pub fn is_empty(&self) -> bool {
+        self.open_log == PostRunTrigger::Never
+            && self.copy_summary == PostRunTrigger::Never
+            && self.commands.is_empty()
+    }
+}
+
+/// Command run by `[[post_run.commands]]`, e.g. `mackup backup`
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct PostRunCommand {
+    pub command: Vec<String>,
+    #[serde(default = "default_post_run_when")]
+    pub when: PostRunTrigger,
+}
+
+/// Which run outcomes trigger a post-run action
+#[derive(Debug, Default, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PostRunTrigger {
+    #[default]
+    Never,
+    Always,
+    /// No task failed
+    Success,
+    /// A required task failed or the run was aborted
+    Failure,
+}
+
+impl PostRunTrigger {
+    pub fn fires(self, success: bool) -> bool {
+        match self {
+            PostRunTrigger::Never => false,
+            PostRunTrigger::Always => true,
+            PostRunTrigger::Success => success,
+            PostRunTrigger::Failure => !success,
+        }
+    }
+}
+
+fn default_post_run_when() -> PostRunTrigger {
+    PostRunTrigger::Always
 }
 
 fn default_true() -> bool {
@@ -127,6 +883,34 @@ fn default_parallel_limit() -> usize {
     4
 }
 
+fn default_completion_notification() -> PostRunTrigger {
+    PostRunTrigger::Always
+}
+
+fn default_retry_transient() -> u32 {
+    2
+}
+
+fn default_min_free_disk_gb() -> u64 {
+    20
+}
+
+fn default_attention_repeat_secs() -> u64 {
+    60
+}
+
+fn default_sudo_prompt_timeout_secs() -> u64 {
+    300
+}
+
+fn default_sudo_password_attempts() -> u32 {
+    3
+}
+
+fn default_output_tail_lines() -> usize {
+    3
+}
+
 impl Config {
     /// Resolve the path that should be used for the configuration file
     pub fn resolve_path(path: Option<&PathBuf>) -> Result<PathBuf> {
@@ -137,11 +921,29 @@ impl Config {
         }
     }
 
-    /// Load configuration from file or use default path
+    /// Load configuration from file or use default path, with its `include`
+    /// files, the `[host."<hostname>"]` section and `config.<hostname>.toml`
+    /// overlay of this machine applied and merged over the managed
+    /// configuration when one is installed
     pub fn load(path: Option<&PathBuf>) -> Result<Self> {
+        Self::load_profile(path, None)
+    }
+
+    /// Load configuration like [`Config::load`] with the given profile
+    /// applied over the user config, before the managed one
+    pub fn load_profile(path: Option<&PathBuf>, profile: Option<&str>) -> Result<Self> {
         let config_path = Self::resolve_path(path)?;
+        let managed_path = managed::path();
+        let managed = if managed_path.exists() && config_path != managed_path {
+            Some(fs::read_to_string(&managed_path).context(format!(
+                "Failed to read managed config: {}",
+                managed_path.display()
+            ))?)
+        } else {
+            None
+        };
 
-        if !config_path.exists() {
+        if !config_path.exists() && managed.is_none() {
             return Err(TideError::Config(format!(
                 "Config file not found: {}\nRun 'tide --init' to create one.",
                 config_path.display()
@@ -149,25 +951,112 @@ impl Config {
             .into());
         }
 
-        let contents = fs::read_to_string(&config_path).context(format!(
-            "Failed to read config file: {}",
-            config_path.display()
-        ))?;
+        let contents = if config_path.exists() {
+            fs::read_to_string(&config_path).context(format!(
+                "Failed to read config file: {}",
+                config_path.display()
+            ))?
+        } else {
+            String::new()
+        };
+
+        let host_overlay = overlay::host_name()
+            .map(|host| overlay::host_path(&config_path, &host))
+            .filter(|path| path.exists());
 
-        toml::from_str(&contents).context("Failed to parse config file")
+        let format = ConfigFormat::of(&config_path);
+        let parsed: Option<Self> =
+            if format == ConfigFormat::Toml && managed.is_none() && host_overlay.is_none() {
+                Some(toml::from_str(&contents).map_err(|err| {
+                    TideError::Config(format!(
+                        "Failed to parse config file {}: {}",
+                        config_path.display(),
+                        err
+                    ))
+                })?)
+            } else {
+                None
+            };
+        let mut config = match parsed {
+            Some(config)
+                if config.include.is_empty() && config.host.is_empty() && profile.is_none() =>
+            {
+                config
+            }
+            _ => {
+                let mut layered = format.parse(&contents).map_err(|err| {
+                    TideError::Config(format!(
+                        "Failed to parse config file {}: {:#}",
+                        config_path.display(),
+                        err
+                    ))
+                })?;
+                let mut notices = Vec::new();
+                include::apply(&mut layered, &config_path, &mut notices)?;
+                if let Some(host) = overlay::host_name() {
+                    profile::apply_host(&mut layered, &host)?;
+                }
+                if let Some(path) = host_overlay {
+                    let text = fs::read_to_string(&path)
+                        .context(format!("Failed to read host overlay: {}", path.display()))?;
+                    let layer = ConfigFormat::of(&path).parse(&text).map_err(|err| {
+                        TideError::Config(format!(
+                            "Failed to parse host overlay {}: {:#}",
+                            path.display(),
+                            err
+                        ))
+                    })?;
+                    overlay::apply(&mut layered, layer, &[], &mut notices);
+                }
+                if let Some(name) = profile {
+                    profile::apply(&mut layered, name)?;
+                }
+                if let Some(managed) = managed {
+                    layered = managed::merge(&managed, layered, &mut notices)?;
+                }
+                let config: Self = toml::Value::Table(layered).try_into().map_err(|err| {
+                    TideError::Config(format!("Failed to parse the layered config: {}", err))
+                })?;
+                Self { notices, ..config }
+            }
+        };
+        vars::apply(&mut config)?;
+        let problems = validate::validate(&config);
+        if !problems.is_empty() {
+            return Err(TideError::Config(format!(
+                "Invalid config file {}:\n  - {}",
+                config_path.display(),
+                problems.join("\n  - ")
+            ))
+            .into());
+        }
+        Ok(config)
     }
 
-    /// Get default configuration path
+    /// Get default configuration path: `config.toml`, or a `config.yaml`,
+    /// `config.yml` or `config.json` next to it when only that one exists
     pub fn default_config_path() -> Result<PathBuf> {
-        Ok(dirs::config_dir()
+        let dir = dirs::config_dir()
             .context("Could not determine config directory")?
-            .join("tide")
-            .join("config.toml"))
+            .join("tide");
+        let toml = dir.join("config.toml");
+        if toml.exists() {
+            return Ok(toml);
+        }
+        Ok(["yaml", "yml", "json"]
+            .iter()
+            .map(|ext| dir.join(format!("config.{}", ext)))
+            .find(|path| path.exists())
+            .unwrap_or(toml))
     }
+}
 
+impl Default for Config {
     /// Create default configuration
-    pub fn default() -> Self {
+    fn default() -> Self {
         Self {
+            include: Vec::new(),
+            vars: HashMap::new(),
             settings: Settings::default(),
             groups: vec![
                 TaskGroup {
@@ -176,6 +1065,9 @@ impl Config {
                     enabled: true,
                     description: "macOS system updates".to_string(),
                     parallel: false,
+                    needs_disk_space: true,
+                    heavy: true,
+                    min_interval: None,
                     tasks: vec![TaskConfig {
                         name: "macOS Updates".to_string(),
                         icon: "🍎".to_string(),
@@ -186,13 +1078,11 @@ impl Config {
                         ],
                         required: true,
                         sudo: true,
-                        enabled: true,
                         check_command: Some("softwareupdate".to_string()),
-                        check_path: None,
                         description: "Install macOS system updates".to_string(),
                         timeout: Some(3600),
-                        env: HashMap::new(),
-                        working_dir: None,
+                        network: true,
+                        ..TaskConfig::default()
                     }],
                 },
                 TaskGroup {
@@ -201,6 +1091,9 @@ impl Config {
                     enabled: true,
                     description: "Homebrew package manager".to_string(),
                     parallel: false,
+                    needs_disk_space: false,
+                    heavy: true,
+                    min_interval: None,
                     tasks: vec![
                         TaskConfig {
                             name: "Update Formulae".to_string(),
@@ -208,13 +1101,11 @@ impl Config {
                             command: vec!["brew".to_string(), "update".to_string()],
                             required: true,
                             sudo: false,
-                            enabled: true,
                             check_command: Some("brew".to_string()),
-                            check_path: None,
                             description: "Update Homebrew package definitions".to_string(),
                             timeout: Some(300),
-                            env: HashMap::new(),
-                            working_dir: None,
+                            network: true,
+                            ..TaskConfig::default()
                         },
                         TaskConfig {
                             name: "Upgrade Packages".to_string(),
@@ -222,17 +1113,21 @@ impl Config {
                             command: vec!["brew".to_string(), "upgrade".to_string()],
                             required: true,
                             sudo: false,
-                            enabled: true,
                             check_command: Some("brew".to_string()),
-                            check_path: None,
                             description: "Upgrade all outdated packages".to_string(),
                             timeout: Some(1200),
-                            env: HashMap::new(),
-                            working_dir: None,
+                            network: true,
+                            ..TaskConfig::default()
                         },
                     ],
                 },
             ],
+            tests: Vec::new(),
+            post_run: PostRun::default(),
+            notifications: NotificationsConfig::default(),
+            profiles: HashMap::new(),
+            host: HashMap::new(),
+            notices: Vec::new(),
         }
     }
 }
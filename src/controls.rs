@@ -0,0 +1,440 @@
+use colored::Colorize;
+use indicatif::MultiProgress;
+use std::io::{IsTerminal, Read};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::thread;
+use std::time::Duration;
+use tokio::sync::{self, Notify, watch};
+
+use crate::orphans;
+use crate::palette::Paint;
+
+/// How long the key listener blocks before re-checking its state
+const POLL_INTERVAL_MS: i32 = 100;
+
+/// Whether new tasks may be dispatched
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DispatchState {
+    Running,
+    Paused,
+    Aborted,
+}
+
+/// A task currently executing, which the user may skip
+pub struct ActiveTask {
+    pub name: String,
+    pid: AtomicU32,
+    skipped: AtomicBool,
+    cancelled: AtomicBool,
+    skip: Notify,
+}
+
+impl ActiveTask {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            pid: AtomicU32::new(0),
+            skipped: AtomicBool::new(false),
+            cancelled: AtomicBool::new(false),
+            skip: Notify::new(),
+        }
+    }
+
+    /// Remember the child process (a process group leader) so it can be
+    /// terminated with everything it started on skip
+    pub fn set_pid(&self, pid: u32) {
+        self.pid.store(pid, Ordering::SeqCst);
+        if self.was_skipped() || self.was_cancelled() {
+            self.kill();
+        }
+    }
+
+    /// Whether the user skipped this task
+    pub fn was_skipped(&self) -> bool {
+        self.skipped.load(Ordering::SeqCst)
+    }
+
+    /// Whether the task was stopped because tide was interrupted
+    pub fn was_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Resolves once the user asks to skip this task or tide is interrupted
+    pub async fn skip_requested(&self) {
+        self.skip.notified().await;
+    }
+
+    fn request_skip(&self) {
+        self.skipped.store(true, Ordering::SeqCst);
+        self.kill();
+        self.skip.notify_one();
+    }
+
+    fn request_cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+        self.kill();
+        self.skip.notify_one();
+    }
+
+    fn kill(&self) {
+        let pid = self.pid.load(Ordering::SeqCst);
+        if pid != 0 {
+            // SAFETY: signalling the process group of a child we spawned
+            unsafe { libc::kill(-(pid as libc::pid_t), libc::SIGTERM) };
+        }
+    }
+}
+
+/// Whether a user can answer prompts and watch spinners: both stdin and
+/// stdout are terminals (not a pipe, `tee` or launchd)
+pub fn terminal_attached() -> bool {
+    std::io::stdin().is_terminal() && std::io::stdout().is_terminal()
+}
+
+/// Keyboard controls available while tasks are executing.
+///
+/// A background thread puts the terminal into cbreak mode (no line
+/// buffering, no echo, signals and output processing untouched) and reacts
+/// to single key presses. Prompts must go through [`RunControls::suspend`]
+/// so the listener does not steal their input and parallel tasks never
+/// prompt at the same time.
+pub struct RunControls {
+    dispatch: watch::Sender<DispatchState>,
+    streaming: AtomicBool,
+    active: Mutex<Vec<Arc<ActiveTask>>>,
+    suspended: AtomicBool,
+    interactive: bool,
+    /// Held while a prompt owns the terminal
+    prompt: Mutex<()>,
+    /// Held across multi-step exchanges such as sudo authentication
+    prompt_turn: sync::Mutex<()>,
+    stopped: AtomicBool,
+    interrupted: AtomicBool,
+    original_termios: Mutex<Option<libc::termios>>,
+    multi_progress: Option<Arc<MultiProgress>>,
+}
+
+impl RunControls {
+    /// Create inactive controls; call [`RunControls::start`] to listen for keys
+    pub fn new(multi_progress: Option<Arc<MultiProgress>>, streaming: bool) -> Self {
+        Self {
+            dispatch: watch::Sender::new(DispatchState::Running),
+            streaming: AtomicBool::new(streaming),
+            active: Mutex::new(Vec::new()),
+            suspended: AtomicBool::new(false),
+            interactive: terminal_attached(),
+            prompt: Mutex::new(()),
+            prompt_turn: sync::Mutex::new(()),
+            stopped: AtomicBool::new(false),
+            interrupted: AtomicBool::new(false),
+            original_termios: Mutex::new(None),
+            multi_progress,
+        }
+    }
+
+    /// Start listening for key presses if stdin is an interactive terminal
+    pub fn start(self: &Arc<Self>) {
+        if !std::io::stdin().is_terminal() || !self.enter_cbreak() {
+            return;
+        }
+
+        self.print(
+            "⌨️  Keys: p pause/resume · s skip current task · q abort run · v toggle output"
+                .dimmed()
+                .to_string(),
+        );
+
+        let weak = Arc::downgrade(self);
+        thread::spawn(move || listen(weak));
+    }
+
+    /// Stop listening and restore the terminal
+    pub fn stop(&self) {
+        self.stopped.store(true, Ordering::SeqCst);
+        self.restore_terminal();
+    }
+
+    /// Whether dispatching new tasks is currently paused
+    pub fn is_paused(&self) -> bool {
+        *self.dispatch.borrow() == DispatchState::Paused
+    }
+
+    /// Whether the user aborted the run
+    pub fn is_aborted(&self) -> bool {
+        *self.dispatch.borrow() == DispatchState::Aborted
+    }
+
+    /// Whether the run was interrupted by SIGINT or SIGTERM
+    pub fn is_interrupted(&self) -> bool {
+        self.interrupted.load(Ordering::SeqCst)
+    }
+
+    /// Wind the run down after SIGINT or SIGTERM: no new tasks start, and
+    /// running ones are terminated with everything they started and marked
+    /// cancelled, so the run ends with the summary of what finished.
+    /// Returns `false` when the run was already interrupted, so a second
+    /// signal ends tide at once.
+    pub fn interrupt(&self) -> bool {
+        if self.interrupted.swap(true, Ordering::SeqCst) {
+            self.restore_terminal();
+            return false;
+        }
+        self.dispatch.send_replace(DispatchState::Aborted);
+        for task in self.active.lock().unwrap().iter() {
+            task.request_cancel();
+        }
+        // Children outside a task's process group, e.g. sudo prompts
+        orphans::terminate_all(libc::SIGTERM);
+        self.print(
+            "⏹️  Interrupted – cancelling running tasks. Press Ctrl+C again to quit at once."
+                .warning()
+                .to_string(),
+        );
+        true
+    }
+
+    /// Whether prompts can be answered; without a terminal, questions that
+    /// need an answer fail instead of waiting for one
+    pub fn is_interactive(&self) -> bool {
+        self.interactive
+    }
+
+    /// Whether command output should be streamed to the terminal
+    pub fn is_streaming(&self) -> bool {
+        self.streaming.load(Ordering::SeqCst)
+    }
+
+    /// Wait while dispatching is paused; returns `false` once the run is aborted
+    pub async fn wait_for_dispatch(&self) -> bool {
+        let mut rx = self.dispatch.subscribe();
+        match rx.wait_for(|state| *state != DispatchState::Paused).await {
+            Ok(state) => *state == DispatchState::Running,
+            Err(_) => true,
+        }
+    }
+
+    /// Track a task that started executing
+    pub fn register(&self, name: &str) -> Arc<ActiveTask> {
+        let task = Arc::new(ActiveTask::new(name));
+        if self.is_interrupted() {
+            task.request_cancel();
+        }
+        self.active.lock().unwrap().push(Arc::clone(&task));
+        task
+    }
+
+    /// Stop tracking a finished task
+    pub fn unregister(&self, task: &Arc<ActiveTask>) {
+        self.active
+            .lock()
+            .unwrap()
+            .retain(|active| !Arc::ptr_eq(active, task));
+    }
+
+    /// Run an interactive prompt with the key listener and spinners
+    /// suspended, waiting until no other prompt is shown
+    pub fn suspend<T>(&self, prompt: impl FnOnce() -> T) -> T {
+        let _prompt = self
+            .prompt
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let was_suspended = self.suspended.swap(true, Ordering::SeqCst);
+        let had_cbreak = self.restore_terminal();
+
+        let result = match &self.multi_progress {
+            Some(multi) => multi.suspend(prompt),
+            None => prompt(),
+        };
+
+        if had_cbreak && !self.stopped.load(Ordering::SeqCst) {
+            self.enter_cbreak();
+        }
+        self.suspended.store(was_suspended, Ordering::SeqCst);
+        result
+    }
+
+    /// Wait for exclusive use of the prompts for an exchange spanning several
+    /// of them. Tasks that waited should check whether their question was
+    /// answered in the meantime (e.g. another task already authenticated sudo).
+    pub async fn prompt_turn(&self) -> sync::MutexGuard<'_, ()> {
+        self.prompt_turn.lock().await
+    }
+
+    fn toggle_pause(&self) {
+        if self.is_aborted() {
+            return;
+        }
+        let paused = !self.is_paused();
+        self.dispatch.send_replace(if paused {
+            DispatchState::Paused
+        } else {
+            DispatchState::Running
+        });
+        let message = if paused {
+            "⏸️  Paused – running tasks will finish, no new tasks start. Press p to resume."
+                .warning()
+        } else {
+            "▶️  Resumed".success()
+        };
+        self.print(message.to_string());
+    }
+
+    /// Skip the longest-running active task
+    fn skip_current(&self) {
+        let task = self.active.lock().unwrap().first().cloned();
+        match task {
+            Some(task) => {
+                task.request_skip();
+                self.print(format!("⏭️  Skipping {}", task.name).warning().to_string());
+            }
+            None => self.print("No running task to skip".dimmed().to_string()),
+        }
+    }
+
+    fn abort(&self) {
+        if self.is_aborted() {
+            return;
+        }
+        self.dispatch.send_replace(DispatchState::Aborted);
+        self.print(
+            "⏹️  Aborting – running tasks will finish, remaining tasks are skipped."
+                .warning()
+                .to_string(),
+        );
+    }
+
+    fn toggle_streaming(&self) {
+        let streaming = !self.streaming.fetch_xor(true, Ordering::SeqCst);
+        let message = if streaming {
+            "🔊 Output streaming on (applies to tasks started from now)"
+        } else {
+            "🔇 Output streaming off (applies to tasks started from now)"
+        };
+        self.print(message.dimmed().to_string());
+    }
+
+    fn handle_key(&self, key: u8) {
+        match key {
+            b'p' | b'P' => self.toggle_pause(),
+            b's' | b'S' => self.skip_current(),
+            b'q' | b'Q' => self.abort(),
+            b'v' | b'V' => self.toggle_streaming(),
+            _ => {}
+        }
+    }
+
+    /// Print a line above the progress bars
+    pub fn print(&self, message: String) {
+        match &self.multi_progress {
+            Some(multi) => {
+                let _ = multi.println(message);
+            }
+            None => println!("{}", message),
+        }
+    }
+
+    /// Switch stdin to cbreak mode, remembering the original settings
+    fn enter_cbreak(&self) -> bool {
+        let mut guard = self.original_termios.lock().unwrap();
+        let mut termios = std::mem::MaybeUninit::<libc::termios>::uninit();
+        // SAFETY: tcgetattr initializes the struct when it returns 0
+        if unsafe { libc::tcgetattr(libc::STDIN_FILENO, termios.as_mut_ptr()) } != 0 {
+            return false;
+        }
+        let original = unsafe { termios.assume_init() };
+        let mut cbreak = original;
+        cbreak.c_lflag &= !(libc::ICANON | libc::ECHO);
+        cbreak.c_cc[libc::VMIN] = 1;
+        cbreak.c_cc[libc::VTIME] = 0;
+        // SAFETY: cbreak is a valid termios copied from the current settings
+        if unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &cbreak) } != 0 {
+            return false;
+        }
+        guard.get_or_insert(original);
+        true
+    }
+
+    /// Restore the original terminal settings; returns whether cbreak was active
+    fn restore_terminal(&self) -> bool {
+        let mut guard = self.original_termios.lock().unwrap();
+        match guard.take() {
+            Some(original) => {
+                // SAFETY: original was obtained from tcgetattr
+                unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &original) };
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl Drop for RunControls {
+    fn drop(&mut self) {
+        self.restore_terminal();
+    }
+}
+
+/// Key listener loop; exits once the controls are stopped or dropped
+fn listen(weak: Weak<RunControls>) {
+    loop {
+        let Some(controls) = weak.upgrade() else {
+            return;
+        };
+        if controls.stopped.load(Ordering::SeqCst) {
+            return;
+        }
+        if controls.suspended.load(Ordering::SeqCst) {
+            drop(controls);
+            thread::sleep(Duration::from_millis(POLL_INTERVAL_MS as u64));
+            continue;
+        }
+        drop(controls);
+
+        let mut fds = libc::pollfd {
+            fd: libc::STDIN_FILENO,
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        // SAFETY: fds is a valid pollfd for the duration of the call
+        let ready = unsafe { libc::poll(&mut fds, 1, POLL_INTERVAL_MS) };
+        if ready <= 0 {
+            continue;
+        }
+
+        let Some(controls) = weak.upgrade() else {
+            return;
+        };
+        // A prompt may have taken over the terminal while we were polling
+        if controls.suspended.load(Ordering::SeqCst) || controls.stopped.load(Ordering::SeqCst) {
+            continue;
+        }
+
+        let mut byte = [0u8; 1];
+        match std::io::stdin().read(&mut byte) {
+            Ok(1) => controls.handle_key(byte[0]),
+            Ok(_) | Err(_) => return,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interrupt_cancels_tasks() {
+        let controls = RunControls::new(None, false);
+        let running = controls.register("Upgrade Packages");
+        assert!(!running.was_cancelled());
+
+        assert!(controls.interrupt());
+        assert!(controls.is_interrupted());
+        assert!(controls.is_aborted());
+        assert!(running.was_cancelled());
+        assert!(controls.register("Cleanup").was_cancelled());
+
+        // A second signal is left to end tide
+        assert!(!controls.interrupt());
+    }
+}
@@ -0,0 +1,80 @@
+use std::process::Command;
+
+/// Usage of the root volume as reported by `df -k /`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiskUsage {
+    pub total_kb: u64,
+    pub used_kb: u64,
+    pub available_kb: u64,
+    pub capacity: String,
+}
+
+impl DiskUsage {
+    /// Free space in gigabytes (1 GB = 1024³ bytes, matching `df -h`)
+    pub fn available_gb(&self) -> f64 {
+        self.available_kb as f64 / (1024.0 * 1024.0)
+    }
+}
+
+/// Query the usage of `/`; returns `None` if `df` is unavailable or unparsable
+pub fn root_usage() -> Option<DiskUsage> {
+    let output = Command::new("df").args(["-k", "/"]).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_df(&String::from_utf8_lossy(&output.stdout))
+}
+
+fn parse_df(output: &str) -> Option<DiskUsage> {
+    let line = output.lines().nth(1)?;
+    let parts: Vec<&str> = line.split_whitespace().collect();
+    if parts.len() < 5 {
+        return None;
+    }
+    Some(DiskUsage {
+        total_kb: parts[1].parse().ok()?,
+        used_kb: parts[2].parse().ok()?,
+        available_kb: parts[3].parse().ok()?,
+        capacity: parts[4].to_string(),
+    })
+}
+
+/// Format a kilobyte count the way `df -h` does
+pub fn format_kb(kb: u64) -> String {
+    let mut value = kb as f64;
+    let mut unit = "K";
+    for next in ["M", "G", "T"] {
+        if value < 1024.0 {
+            break;
+        }
+        value /= 1024.0;
+        unit = next;
+    }
+    if value < 10.0 {
+        format!("{:.1}{}i", value, unit)
+    } else {
+        format!("{:.0}{}i", value, unit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_df_output() {
+        let output = "\
+Filesystem     1024-blocks      Used Available Capacity iused      ifree %iused  Mounted on
+/dev/disk3s1s1   482797652  10795980 183543308     6%  404167 1835433080    0%   /
+";
+        let usage = parse_df(output).unwrap();
+        assert_eq!(usage.total_kb, 482797652);
+        assert_eq!(usage.available_kb, 183543308);
+        assert_eq!(usage.capacity, "6%");
+        assert!((usage.available_gb() - 175.04).abs() < 0.01);
+
+        assert_eq!(parse_df("Filesystem\n"), None);
+        assert_eq!(format_kb(482797652), "460Gi");
+        assert_eq!(format_kb(5 * 1024 * 1024), "5.0Gi");
+    }
+}
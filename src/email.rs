@@ -0,0 +1,211 @@
+use anyhow::{Context, Result};
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use serde::{Deserialize, Serialize};
+
+use crate::config::PostRunTrigger;
+use crate::executor::TaskStatus;
+use crate::keychain;
+use crate::report::RunReport;
+
+/// Lines of a failed task's output quoted in the email
+const EXCERPT_LINES: usize = 20;
+/// Characters of a failed task's output quoted in the email
+const EXCERPT_CHARS: usize = 2000;
+
+/// `[notifications.email]`: mail the outcome of runs through an SMTP server
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct EmailConfig {
+    pub smtp_host: String,
+    /// 465 connects with TLS; other ports upgrade the connection with STARTTLS
+    #[serde(default = "default_smtp_port")]
+    pub smtp_port: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    /// Keychain entry holding the SMTP password
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password_keychain: Option<String>,
+    pub from: String,
+    pub to: Vec<String>,
+    /// Which runs are mailed
+    #[serde(default = "default_when")]
+    pub when: PostRunTrigger,
+}
+
+fn default_smtp_port() -> u16 {
+    587
+}
+
+fn default_when() -> PostRunTrigger {
+    PostRunTrigger::Failure
+}
+
+/// Subject line, e.g. "Tide: 2 task(s) failed on mac-mini"
+pub fn subject(host: &str, report: &RunReport) -> String {
+    if report.success {
+        format!("Tide: {} task(s) succeeded on {}", report.succeeded, host)
+    } else if report.failed > 0 {
+        format!("Tide: {} task(s) failed on {}", report.failed, host)
+    } else {
+        format!("Tide: run failed on {}", host)
+    }
+}
+
+/// Plain-text body listing the failed tasks with an excerpt of their
+/// output, followed by the outcome of every task
+pub fn body(run_id: &str, host: &str, report: &RunReport) -> String {
+    let mut text = format!(
+        "Tide run {} on {} {} after {:.0}s.\n",
+        run_id,
+        host,
+        if report.success {
+            "succeeded"
+        } else {
+            "failed"
+        },
+        report.duration_secs
+    );
+    if let Some(error) = &report.error {
+        text.push_str(&format!("\n{}\n", error.message));
+    }
+
+    let failed: Vec<_> = report
+        .tasks
+        .iter()
+        .filter(|task| task.status == TaskStatus::Failed)
+        .collect();
+    if !failed.is_empty() {
+        text.push_str("\nFailed tasks:\n");
+        for task in failed {
+            let exit = task
+                .exit_code
+                .map(|code| format!(" (exit {})", code))
+                .unwrap_or_default();
+            text.push_str(&format!("\n✗ {} › {}{}\n", task.group, task.name, exit));
+            let output = task.output.as_deref().unwrap_or_default().trim();
+            let mut excerpt: String = output
+                .lines()
+                .take(EXCERPT_LINES)
+                .collect::<Vec<_>>()
+                .join("\n")
+                .chars()
+                .take(EXCERPT_CHARS)
+                .collect();
+            if excerpt.len() < output.len() {
+                excerpt.push_str("\n…");
+            }
+            for line in excerpt.lines() {
+                text.push_str(&format!("    {}\n", line));
+            }
+        }
+    }
+
+    text.push_str(&format!(
+        "\nSucceeded: {}  Failed: {}  Skipped: {}  Cancelled: {}\n",
+        report.succeeded, report.failed, report.skipped, report.cancelled
+    ));
+    for task in &report.tasks {
+        let status = match task.status {
+            TaskStatus::Success => "✓",
+            TaskStatus::Failed => "✗",
+            TaskStatus::Skipped => "○",
+            TaskStatus::Cancelled => "⊘",
+        };
+        text.push_str(&format!(
+            "{} {} › {} ({:.0}s)\n",
+            status, task.group, task.name, task.duration_secs
+        ));
+    }
+    text
+}
+
+/// Send a plain-text email to every recipient
+pub async fn send(config: &EmailConfig, subject: String, body: String) -> Result<()> {
+    let from: Mailbox = config
+        .from
+        .parse()
+        .with_context(|| format!("Invalid sender address '{}'", config.from))?;
+    let mut message = Message::builder().from(from).subject(subject);
+    for to in &config.to {
+        let to: Mailbox = to
+            .parse()
+            .with_context(|| format!("Invalid recipient address '{}'", to))?;
+        message = message.to(to);
+    }
+    let message = message.body(body)?;
+
+    let mut transport = if config.smtp_port == 465 {
+        AsyncSmtpTransport::<Tokio1Executor>::relay(&config.smtp_host)?
+    } else {
+        AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.smtp_host)?
+    }
+    .port(config.smtp_port);
+    if let Some(username) = &config.username {
+        let password = match &config.password_keychain {
+            Some(label) => keychain::get_password(label)?,
+            None => String::new(),
+        };
+        transport = transport.credentials(Credentials::new(username.clone(), password));
+    }
+    transport
+        .build()
+        .send(message)
+        .await
+        .with_context(|| format!("Failed to send email via {}", config.smtp_host))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::TideError;
+    use crate::executor::TaskResult;
+    use crate::failure::FailureClass;
+    use chrono::Local;
+    use std::time::{Duration, Instant};
+
+    fn result(name: &str, status: TaskStatus, output: &str) -> TaskResult {
+        TaskResult {
+            name: name.to_string(),
+            group: "Homebrew".to_string(),
+            group_icon: "🍺".to_string(),
+            status,
+            started: Instant::now(),
+            duration: Duration::from_secs(30),
+            output: Some(output.to_string()),
+            exit_code: Some(if status == TaskStatus::Failed { 1 } else { 0 }),
+            failure: (status == TaskStatus::Failed).then_some(FailureClass::NonZeroExit),
+            log_line: None,
+        }
+    }
+
+    #[test]
+    fn test_email_body() {
+        let noisy: String = (1..=30).map(|n| format!("line {}\n", n)).collect();
+        let results = [
+            result(
+                "Update Formulae",
+                TaskStatus::Success,
+                "Already up-to-date.",
+            ),
+            result("Upgrade Packages", TaskStatus::Failed, &noisy),
+        ];
+        let error = TideError::from_results(&results);
+        let report = RunReport::new(
+            Local::now(),
+            Duration::from_secs(60),
+            &results,
+            error.as_ref(),
+        );
+
+        assert_eq!(subject("mini", &report), "Tide: 1 task(s) failed on mini");
+        let body = body("run-1", "mini", &report);
+        assert!(body.starts_with("Tide run run-1 on mini failed after 60s.\n"));
+        assert!(body.contains("\n✗ Homebrew › Upgrade Packages (exit 1)\n    line 1\n"));
+        assert!(body.contains("    line 20\n    …\n"));
+        assert!(!body.contains("line 21"));
+        assert!(!body.contains("Already up-to-date."));
+        assert!(body.contains("✓ Homebrew › Update Formulae (30s)\n"));
+    }
+}
@@ -0,0 +1,95 @@
+use anyhow::{Context, Result};
+use std::path::Path;
+use std::process::Stdio;
+use tokio::process::Command;
+
+/// Decrypt an age-encrypted dotenv file and parse its variables. The
+/// plaintext only ever exists in memory.
+pub async fn load_encrypted(path: &Path, identity: &Path) -> Result<Vec<(String, String)>> {
+    let output = Command::new("age")
+        .arg("--decrypt")
+        .arg("--identity")
+        .arg(identity)
+        .arg(path)
+        .stdin(Stdio::null())
+        .output()
+        .await
+        .context("Failed to execute age")?;
+    anyhow::ensure!(
+        output.status.success(),
+        "Could not decrypt {}: {}",
+        path.display(),
+        String::from_utf8_lossy(&output.stderr).trim()
+    );
+    let text = String::from_utf8(output.stdout)
+        .with_context(|| format!("{} is not valid UTF-8", path.display()))?;
+    parse(&text).with_context(|| format!("Invalid env file {}", path.display()))
+}
+
+/// Parse `KEY=value` lines as written in `.env` files: blank lines and
+/// `#` comments are ignored, an `export ` prefix and matching quotes around
+/// the value are stripped.
+pub fn parse(text: &str) -> Result<Vec<(String, String)>> {
+    let mut vars = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        // Errors name the line only, never its content: it may be a secret
+        let (key, value) = line
+            .split_once('=')
+            .with_context(|| format!("line {} is not KEY=value", index + 1))?;
+        let key = key.trim();
+        anyhow::ensure!(
+            !key.is_empty()
+                && !key.starts_with(|c: char| c.is_ascii_digit())
+                && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'),
+            "line {} has an invalid variable name",
+            index + 1
+        );
+        vars.push((key.to_string(), unquote(value.trim()).to_string()));
+    }
+    Ok(vars)
+}
+
+fn unquote(value: &str) -> &str {
+    ['"', '\'']
+        .iter()
+        .find_map(|&quote| value.strip_prefix(quote)?.strip_suffix(quote))
+        .unwrap_or(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_env_file() {
+        let vars = parse(
+            r#"
+# Tokens for the release scripts
+GITHUB_TOKEN=ghp_abc123
+export HOMEBREW_GITHUB_API_TOKEN = "ghp def"
+NPM_TOKEN='npm=token'
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            vars,
+            [
+                ("GITHUB_TOKEN".to_string(), "ghp_abc123".to_string()),
+                (
+                    "HOMEBREW_GITHUB_API_TOKEN".to_string(),
+                    "ghp def".to_string()
+                ),
+                ("NPM_TOKEN".to_string(), "npm=token".to_string()),
+            ]
+        );
+
+        let err = parse("A=1\nsecret-value\n").unwrap_err();
+        assert_eq!(err.to_string(), "line 2 is not KEY=value");
+        assert!(parse("1A=x").is_err());
+    }
+}
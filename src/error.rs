@@ -1,7 +1,16 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
 use std::io;
+use std::sync::atomic::{AtomicU8, Ordering};
 use thiserror::Error;
 
+use crate::executor::{TaskResult, TaskStatus};
+use crate::failure::FailureClass;
+
 /// Custom error types for Tide
+///
+/// Every variant has a stable numeric [`code`](TideError::code) that is used
+/// as the process exit status and reported in JSON output.
 #[derive(Error, Debug)]
 pub enum TideError {
     #[error("Configuration error: {0}")]
@@ -9,4 +18,209 @@ pub enum TideError {
 
     #[error("IO error: {0}")]
     Io(#[from] io::Error),
+
+    #[error("Task '{task}' in group '{group}' failed{}", exit_code.map(|c| format!(" with exit code {}", c)).unwrap_or_default())]
+    TaskFailed {
+        group: String,
+        task: String,
+        exit_code: Option<i32>,
+    },
+
+    #[error("Task '{task}' in group '{group}' timed out")]
+    Timeout { group: String, task: String },
+
+    #[error("Sudo authentication failed: {0}")]
+    SudoAuth(String),
+
+    #[error("Keychain error: {0}")]
+    Keychain(String),
+
+    #[error("Network error: {0}")]
+    Network(String),
+
+    #[error("Cancelled: {0}")]
+    Cancelled(String),
+
+    #[error("Already running: {0}")]
+    AlreadyRunning(String),
+}
+
+impl TideError {
+    /// Stable numeric code; also the exit status of the `tide` binary
+    pub fn code(&self) -> i32 {
+        match self {
+            TideError::Config(_) => 10,
+            TideError::Io(_) => 11,
+            TideError::TaskFailed { .. } => 20,
+            TideError::Timeout { .. } => 21,
+            TideError::Network(_) => 22,
+            TideError::SudoAuth(_) => 30,
+            TideError::Keychain(_) => 31,
+            TideError::Cancelled(_) => 40,
+            TideError::AlreadyRunning(_) => 41,
+        }
+    }
+
+    /// Stable machine-readable name of the variant
+    pub fn kind(&self) -> &'static str {
+        match self {
+            TideError::Config(_) => "config",
+            TideError::Io(_) => "io",
+            TideError::TaskFailed { .. } => "task_failed",
+            TideError::Timeout { .. } => "timeout",
+            TideError::Network(_) => "network",
+            TideError::SudoAuth(_) => "sudo_auth",
+            TideError::Keychain(_) => "keychain",
+            TideError::Cancelled(_) => "cancelled",
+            TideError::AlreadyRunning(_) => "already_running",
+        }
+    }
+
+    /// Error describing the first failed task of a run, if any task failed
+    pub fn from_results(results: &[TaskResult]) -> Option<Self> {
+        let failed = results.iter().find(|r| r.status == TaskStatus::Failed)?;
+        let group = failed.group.clone();
+        let task = failed.name.clone();
+        Some(match failed.failure {
+            Some(FailureClass::Timeout) => TideError::Timeout { group, task },
+            Some(FailureClass::Network) => TideError::Network(format!(
+                "Task '{}' in group '{}' could not reach the network",
+                task, group
+            )),
+            _ => TideError::TaskFailed {
+                group,
+                task,
+                exit_code: failed.exit_code,
+            },
+        })
+    }
+}
+
+/// How errors map to the exit status (`settings.exit_code_policy`,
+/// `--exit-code-policy`)
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum ExitCodePolicy {
+    /// The error's own [`code`](TideError::code), one per kind
+    #[default]
+    Detailed,
+    /// 2 for configuration errors, 1 for every other failure
+    Simple,
+}
+
+impl ExitCodePolicy {
+    /// Exit status of a run that ended with `error`
+    pub fn status(self, error: &TideError) -> i32 {
+        match (self, error) {
+            (ExitCodePolicy::Detailed, error) => error.code(),
+            (ExitCodePolicy::Simple, TideError::Config(_)) => 2,
+            (ExitCodePolicy::Simple, _) => 1,
+        }
+    }
+}
+
+static POLICY: AtomicU8 = AtomicU8::new(ExitCodePolicy::Detailed as u8);
+
+/// Select the policy used by [`exit_status`] for the rest of the process
+pub fn set_exit_code_policy(policy: ExitCodePolicy) {
+    POLICY.store(policy as u8, Ordering::Relaxed);
+}
+
+/// The currently selected policy
+pub fn exit_code_policy() -> ExitCodePolicy {
+    match POLICY.load(Ordering::Relaxed) {
+        x if x == ExitCodePolicy::Simple as u8 => ExitCodePolicy::Simple,
+        _ => ExitCodePolicy::Detailed,
+    }
+}
+
+/// Exit status for an error returned from the CLI, following the selected
+/// [`ExitCodePolicy`]
+pub fn exit_status(error: &anyhow::Error) -> i32 {
+    error
+        .downcast_ref::<TideError>()
+        .map_or(1, |error| exit_code_policy().status(error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    fn result(name: &str, status: TaskStatus, failure: Option<FailureClass>) -> TaskResult {
+        TaskResult {
+            name: name.to_string(),
+            group: "Homebrew".to_string(),
+            group_icon: String::new(),
+            status,
+            started: Instant::now(),
+            duration: Duration::ZERO,
+            output: None,
+            exit_code: Some(1),
+            failure,
+            log_line: None,
+        }
+    }
+
+    #[test]
+    fn test_from_results_uses_first_failure() {
+        assert!(TideError::from_results(&[result("a", TaskStatus::Success, None)]).is_none());
+
+        let error = TideError::from_results(&[
+            result("a", TaskStatus::Success, None),
+            result("b", TaskStatus::Failed, Some(FailureClass::Timeout)),
+            result("c", TaskStatus::Failed, Some(FailureClass::NonZeroExit)),
+        ])
+        .unwrap();
+        assert_eq!(error.code(), 21);
+        assert_eq!(error.kind(), "timeout");
+
+        let error = TideError::from_results(&[result(
+            "c",
+            TaskStatus::Failed,
+            Some(FailureClass::NonZeroExit),
+        )])
+        .unwrap();
+        assert_eq!(
+            error.to_string(),
+            "Task 'c' in group 'Homebrew' failed with exit code 1"
+        );
+        assert_eq!(exit_status(&error.into()), 20);
+        assert_eq!(exit_status(&anyhow::anyhow!("other")), 1);
+    }
+
+    #[test]
+    fn test_exit_code_policy() {
+        let failed = TideError::Timeout {
+            group: "Homebrew".to_string(),
+            task: "Upgrade Packages".to_string(),
+        };
+        let config = TideError::Config("missing".to_string());
+        assert_eq!(ExitCodePolicy::Detailed.status(&failed), 21);
+        assert_eq!(ExitCodePolicy::Detailed.status(&config), 10);
+        assert_eq!(ExitCodePolicy::Simple.status(&failed), 1);
+        assert_eq!(ExitCodePolicy::Simple.status(&config), 2);
+        assert_eq!(
+            ExitCodePolicy::Simple.status(&TideError::Cancelled("q".to_string())),
+            1
+        );
+    }
+
+    #[test]
+    fn test_malformed_config_is_a_config_error() {
+        let dir = std::env::temp_dir().join(format!("tide-error-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for (name, text) in [
+            ("config.toml", "[settings\nverbose = true\n"),
+            ("config.yaml", "settings:\n  verbose: [true\n"),
+        ] {
+            let path = dir.join(name);
+            std::fs::write(&path, text).unwrap();
+            let error = crate::config::Config::load(Some(&path)).unwrap_err();
+            assert_eq!(exit_status(&error), 10, "{:#}", error);
+            let error = error.downcast_ref::<TideError>().unwrap();
+            assert_eq!(ExitCodePolicy::Simple.status(error), 2);
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 }
@@ -1,16 +1,40 @@
 use anyhow::{Context, Result};
-use colored::Colorize;
-use dialoguer::{Confirm, Password, theme::ColorfulTheme};
-use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
-use std::path::Path;
-use std::process::{Command, Stdio};
+use colored::{Color, Colorize};
+use dialoguer::{Confirm, theme::ColorfulTheme};
+use indicatif::{MultiProgress, ProgressBar, ProgressState, ProgressStyle};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::{Output, Stdio};
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::{Duration, Instant};
+use tokio::process::{Child, Command};
 
-use crate::config::TaskConfig;
+use crate::attention::{self, AttentionGuard};
+use crate::audit::AuditLog;
+use crate::config::{TaskConfig, TaskKind};
+use crate::controls::{ActiveTask, RunControls};
+use crate::envfile;
+use crate::error::TideError;
+use crate::failure::{self, FailureClass};
+use crate::integrations::{self, BrewCache, IntegrationContext};
 use crate::keychain;
-use crate::logger::Logger;
+use crate::logger::{LogEvent, Logger, TaskLog};
 use crate::notifications::NotificationManager;
+use crate::orphans;
+use crate::outputs::Outputs;
+use crate::palette::Paint;
+use crate::password;
+use crate::replay::Replay;
+use crate::runas;
+use crate::runenv::RunEnv;
+use crate::sandbox::Sandbox;
+use crate::tail::{self, OutputTail};
+use crate::ui;
+
+/// Base delay before retrying a transient network failure
+const RETRY_DELAY_SECS: u64 = 5;
 
 /// Task execution result
 #[derive(Debug)]
@@ -19,16 +43,99 @@ pub struct TaskResult {
     pub group: String,
     pub group_icon: String,
     pub status: TaskStatus,
+    pub started: Instant,
     pub duration: Duration,
     pub output: Option<String>,
+    pub exit_code: Option<i32>,
+    /// Why the task failed; also set for optional tasks whose error was
+    /// turned into a skip
+    pub failure: Option<FailureClass>,
+    /// Line of the log file where the task's outcome and output were written
+    pub log_line: Option<usize>,
+}
+
+impl TaskResult {
+    /// Result for a task whose preconditions failed before the run started
+    pub fn precondition_skipped(
+        name: String,
+        group: String,
+        group_icon: String,
+        reason: &SkipReason,
+    ) -> Self {
+        Self {
+            name,
+            group,
+            group_icon,
+            status: TaskStatus::Skipped,
+            started: Instant::now(),
+            duration: Duration::ZERO,
+            output: Some(reason.to_string()),
+            exit_code: None,
+            failure: None,
+            log_line: None,
+        }
+    }
+
+    /// Result for a task that never started because the run was aborted
+    pub fn aborted(name: String, group: String, group_icon: String) -> Self {
+        Self {
+            name,
+            group,
+            group_icon,
+            status: TaskStatus::Skipped,
+            started: Instant::now(),
+            duration: Duration::ZERO,
+            output: Some("Run aborted by user".to_string()),
+            exit_code: None,
+            failure: None,
+            log_line: None,
+        }
+    }
 }
 
 /// Task execution status
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum TaskStatus {
     Success,
     Failed,
     Skipped,
+    /// Stopped because tide was interrupted (SIGINT/SIGTERM) while it ran
+    Cancelled,
+}
+
+/// Names of a task for log entries: plain for JSON records, with icons
+/// for the text log
+struct TaskNames<'a> {
+    group: &'a str,
+    task: &'a str,
+    group_label: &'a str,
+    task_label: &'a str,
+}
+
+/// How a running command's output is shown while it is captured
+struct LiveOutput {
+    /// Prefix for lines streamed in verbose mode
+    label: String,
+    /// Last lines shown beneath the spinner
+    tail: Option<Arc<OutputTail>>,
+    /// File receiving the task's complete output (`log_dir`)
+    log: Option<TaskLog>,
+}
+
+/// Arguments of `sudo` with the environment handed to it and the directory
+/// the command starts in
+struct SudoCommand<'a> {
+    args: &'a [String],
+    env: &'a [(String, String)],
+    dir: Option<&'a Path>,
+}
+
+/// Style of task spinners; the bar prefix is used for indentation
+pub fn spinner_style() -> ProgressStyle {
+    ProgressStyle::with_template("{prefix}{spinner:.cyan} {msg}{tail}")
+        .unwrap()
+        .tick_strings(&["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"])
 }
 
 /// Task executor with progress tracking
@@ -38,8 +145,32 @@ pub struct TaskExecutor {
     pub dry_run: bool,
     pub verbose: bool,
     pub notifier: Arc<NotificationManager>,
+    pub controls: Arc<RunControls>,
+    /// Recorded outcomes to serve instead of executing tasks (`--replay`)
+    pub replay: Option<Arc<Replay>>,
+    /// Lines of live output shown under each running spinner (0 disables)
+    pub output_tail_lines: usize,
+    /// How long a sudo password prompt waits for input (`None` waits forever)
+    pub sudo_prompt_timeout: Option<Duration>,
+    /// Wrong passwords accepted before sudo authentication fails
+    pub sudo_password_attempts: u32,
+    sudo_prompt_expired: Arc<AtomicBool>,
+    /// Hash-chained record of every executed command
+    pub audit: Option<Arc<AuditLog>>,
+    /// Run metadata exported to task commands as `TIDE_*` variables
+    pub run_env: Arc<RunEnv>,
+    /// Stdout of tasks with `register`, interpolated into later tasks
+    pub outputs: Arc<Outputs>,
+    /// Homebrew queries shared by the built-in brew tasks of the run
+    pub brew: Arc<BrewCache>,
+    /// age identity for `env_file_encrypted`, resolved against the config
+    pub age_identity: Option<PathBuf>,
+    /// Print no per-task lines; the run is reported as a whole (`--output json`)
+    pub silent: bool,
     logger: Option<Arc<Logger>>,
     show_progress: bool,
+    retry_transient: u32,
+    attention_repeat: Option<Duration>,
 }
 
 impl TaskExecutor {
@@ -49,22 +180,47 @@ impl TaskExecutor {
         verbose: bool,
         notifications_enabled: bool,
         show_progress: bool,
+        retry_transient: u32,
+        attention_repeat: Option<Duration>,
         logger: Option<Arc<Logger>>,
     ) -> Self {
+        let multi_progress = show_progress.then(|| Arc::new(MultiProgress::new()));
         Self {
-            multi_progress: show_progress.then(|| Arc::new(MultiProgress::new())),
+            controls: Arc::new(RunControls::new(multi_progress.clone(), verbose)),
+            multi_progress,
             dry_run,
             verbose,
             notifier: Arc::new(NotificationManager::new(notifications_enabled)),
+            replay: None,
+            output_tail_lines: 0,
+            sudo_prompt_timeout: None,
+            sudo_password_attempts: 3,
+            sudo_prompt_expired: Arc::new(AtomicBool::new(false)),
+            audit: None,
+            run_env: Arc::new(RunEnv::new(dry_run, None)),
+            outputs: Arc::new(Outputs::new()),
+            brew: Arc::new(BrewCache::new()),
+            age_identity: None,
+            silent: false,
             logger,
             show_progress,
+            retry_transient,
+            attention_repeat,
         }
     }
 
+    /// Escalate a sudo password prompt until the returned guard is dropped
+    fn sudo_attention(&self) -> AttentionGuard {
+        let notifier = Arc::clone(&self.notifier);
+        AttentionGuard::request(self.attention_repeat, move || {
+            let _ = notifier.notify_sudo_required();
+        })
+    }
+
     fn update_progress(&self, pb: &ProgressBar, message: &str) {
         if self.show_progress {
             pb.set_message(message.to_string());
-        } else {
+        } else if !self.silent {
             println!("{}", message);
         }
     }
@@ -72,77 +228,110 @@ impl TaskExecutor {
     fn finish_progress(&self, pb: &ProgressBar, message: &str) {
         if self.show_progress {
             pb.finish_with_message(message.to_string());
-        } else {
+        } else if !self.silent {
             println!("{}", message);
         }
     }
 
-    fn log_line(&self, message: String) {
-        if let Some(logger) = &self.logger
-            && let Err(err) = logger.log_line(&message)
-            && self.verbose
-        {
-            eprintln!("{}", format!("Failed to write log entry: {}", err).yellow());
+    fn log_event(&self, event: LogEvent, text: String) -> Option<usize> {
+        match self.logger.as_ref()?.log_event(&event, &text) {
+            Ok(line) => Some(line),
+            Err(err) => {
+                if self.verbose {
+                    eprintln!(
+                        "{}",
+                        format!("Failed to write log entry: {}", err).warning()
+                    );
+                }
+                None
+            }
+        }
+    }
+
+    /// Open the task's own log file when `log_dir` is set and its output
+    /// is logged at all
+    fn open_task_log(&self, group: &str, task: &str, log_output: bool) -> Option<TaskLog> {
+        if !log_output {
+            return None;
+        }
+        match self.logger.as_ref()?.task_log(group, task) {
+            Ok(log) => log,
+            Err(err) => {
+                if self.verbose {
+                    eprintln!("{}", format!("Failed to open task log: {}", err).warning());
+                }
+                None
+            }
         }
     }
 
     fn log_task_completion(
         &self,
-        group_label: &str,
-        task_label: &str,
+        names: &TaskNames,
         status: TaskStatus,
         duration: Duration,
         output: Option<&str>,
-    ) {
-        if self.logger.is_none() {
-            return;
-        }
+        task_log: Option<&TaskLog>,
+    ) -> Option<usize> {
+        self.logger.as_ref()?;
 
         let status_prefix = match status {
             TaskStatus::Success => "✓ SUCCESS",
             TaskStatus::Failed => "✗ FAILED",
             TaskStatus::Skipped => "○ SKIPPED",
+            TaskStatus::Cancelled => "⊘ CANCELLED",
         };
-        self.log_line(format!(
-            "{} [{}] {} ({})",
-            status_prefix,
-            group_label,
-            task_label,
-            format_duration(duration)
-        ));
+        if let Some(task_log) = task_log
+            && let Err(err) = task_log.finish(
+                &format!("{} ({})", status_prefix, ui::format_duration(duration)),
+                output,
+            )
+            && self.verbose
+        {
+            eprintln!("{}", format!("Failed to write task log: {}", err).warning());
+        }
+        let line = self.log_event(
+            LogEvent::TaskFinished {
+                group: names.group,
+                task: names.task,
+                status,
+                duration_secs: duration.as_secs_f64(),
+            },
+            format!(
+                "{} [{}] {} ({})",
+                status_prefix,
+                names.group_label,
+                names.task_label,
+                ui::format_duration(duration)
+            ),
+        );
 
         if let Some(output) = output {
             let trimmed = output.trim();
             if trimmed.is_empty() {
-                return;
-            }
-            let header = format!("└ output [{}] {}", group_label, task_label);
-            if let Some(logger) = &self.logger
-                && let Err(err) = logger.log_block(&header, trimmed)
-                && self.verbose
-            {
-                eprintln!("{}", format!("Failed to write log entry: {}", err).yellow());
+                return line;
             }
+            self.log_event(
+                LogEvent::Output {
+                    group: names.group,
+                    task: names.task,
+                    output: trimmed,
+                },
+                format!("└ output [{}] {}", names.group_label, names.task_label),
+            );
         }
+        line
     }
 
     /// Ensure sudo authentication is valid before executing tasks
     /// This prevents tasks from hanging on password prompts
     /// Returns Ok if auth succeeded or was already valid
-    /// Returns Err only if user provided wrong password
+    /// Returns Err if the user skipped, ran out of attempts or the prompt timed out
     pub async fn ensure_sudo_auth(&self, keychain_label: &str) -> Result<()> {
         // Check if sudo timestamp is already cached
-        if Command::new("sudo")
-            .arg("-n")
-            .arg("true")
-            .stdout(Stdio::null())
-            .stderr(Stdio::null())
-            .status()
-            .map(|s| s.success())
-            .unwrap_or(false)
-        {
+        if sudo_timestamp_valid().await {
             if self.verbose {
-                println!("{}", "✓ Sudo timestamp already valid".green());
+                println!("{}", "✓ Sudo timestamp already valid".success());
             }
             return Ok(());
         }
@@ -151,7 +340,7 @@ impl TaskExecutor {
         if let Ok(password) = keychain::get_password(keychain_label) {
             if authenticate_sudo(&password).await? {
                 if self.verbose {
-                    println!("{}", "✓ Sudo authenticated via keychain".green());
+                    println!("{}", "✓ Sudo authenticated via keychain".success());
                 }
                 return Ok(());
             } else {
@@ -159,7 +348,7 @@ impl TaskExecutor {
                 if self.verbose {
                     println!(
                         "{}",
-                        "⚠️  Keychain password is outdated, prompting for new password".yellow()
+                        "⚠️  Keychain password is outdated, prompting for new password".warning()
                     );
                 }
             }
@@ -171,59 +360,94 @@ impl TaskExecutor {
             "🔐 Some tasks may require sudo privileges.".bright_blue()
         );
 
-        // Send desktop notification
-        let _ = self.notifier.notify_sudo_required();
-
-        let password = match Password::with_theme(&ColorfulTheme::default())
-            .with_prompt("Enter sudo password (or press Ctrl+C to skip)")
-            .allow_empty_password(true)
-            .interact()
+        let password = match self
+            .prompt_sudo_password("Enter sudo password (or press Enter to skip)")
+            .await?
         {
-            Ok(pwd) if pwd.is_empty() => {
-                println!("{}", "Skipping sudo authentication.".yellow());
-                return Err(anyhow::anyhow!("User skipped sudo authentication"));
-            }
-            Ok(pwd) => pwd,
-            Err(_) => {
-                println!("{}", "Sudo authentication cancelled.".yellow());
-                return Err(anyhow::anyhow!("User cancelled sudo authentication"));
+            Some(password) => password,
+            None => {
+                println!("{}", "Skipping sudo authentication.".warning());
+                return Err(
+                    TideError::Cancelled("User skipped sudo authentication".to_string()).into(),
+                );
             }
         };
 
-        if !authenticate_sudo(&password).await? {
-            return Err(anyhow::anyhow!("Invalid sudo password"));
-        }
-
         if self.verbose {
-            println!("{}", "✓ Sudo authenticated successfully".green());
+            println!("{}", "✓ Sudo authenticated successfully".success());
         }
 
         // Optionally save password into keychain
         if !keychain::entry_exists(keychain_label)
-            && Confirm::with_theme(&ColorfulTheme::default())
-                .with_prompt("Save password to keychain for future use?")
-                .default(true)
-                .interact()?
+            && self.controls.suspend(|| {
+                Confirm::with_theme(&ColorfulTheme::default())
+                    .with_prompt("Save password to keychain for future use?")
+                    .default(true)
+                    .interact()
+            })?
         {
             keychain::save_password(keychain_label, &password)?;
             println!(
                 "{}",
-                "✓ Password saved to keychain (service: tide-sudo)".green()
+                "✓ Password saved to keychain (service: tide-sudo)".success()
             );
         }
 
         Ok(())
     }
 
+    /// Prompt until sudo accepts the password, at most `sudo_password_attempts`
+    /// times, giving up after `sudo_prompt_timeout` without input. Once a
+    /// prompt timed out nobody is assumed to be at the keyboard, and later
+    /// prompts fail right away. Returns `None` for an empty password.
+    async fn prompt_sudo_password(&self, prompt: &str) -> Result<Option<String>> {
+        if !self.controls.is_interactive() {
+            return Err(TideError::SudoAuth(
+                "no terminal to prompt for the password (store it in the Keychain)".to_string(),
+            )
+            .into());
+        }
+        if self.sudo_prompt_expired.load(Ordering::SeqCst) {
+            return Err(TideError::SudoAuth(
+                "an earlier password prompt timed out without input".to_string(),
+            )
+            .into());
+        }
+
+        // Ring the bell and notify, repeating until the password is entered
+        let _attention = self.sudo_attention();
+        let attempts = self.sudo_password_attempts.max(1);
+        for attempt in 1..=attempts {
+            let password = self
+                .controls
+                .suspend(|| password::read_password(prompt, self.sudo_prompt_timeout))
+                .map_err(|err| TideError::Cancelled(format!("Password prompt failed: {}", err)))?;
+            let Some(password) = password else {
+                self.sudo_prompt_expired.store(true, Ordering::SeqCst);
+                let waited = self.sudo_prompt_timeout.unwrap_or_default().as_secs();
+                return Err(
+                    TideError::SudoAuth(format!("no password entered within {}s", waited)).into(),
+                );
+            };
+            if password.is_empty() {
+                return Ok(None);
+            }
+            if authenticate_sudo(&password).await? {
+                return Ok(Some(password));
+            }
+            if attempt < attempts {
+                self.controls
+                    .print("Sorry, try again.".warning().to_string());
+            }
+        }
+        Err(TideError::SudoAuth(format!("incorrect password after {} attempts", attempts)).into())
+    }
+
     /// Create a configured spinner progress bar
     pub fn new_spinner(&self) -> ProgressBar {
         if let Some(multi) = &self.multi_progress {
             let pb = multi.add(ProgressBar::new_spinner());
-            pb.set_style(
-                ProgressStyle::with_template("{spinner:.cyan} {msg}")
-                    .unwrap()
-                    .tick_strings(&["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"]),
-            );
+            pb.set_style(spinner_style());
             pb.enable_steady_tick(Duration::from_millis(120));
             pb
         } else {
@@ -234,7 +458,7 @@ impl TaskExecutor {
     /// Execute a single task
     pub async fn execute_task(
         &self,
-        task: TaskConfig,
+        mut task: TaskConfig,
         group_name: String,
         group_icon: String,
         pb: ProgressBar,
@@ -245,109 +469,130 @@ impl TaskExecutor {
         let group_label = format_group_label(&group_name, &group_icon);
         let task_label = format_task_label(&task_name, &task.icon);
         let progress_label = format!("[{}] {}", group_label, task_label);
+        let names = TaskNames {
+            group: &group_name,
+            task: &task_name,
+            group_label: &group_label,
+            task_label: &task_label,
+        };
         let running_message = format!("{} {}", progress_label.bold(), "Running…".bright_white());
         self.update_progress(&pb, &running_message);
 
-        let mut cmd = task.command.clone();
+        let mut cmd = task.effective_command();
+        let missing_output = self.outputs.apply(&mut cmd, &mut task).err();
         if task.sudo && !cmd.is_empty() && cmd[0] != "sudo" {
             cmd.insert(0, "sudo".to_string());
         }
-        let command_display = if cmd.is_empty() {
+        if let Some(sandbox) = task.sandbox.as_deref().and_then(Sandbox::parse)
+            && task.kind.is_none()
+        {
+            cmd = sandbox.wrap(cmd);
+        }
+        if let Some(user) = &task.run_as {
+            cmd = runas::wrap(user, cmd);
+        }
+        let command_display = if let Some(kind) = task.kind {
+            format!("<{}>", kind.as_str())
+        } else if cmd.is_empty() {
             "<empty command>".to_string()
         } else {
             cmd.join(" ")
         };
-        self.log_line(format!(
-            "▶ [{}] {} :: {}",
-            group_label, task_label, command_display
-        ));
+        self.log_event(
+            LogEvent::TaskStarted {
+                group: &group_name,
+                task: &task_name,
+                command: &command_display,
+            },
+            format!("▶ [{}] {} :: {}", group_label, task_label, command_display),
+        );
+
+        if let Some(replay) = &self.replay {
+            let result = match replay.take(&group_name, &task_name) {
+                Some(recorded) => recorded.into_result(group_icon),
+                None => TaskResult {
+                    name: task_name.clone(),
+                    group: group_name.clone(),
+                    group_icon,
+                    status: TaskStatus::Skipped,
+                    started: start,
+                    duration: Duration::ZERO,
+                    output: Some("Not in recording".to_string()),
+                    exit_code: None,
+                    failure: None,
+                    log_line: None,
+                },
+            };
+            return self.finish_replayed(result, &pb, &progress_label, &names, task.log_output);
+        }
 
         if self.dry_run {
             tokio::time::sleep(Duration::from_millis(100)).await;
             let dry_run_msg = format!(
                 "{} {} {}",
                 progress_label.bold(),
-                "○".yellow(),
+                "○".warning(),
                 "[dry run]".dimmed()
             );
             self.finish_progress(&pb, &dry_run_msg);
             let duration = start.elapsed();
             let reason = "Dry run - command not executed".to_string();
-            self.log_task_completion(
-                &group_label,
-                &task_label,
+            let log_line = self.log_task_completion(
+                &names,
                 TaskStatus::Skipped,
                 duration,
                 Some(reason.as_str()),
+                None,
             );
             return TaskResult {
                 name: task_name.clone(),
                 group: group_name,
                 group_icon,
                 status: TaskStatus::Skipped,
+                started: start,
                 duration,
                 output: Some(reason),
+                exit_code: None,
+                failure: None,
+                log_line,
             };
         }
 
         // Check preconditions
-        if let Some(check_cmd) = &task.check_command
-            && !keychain::command_exists(check_cmd)
-        {
+        let preconditions = match missing_output {
+            Some(name) => Err(SkipReason::MissingOutput(name)),
+            None => check_preconditions(&task),
+        };
+        if let Err(reason) = preconditions {
             let skip_msg = format!(
                 "{} {}",
                 progress_label.bold(),
-                "[skipped: command not found]".dimmed()
+                format!("[skipped: {}]", reason.short()).dimmed()
             );
             self.finish_progress(&pb, &skip_msg);
             let duration = start.elapsed();
-            let reason = format!("Command '{}' not found", check_cmd);
-            self.log_task_completion(
-                &group_label,
-                &task_label,
+            let reason = reason.to_string();
+            let log_line = self.log_task_completion(
+                &names,
                 TaskStatus::Skipped,
                 duration,
                 Some(reason.as_str()),
+                None,
             );
             return TaskResult {
                 name: task_name.clone(),
                 group: group_name,
                 group_icon,
                 status: TaskStatus::Skipped,
+                started: start,
                 duration,
                 output: Some(reason),
+                exit_code: None,
+                failure: None,
+                log_line,
             };
         }
 
-        if let Some(check_path) = &task.check_path {
-            let expanded = shellexpand::tilde(check_path);
-            if !Path::new(expanded.as_ref()).exists() {
-                let skip_msg = format!(
-                    "{} {}",
-                    progress_label.bold(),
-                    "[skipped: path not found]".dimmed()
-                );
-                self.finish_progress(&pb, &skip_msg);
-                let duration = start.elapsed();
-                let reason = format!("Path '{}' not found", check_path);
-                self.log_task_completion(
-                    &group_label,
-                    &task_label,
-                    TaskStatus::Skipped,
-                    duration,
-                    Some(reason.as_str()),
-                );
-                return TaskResult {
-                    name: task_name.clone(),
-                    group: group_name,
-                    group_icon,
-                    status: TaskStatus::Skipped,
-                    duration,
-                    output: Some(reason),
-                };
-            }
-        }
-
         // Warn if command might internally call sudo (heuristic check)
         if !task.sudo && self.verbose && !cmd.is_empty() {
             let cmd_str = cmd.join(" ").to_lowercase();
@@ -358,50 +603,176 @@ impl TaskExecutor {
                         "⚠️  Task '{}' may call sudo internally. Consider setting 'sudo: true'",
                         task_name
                     )
-                    .yellow()
+                    .warning()
                 ));
             }
         }
 
-        // Execute command
-        let result = if cmd.first().map(|s| s.as_str()) == Some("sudo") {
-            self.run_sudo_command(&cmd[1..], keychain_label).await
-        } else {
-            self.run_command(&cmd, &task, &task_name, &group_name).await
+        let live = LiveOutput {
+            label: stream_label(
+                &task_name,
+                if task.icon.trim().is_empty() {
+                    &group_icon
+                } else {
+                    &task.icon
+                },
+            ),
+            tail: self.attach_output_tail(&pb),
+            log: self.open_task_log(&group_name, &task_name, task.log_output),
         };
 
+        // Execute command, racing it against a skip request from the keyboard
+        let active = self.controls.register(&task_name);
+        let mut retries = 0;
+        let result = loop {
+            let execution = async {
+                if let Some(kind) = task.kind {
+                    self.run_builtin(kind, &task, &group_name).await
+                } else if task.run_as.is_some() {
+                    self.run_as_command(&cmd, &task, &group_name, keychain_label, &active, &live)
+                        .await
+                } else if cmd.first().map(|s| s.as_str()) == Some("sudo") {
+                    self.run_sudo_task(&cmd, &task, &group_name, keychain_label, &active, &live)
+                        .await
+                } else {
+                    self.run_command(&cmd, &task, &task_name, &group_name, &active, &live)
+                        .await
+                }
+            };
+            let result = tokio::select! {
+                result = execution => result,
+                _ = active.skip_requested() => Err(anyhow::anyhow!("Skipped by user")),
+            };
+
+            // Retry transient network failures after a growing delay
+            match &result {
+                Err(e)
+                    if retries < self.retry_transient
+                        && !active.was_skipped()
+                        && !active.was_cancelled()
+                        && classify_error(e).1 == FailureClass::Network =>
+                {
+                    retries += 1;
+                    let delay = Duration::from_secs(RETRY_DELAY_SECS * retries as u64);
+                    let retry_message = format!(
+                        "{} {}",
+                        progress_label.bold(),
+                        format!(
+                            "⟳ network error, retrying in {}s ({}/{})",
+                            delay.as_secs(),
+                            retries,
+                            self.retry_transient
+                        )
+                        .warning()
+                    );
+                    self.update_progress(&pb, &retry_message);
+                    let error = e.to_string();
+                    self.log_event(
+                        LogEvent::TaskRetry {
+                            group: &group_name,
+                            task: &task_name,
+                            attempt: retries,
+                            attempts: self.retry_transient,
+                            error: error.trim(),
+                        },
+                        format!(
+                            "⟳ [{}] {} transient network error, retry {}/{}: {}",
+                            group_label,
+                            task_label,
+                            retries,
+                            self.retry_transient,
+                            error.trim()
+                        ),
+                    );
+                    tokio::select! {
+                        _ = tokio::time::sleep(delay) => {}
+                        _ = active.skip_requested() => break result,
+                    }
+                    self.update_progress(&pb, &running_message);
+                }
+                _ => break result,
+            }
+        };
+        self.controls.unregister(&active);
+        if let Some(tail) = &live.tail {
+            tail.clear();
+        }
+        // Commands involving brew may have changed what is installed
+        if task.kind.is_none() && cmd.iter().any(|arg| arg.contains("brew")) {
+            self.brew.invalidate().await;
+        }
+
+        let mut exit_code = None;
+        let mut failure = None;
         let (status, output) = match result {
+            _ if active.was_cancelled() => (
+                TaskStatus::Cancelled,
+                Some("Cancelled: tide was interrupted".to_string()),
+            ),
+            _ if active.was_skipped() => (TaskStatus::Skipped, Some("Skipped by user".to_string())),
             Ok(output) => (TaskStatus::Success, Some(output)),
             Err(e) if task.required => {
                 // Send notification for failed required task
                 let _ = self
                     .notifier
                     .notify_task_failed(&task_name, &group_name, &e.to_string());
+                let (code, class) = classify_error(&e);
+                exit_code = code;
+                failure = Some(class);
                 (TaskStatus::Failed, Some(e.to_string()))
             }
-            Err(e) => (TaskStatus::Skipped, Some(e.to_string())),
+            Err(e) => {
+                let (code, class) = classify_error(&e);
+                exit_code = code;
+                failure = Some(class);
+                (TaskStatus::Skipped, Some(e.to_string()))
+            }
         };
 
+        if let Some(name) = &task.register
+            && status == TaskStatus::Success
+        {
+            self.outputs
+                .record(name, output.as_deref().unwrap_or_default());
+        }
+
+        if let Some(audit) = &self.audit
+            && let Err(err) = audit.append(
+                &group_name,
+                &task_name,
+                &command_display,
+                task.sudo || cmd.first().is_some_and(|program| program == "sudo"),
+                exit_code.or((status == TaskStatus::Success).then_some(0)),
+                status,
+            )
+        {
+            pb.println(format!(
+                "{}",
+                format!("⚠️  Failed to write audit log: {}", err).warning()
+            ));
+        }
+
         let duration = start.elapsed();
         let status_icon = match status {
-            TaskStatus::Success => "✓".green(),
-            TaskStatus::Failed => "✗".red(),
-            TaskStatus::Skipped => "○".yellow(),
+            TaskStatus::Success => "✓".success(),
+            TaskStatus::Failed => "✗".failure(),
+            TaskStatus::Skipped => "○".warning(),
+            TaskStatus::Cancelled => "⊘".warning(),
         };
 
         let completion_message = format!(
             "{} {} {}",
             progress_label.bold(),
             status_icon,
-            format!("({})", format_duration(duration)).dimmed()
+            format!("({})", ui::format_duration(duration)).dimmed()
         );
         self.finish_progress(&pb, &completion_message);
-        self.log_task_completion(
-            &group_label,
-            &task_label,
+        let log_line = self.log_task_completion(
+            &names,
             status,
             duration,
-            output.as_deref(),
+            output.as_deref().filter(|_| task.log_output),
+            live.log.as_ref(),
         );
 
         TaskResult {
@@ -409,9 +780,187 @@ impl TaskExecutor {
             group: group_name,
             group_icon,
             status,
+            started: start,
             duration,
             output,
+            exit_code,
+            failure,
+            log_line,
+        }
+    }
+
+    /// Show the last lines of the task's output beneath its spinner
+    fn attach_output_tail(&self, pb: &ProgressBar) -> Option<Arc<OutputTail>> {
+        if !self.show_progress || self.output_tail_lines == 0 {
+            return None;
+        }
+        let tail = Arc::new(OutputTail::new(self.output_tail_lines));
+        let rendered = Arc::clone(&tail);
+        pb.set_style(spinner_style().with_key(
+            "tail",
+            move |_: &ProgressState, w: &mut dyn std::fmt::Write| {
+                let width = crossterm::terminal::size().map_or(80, |(cols, _)| cols as usize);
+                let _ = w.write_str(&rendered.render(width));
+            },
+        ));
+        Some(tail)
+    }
+
+    /// Report a replayed result exactly like a freshly executed one
+    fn finish_replayed(
+        &self,
+        mut result: TaskResult,
+        pb: &ProgressBar,
+        progress_label: &str,
+        names: &TaskNames<'_>,
+        log_output: bool,
+    ) -> TaskResult {
+        let status_icon = match result.status {
+            TaskStatus::Success => "✓".success(),
+            TaskStatus::Failed => "✗".failure(),
+            TaskStatus::Skipped => "○".warning(),
+            TaskStatus::Cancelled => "⊘".warning(),
+        };
+        let completion_message = format!(
+            "{} {} {}",
+            progress_label.bold(),
+            status_icon,
+            format!("({}, replayed)", ui::format_duration(result.duration)).dimmed()
+        );
+        self.finish_progress(pb, &completion_message);
+        result.log_line = self.log_task_completion(
+            names,
+            result.status,
+            result.duration,
+            result.output.as_deref().filter(|_| log_output),
+            None,
+        );
+
+        if result.status == TaskStatus::Failed {
+            let _ = self.notifier.notify_task_failed(
+                &result.name,
+                &result.group,
+                result.output.as_deref().unwrap_or_default(),
+            );
+        }
+        result
+    }
+
+    /// Run a built-in task type under the task's timeout
+    async fn run_builtin(
+        &self,
+        kind: TaskKind,
+        task: &TaskConfig,
+        group_name: &str,
+    ) -> Result<String> {
+        let ctx = IntegrationContext {
+            task,
+            controls: &self.controls,
+            brew: &self.brew,
+        };
+        let timeout_secs = task.timeout.unwrap_or(300);
+
+        match tokio::time::timeout(
+            Duration::from_secs(timeout_secs),
+            integrations::run(kind, &ctx),
+        )
+        .await
+        {
+            Ok(result) => result,
+            Err(_) => {
+                let _ = self
+                    .notifier
+                    .notify_task_timeout(&task.name, group_name, timeout_secs);
+                Err(CommandTimedOut {
+                    secs: timeout_secs,
+                    hint: "",
+                }
+                .into())
+            }
+        }
+    }
+
+    /// Environment of a task's command: tide's run variables, the decrypted
+    /// env file and the task's own `env`, later entries overriding earlier ones
+    async fn task_env(
+        &self,
+        task: &TaskConfig,
+        group_name: &str,
+        task_name: &str,
+    ) -> Result<Vec<(String, String)>> {
+        let mut env: Vec<(String, String)> = self
+            .run_env
+            .task_vars(group_name, task_name)
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value))
+            .collect();
+        if let Some(file) = &task.env_file_encrypted {
+            let identity = self
+                .age_identity
+                .as_deref()
+                .context("env_file_encrypted needs the age_identity setting")?;
+            env.extend(envfile::load_encrypted(Path::new(file), identity).await?);
         }
+        env.extend(task.env.iter().map(|(k, v)| (k.clone(), v.clone())));
+        Ok(env)
+    }
+
+    /// Run a `run_as` task's command as the other user. `cmd` is already
+    /// wrapped by [`runas::wrap`]; the prefix is rebuilt so sudo keeps the
+    /// task's environment, and the working directory is resolved against
+    /// that user's home.
+    async fn run_as_command(
+        &self,
+        cmd: &[String],
+        task: &TaskConfig,
+        group_name: &str,
+        keychain_label: &str,
+        active: &ActiveTask,
+        live: &LiveOutput,
+    ) -> Result<String> {
+        let user = task.run_as.as_deref().context("Task has no run_as user")?;
+        let env = self.task_env(task, group_name, &task.name).await?;
+        let keys: Vec<&str> = env.iter().map(|(key, _)| key.as_str()).collect();
+        let mut args = runas::sudo_args(user, &keys);
+        args.extend_from_slice(&cmd[runas::wrap(user, Vec::new()).len()..]);
+        let dir = task
+            .working_dir
+            .as_deref()
+            .map(|dir| runas::working_dir(user, dir));
+        let sudo = SudoCommand {
+            args: &args[1..],
+            env: &env,
+            dir: dir.as_deref(),
+        };
+        self.run_sudo_command(sudo, task, group_name, keychain_label, active, live)
+            .await
+    }
+
+    /// Run a command starting with `sudo`, keeping the task's environment
+    /// across sudo and starting it in the task's working directory
+    async fn run_sudo_task(
+        &self,
+        cmd: &[String],
+        task: &TaskConfig,
+        group_name: &str,
+        keychain_label: &str,
+        active: &ActiveTask,
+        live: &LiveOutput,
+    ) -> Result<String> {
+        let env = self.task_env(task, group_name, &task.name).await?;
+        let keys: Vec<&str> = env.iter().map(|(key, _)| key.as_str()).collect();
+        let args = runas::sudo_with_env(cmd, &keys);
+        let dir = task
+            .working_dir
+            .as_deref()
+            .map(|dir| PathBuf::from(shellexpand::tilde(dir).as_ref()));
+        let sudo = SudoCommand {
+            args: &args[1..],
+            env: &env,
+            dir: dir.as_deref(),
+        };
+        self.run_sudo_command(sudo, task, group_name, keychain_label, active, live)
+            .await
     }
 
     /// Run a regular command
@@ -421,6 +970,8 @@ impl TaskExecutor {
         task: &TaskConfig,
         task_name: &str,
         group_name: &str,
+        active: &ActiveTask,
+        live: &LiveOutput,
     ) -> Result<String> {
         if cmd.is_empty() {
             return Err(anyhow::anyhow!("Empty command"));
@@ -435,121 +986,386 @@ impl TaskExecutor {
             command.current_dir(expanded.as_ref());
         }
 
-        // Set environment variables
-        for (key, value) in &task.env {
-            command.env(key, value);
-        }
+        command.envs(self.task_env(task, group_name, task_name).await?);
 
         // CRITICAL: Redirect stdin to /dev/null to prevent blocking on password prompts
         // This prevents commands from hanging if they internally require interactive input
         command.stdin(Stdio::null());
 
-        if !self.verbose {
-            command.stdout(Stdio::piped()).stderr(Stdio::piped());
-        }
+        command.stdout(Stdio::piped()).stderr(Stdio::piped());
+
+        // Its own process group, so skipping or aborting reaches grandchildren
+        command.process_group(0);
+
+        let child = command.spawn()?;
+        let pid = child.id().context("Command exited before it was tracked")?;
+        let tracked = orphans::track(pid, &cmd[0]);
+        active.set_pid(pid);
 
         // Apply timeout if specified in task config
-        let command_future = tokio::task::spawn_blocking(move || command.output());
         let timeout_secs = task.timeout.unwrap_or(300);
+        let Some(output) = self
+            .collect_within(child, &tracked, live, timeout_secs)
+            .await?
+        else {
+            // Send notification that task timed out (likely waiting for input)
+            attention::ring();
+            let _ = self
+                .notifier
+                .notify_interactive_input_detected(task_name, group_name);
+            let _ = self
+                .notifier
+                .notify_task_timeout(task_name, group_name, timeout_secs);
 
-        let output = match tokio::time::timeout(Duration::from_secs(timeout_secs), command_future)
-            .await
-        {
-            Ok(Ok(result)) => result?,
-            Ok(Err(e)) => return Err(anyhow::anyhow!("Command execution error: {}", e)),
-            Err(_) => {
-                // Send notification that task timed out (likely waiting for input)
-                let _ = self
-                    .notifier
-                    .notify_interactive_input_detected(task_name, group_name);
-                let _ = self
-                    .notifier
-                    .notify_task_timeout(task_name, group_name, timeout_secs);
-
-                return Err(anyhow::anyhow!(
-                    "Command timed out after {} seconds. This may indicate the command is waiting for input (like sudo password). Consider setting 'sudo: true' or 'timeout: <seconds>' in the task config.",
-                    timeout_secs
-                ));
+            return Err(CommandTimedOut {
+                secs: timeout_secs,
+                hint: " This may indicate the command is waiting for input (like sudo password). Consider setting 'sudo: true' or 'timeout: <seconds>' in the task config.",
             }
+            .into());
         };
 
-        if output.status.success() {
-            Ok(String::from_utf8_lossy(&output.stdout).to_string())
-        } else {
-            Err(anyhow::anyhow!(
-                "Command failed: {}",
-                String::from_utf8_lossy(&output.stderr)
-            ))
-        }
-    }
-
-    /// Run a sudo command with keychain support
-    async fn run_sudo_command(&self, args: &[String], keychain_label: &str) -> Result<String> {
-        // Helper to actually execute the sudo command once authentication timestamp is valid.
-        fn run_actual(args: &[String]) -> Result<String> {
-            let output = Command::new("sudo")
-                .args(args)
-                .output()
-                .context("Failed to execute sudo command")?;
-            if output.status.success() {
-                Ok(String::from_utf8_lossy(&output.stdout).to_string())
-            } else {
-                Err(anyhow::anyhow!(
-                    "Command failed: {}",
-                    String::from_utf8_lossy(&output.stderr)
-                ))
+        command_output(output)
+    }
+
+    /// Collect a tracked child's output within `timeout_secs`. Abandoning
+    /// the wait would leave the command running unseen, so on timeout its
+    /// process group is ended and reaped, and `None` is returned.
+    async fn collect_within(
+        &self,
+        child: Child,
+        tracked: &orphans::Tracked,
+        live: &LiveOutput,
+        timeout_secs: u64,
+    ) -> Result<Option<Output>> {
+        let mut output = std::pin::pin!(self.collect_output(child, live));
+        match tokio::time::timeout(Duration::from_secs(timeout_secs), output.as_mut()).await {
+            Ok(result) => result.map(Some),
+            Err(_) => {
+                tracked.terminate();
+                let _ = tokio::time::timeout(orphans::KILL_GRACE * 2, output.as_mut()).await;
+                Ok(None)
             }
         }
+    }
+
+    /// Wait for a child with piped stdout/stderr. Streamed lines are
+    /// prefixed with the task label so interleaved parallel output stays
+    /// attributable; otherwise they feed the spinner tail.
+    async fn collect_output(&self, child: Child, live: &LiveOutput) -> Result<Output> {
+        let streaming = self.controls.is_streaming();
+        if !streaming && live.tail.is_none() && live.log.is_none() {
+            return Ok(child.wait_with_output().await?);
+        }
+        let output = tail::wait_with_lines(child, |line| {
+            if let Some(log) = &live.log {
+                let _ = log.write_line(line);
+            }
+            if streaming {
+                self.controls
+                    .print(format!("{} {}", live.label, line.trim_end()));
+            } else if let Some(tail) = &live.tail {
+                tail.push(line);
+            }
+        })
+        .await?;
+        Ok(output)
+    }
+
+    /// Run a sudo command of `task` with keychain support. The task's
+    /// timeout starts once sudo is authenticated.
+    async fn run_sudo_command(
+        &self,
+        sudo: SudoCommand<'_>,
+        task: &TaskConfig,
+        group_name: &str,
+        keychain_label: &str,
+        active: &ActiveTask,
+        live: &LiveOutput,
+    ) -> Result<String> {
+        let timeout_secs = task.timeout.unwrap_or(300);
+        // Execute the command once the sudo timestamp is valid
+        let run_actual = || {
+            let mut command = Command::new("sudo");
+            if let Some(dir) = sudo.dir {
+                command.current_dir(dir);
+            }
+            let child = command
+                .args(sudo.args)
+                .envs(sudo.env.iter().map(|(k, v)| (k, v)))
+                .process_group(0)
+                .stdin(Stdio::null())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .spawn()
+                .context("Failed to execute sudo command");
+            async move {
+                let child = child?;
+                let pid = child.id().context("sudo exited before it was tracked")?;
+                let tracked = orphans::track(pid, "sudo");
+                active.set_pid(pid);
+                match self
+                    .collect_within(child, &tracked, live, timeout_secs)
+                    .await?
+                {
+                    Some(output) => command_output(output),
+                    None => {
+                        let _ =
+                            self.notifier
+                                .notify_task_timeout(&task.name, group_name, timeout_secs);
+                        Err(CommandTimedOut {
+                            secs: timeout_secs,
+                            hint: "",
+                        }
+                        .into())
+                    }
+                }
+            }
+        };
 
         // 1. If sudo timestamp is already cached, just run the command.
-        if Command::new("sudo")
-            .arg("-n")
-            .arg("true")
-            .stdout(Stdio::null())
-            .stderr(Stdio::null())
-            .status()
-            .map(|s| s.success())
-            .unwrap_or(false)
-        {
-            return run_actual(args);
+        if sudo_timestamp_valid().await {
+            return run_actual().await;
+        }
+
+        // Only one task authenticates at a time; the others wait for their
+        // turn and then reuse the timestamp it refreshed.
+        let turn = self.controls.prompt_turn().await;
+        if sudo_timestamp_valid().await {
+            drop(turn);
+            return run_actual().await;
         }
 
         // 2. Try keychain password (if stored) to refresh sudo timestamp.
         if let Ok(password) = keychain::get_password(keychain_label)
             && authenticate_sudo(&password).await?
         {
-            return run_actual(args);
+            drop(turn);
+            return run_actual().await;
         }
 
         // 3. Prompt user for password
-        let password = Password::with_theme(&ColorfulTheme::default())
-            .with_prompt("Enter sudo password")
-            .interact()?;
-
-        if !authenticate_sudo(&password).await? {
-            return Err(anyhow::anyhow!("Failed to authenticate sudo"));
-        }
+        let password = self
+            .prompt_sudo_password("Enter sudo password")
+            .await?
+            .ok_or_else(|| TideError::Cancelled("No sudo password entered".to_string()))?;
 
         // 4. Optionally save password into keychain
         if !keychain::entry_exists(keychain_label)
-            && Confirm::with_theme(&ColorfulTheme::default())
-                .with_prompt("Save password to keychain for future use?")
-                .default(true)
-                .interact()?
+            && self.controls.suspend(|| {
+                Confirm::with_theme(&ColorfulTheme::default())
+                    .with_prompt("Save password to keychain for future use?")
+                    .default(true)
+                    .interact()
+            })?
         {
             keychain::save_password(keychain_label, &password)?;
         }
+        drop(turn);
+
+        run_actual().await
+    }
+}
+
+/// Whether sudo runs without a password thanks to a cached timestamp
+async fn sudo_timestamp_valid() -> bool {
+    Command::new("sudo")
+        .arg("-n")
+        .arg("true")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .await
+        .is_ok_and(|status| status.success())
+}
 
-        run_actual(args)
+/// Error returned when a command exits unsuccessfully
+#[derive(Debug, thiserror::Error)]
+#[error("Command failed: {stderr}")]
+pub struct CommandFailed {
+    pub exit_code: Option<i32>,
+    pub stderr: String,
+}
+
+/// Error returned when a command exceeds its timeout
+#[derive(Debug, thiserror::Error)]
+#[error("Command timed out after {secs} seconds.{hint}")]
+pub struct CommandTimedOut {
+    pub secs: u64,
+    hint: &'static str,
+}
+
+/// Convert a finished process into its stdout or a [`CommandFailed`] error
+fn command_output(output: Output) -> Result<String> {
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    } else {
+        Err(CommandFailed {
+            exit_code: output.status.code(),
+            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+        }
+        .into())
+    }
+}
+
+/// Classify a task error for the summary
+fn classify_error(error: &anyhow::Error) -> (Option<i32>, FailureClass) {
+    let exit_code = error
+        .downcast_ref::<CommandFailed>()
+        .and_then(|failed| failed.exit_code);
+    let timed_out = error.downcast_ref::<CommandTimedOut>().is_some();
+    (
+        exit_code,
+        failure::classify(exit_code, timed_out, &error.to_string()),
+    )
+}
+
+/// Reason a task's preconditions are not met
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SkipReason {
+    CommandNotFound(String),
+    PathNotFound(String),
+    /// A `${outputs.<name>}` whose task did not succeed (or did not run)
+    MissingOutput(String),
+    /// The `run_as` user does not exist on this Mac
+    UserNotFound(String),
+    /// A `depends_on` task failed or was skipped
+    DependencyUnmet(String),
+    /// The task succeeded this long ago, within its `min_interval`
+    RanRecently {
+        ago: Duration,
+        interval: String,
+    },
+}
+
+impl SkipReason {
+    /// Short label used next to progress spinners
+    pub fn short(&self) -> &'static str {
+        match self {
+            SkipReason::CommandNotFound(_) => "command not found",
+            SkipReason::PathNotFound(_) => "path not found",
+            SkipReason::MissingOutput(_) => "missing output",
+            SkipReason::UserNotFound(_) => "user not found",
+            SkipReason::DependencyUnmet(_) => "dependency unmet",
+            SkipReason::RanRecently { .. } => "ran recently",
+        }
+    }
+}
+
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SkipReason::CommandNotFound(cmd) => write!(f, "Command '{}' not found", cmd),
+            SkipReason::PathNotFound(path) => write!(f, "Path '{}' not found", path),
+            SkipReason::MissingOutput(name) => {
+                write!(f, "Output '{}' was not produced by an earlier task", name)
+            }
+            SkipReason::UserNotFound(user) => write!(f, "User '{}' not found", user),
+            SkipReason::DependencyUnmet(task) => {
+                write!(f, "Dependency '{}' did not succeed", task)
+            }
+            SkipReason::RanRecently { ago, interval } => {
+                let minutes = ago.as_secs() / 60;
+                let ago = match minutes {
+                    0 => "less than a minute".to_string(),
+                    1..60 => format!("{}m", minutes),
+                    60..1440 => format!("{}h {}m", minutes / 60, minutes % 60),
+                    _ => format!("{}d {}h", minutes / 1440, minutes % 1440 / 60),
+                };
+                write!(
+                    f,
+                    "Succeeded {} ago, within its min_interval of {}",
+                    ago, interval
+                )
+            }
+        }
     }
 }
 
+/// Evaluate a task's `check_command` and `check_path` preconditions and
+/// whether its `run_as` user exists
+pub fn check_preconditions(task: &TaskConfig) -> std::result::Result<(), SkipReason> {
+    if let Some(user) = &task.run_as
+        && runas::home_dir(user).is_none()
+    {
+        return Err(SkipReason::UserNotFound(user.clone()));
+    }
+    check_preconditions_with(task, keychain::command_exists, |path| path.exists())
+}
+
+/// Evaluate preconditions against the given command and path probes, so
+/// they can be checked against a simulated machine
+pub fn check_preconditions_with(
+    task: &TaskConfig,
+    command_exists: impl Fn(&str) -> bool,
+    path_exists: impl Fn(&Path) -> bool,
+) -> std::result::Result<(), SkipReason> {
+    if let Some(kind) = task.kind
+        && !command_exists(kind.required_command())
+    {
+        return Err(SkipReason::CommandNotFound(
+            kind.required_command().to_string(),
+        ));
+    }
+
+    if let Some((runner, _)) = task.recipe()
+        && !command_exists(runner.program())
+    {
+        return Err(SkipReason::CommandNotFound(runner.program().to_string()));
+    }
+
+    if let Some(shell) = task.shell_program()
+        && !task.command.is_empty()
+        && !command_exists(&shell)
+    {
+        return Err(SkipReason::CommandNotFound(shell));
+    }
+
+    if task.env_file_encrypted.is_some() && !command_exists("age") {
+        return Err(SkipReason::CommandNotFound("age".to_string()));
+    }
+
+    if task.sandbox.is_some() && !command_exists("sandbox-exec") {
+        return Err(SkipReason::CommandNotFound("sandbox-exec".to_string()));
+    }
+
+    if let Some(Sandbox::Profile(profile)) = task.sandbox.as_deref().and_then(Sandbox::parse)
+        && !path_exists(&profile)
+    {
+        return Err(SkipReason::PathNotFound(profile.display().to_string()));
+    }
+
+    if task.is_osascript() && !command_exists("osascript") {
+        return Err(SkipReason::CommandNotFound("osascript".to_string()));
+    }
+
+    if let Some(script) = &task.applescript_file {
+        let expanded = shellexpand::tilde(script);
+        if !path_exists(Path::new(expanded.as_ref())) {
+            return Err(SkipReason::PathNotFound(script.clone()));
+        }
+    }
+
+    if let Some(check_cmd) = &task.check_command
+        && !command_exists(check_cmd)
+    {
+        return Err(SkipReason::CommandNotFound(check_cmd.clone()));
+    }
+
+    if let Some(check_path) = &task.check_path {
+        let expanded = shellexpand::tilde(check_path);
+        if !path_exists(Path::new(expanded.as_ref())) {
+            return Err(SkipReason::PathNotFound(check_path.clone()));
+        }
+    }
+
+    Ok(())
+}
+
 /// Authenticate sudo with password
 async fn authenticate_sudo(password: &str) -> Result<bool> {
     use tokio::io::AsyncWriteExt;
-    use tokio::process::Command as TokioCommand;
 
-    let mut child = TokioCommand::new("sudo")
+    let mut child = Command::new("sudo")
         .arg("-S")
         .arg("true")
         .stdin(Stdio::piped())
@@ -567,16 +1383,7 @@ async fn authenticate_sudo(password: &str) -> Result<bool> {
     Ok(status.success())
 }
 
-/// Format duration for display
-fn format_duration(d: Duration) -> String {
-    let secs = d.as_secs();
-    if secs < 60 {
-        format!("{}s", secs)
-    } else {
-        format!("{}m {}s", secs / 60, secs % 60)
-    }
-}
-
+/// Group name prefixed with its icon, if any
 fn format_group_label(name: &str, icon: &str) -> String {
     if icon.trim().is_empty() {
         name.to_string()
@@ -585,6 +1392,27 @@ fn format_group_label(name: &str, icon: &str) -> String {
     }
 }
 
+/// Colored `[🍺 Upgrade]` prefix for streamed output lines.
+///
+/// The color is derived from the task name so a task keeps its color
+/// throughout the run.
+fn stream_label(name: &str, icon: &str) -> String {
+    const COLORS: [Color; 6] = [
+        Color::Cyan,
+        Color::Magenta,
+        Color::Blue,
+        Color::BrightCyan,
+        Color::BrightMagenta,
+        Color::BrightBlue,
+    ];
+    let hash = name.bytes().fold(0usize, |hash, byte| {
+        hash.wrapping_mul(31).wrapping_add(byte as usize)
+    });
+    format!("[{}]", format_task_label(name, icon))
+        .color(COLORS[hash % COLORS.len()])
+        .to_string()
+}
+
 fn format_task_label(name: &str, icon: &str) -> String {
     let icon = icon.trim();
     if icon.is_empty() {
@@ -0,0 +1,350 @@
+use clap::ValueEnum;
+use std::fmt::Write;
+
+use crate::config::{Config, Matrix, TaskConfig};
+use crate::matrix;
+use crate::plan::{self, PlannedTask};
+
+/// Target format of `tide export`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    /// A justfile with one recipe per task and per group
+    Just,
+    /// A Makefile with one phony target per task and per group
+    Make,
+    /// An Ansible playbook running the tasks on localhost
+    Ansible,
+}
+
+/// Tasks of one group, in configuration order
+struct ExportGroup<'a> {
+    name: &'a str,
+    slug: String,
+    tasks: Vec<&'a PlannedTask>,
+}
+
+/// Convert the selected groups and tasks into `format`.
+///
+/// Preconditions (`check_command`/`check_path`) become shell guards that skip
+/// the step, optional tasks ignore failures, and built-in task types, which
+/// have no shell equivalent, are emitted as comments.
+pub fn export(
+    config: &Config,
+    format: ExportFormat,
+    groups: Option<&[String]>,
+    skip_groups: Option<&[String]>,
+) -> String {
+    // Static matrices expand into one step per item; matrix commands would
+    // run at export time, so those tasks keep their `${item}` placeholder
+    let tasks: Vec<PlannedTask> = plan::collect_tasks(config, groups, skip_groups)
+        .into_iter()
+        .flat_map(|planned| match &planned.task.matrix {
+            Some(Matrix::Items(items)) => matrix::expand(&planned, items),
+            _ => vec![planned],
+        })
+        .collect();
+    let mut export_groups: Vec<ExportGroup> = Vec::new();
+    for planned in &tasks {
+        match export_groups.last_mut() {
+            Some(group) if group.name == planned.group => group.tasks.push(planned),
+            _ => export_groups.push(ExportGroup {
+                name: &planned.group,
+                slug: slug(&planned.group),
+                tasks: vec![planned],
+            }),
+        }
+    }
+
+    match format {
+        ExportFormat::Just => render_just(&export_groups),
+        ExportFormat::Make => render_make(&export_groups),
+        ExportFormat::Ansible => render_ansible(&export_groups),
+    }
+}
+
+fn render_just(groups: &[ExportGroup]) -> String {
+    let mut out = String::from("# Generated by `tide export --format just`\n\n");
+    let all: Vec<&str> = groups.iter().map(|g| g.slug.as_str()).collect();
+    let _ = writeln!(out, "# Run every group\nall: {}\n", all.join(" "));
+
+    for group in groups {
+        let recipes: Vec<String> = group
+            .tasks
+            .iter()
+            .filter(|t| t.task.kind.is_none())
+            .map(|t| task_slug(group, &t.task))
+            .collect();
+        let _ = writeln!(
+            out,
+            "# {}\n{}: {}\n",
+            group.name,
+            group.slug,
+            recipes.join(" ")
+        );
+
+        for planned in &group.tasks {
+            let task = &planned.task;
+            let Some(line) = shell_line(task, true) else {
+                let _ = writeln!(out, "{}\n", unsupported_comment(task));
+                continue;
+            };
+            let doc = description(task);
+            let ignore = if task.required { "" } else { "-" };
+            let _ = writeln!(
+                out,
+                "# {}\n{}:\n    {}{}\n",
+                doc,
+                task_slug(group, task),
+                ignore,
+                line.replace("{{", "{{{{")
+            );
+        }
+    }
+    out
+}
+
+fn render_make(groups: &[ExportGroup]) -> String {
+    let mut out = String::from("# Generated by `tide export --format make`\n\n");
+    let mut phony: Vec<String> = vec!["all".to_string()];
+    let mut body = String::new();
+
+    let all: Vec<&str> = groups.iter().map(|g| g.slug.as_str()).collect();
+    let _ = writeln!(body, "all: {}\n", all.join(" "));
+
+    for group in groups {
+        let targets: Vec<String> = group
+            .tasks
+            .iter()
+            .filter(|t| t.task.kind.is_none())
+            .map(|t| task_slug(group, &t.task))
+            .collect();
+        phony.push(group.slug.clone());
+        let _ = writeln!(
+            body,
+            "# {}\n{}: {}\n",
+            group.name,
+            group.slug,
+            targets.join(" ")
+        );
+
+        for planned in &group.tasks {
+            let task = &planned.task;
+            let Some(line) = shell_line(task, true) else {
+                let _ = writeln!(body, "{}\n", unsupported_comment(task));
+                continue;
+            };
+            let target = task_slug(group, task);
+            let ignore = if task.required { "" } else { "-" };
+            let _ = writeln!(
+                body,
+                "# {}\n{}:\n\t{}{}\n",
+                description(task),
+                target,
+                ignore,
+                line.replace('$', "$$")
+            );
+            phony.push(target);
+        }
+    }
+
+    let _ = writeln!(out, ".PHONY: {}\n", phony.join(" "));
+    out.push_str(&body);
+    out
+}
+
+fn render_ansible(groups: &[ExportGroup]) -> String {
+    let mut out = String::from(
+        "# Generated by `tide export --format ansible`\n\
+         - name: Tide maintenance\n  hosts: localhost\n  connection: local\n  gather_facts: false\n  tasks:\n",
+    );
+
+    for group in groups {
+        for planned in &group.tasks {
+            let task = &planned.task;
+            let Some(line) = shell_line(task, false) else {
+                let _ = writeln!(out, "    {}", unsupported_comment(task));
+                continue;
+            };
+            let _ = writeln!(
+                out,
+                "    - name: {}\n      ansible.builtin.shell: {}\n      tags: [{}]",
+                quote_yaml(&format!("{}: {}", group.name, task.name)),
+                quote_yaml(&line),
+                group.slug
+            );
+            if task.needs_sudo() {
+                out.push_str("      become: true\n");
+            }
+            if let Some(user) = &task.run_as {
+                let _ = writeln!(out, "      become_user: {}", quote_yaml(user));
+            }
+            if !task.required {
+                out.push_str("      ignore_errors: true\n");
+            }
+            if let Some(dir) = &task.working_dir {
+                let _ = writeln!(out, "      args:\n        chdir: {}", quote_yaml(dir));
+            }
+            if !task.env.is_empty() {
+                out.push_str("      environment:\n");
+                let mut env: Vec<_> = task.env.iter().collect();
+                env.sort();
+                for (key, value) in env {
+                    let _ = writeln!(out, "        {}: {}", key, quote_yaml(value));
+                }
+            }
+        }
+    }
+    out
+}
+
+/// The task as one shell line guarded by its preconditions; `None` for
+/// built-in task types. With `inline`, sudo, the environment and the working
+/// directory are part of the line; otherwise the caller applies them.
+fn shell_line(task: &TaskConfig, inline: bool) -> Option<String> {
+    if task.kind.is_some() {
+        return None;
+    }
+
+    let mut cmd = task.effective_command();
+    if cmd.first().is_some_and(|c| c == "sudo") {
+        cmd.remove(0);
+    }
+    if inline && !task.env.is_empty() {
+        let mut env: Vec<_> = task.env.iter().collect();
+        env.sort();
+        let assignments = env
+            .into_iter()
+            .map(|(key, value)| format!("{}={}", key, value));
+        cmd.splice(0..0, std::iter::once("env".to_string()).chain(assignments));
+    }
+    if inline && let Some(user) = &task.run_as {
+        cmd.splice(0..0, ["sudo".to_string(), "-u".to_string(), user.clone()]);
+    } else if inline && task.needs_sudo() {
+        cmd.insert(0, "sudo".to_string());
+    }
+    let command = cmd
+        .iter()
+        .map(|arg| quote_shell(arg))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let line = guarded(task, &command);
+    Some(match &task.working_dir {
+        Some(dir) if inline => format!("cd {} && {}", quote_shell(dir), line),
+        _ => line,
+    })
+}
+
+fn guarded(task: &TaskConfig, command: &str) -> String {
+    let mut conditions = Vec::new();
+    if let Some(check) = &task.check_command {
+        conditions.push(format!("command -v {} >/dev/null 2>&1", quote_shell(check)));
+    }
+    if let Some(path) = &task.check_path {
+        conditions.push(format!("[ -e {} ]", quote_shell(path)));
+    }
+    if conditions.is_empty() {
+        command.to_string()
+    } else {
+        format!("if {}; then {}; fi", conditions.join(" && "), command)
+    }
+}
+
+fn unsupported_comment(task: &TaskConfig) -> String {
+    format!(
+        "# '{}' uses the built-in tide task type `{}`, which has no export equivalent",
+        task.name,
+        task.kind.map(|kind| kind.as_str()).unwrap_or_default()
+    )
+}
+
+fn description(task: &TaskConfig) -> String {
+    if task.description.is_empty() {
+        task.name.clone()
+    } else {
+        task.description.clone()
+    }
+}
+
+fn task_slug(group: &ExportGroup, task: &TaskConfig) -> String {
+    format!("{}-{}", group.slug, slug(&task.name))
+}
+
+/// Lowercase ASCII name joined by dashes: "System Updates" -> "system-updates"
+pub(crate) fn slug(name: &str) -> String {
+    name.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(str::to_ascii_lowercase)
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Quote an argument for POSIX shells, keeping a leading `~/` expandable.
+///
+/// Multi-line arguments (e.g. `sh -c` scripts) are rebuilt with `printf` so
+/// the result stays on one line, as justfile and Makefile recipes require.
+fn quote_shell(arg: &str) -> String {
+    if arg.contains('\n') {
+        let escaped = arg.replace('\\', "\\\\").replace('\n', "\\n");
+        return format!("\"$(printf '%b' {})\"", quote_shell(&escaped));
+    }
+    let safe = |c: char| c.is_ascii_alphanumeric() || "_-./=:@%+,".contains(c);
+    if let Some(rest) = arg.strip_prefix("~/")
+        && rest.chars().all(safe)
+    {
+        return arg.to_string();
+    }
+    if !arg.is_empty() && arg.chars().all(safe) {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
+
+/// JSON strings are valid double-quoted YAML scalars
+fn quote_yaml(value: &str) -> String {
+    serde_json::to_string(value).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_formats() {
+        let mut config = Config::default();
+        config.groups[1].tasks[1].required = false;
+        config.groups[1].tasks[1]
+            .env
+            .insert("HOMEBREW_NO_ENV_HINTS".to_string(), "1".to_string());
+        let homebrew = ["Homebrew".to_string()];
+
+        let just = export(&config, ExportFormat::Just, Some(&homebrew), None);
+        assert!(just.contains("all: homebrew\n"));
+        assert!(just.contains(
+            "homebrew-upgrade-packages:\n    -if command -v brew >/dev/null 2>&1; then env HOMEBREW_NO_ENV_HINTS=1 brew upgrade; fi"
+        ));
+
+        let make = export(&config, ExportFormat::Make, None, None);
+        assert!(make.starts_with(
+            "# Generated by `tide export --format make`\n\n.PHONY: all system-updates"
+        ));
+        assert!(make.contains(
+            "system-updates-macos-updates:\n\tif command -v softwareupdate >/dev/null 2>&1; then sudo softwareupdate --install --all; fi"
+        ));
+
+        let ansible = export(&config, ExportFormat::Ansible, None, None);
+        assert!(ansible.contains(
+            "    - name: \"System Updates: macOS Updates\"\n      ansible.builtin.shell: \"if command -v softwareupdate >/dev/null 2>&1; then softwareupdate --install --all; fi\"\n      tags: [system-updates]\n      become: true\n"
+        ));
+        assert!(ansible.contains("      environment:\n        HOMEBREW_NO_ENV_HINTS: \"1\"\n"));
+
+        assert_eq!(quote_shell("it's"), r"'it'\''s'");
+        assert_eq!(quote_shell("~/bin/x.sh"), "~/bin/x.sh");
+        assert_eq!(quote_shell("a b"), "'a b'");
+        assert_eq!(
+            quote_shell("brew update\nbrew upgrade"),
+            "\"$(printf '%b' 'brew update\\nbrew upgrade')\""
+        );
+    }
+}
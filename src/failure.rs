@@ -0,0 +1,150 @@
+use serde::{Deserialize, Serialize};
+
+/// Stderr fragments that indicate a network problem
+const NETWORK_PATTERNS: &[&str] = &[
+    "could not resolve host",
+    "could not resolve",
+    "temporary failure in name resolution",
+    "name or service not known",
+    "nodename nor servname provided",
+    "failed to connect",
+    "connection refused",
+    "connection reset",
+    "connection timed out",
+    "operation timed out",
+    "network is unreachable",
+    "no route to host",
+    "tls handshake",
+    "ssl_connect",
+    "ssl_error",
+    "curl: (6)",
+    "curl: (7)",
+    "curl: (28)",
+    "curl: (35)",
+    "curl: (56)",
+];
+
+/// Stderr fragments that indicate missing privileges
+const PERMISSION_PATTERNS: &[&str] = &[
+    "permission denied",
+    "operation not permitted",
+    "a password is required",
+    "incorrect password",
+    "not in the sudoers",
+    "must be run as root",
+    "requires root",
+    "authentication failed",
+];
+
+/// Stderr fragments that indicate a missing executable
+const NOT_FOUND_PATTERNS: &[&str] = &[
+    "command not found",
+    "no such file or directory (os error 2)",
+];
+
+/// Broad category of a task failure
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailureClass {
+    Network,
+    Permission,
+    Timeout,
+    CommandNotFound,
+    NonZeroExit,
+}
+
+impl FailureClass {
+    /// Heading used in the summary
+    pub fn label(&self) -> &'static str {
+        match self {
+            FailureClass::Network => "Network",
+            FailureClass::Permission => "Permission / sudo",
+            FailureClass::Timeout => "Timeout",
+            FailureClass::CommandNotFound => "Command not found",
+            FailureClass::NonZeroExit => "Non-zero exit",
+        }
+    }
+
+    /// Icon used in the summary
+    pub fn icon(&self) -> &'static str {
+        match self {
+            FailureClass::Network => "🌐",
+            FailureClass::Permission => "🔒",
+            FailureClass::Timeout => "⏱️",
+            FailureClass::CommandNotFound => "❓",
+            FailureClass::NonZeroExit => "💥",
+        }
+    }
+
+    /// Targeted hint for `count` failures of this class
+    pub fn hint(&self, count: usize) -> String {
+        match self {
+            FailureClass::Network => format!("{} network failure(s) — are you offline?", count),
+            FailureClass::Permission => format!(
+                "{} permission failure(s) — check sudo authentication or set 'sudo = true'",
+                count
+            ),
+            FailureClass::Timeout => format!(
+                "{} timeout(s) — raise 'timeout' or check for commands waiting on input",
+                count
+            ),
+            FailureClass::CommandNotFound => format!(
+                "{} missing command(s) — install the tool or add a 'check_command'",
+                count
+            ),
+            FailureClass::NonZeroExit => {
+                format!("{} command(s) exited with an error", count)
+            }
+        }
+    }
+}
+
+/// Classify a failure from its exit code and error output
+pub fn classify(exit_code: Option<i32>, timed_out: bool, message: &str) -> FailureClass {
+    if timed_out {
+        return FailureClass::Timeout;
+    }
+
+    let message = message.to_lowercase();
+    let matches = |patterns: &[&str]| patterns.iter().any(|p| message.contains(p));
+
+    if exit_code == Some(127) || matches(NOT_FOUND_PATTERNS) {
+        FailureClass::CommandNotFound
+    } else if matches(NETWORK_PATTERNS) {
+        FailureClass::Network
+    } else if exit_code == Some(126) || matches(PERMISSION_PATTERNS) {
+        FailureClass::Permission
+    } else {
+        FailureClass::NonZeroExit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_failures() {
+        assert_eq!(
+            classify(
+                Some(6),
+                false,
+                "curl: (6) Could not resolve host: example.com"
+            ),
+            FailureClass::Network
+        );
+        assert_eq!(
+            classify(Some(1), false, "sudo: a password is required"),
+            FailureClass::Permission
+        );
+        assert_eq!(classify(None, true, ""), FailureClass::Timeout);
+        assert_eq!(
+            classify(Some(127), false, "zsh: command not found: mas"),
+            FailureClass::CommandNotFound
+        );
+        assert_eq!(
+            classify(Some(1), false, "Error: formula failed to build"),
+            FailureClass::NonZeroExit
+        );
+    }
+}
@@ -0,0 +1,131 @@
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use serde::Serialize;
+use std::path::Path;
+use toml::{Table, Value};
+
+use crate::yaml;
+
+/// Syntax of a config file, told apart by its extension
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ConfigFormat {
+    /// `config.toml`
+    #[default]
+    Toml,
+    /// `config.yaml`, with anchors and merge keys for shared task settings
+    Yaml,
+    /// `config.json`
+    Json,
+}
+
+impl ConfigFormat {
+    /// `.yaml`/`.yml` and `.json` files; TOML otherwise
+    pub fn of(path: &Path) -> Self {
+        match path
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .as_deref()
+        {
+            Some("yaml" | "yml") => ConfigFormat::Yaml,
+            Some("json") => ConfigFormat::Json,
+            _ => ConfigFormat::Toml,
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ConfigFormat::Toml => "toml",
+            ConfigFormat::Yaml => "yaml",
+            ConfigFormat::Json => "json",
+        }
+    }
+
+    /// Parse a document into the TOML table configs are layered and
+    /// deserialized from
+    pub fn parse(self, text: &str) -> Result<Table> {
+        match self {
+            ConfigFormat::Toml => Ok(toml::from_str(text)?),
+            ConfigFormat::Yaml => yaml::parse(text),
+            ConfigFormat::Json => {
+                let json: serde_json::Value = serde_json::from_str(text)?;
+                match from_json(json) {
+                    Some(Value::Table(table)) => Ok(table),
+                    _ => anyhow::bail!("the document must be an object"),
+                }
+            }
+        }
+    }
+
+    /// Write a config (or any part of one) in this format
+    pub fn render(self, value: &impl Serialize) -> Result<String> {
+        if self == ConfigFormat::Toml {
+            return Ok(toml::to_string_pretty(value)?);
+        }
+        let Value::Table(table) = Value::try_from(value)? else {
+            anyhow::bail!("only tables can be written as a config");
+        };
+        Ok(match self {
+            ConfigFormat::Yaml => yaml::to_string(&table),
+            _ => {
+                let mut json = serde_json::to_string_pretty(&table)
+                    .context("Failed to render the config as JSON")?;
+                json.push('\n');
+                json
+            }
+        })
+    }
+}
+
+/// JSON has `null`, TOML does not: null values are left out
+fn from_json(value: serde_json::Value) -> Option<Value> {
+    Some(match value {
+        serde_json::Value::Null => return None,
+        serde_json::Value::Bool(flag) => Value::Boolean(flag),
+        serde_json::Value::Number(number) => match number.as_i64() {
+            Some(integer) => Value::Integer(integer),
+            None => Value::Float(number.as_f64()?),
+        },
+        serde_json::Value::String(text) => Value::String(text),
+        serde_json::Value::Array(items) => {
+            Value::Array(items.into_iter().filter_map(from_json).collect())
+        }
+        serde_json::Value::Object(entries) => Value::Table(
+            entries
+                .into_iter()
+                .filter_map(|(key, value)| Some((key, from_json(value)?)))
+                .collect(),
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn test_config_formats_round_trip() {
+        assert_eq!(ConfigFormat::of(Path::new("tide.yml")), ConfigFormat::Yaml);
+        assert_eq!(
+            ConfigFormat::of(Path::new("config.JSON")),
+            ConfigFormat::Json
+        );
+        assert_eq!(ConfigFormat::of(Path::new("config")), ConfigFormat::Toml);
+
+        let json = r#"{"settings": {"parallel_limit": 3, "keychain_label": null},
+            "groups": [{"name": "Homebrew", "tasks": [{"name": "Update", "command": ["brew", "update"]}]}]}"#;
+        let config: Config = Value::Table(ConfigFormat::Json.parse(json).unwrap())
+            .try_into()
+            .unwrap();
+        assert_eq!(config.settings.parallel_limit, 3);
+        assert_eq!(config.groups[0].tasks[0].command, ["brew", "update"]);
+
+        let Value::Table(default) = Value::try_from(Config::default()).unwrap() else {
+            panic!("the config serializes to a table");
+        };
+        for format in [ConfigFormat::Toml, ConfigFormat::Yaml, ConfigFormat::Json] {
+            let text = format.render(&Config::default()).unwrap();
+            assert_eq!(format.parse(&text).unwrap(), default, "{:?}", format);
+        }
+    }
+}
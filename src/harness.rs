@@ -0,0 +1,171 @@
+use colored::Colorize;
+use std::path::Path;
+
+use crate::config::{Config, ConfigTest, TaskConfig, TaskExpectation, TaskGroup};
+use crate::executor;
+use crate::palette::Paint;
+use crate::suggest;
+
+/// Evaluate a single `[[tests]]` entry; the error describes the mismatch
+pub fn evaluate(config: &Config, test: &ConfigTest) -> Result<(), String> {
+    if test.expect.is_none() && test.enabled_tasks.is_none() {
+        return Err("no expectation given (set `expect` or `enabled_tasks`)".to_string());
+    }
+
+    let group =
+        match &test.group {
+            Some(name) => Some(config.groups.iter().find(|g| &g.name == name).ok_or_else(
+                || {
+                    format!(
+                        "group '{}' not found.{}",
+                        name,
+                        suggest::hint(name, config.groups.iter().map(|g| g.name.as_str()))
+                    )
+                },
+            )?),
+            None => None,
+        };
+
+    if let Some(expected) = test.enabled_tasks {
+        let group = group.ok_or("`enabled_tasks` requires `group`")?;
+        let actual = group.tasks.iter().filter(|t| t.enabled).count();
+        if actual != expected {
+            return Err(format!(
+                "group '{}' has {} enabled task(s), expected {}",
+                group.name, actual, expected
+            ));
+        }
+    }
+
+    if let Some(expected) = test.expect {
+        let name = test.task.as_deref().ok_or("`expect` requires `task`")?;
+        let (group, task) = find_task(config, group, name)?;
+        let (actual, reason) = simulate(test, group, task);
+        if actual != expected {
+            let verb = match expected {
+                TaskExpectation::Run => "run",
+                TaskExpectation::Skip => "skip",
+            };
+            return Err(format!("task '{}' should {} but {}", name, verb, reason));
+        }
+    }
+
+    Ok(())
+}
+
+fn find_task<'a>(
+    config: &'a Config,
+    group: Option<&'a TaskGroup>,
+    name: &str,
+) -> Result<(&'a TaskGroup, &'a TaskConfig), String> {
+    let groups: Vec<&TaskGroup> = match group {
+        Some(group) => vec![group],
+        None => config.groups.iter().collect(),
+    };
+    groups
+        .into_iter()
+        .find_map(|g| g.tasks.iter().find(|t| t.name == name).map(|t| (g, t)))
+        .ok_or_else(|| {
+            let tasks = config
+                .groups
+                .iter()
+                .flat_map(|g| g.tasks.iter().map(|t| t.name.as_str()));
+            format!("task '{}' not found.{}", name, suggest::hint(name, tasks))
+        })
+}
+
+/// Decide whether the task would run on the simulated machine
+fn simulate(test: &ConfigTest, group: &TaskGroup, task: &TaskConfig) -> (TaskExpectation, String) {
+    if !group.enabled {
+        return (TaskExpectation::Skip, "its group is disabled".to_string());
+    }
+    if !task.enabled {
+        return (TaskExpectation::Skip, "it is disabled".to_string());
+    }
+
+    let command_exists = |cmd: &str| !test.missing_commands.iter().any(|m| m == cmd);
+    let path_exists = |path: &Path| {
+        !test
+            .missing_paths
+            .iter()
+            .any(|m| Path::new(shellexpand::tilde(m).as_ref()) == path)
+    };
+    match executor::check_preconditions_with(task, command_exists, path_exists) {
+        Ok(()) => (TaskExpectation::Run, "it runs".to_string()),
+        Err(reason) => (TaskExpectation::Skip, format!("it is skipped ({})", reason)),
+    }
+}
+
+/// Run all configured tests and print a report; returns whether all passed
+pub fn run_all(config: &Config) -> bool {
+    println!("{}", "🧪 Config Tests".bright_blue().bold());
+    println!("{}", "═".repeat(60).bright_blue());
+
+    if config.tests.is_empty() {
+        println!("{}", "No [[tests]] defined in the config.".dimmed());
+        return true;
+    }
+
+    let mut failures = 0;
+    for test in &config.tests {
+        match evaluate(config, test) {
+            Ok(()) => println!("  {} {}", "✓".success(), test.name),
+            Err(message) => {
+                failures += 1;
+                println!("  {} {}", "✗".failure(), test.name.bold());
+                println!("      {}", message.failure());
+            }
+        }
+    }
+
+    let passed = config.tests.len() - failures;
+    println!(
+        "\n{} passed, {} failed",
+        passed.to_string().success(),
+        failures.to_string().failure()
+    );
+    failures == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test(name: &str) -> ConfigTest {
+        ConfigTest {
+            name: name.to_string(),
+            group: None,
+            task: None,
+            missing_commands: Vec::new(),
+            missing_paths: Vec::new(),
+            expect: None,
+            enabled_tasks: None,
+        }
+    }
+
+    #[test]
+    fn test_evaluate_expectations() {
+        let config = Config::default();
+
+        let mut skips = test("upgrade skips without brew");
+        skips.task = Some("Upgrade Packages".to_string());
+        skips.missing_commands = vec!["brew".to_string()];
+        skips.expect = Some(TaskExpectation::Skip);
+        assert_eq!(evaluate(&config, &skips), Ok(()));
+
+        skips.missing_commands.clear();
+        assert_eq!(
+            evaluate(&config, &skips),
+            Err("task 'Upgrade Packages' should skip but it runs".to_string())
+        );
+
+        let mut count = test("homebrew tasks");
+        count.group = Some("Homebrew".to_string());
+        count.enabled_tasks = Some(2);
+        assert_eq!(evaluate(&config, &count), Ok(()));
+        count.enabled_tasks = Some(5);
+        assert!(evaluate(&config, &count).is_err());
+
+        assert!(evaluate(&config, &test("empty")).is_err());
+    }
+}
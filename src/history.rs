@@ -0,0 +1,422 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use rusqlite::{Connection, params};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::executor::{TaskResult, TaskStatus};
+use crate::import::sha256_hex;
+
+/// Number of recent runs considered for duration estimates
+const ESTIMATE_WINDOW: usize = 10;
+
+/// Persisted record of a completed run
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RunRecord {
+    pub started_at: DateTime<Local>,
+    pub duration_secs: f64,
+    pub tasks: Vec<TaskRecord>,
+    /// Started with `--resume`, so it only holds the tasks left over from
+    /// the runs before it
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub resumed: bool,
+}
+
+/// Persisted result of a single task within a run
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TaskRecord {
+    pub group: String,
+    pub name: String,
+    pub status: TaskStatus,
+    pub duration_secs: f64,
+    /// First line of the skip reason or error of a task that did not succeed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// When the task started
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<DateTime<Local>>,
+    /// SHA-256 of the captured output, to tell whether a task's output changed
+    /// between runs without storing it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_hash: Option<String>,
+}
+
+impl RunRecord {
+    /// Build a record from the results of a finished run
+    pub fn from_results(
+        started_at: DateTime<Local>,
+        duration: Duration,
+        results: &[TaskResult],
+    ) -> Self {
+        let now = Local::now();
+        Self {
+            started_at,
+            duration_secs: duration.as_secs_f64(),
+            resumed: false,
+            tasks: results
+                .iter()
+                .map(|result| TaskRecord {
+                    group: result.group.clone(),
+                    name: result.name.clone(),
+                    status: result.status,
+                    duration_secs: result.duration.as_secs_f64(),
+                    reason: failure_reason(result),
+                    started_at: chrono::Duration::from_std(result.started.elapsed())
+                        .ok()
+                        .map(|elapsed| now - elapsed),
+                    output_hash: result.output.as_deref().map(sha256_hex),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Longest reason kept per task, in characters
+const REASON_LIMIT: usize = 200;
+
+fn failure_reason(result: &TaskResult) -> Option<String> {
+    if result.status == TaskStatus::Success {
+        return None;
+    }
+    let line = result
+        .output
+        .as_deref()?
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())?;
+    Some(line.chars().take(REASON_LIMIT).collect())
+}
+
+/// Run history stored in an SQLite database: one row per run in `runs` and
+/// one per task result in `tasks`
+pub struct History {
+    path: PathBuf,
+}
+
+impl History {
+    /// Open the history database at the given path
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Open the history database at its default location
+    pub fn open_default() -> Result<Self> {
+        Ok(Self::new(Self::default_path()?))
+    }
+
+    /// Default history location: `~/.local/share/tide/history.db`
+    pub fn default_path() -> Result<PathBuf> {
+        Ok(dirs::home_dir()
+            .context("Could not determine home directory")?
+            .join(".local")
+            .join("share")
+            .join("tide")
+            .join("history.db"))
+    }
+
+    /// Connect to the database, creating it on first use. A new database
+    /// takes over the runs of the JSON-lines history older versions wrote
+    /// next to it.
+    fn connect(&self) -> Result<Connection> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).with_context(|| {
+                format!("Failed to create history directory {}", parent.display())
+            })?;
+        }
+        let is_new = !self.path.exists();
+        let mut conn = Connection::open(&self.path)
+            .with_context(|| format!("Failed to open history database {}", self.path.display()))?;
+        conn.execute_batch(SCHEMA).with_context(|| {
+            format!("Failed to set up history database {}", self.path.display())
+        })?;
+        let has_resumed = conn
+            .prepare("SELECT 1 FROM pragma_table_info('runs') WHERE name = 'resumed'")?
+            .exists([])?;
+        if !has_resumed {
+            conn.execute_batch("ALTER TABLE runs ADD COLUMN resumed INTEGER NOT NULL DEFAULT 0")?;
+        }
+        if is_new {
+            let legacy = self.path.with_extension("jsonl");
+            if let Ok(contents) = fs::read_to_string(&legacy) {
+                let tx = conn.transaction()?;
+                for run in contents
+                    .lines()
+                    .filter_map(|line| serde_json::from_str::<RunRecord>(line).ok())
+                {
+                    insert_run(&tx, &run)?;
+                }
+                tx.commit()?;
+            }
+        }
+        Ok(conn)
+    }
+
+    /// Add a run and its task results to the history
+    pub fn record(&self, run: &RunRecord) -> Result<()> {
+        let mut conn = self.connect()?;
+        let tx = conn.transaction()?;
+        insert_run(&tx, run)?;
+        tx.commit()
+            .with_context(|| format!("Failed to write history {}", self.path.display()))?;
+        Ok(())
+    }
+
+    /// Load the most recent runs, oldest first. Unreadable rows are ignored.
+    pub fn recent_runs(&self, limit: usize) -> Result<Vec<RunRecord>> {
+        if !self.path.exists() && !self.path.with_extension("jsonl").exists() {
+            return Ok(Vec::new());
+        }
+        let conn = self.connect()?;
+        let limit = i64::try_from(limit).unwrap_or(i64::MAX);
+        let mut runs_query = conn.prepare(
+            "SELECT id, started_at, duration_secs, resumed FROM runs ORDER BY id DESC LIMIT ?1",
+        )?;
+        let mut tasks_query = conn.prepare(
+            "SELECT grp, name, status, duration_secs, reason, started_at, output_hash
+             FROM tasks WHERE run_id = ?1 ORDER BY position",
+        )?;
+        let mut runs = Vec::new();
+        let rows = runs_query.query_map([limit], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, f64>(2)?,
+                row.get::<_, bool>(3)?,
+            ))
+        })?;
+        for (id, started_at, duration_secs, resumed) in rows.flatten() {
+            let Ok(started_at) = DateTime::parse_from_rfc3339(&started_at) else {
+                continue;
+            };
+            let tasks = tasks_query
+                .query_map([id], |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, String>(2)?,
+                        row.get::<_, f64>(3)?,
+                        row.get::<_, Option<String>>(4)?,
+                        row.get::<_, Option<String>>(5)?,
+                        row.get::<_, Option<String>>(6)?,
+                    ))
+                })?
+                .flatten()
+                .filter_map(
+                    |(group, name, status, duration_secs, reason, started_at, output_hash)| {
+                        Some(TaskRecord {
+                            group,
+                            name,
+                            status: parse_status(&status)?,
+                            duration_secs,
+                            reason,
+                            started_at: started_at
+                                .and_then(|at| DateTime::parse_from_rfc3339(&at).ok())
+                                .map(|at| at.with_timezone(&Local)),
+                            output_hash,
+                        })
+                    },
+                )
+                .collect();
+            runs.push(RunRecord {
+                started_at: started_at.with_timezone(&Local),
+                duration_secs,
+                tasks,
+                resumed,
+            });
+        }
+        runs.reverse();
+        Ok(runs)
+    }
+
+    /// When each `(group, task)` last succeeded, over the whole history
+    pub fn last_successes(&self) -> Result<HashMap<(String, String), DateTime<Local>>> {
+        if !self.path.exists() && !self.path.with_extension("jsonl").exists() {
+            return Ok(HashMap::new());
+        }
+        let conn = self.connect()?;
+        let mut query = conn.prepare(
+            "SELECT tasks.grp, tasks.name, COALESCE(tasks.started_at, runs.started_at)
+             FROM tasks JOIN runs ON runs.id = tasks.run_id
+             WHERE tasks.status = 'success'",
+        )?;
+        let rows = query.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })?;
+        let mut last: HashMap<(String, String), DateTime<Local>> = HashMap::new();
+        for (group, name, at) in rows.flatten() {
+            let Ok(at) = DateTime::parse_from_rfc3339(&at) else {
+                continue;
+            };
+            let at = at.with_timezone(&Local);
+            let entry = last.entry((group, name)).or_insert(at);
+            *entry = (*entry).max(at);
+        }
+        Ok(last)
+    }
+
+    /// Average duration of successful executions per `(group, task)` over
+    /// the recent runs.
+    pub fn duration_estimates(&self) -> Result<HashMap<(String, String), Duration>> {
+        let mut totals: HashMap<(String, String), (f64, u32)> = HashMap::new();
+        for run in self.recent_runs(ESTIMATE_WINDOW)? {
+            for task in run.tasks {
+                if task.status != TaskStatus::Success {
+                    continue;
+                }
+                let entry = totals.entry((task.group, task.name)).or_default();
+                entry.0 += task.duration_secs;
+                entry.1 += 1;
+            }
+        }
+
+        Ok(totals
+            .into_iter()
+            .map(|(key, (total, count))| (key, Duration::from_secs_f64(total / count as f64)))
+            .collect())
+    }
+}
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS runs (
+    id INTEGER PRIMARY KEY,
+    started_at TEXT NOT NULL,
+    duration_secs REAL NOT NULL,
+    resumed INTEGER NOT NULL DEFAULT 0
+);
+CREATE TABLE IF NOT EXISTS tasks (
+    run_id INTEGER NOT NULL REFERENCES runs(id),
+    position INTEGER NOT NULL,
+    grp TEXT NOT NULL,
+    name TEXT NOT NULL,
+    status TEXT NOT NULL,
+    duration_secs REAL NOT NULL,
+    reason TEXT,
+    started_at TEXT,
+    output_hash TEXT
+);
+CREATE INDEX IF NOT EXISTS tasks_by_run ON tasks (run_id);
+";
+
+fn insert_run(conn: &Connection, run: &RunRecord) -> Result<()> {
+    conn.execute(
+        "INSERT INTO runs (started_at, duration_secs, resumed) VALUES (?1, ?2, ?3)",
+        params![run.started_at.to_rfc3339(), run.duration_secs, run.resumed],
+    )?;
+    let run_id = conn.last_insert_rowid();
+    for (position, task) in run.tasks.iter().enumerate() {
+        conn.execute(
+            "INSERT INTO tasks (run_id, position, grp, name, status, duration_secs, reason, started_at, output_hash)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                run_id,
+                position as i64,
+                task.group,
+                task.name,
+                status_name(task.status),
+                task.duration_secs,
+                task.reason,
+                task.started_at.map(|at| at.to_rfc3339()),
+                task.output_hash,
+            ],
+        )?;
+    }
+    Ok(())
+}
+
+fn status_name(status: TaskStatus) -> &'static str {
+    match status {
+        TaskStatus::Success => "success",
+        TaskStatus::Failed => "failed",
+        TaskStatus::Skipped => "skipped",
+        TaskStatus::Cancelled => "cancelled",
+    }
+}
+
+fn parse_status(name: &str) -> Option<TaskStatus> {
+    match name {
+        "success" => Some(TaskStatus::Success),
+        "failed" => Some(TaskStatus::Failed),
+        "skipped" => Some(TaskStatus::Skipped),
+        "cancelled" => Some(TaskStatus::Cancelled),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(name: &str, status: TaskStatus, duration_secs: f64) -> TaskRecord {
+        TaskRecord {
+            group: "Homebrew".to_string(),
+            name: name.to_string(),
+            status,
+            duration_secs,
+            reason: (status == TaskStatus::Failed).then(|| "Error: offline".to_string()),
+            started_at: Some(Local::now()),
+            output_hash: Some(sha256_hex("Already up-to-date.\n")),
+        }
+    }
+
+    #[test]
+    fn test_history_database() {
+        let dir = std::env::temp_dir().join(format!("tide-history-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let legacy = RunRecord {
+            started_at: Local::now(),
+            resumed: false,
+            duration_secs: 12.0,
+            tasks: vec![task("Update Formulae", TaskStatus::Success, 4.0)],
+        };
+        fs::write(
+            dir.join("history.jsonl"),
+            format!("{}\nnot json\n", serde_json::to_string(&legacy).unwrap()),
+        )
+        .unwrap();
+
+        let history = History::new(dir.join("history.db"));
+        history
+            .record(&RunRecord {
+                started_at: Local::now(),
+                resumed: false,
+                duration_secs: 20.0,
+                tasks: vec![
+                    task("Update Formulae", TaskStatus::Success, 6.0),
+                    task("Upgrade Packages", TaskStatus::Failed, 2.0),
+                ],
+            })
+            .unwrap();
+
+        let runs = history.recent_runs(10).unwrap();
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].duration_secs, 12.0);
+        assert_eq!(runs[1].tasks[1].status, TaskStatus::Failed);
+        assert_eq!(runs[1].tasks[1].reason.as_deref(), Some("Error: offline"));
+        assert_eq!(
+            runs[1].tasks[0].output_hash,
+            Some(sha256_hex("Already up-to-date.\n"))
+        );
+        assert_eq!(history.recent_runs(1).unwrap()[0].duration_secs, 20.0);
+
+        let last = history.last_successes().unwrap();
+        assert_eq!(last.len(), 1);
+        assert!(last.contains_key(&("Homebrew".to_string(), "Update Formulae".to_string())));
+
+        let estimates = history.duration_estimates().unwrap();
+        assert_eq!(
+            estimates[&("Homebrew".to_string(), "Update Formulae".to_string())],
+            Duration::from_secs(5)
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,109 @@
+use std::io::IsTerminal;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Whether stdout is a terminal known to render OSC 8 hyperlinks.
+/// `FORCE_HYPERLINK=1` or `=0` overrides the detection.
+pub fn supported() -> bool {
+    static SUPPORTED: OnceLock<bool> = OnceLock::new();
+    *SUPPORTED.get_or_init(|| {
+        detect(
+            |name| std::env::var(name).ok(),
+            std::io::stdout().is_terminal(),
+        )
+    })
+}
+
+fn detect(var: impl Fn(&str) -> Option<String>, is_terminal: bool) -> bool {
+    if let Some(force) = var("FORCE_HYPERLINK") {
+        return force != "0";
+    }
+    if !is_terminal || var("TERM").as_deref() == Some("dumb") {
+        return false;
+    }
+    let program = var("TERM_PROGRAM").unwrap_or_default();
+    matches!(
+        program.as_str(),
+        "iTerm.app" | "WezTerm" | "vscode" | "ghostty" | "Hyper" | "Tabby" | "rio"
+    ) || ["KITTY_WINDOW_ID", "ALACRITTY_WINDOW_ID", "WT_SESSION"]
+        .iter()
+        .any(|name| var(name).is_some())
+        || var("VTE_VERSION")
+            .and_then(|version| version.parse::<u32>().ok())
+            .is_some_and(|version| version >= 5000)
+}
+
+/// `text` linking to `url`, or `text` alone where links are not supported
+pub fn link(url: &str, text: &str) -> String {
+    if supported() {
+        osc8(url, text)
+    } else {
+        text.to_string()
+    }
+}
+
+fn osc8(url: &str, text: &str) -> String {
+    format!("\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\", url, text)
+}
+
+/// `file://` URL of an absolute path
+pub fn file_url(path: &Path) -> String {
+    let mut url = String::from("file://");
+    for byte in path.to_string_lossy().bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'/' | b'-' | b'_' | b'.' | b'~' => {
+                url.push(byte as char)
+            }
+            _ => url.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    url
+}
+
+/// The path, clickable to open the file
+pub fn path(path: &Path) -> String {
+    link(&file_url(path), &path.display().to_string())
+}
+
+/// `path:line`, clickable to open the file. Terminals that understand line
+/// fragments (e.g. iTerm2 with a semantic-history editor) jump to the line.
+pub fn path_line(path: &Path, line: usize) -> String {
+    link(
+        &format!("{}#L{}", file_url(path), line),
+        &format!("{}:{}", path.display(), line),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hyperlinks() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(key, _)| *key == name)
+                    .map(|(_, value)| value.to_string())
+            }
+        };
+        assert!(detect(env(&[("TERM_PROGRAM", "iTerm.app")]), true));
+        assert!(!detect(env(&[("TERM_PROGRAM", "iTerm.app")]), false));
+        assert!(!detect(env(&[("TERM_PROGRAM", "Apple_Terminal")]), true));
+        assert!(detect(env(&[("VTE_VERSION", "7600")]), true));
+        assert!(detect(env(&[("FORCE_HYPERLINK", "1")]), false));
+        assert!(!detect(
+            env(&[("FORCE_HYPERLINK", "0"), ("TERM_PROGRAM", "WezTerm")]),
+            true
+        ));
+
+        assert_eq!(
+            file_url(Path::new("/Users/me/Library/Logs/tide run.log")),
+            "file:///Users/me/Library/Logs/tide%20run.log"
+        );
+        assert_eq!(
+            osc8("https://brew.sh", "brew"),
+            "\x1b]8;;https://brew.sh\x1b\\brew\x1b]8;;\x1b\\"
+        );
+    }
+}
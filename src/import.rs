@@ -0,0 +1,775 @@
+use anyhow::{Context, Result};
+use ring::digest;
+use serde::Deserialize;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::backup;
+use crate::config::{Config, TaskConfig, TaskGroup};
+use crate::error::TideError;
+use crate::format::ConfigFormat;
+use crate::managed;
+
+/// Shell words that are not programs worth a `check_command`
+const SHELL_BUILTINS: &[&str] = &[
+    "if", "for", "while", "until", "case", "cd", "export", "echo", "printf", "test", "[", "[[",
+    "source", ".", "eval", "exec", "set", "unset", "local", "read", "true", "false", "exit",
+];
+
+/// Characters that need a shell to interpret the line
+const SHELL_SYNTAX: &[char] = &[
+    '|', '&', ';', '<', '>', '(', ')', '$', '`', '"', '\'', '\\', '*', '?', '[', ']', '{', '}',
+    '~', '#',
+];
+
+/// Split a maintenance shell script into proposed tasks.
+///
+/// Blank lines separate tasks and the comment lines above a block name it
+/// (further comment lines become the description). Divider comments such as
+/// `# ======` are ignored, as are the shebang and `set` options. Simple
+/// one-line commands are split into arguments; anything else runs via
+/// `sh -c`.
+pub fn parse_script(script: &str) -> Vec<TaskConfig> {
+    let mut tasks: Vec<TaskConfig> = Vec::new();
+    let mut comments: Vec<String> = Vec::new();
+    let mut block: Vec<&str> = Vec::new();
+
+    for (index, line) in script.lines().enumerate() {
+        let trimmed = line.trim();
+        if (index == 0 && trimmed.starts_with("#!")) || trimmed.starts_with("set -") {
+            continue;
+        }
+
+        if trimmed.is_empty() {
+            flush(&mut tasks, &mut comments, &mut block);
+        } else if let Some(comment) = trimmed.strip_prefix('#') {
+            if !block.is_empty() {
+                flush(&mut tasks, &mut comments, &mut block);
+            }
+            let text = comment.trim_matches(|c: char| c.is_whitespace() || "#=-*~".contains(c));
+            if !text.is_empty() {
+                comments.push(text.to_string());
+            }
+        } else {
+            block.push(line);
+        }
+    }
+    flush(&mut tasks, &mut comments, &mut block);
+    tasks
+}
+
+fn flush(tasks: &mut Vec<TaskConfig>, comments: &mut Vec<String>, block: &mut Vec<&str>) {
+    if block.is_empty() {
+        // A comment-only block is the header of the next one
+        return;
+    }
+
+    let mut task = command_task(block);
+    let mut comment_lines = comments.drain(..);
+    let name = comment_lines
+        .next()
+        .unwrap_or_else(|| derived_name(block[0]));
+    task.description = comment_lines.collect::<Vec<_>>().join(" ");
+    task.name = unique_name(tasks, name);
+    tasks.push(task);
+    block.clear();
+}
+
+fn command_task(block: &[&str]) -> TaskConfig {
+    let first = block[0].trim();
+    let simple = block.len() == 1 && !first.contains(SHELL_SYNTAX);
+
+    let mut task = TaskConfig::default();
+    if simple {
+        let mut words: Vec<String> = first.split_whitespace().map(str::to_string).collect();
+        if words.len() > 1 && words[0] == "sudo" {
+            words.remove(0);
+            task.sudo = true;
+        }
+        task.command = words;
+    } else {
+        let body = block
+            .iter()
+            .map(|line| line.trim_end())
+            .collect::<Vec<_>>()
+            .join("\n");
+        task.command = vec!["sh".to_string(), "-c".to_string(), dedent(&body)];
+    }
+
+    let program = first
+        .split_whitespace()
+        .find(|word| *word != "sudo")
+        .unwrap_or_default();
+    if !program.is_empty()
+        && !SHELL_BUILTINS.contains(&program)
+        && !program.contains(['/', '=', '$'])
+    {
+        task.check_command = Some(program.to_string());
+    }
+    task
+}
+
+/// Name for an uncommented block: its first few words
+fn derived_name(line: &str) -> String {
+    line.split_whitespace()
+        .filter(|word| *word != "sudo")
+        .take(3)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn unique_name(tasks: &[TaskConfig], name: String) -> String {
+    if !tasks.iter().any(|t| t.name == name) {
+        return name;
+    }
+    (2..)
+        .map(|n| format!("{} ({})", name, n))
+        .find(|candidate| !tasks.iter().any(|t| &t.name == candidate))
+        .unwrap_or(name)
+}
+
+fn dedent(text: &str) -> String {
+    let indent = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    text.lines()
+        .map(|line| line.get(indent..).unwrap_or(line.trim_start()))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Group name derived from a script file name: `mac-maintenance.sh` ->
+/// "Mac Maintenance"
+pub fn group_name_for(path: &Path) -> String {
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let name = stem
+        .split(['-', '_', ' ', '.'])
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+    if name.is_empty() {
+        "Imported".to_string()
+    } else {
+        name
+    }
+}
+
+/// TOML for an imported group, listing only the keys the import sets
+pub fn render_group(group: &TaskGroup) -> String {
+    let string = |value: &str| toml::Value::String(value.to_string()).to_string();
+    let mut out = String::new();
+    let _ = writeln!(out, "[[groups]]\nname = {}", string(&group.name));
+    if !group.icon.is_empty() {
+        let _ = writeln!(out, "icon = {}", string(&group.icon));
+    }
+    if !group.description.is_empty() {
+        let _ = writeln!(out, "description = {}", string(&group.description));
+    }
+
+    for task in &group.tasks {
+        let command = toml::Value::Array(
+            task.command
+                .iter()
+                .map(|arg| toml::Value::String(arg.clone()))
+                .collect(),
+        );
+        let _ = writeln!(
+            out,
+            "\n[[groups.tasks]]\nname = {}\ncommand = {}",
+            string(&task.name),
+            command
+        );
+        if task.sudo {
+            out.push_str("sudo = true\n");
+        }
+        if let Some(check) = &task.check_command {
+            let _ = writeln!(out, "check_command = {}", string(check));
+        }
+        if !task.description.is_empty() {
+            let _ = writeln!(out, "description = {}", string(&task.description));
+        }
+    }
+    out
+}
+
+/// Largest snippet `tide import url` accepts
+const MAX_SNIPPET_BYTES: usize = 1024 * 1024;
+
+/// A group from a shared TOML snippet together with its original text
+#[derive(Debug)]
+pub struct SnippetGroup {
+    pub group: TaskGroup,
+    /// The `[[groups]]` table up to its first task
+    pub head: String,
+    /// Bodies of the group's `[[groups.tasks]]` tables
+    pub tasks: Vec<String>,
+}
+
+impl SnippetGroup {
+    /// The group as written, with every task tagged with its source if given
+    fn render(&self, source: Option<&str>) -> String {
+        let mut out = format!("{}\n", self.head.trim_end());
+        for task in &self.tasks {
+            let task = match source {
+                Some(source) => tag_source(task, source),
+                None => task.trim_end().to_string(),
+            };
+            let _ = write!(out, "\n[[groups.tasks]]\n{}\n", task);
+        }
+        out
+    }
+}
+
+/// Record where a task came from by prepending `source = "..."` to its
+/// `[[groups.tasks]]` body
+pub fn tag_source(task_toml: &str, source: &str) -> String {
+    format!(
+        "source = {}\n{}",
+        toml::Value::String(source.to_string()),
+        task_toml.trim_end()
+    )
+}
+
+#[derive(Deserialize)]
+struct Snippet {
+    groups: Vec<TaskGroup>,
+}
+
+/// Download a snippet over HTTPS
+pub async fn fetch(url: &str) -> Result<String> {
+    if !url.starts_with("https://") {
+        return Err(
+            TideError::Config(format!("Only https:// URLs can be imported: {}", url)).into(),
+        );
+    }
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(30))
+        .user_agent(format!("tide-cli/{}", env!("CARGO_PKG_VERSION")))
+        .build()?;
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .with_context(|| format!("Failed to download {}", url))?;
+    let body = response
+        .bytes()
+        .await
+        .with_context(|| format!("Failed to download {}", url))?;
+    if body.len() > MAX_SNIPPET_BYTES {
+        return Err(TideError::Config(format!("{} is larger than 1 MiB", url)).into());
+    }
+    String::from_utf8(body.to_vec()).with_context(|| format!("{} is not UTF-8 text", url))
+}
+
+/// Lowercase hex SHA-256 of the content
+pub fn sha256_hex(content: &str) -> String {
+    digest::digest(&digest::SHA256, content.as_bytes())
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Fail unless the content hashes to the expected SHA-256
+pub fn verify_sha256(content: &str, expected: &str) -> Result<()> {
+    let actual = sha256_hex(content);
+    if actual.eq_ignore_ascii_case(expected.trim()) {
+        Ok(())
+    } else {
+        Err(TideError::Config(format!(
+            "Checksum mismatch: expected {}, got {}",
+            expected.trim(),
+            actual
+        ))
+        .into())
+    }
+}
+
+/// Parse a shared snippet that may only contain `[[groups]]` tables
+pub fn parse_snippet(text: &str) -> Result<Vec<SnippetGroup>> {
+    let table: toml::Table = toml::from_str(text).context("The snippet is not valid TOML")?;
+    if let Some(key) = table.keys().find(|key| *key != "groups") {
+        return Err(TideError::Config(format!(
+            "Snippets may only define [[groups]], found '{}'",
+            key
+        ))
+        .into());
+    }
+    let snippet: Snippet = toml::from_str(text).context("The snippet has invalid groups")?;
+
+    let segments = split_tables(text, "[[groups]]");
+    if segments.len() != snippet.groups.len() {
+        return Err(TideError::Config(
+            "Write each group as a [[groups]] table so it can be merged".to_string(),
+        )
+        .into());
+    }
+    snippet
+        .groups
+        .into_iter()
+        .zip(segments)
+        .map(|(group, segment)| {
+            let head = segment
+                .split_once("[[groups.tasks]]")
+                .map_or(segment, |(head, _)| head)
+                .to_string();
+            let tasks: Vec<String> = split_tables(segment, "[[groups.tasks]]")
+                .into_iter()
+                .map(|task| {
+                    task.split_once('\n')
+                        .map_or("", |(_, body)| body)
+                        .trim()
+                        .to_string()
+                })
+                .collect();
+            if tasks.len() != group.tasks.len() {
+                return Err(TideError::Config(format!(
+                    "Write the tasks of '{}' as [[groups.tasks]] tables so they can be merged",
+                    group.name
+                ))
+                .into());
+            }
+            Ok(SnippetGroup { group, head, tasks })
+        })
+        .collect()
+}
+
+/// Sections of `text` starting at each line that is exactly `header`
+fn split_tables<'a>(text: &'a str, header: &str) -> Vec<&'a str> {
+    let mut starts = Vec::new();
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        if line.trim() == header {
+            starts.push(offset);
+        }
+        offset += line.len();
+    }
+    starts
+        .iter()
+        .enumerate()
+        .map(|(index, &start)| &text[start..starts.get(index + 1).copied().unwrap_or(text.len())])
+        .collect()
+}
+
+/// Append a group to the config file without rewriting the rest of it.
+///
+/// The config is backed up first; returns the backup path.
+pub fn append_group(config_path: &Path, group: &TaskGroup) -> Result<Option<PathBuf>> {
+    let existing = fs::read_to_string(config_path).unwrap_or_default();
+    write_verified(
+        config_path,
+        &existing,
+        &appended(&existing, &render_group(group)),
+    )
+}
+
+/// Add a `[[groups.tasks]]` table to the named group, creating the group at
+/// the end of the config when it does not exist yet.
+///
+/// The rest of the file is left untouched; the config is backed up first and
+/// restored when the result no longer loads. Returns the backup path.
+pub fn insert_task(config_path: &Path, group: &str, task_toml: &str) -> Result<Option<PathBuf>> {
+    let existing = fs::read_to_string(config_path).unwrap_or_default();
+    write_verified(
+        config_path,
+        &existing,
+        &with_task(&existing, group, task_toml),
+    )
+}
+
+/// Merge shared groups into the config: tasks of groups that already exist
+/// are added to them, new groups are appended as written. Every task is
+/// tagged with `source`.
+///
+/// Backed up and verified like [`insert_task`]; returns the backup path.
+pub fn merge_snippet(
+    config_path: &Path,
+    groups: &[SnippetGroup],
+    source: &str,
+) -> Result<Option<PathBuf>> {
+    let existing = fs::read_to_string(config_path).unwrap_or_default();
+    let updated = groups.iter().fold(existing.clone(), |config, shared| {
+        if group_end(&config, &shared.group.name).is_some() {
+            shared.tasks.iter().fold(config, |config, task| {
+                with_task(&config, &shared.group.name, &tag_source(task, source))
+            })
+        } else {
+            appended(&config, &shared.render(Some(source)))
+        }
+    });
+    write_verified(config_path, &existing, &updated)
+}
+
+fn appended(existing: &str, text: &str) -> String {
+    let separator = if existing.is_empty() || existing.ends_with("\n\n") {
+        ""
+    } else if existing.ends_with('\n') {
+        "\n"
+    } else {
+        "\n\n"
+    };
+    format!("{}{}{}", existing, separator, text)
+}
+
+fn with_task(existing: &str, group: &str, task_toml: &str) -> String {
+    let table = format!("[[groups.tasks]]\n{}", task_toml.trim_end());
+    match group_end(existing, group) {
+        Some(end) => format!(
+            "{}\n\n{}\n{}",
+            existing[..end].trim_end(),
+            table,
+            &existing[end..]
+        ),
+        None => {
+            let name = toml::Value::String(group.to_string());
+            appended(
+                existing,
+                &format!("[[groups]]\nname = {}\n\n{}\n", name, table),
+            )
+        }
+    }
+}
+
+/// Back up the config, write the updated text and restore the original when
+/// the result no longer loads
+fn write_verified(config_path: &Path, existing: &str, updated: &str) -> Result<Option<PathBuf>> {
+    managed::ensure_writable(config_path)?;
+    if ConfigFormat::of(config_path) != ConfigFormat::Toml {
+        return Err(TideError::Config(format!(
+            "tide only edits TOML configs and {} is {}; add the tasks to it by hand.",
+            config_path.display(),
+            ConfigFormat::of(config_path).extension().to_uppercase()
+        ))
+        .into());
+    }
+    let backup = backup::create(config_path)?;
+    fs::write(config_path, updated)
+        .with_context(|| format!("Failed to write {}", config_path.display()))?;
+    if let Err(err) = Config::load(Some(&config_path.to_path_buf())) {
+        fs::write(config_path, existing)
+            .with_context(|| format!("Failed to restore {}", config_path.display()))?;
+        return Err(err.context("The config would no longer load; left it unchanged"));
+    }
+    Ok(backup)
+}
+
+/// Byte offset just after the last setting or task of the named group,
+/// before any comments that introduce the next table
+fn group_end(config: &str, group: &str) -> Option<usize> {
+    group_span(config, group).map(|(_, end)| end)
+}
+
+/// Byte range of the named group: from its `[[groups]]` header to just
+/// after its last setting or task
+fn group_span(config: &str, group: &str) -> Option<(usize, usize)> {
+    let mut offset = 0;
+    let mut in_group = false;
+    let mut header = None;
+    let mut start = 0;
+    let mut end = None;
+
+    for line in config.split_inclusive('\n') {
+        let trimmed = line.trim();
+        let top_level = trimmed.starts_with('[')
+            && !trimmed.starts_with("[groups.")
+            && !trimmed.starts_with("[[groups.");
+        if top_level {
+            if in_group {
+                return end.map(|end| (start, end));
+            }
+            header = (trimmed == "[[groups]]").then_some(offset);
+        } else if let Some(header_start) = header
+            && let Some(value) = trimmed.strip_prefix("name")
+        {
+            // The first `name` after `[[groups]]` names the group
+            header = None;
+            start = header_start;
+            in_group = toml::from_str::<toml::Table>(&format!("name{}", value))
+                .ok()
+                .and_then(|table| table.get("name")?.as_str().map(|name| name == group))
+                .unwrap_or(false);
+        }
+
+        offset += line.len();
+        if in_group && !trimmed.is_empty() && !trimmed.starts_with('#') {
+            end = Some(offset);
+        }
+    }
+    if in_group {
+        end.map(|end| (start, end))
+    } else {
+        None
+    }
+}
+
+/// The named group with its tasks as a standalone TOML document, exactly as
+/// written in the config (comments between its tables included)
+pub fn export_group(config: &str, group: &str) -> Option<String> {
+    let (start, end) = group_span(config, group)?;
+    Some(format!("{}\n", config[start..end].trim_end()))
+}
+
+/// Append the groups of an exported document to the config as written.
+///
+/// Backed up and verified like [`insert_task`]; returns the backup path.
+pub fn import_groups(config_path: &Path, groups: &[SnippetGroup]) -> Result<Option<PathBuf>> {
+    let existing = fs::read_to_string(config_path).unwrap_or_default();
+    let updated = groups.iter().fold(existing.clone(), |config, shared| {
+        appended(&config, &shared.render(None))
+    });
+    write_verified(config_path, &existing, &updated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCRIPT: &str = r#"#!/bin/bash
+set -euo pipefail
+
+# ==== Homebrew ====
+# Refresh formulae and upgrade everything
+brew update
+brew upgrade
+
+sudo softwareupdate --install --all
+
+# Clear caches
+rm -rf ~/Library/Caches/pip
+# Cargo
+cargo install-update -a
+"#;
+
+    #[test]
+    fn test_parse_script_into_tasks() {
+        let tasks = parse_script(SCRIPT);
+        assert_eq!(tasks.len(), 4);
+
+        assert_eq!(tasks[0].name, "Homebrew");
+        assert_eq!(
+            tasks[0].description,
+            "Refresh formulae and upgrade everything"
+        );
+        assert_eq!(tasks[0].command, ["sh", "-c", "brew update\nbrew upgrade"]);
+        assert_eq!(tasks[0].check_command.as_deref(), Some("brew"));
+
+        assert_eq!(tasks[1].name, "softwareupdate --install --all");
+        assert!(tasks[1].sudo);
+        assert_eq!(tasks[1].command, ["softwareupdate", "--install", "--all"]);
+
+        assert_eq!(tasks[2].name, "Clear caches");
+        assert_eq!(tasks[2].command[0], "sh");
+        assert_eq!(tasks[2].check_command.as_deref(), Some("rm"));
+
+        assert_eq!(tasks[3].name, "Cargo");
+        assert_eq!(tasks[3].command, ["cargo", "install-update", "-a"]);
+
+        assert_eq!(
+            group_name_for(Path::new("/tmp/mac-maintenance.sh")),
+            "Mac Maintenance"
+        );
+    }
+
+    #[test]
+    fn test_append_group_keeps_config_loadable() {
+        let dir = std::env::temp_dir().join(format!("tide-import-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let config_path = dir.join("config.toml");
+        fs::write(&config_path, "# my settings\n[settings]\nverbose = true\n").unwrap();
+
+        let group = TaskGroup {
+            name: "Maintenance".to_string(),
+            icon: String::new(),
+            enabled: true,
+            description: String::new(),
+            parallel: false,
+            needs_disk_space: false,
+            heavy: false,
+            min_interval: None,
+            tasks: parse_script(SCRIPT),
+        };
+        let backup = append_group(&config_path, &group).unwrap();
+        assert!(backup.is_some());
+
+        let contents = fs::read_to_string(&config_path).unwrap();
+        assert!(contents.starts_with("# my settings\n"));
+        let config = Config::load(Some(&config_path)).unwrap();
+        assert!(config.settings.verbose);
+        assert_eq!(config.groups.len(), 1);
+        let imported: Vec<_> = config.groups[0].tasks.iter().map(|t| &t.command).collect();
+        let expected: Vec<_> = group.tasks.iter().map(|t| &t.command).collect();
+        assert_eq!(imported, expected);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_insert_task_into_existing_group() {
+        let dir = std::env::temp_dir().join(format!("tide-insert-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let config_path = dir.join("config.toml");
+        fs::write(
+            &config_path,
+            "[[groups]]\nname = \"Homebrew\"\n\n[[groups.tasks]]\nname = \"Update\"\ncommand = [\"brew\", \"update\"]\n\n# ==== Rust ====\n[[groups]]\nname = \"Rust\"\n",
+        )
+        .unwrap();
+
+        insert_task(
+            &config_path,
+            "Homebrew",
+            "name = \"Cleanup\"\ncommand = [\"brew\", \"cleanup\"]\n",
+        )
+        .unwrap();
+        insert_task(
+            &config_path,
+            "Docker",
+            "name = \"Prune\"\ntype = \"docker-maintenance\"\n",
+        )
+        .unwrap();
+        assert!(insert_task(&config_path, "Rust", "name = \"Broken\"\ncommand = 1\n").is_err());
+
+        let config = Config::load(Some(&config_path)).unwrap();
+        let tasks = |group: &str| -> Vec<String> {
+            config
+                .groups
+                .iter()
+                .find(|g| g.name == group)
+                .unwrap()
+                .tasks
+                .iter()
+                .map(|t| t.name.clone())
+                .collect()
+        };
+        assert_eq!(tasks("Homebrew"), ["Update", "Cleanup"]);
+        assert!(tasks("Rust").is_empty());
+        assert_eq!(tasks("Docker"), ["Prune"]);
+        assert!(
+            fs::read_to_string(&config_path)
+                .unwrap()
+                .contains("\n\n# ==== Rust ====\n[[groups]]")
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_merge_shared_snippet() {
+        let snippet = r#"# Shared recipes
+[[groups]]
+name = "Homebrew"
+
+[[groups.tasks]]
+name = "Cleanup"
+command = ["brew", "cleanup"]
+
+[[groups]]
+name = "Docker"
+icon = "🐳"
+parallel = true
+
+[[groups.tasks]]
+name = "Prune"
+type = "docker-maintenance"
+
+  [groups.tasks.docker]
+  prune_volumes = true
+"#;
+        assert_eq!(
+            sha256_hex("abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert!(verify_sha256(snippet, &sha256_hex(snippet).to_uppercase()).is_ok());
+        assert!(verify_sha256(snippet, &sha256_hex("abc")).is_err());
+        assert!(parse_snippet("[settings]\nverbose = true\n").is_err());
+
+        let groups = parse_snippet(snippet).unwrap();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(
+            groups[1].tasks[0],
+            "name = \"Prune\"\ntype = \"docker-maintenance\"\n\n  [groups.tasks.docker]\n  prune_volumes = true"
+        );
+
+        let dir = std::env::temp_dir().join(format!("tide-merge-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let config_path = dir.join("config.toml");
+        fs::write(&config_path, "[[groups]]\nname = \"Homebrew\"\n\n[[groups.tasks]]\nname = \"Update\"\ncommand = [\"brew\", \"update\"]\n").unwrap();
+
+        merge_snippet(&config_path, &groups, "https://example.com/recipes.toml").unwrap();
+        let config = Config::load(Some(&config_path)).unwrap();
+        assert_eq!(config.groups.len(), 2);
+        assert_eq!(config.groups[0].tasks.len(), 2);
+        assert!(config.groups[1].parallel);
+        assert!(
+            config.groups[1].tasks[0]
+                .docker
+                .as_ref()
+                .unwrap()
+                .prune_volumes
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_export_and_import_group() {
+        let config = r#"[settings]
+verbose = true
+
+# ==== Docker ====
+[[groups]]
+name = "Docker"
+icon = "🐳"
+
+[[groups.tasks]]
+# Keep named volumes
+name = "Prune"
+type = "docker-maintenance"
+
+[[groups]]
+name = "Rust"
+"#;
+        assert!(export_group(config, "Python").is_none());
+        let document = export_group(config, "Docker").unwrap();
+        assert!(document.starts_with("[[groups]]\nname = \"Docker\""));
+        assert!(document.ends_with("type = \"docker-maintenance\"\n"));
+        assert_eq!(
+            export_group(config, "Rust").unwrap(),
+            "[[groups]]\nname = \"Rust\"\n"
+        );
+
+        let dir = std::env::temp_dir().join(format!("tide-group-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let config_path = dir.join("config.toml");
+        fs::write(&config_path, "[settings]\nverbose = false\n").unwrap();
+
+        import_groups(&config_path, &parse_snippet(&document).unwrap()).unwrap();
+        let contents = fs::read_to_string(&config_path).unwrap();
+        assert!(contents.contains("# Keep named volumes\nname = \"Prune\""));
+        assert!(!contents.contains("source ="));
+        let imported = Config::load(Some(&config_path)).unwrap();
+        assert_eq!(imported.groups[0].name, "Docker");
+        assert_eq!(imported.groups[0].tasks.len(), 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,189 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use toml::Table;
+
+use crate::error::TideError;
+use crate::format::ConfigFormat;
+use crate::overlay;
+
+/// Merge the files named by the `include` patterns of a config into it, in
+/// order.
+///
+/// Patterns are relative to the directory of the config and may use `*` and
+/// `?` in their file name (`groups/*.toml`); matches are taken in
+/// alphabetical order. Included files hold `[[groups]]` and `[[tests]]` in
+/// any config format and are merged like a host overlay, so a group named
+/// like an existing one adds its tasks to it.
+pub fn apply(config: &mut Table, config_path: &Path, notices: &mut Vec<String>) -> Result<()> {
+    let Some(patterns) = config.get("include") else {
+        return Ok(());
+    };
+    let patterns: Vec<String> = patterns
+        .clone()
+        .try_into()
+        .map_err(|_| TideError::Config("`include` must be a list of file patterns".to_string()))?;
+    let dir = config_path.parent().unwrap_or_else(|| Path::new("."));
+
+    for pattern in &patterns {
+        for path in expand(pattern, dir)? {
+            if path == config_path {
+                continue;
+            }
+            let text = fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read included file {}", path.display()))?;
+            let layer = ConfigFormat::of(&path).parse(&text).map_err(|err| {
+                TideError::Config(format!(
+                    "Failed to parse included file {}: {:#}",
+                    path.display(),
+                    err
+                ))
+            })?;
+            if let Some(key) = layer.keys().find(|key| *key != "groups" && *key != "tests") {
+                return Err(TideError::Config(format!(
+                    "Included file {} may only define groups and tests, not `{}`",
+                    path.display(),
+                    key
+                ))
+                .into());
+            }
+            overlay::apply(config, layer, &[], notices);
+        }
+    }
+    Ok(())
+}
+
+/// Files matching `pattern` relative to `dir`, sorted. A pattern without
+/// wildcards must name an existing file; one with wildcards may match none.
+pub fn expand(pattern: &str, dir: &Path) -> Result<Vec<PathBuf>> {
+    let path = dir.join(shellexpand::tilde(pattern).as_ref());
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let parent = path.parent().unwrap_or(dir);
+    if parent.to_string_lossy().contains(['*', '?']) {
+        return Err(TideError::Config(format!(
+            "Include pattern '{}' may only use wildcards in the file name",
+            pattern
+        ))
+        .into());
+    }
+    if !name.contains(['*', '?']) {
+        if !path.is_file() {
+            return Err(
+                TideError::Config(format!("Included file not found: {}", path.display())).into(),
+            );
+        }
+        return Ok(vec![path]);
+    }
+
+    let entries = match fs::read_dir(parent) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => {
+            return Err(err).with_context(|| format!("Failed to read {}", parent.display()));
+        }
+    };
+    let pattern: Vec<char> = name.chars().collect();
+    let mut matches: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file())
+        .filter(|path| {
+            path.file_name()
+                .map(|name| name.to_string_lossy().chars().collect::<Vec<_>>())
+                // Hidden files only match patterns that start with a dot
+                .is_some_and(|name| {
+                    (name.first() != Some(&'.') || pattern.first() == Some(&'.'))
+                        && wildcard(&pattern, &name)
+                })
+        })
+        .collect();
+    matches.sort();
+    Ok(matches)
+}
+
+/// Whether `name` matches `pattern`, where `*` stands for any run of
+/// characters and `?` for a single one
+fn wildcard(pattern: &[char], name: &[char]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some(('*', rest)) => (0..=name.len()).any(|skip| wildcard(rest, &name[skip..])),
+        Some((&c, rest)) => name
+            .split_first()
+            .is_some_and(|(&n, name)| (c == '?' || c == n) && wildcard(rest, name)),
+    }
+}
+
+/// Files included by `patterns`, for display
+pub fn files(patterns: &[String], config_path: &Path) -> Vec<PathBuf> {
+    let dir = config_path.parent().unwrap_or_else(|| Path::new("."));
+    patterns
+        .iter()
+        .filter_map(|pattern| expand(pattern, dir).ok())
+        .flatten()
+        .filter(|path| path != config_path)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn test_include_group_files() {
+        let dir = std::env::temp_dir().join(format!("tide-include-test-{}", std::process::id()));
+        fs::create_dir_all(dir.join("groups")).unwrap();
+        let config_path = dir.join("config.toml");
+        fs::write(
+            &config_path,
+            r#"
+include = ["groups/*.toml", "groups/dev.yaml"]
+
+[[groups]]
+name = "Homebrew"
+
+[[groups.tasks]]
+name = "Update"
+command = ["brew", "update"]
+"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.join("groups/brew.toml"),
+            r#"
+[[groups]]
+name = "Homebrew"
+
+[[groups.tasks]]
+name = "Cleanup"
+command = ["brew", "cleanup"]
+"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.join("groups/dev.yaml"),
+            "groups:\n- name: Rust\n  tasks:\n  - name: Update\n    command: [rustup, update]\n",
+        )
+        .unwrap();
+
+        let config = Config::load(Some(&config_path)).unwrap();
+        let names: Vec<&str> = config.groups.iter().map(|g| g.name.as_str()).collect();
+        assert_eq!(names, ["Homebrew", "Rust"]);
+        assert_eq!(config.groups[0].tasks[1].command, ["brew", "cleanup"]);
+
+        fs::write(
+            dir.join("groups/settings.toml"),
+            "[settings]\nverbose = true\n",
+        )
+        .unwrap();
+        assert!(Config::load(Some(&config_path)).is_err());
+        assert!(expand("groups/missing.toml", &dir).is_err());
+        assert!(expand("*/brew.toml", &dir).is_err());
+        assert!(wildcard(&['*', '.', 't'], &['a', '.', 't']));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,471 @@
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+use super::{IntegrationContext, capture, succeeds};
+use crate::config::{CaskOptions, RunningAppPolicy};
+
+/// Homebrew state shared by the built-in tasks of a run, so the slow
+/// `brew outdated` and `brew info` queries run once instead of once per task.
+///
+/// Anything that may install or upgrade packages must [`invalidate`] it.
+///
+/// [`invalidate`]: BrewCache::invalidate
+#[derive(Default)]
+pub struct BrewCache {
+    outdated: Mutex<Option<Arc<Value>>>,
+    outdated_greedy: Mutex<Option<Arc<Value>>>,
+    installed: Mutex<Option<Arc<Value>>>,
+}
+
+impl BrewCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `brew outdated --json=v2`, formulae and casks
+    pub async fn outdated(&self, greedy: bool) -> Result<Arc<Value>> {
+        if greedy {
+            cached(
+                &self.outdated_greedy,
+                &["outdated", "--json=v2", "--greedy"],
+            )
+            .await
+        } else {
+            cached(&self.outdated, &["outdated", "--json=v2"]).await
+        }
+    }
+
+    /// `brew info --json=v2 --installed`: every installed formula and cask
+    pub async fn installed(&self) -> Result<Arc<Value>> {
+        cached(&self.installed, &["info", "--json=v2", "--installed"]).await
+    }
+
+    /// Forget everything queried so far
+    pub async fn invalidate(&self) {
+        for slot in [&self.outdated, &self.outdated_greedy, &self.installed] {
+            *slot.lock().await = None;
+        }
+    }
+}
+
+/// Run a JSON-producing brew query unless its result is cached. The lock is
+/// held while it runs so parallel tasks wait for one query instead of
+/// starting their own.
+async fn cached(slot: &Mutex<Option<Arc<Value>>>, args: &[&str]) -> Result<Arc<Value>> {
+    let mut cached = slot.lock().await;
+    if let Some(value) = cached.as_ref() {
+        return Ok(Arc::clone(value));
+    }
+    let value: Value = serde_json::from_str(&capture("brew", args).await?)
+        .with_context(|| format!("Failed to parse `brew {}` output", args[0]))?;
+    let value = Arc::new(value);
+    *cached = Some(Arc::clone(&value));
+    Ok(value)
+}
+
+/// Names of the outdated casks in `brew outdated --json=v2` output
+pub fn outdated_cask_names(outdated: &Value) -> impl Iterator<Item = &str> {
+    outdated["casks"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|cask| cask["name"].as_str())
+}
+
+/// An outdated cask together with the app bundles it installs
+#[derive(Debug, PartialEq, Eq)]
+struct OutdatedCask {
+    token: String,
+    apps: Vec<String>,
+}
+
+/// Upgrade outdated casks, handling apps that are currently running
+pub async fn upgrade_casks(ctx: &IntegrationContext<'_>) -> Result<String> {
+    let options = ctx.task.cask.clone().unwrap_or_default();
+    let casks = outdated_casks(ctx, &options).await?;
+    if casks.is_empty() {
+        return Ok("All casks are up to date".to_string());
+    }
+
+    let mut upgrade_now = Vec::new();
+    let mut restart = Vec::new();
+    let mut skipped = Vec::new();
+
+    for cask in casks {
+        let running = running_apps(&cask.apps).await;
+        if running.is_empty() {
+            upgrade_now.push(cask.token);
+            continue;
+        }
+
+        let quit_and_relaunch = match options.running_apps {
+            RunningAppPolicy::Skip => false,
+            RunningAppPolicy::Restart => true,
+            RunningAppPolicy::Prompt => ctx.confirm(
+                &format!(
+                    "{} is running. Quit it, upgrade '{}' and relaunch?",
+                    running.join(", "),
+                    cask.token
+                ),
+                false,
+            ),
+        };
+
+        if quit_and_relaunch {
+            restart.push((cask.token, running));
+        } else {
+            skipped.push(cask.token);
+        }
+    }
+
+    let mut report = Vec::new();
+    if !upgrade_now.is_empty() || !restart.is_empty() {
+        ctx.brew.invalidate().await;
+    }
+
+    if !upgrade_now.is_empty() {
+        let mut args = vec!["upgrade", "--cask"];
+        if options.greedy {
+            args.push("--greedy");
+        }
+        args.extend(upgrade_now.iter().map(String::as_str));
+        capture("brew", &args).await?;
+        report.push(format!("Upgraded: {}", upgrade_now.join(", ")));
+    }
+
+    let mut restarted = Vec::new();
+    for (token, apps) in restart {
+        // Spawned so the task's timeout cannot cut it short between quitting
+        // the apps and reopening them
+        tokio::spawn(restart_cask(token.clone(), apps))
+            .await
+            .with_context(|| format!("Upgrading '{}' was interrupted", token))??;
+        restarted.push(token);
+    }
+    if !restarted.is_empty() {
+        report.push(format!("Upgraded and relaunched: {}", restarted.join(", ")));
+    }
+
+    if !skipped.is_empty() {
+        report.push(format!("Skipped (app running): {}", skipped.join(", ")));
+    }
+
+    Ok(report.join("\n"))
+}
+
+/// Quit the running apps of a cask, upgrade it and reopen them
+async fn restart_cask(token: String, apps: Vec<String>) -> Result<()> {
+    let mut relaunch = Relaunch::default();
+    for app in apps {
+        quit_app(&app).await?;
+        relaunch.apps.push(app);
+    }
+    capture("brew", &["upgrade", "--cask", &token]).await?;
+    Ok(())
+}
+
+/// Reopens the apps it holds when dropped, so the user is not left without
+/// an app quit for an upgrade however the upgrade ends
+#[derive(Default)]
+struct Relaunch {
+    apps: Vec<String>,
+}
+
+impl Drop for Relaunch {
+    fn drop(&mut self) {
+        for app in &self.apps {
+            let _ = std::process::Command::new("open")
+                .args(["-a", app])
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn();
+        }
+    }
+}
+
+/// Outdated casks and the app bundles they install
+async fn outdated_casks(
+    ctx: &IntegrationContext<'_>,
+    options: &CaskOptions,
+) -> Result<Vec<OutdatedCask>> {
+    let outdated = ctx.brew.outdated(options.greedy).await?;
+    let tokens = outdated_tokens(&outdated, &options.exclude);
+    if tokens.is_empty() {
+        return Ok(Vec::new());
+    }
+    let info = ctx.brew.installed().await?;
+    Ok(with_apps(tokens, &info))
+}
+
+/// Outdated casks of `brew outdated --json=v2` output that are not excluded
+fn outdated_tokens<'a>(outdated: &'a Value, exclude: &[String]) -> Vec<&'a str> {
+    outdated_cask_names(outdated)
+        .filter(|token| !exclude.iter().any(|ex| ex == token))
+        .collect()
+}
+
+/// The casks with the app bundles `brew info --json=v2 --installed` lists
+/// for them
+fn with_apps(tokens: Vec<&str>, info: &Value) -> Vec<OutdatedCask> {
+    tokens
+        .into_iter()
+        .map(|token| {
+            let apps = info["casks"]
+                .as_array()
+                .and_then(|casks| casks.iter().find(|cask| cask["token"] == token))
+                .map(cask_apps)
+                .unwrap_or_default();
+            OutdatedCask {
+                token: token.to_string(),
+                apps,
+            }
+        })
+        .collect()
+}
+
+/// Extract the `.app` bundle names from a cask's artifacts
+fn cask_apps(cask: &Value) -> Vec<String> {
+    cask["artifacts"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|artifact| artifact["app"].as_array())
+        .flatten()
+        .filter_map(|app| match app {
+            Value::String(name) => Some(name.clone()),
+            // Renamed artifacts: ["Source.app", {"target": "Target.app"}]
+            Value::Object(map) => map
+                .get("target")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+            _ => None,
+        })
+        .filter(|name| name.ends_with(".app"))
+        .map(|name| name.trim_end_matches(".app").to_string())
+        .collect()
+}
+
+/// Return the apps from the list that currently have a running process
+async fn running_apps(apps: &[String]) -> Vec<String> {
+    let mut running = Vec::new();
+    for app in apps {
+        let pattern = format!("{}.app/Contents/MacOS/", app);
+        if succeeds("pgrep", &["-f", &pattern]).await {
+            running.push(app.clone());
+        }
+    }
+    running
+}
+
+/// Ask an app to quit gracefully and wait for it to exit
+async fn quit_app(app: &str) -> Result<()> {
+    let script = format!("quit app \"{}\"", app.replace('"', "\\\""));
+    capture("osascript", &["-e", &script])
+        .await
+        .with_context(|| format!("Failed to quit {}", app))?;
+
+    let pattern = format!("{}.app/Contents/MacOS/", app);
+    for _ in 0..20 {
+        if !succeeds("pgrep", &["-f", &pattern]).await {
+            return Ok(());
+        }
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+    Err(anyhow::anyhow!("{} did not quit within 10 seconds", app))
+}
+
+/// A running Homebrew service and the formula it belongs to
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct RunningService {
+    name: String,
+    pid: u32,
+}
+
+/// Restart running `brew services` whose formula was upgraded after the
+/// service process started, so they stop running the old binary
+pub async fn restart_services(ctx: &IntegrationContext<'_>) -> Result<String> {
+    let options = ctx.task.services.clone().unwrap_or_default();
+    let services =
+        parse_services(&capture("brew", &["services", "info", "--all", "--json"]).await?)?;
+    let services: Vec<RunningService> = services
+        .into_iter()
+        .filter(|service| options.allows(&service.name))
+        .collect();
+    if services.is_empty() {
+        return Ok("No running services".to_string());
+    }
+
+    let info = ctx.brew.installed().await?;
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default();
+
+    let mut restarted = Vec::new();
+    for service in &services {
+        let Some(installed_at) = install_time(&info, &service.name) else {
+            continue;
+        };
+        let Some(uptime) = process_uptime(service.pid).await else {
+            continue;
+        };
+        if now.saturating_sub(uptime) < installed_at {
+            capture("brew", &["services", "restart", &service.name]).await?;
+            restarted.push(service.name.clone());
+        }
+    }
+
+    if restarted.is_empty() {
+        Ok(format!(
+            "{} running service(s) already use the installed version",
+            services.len()
+        ))
+    } else {
+        Ok(format!("Restarted: {}", restarted.join(", ")))
+    }
+}
+
+/// Running services from `brew services info --all --json`
+fn parse_services(json: &str) -> Result<Vec<RunningService>> {
+    let services: Value =
+        serde_json::from_str(json).context("Failed to parse `brew services info` output")?;
+    Ok(services
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|service| service["running"].as_bool().unwrap_or(false))
+        .filter_map(|service| {
+            Some(RunningService {
+                name: service["name"].as_str()?.to_string(),
+                pid: service["pid"].as_u64()?.try_into().ok()?,
+            })
+        })
+        .collect())
+}
+
+/// Unix time at which the linked version of a formula was installed
+fn install_time(info: &Value, formula: &str) -> Option<u64> {
+    info["formulae"]
+        .as_array()?
+        .iter()
+        .find(|f| f["name"] == formula || f["full_name"] == formula)?["installed"]
+        .as_array()?
+        .iter()
+        .filter_map(|keg| keg["time"].as_u64())
+        .max()
+}
+
+/// Seconds since the process started, from `ps -o etimes=`
+async fn process_uptime(pid: u32) -> Option<u64> {
+    capture("ps", &["-o", "etimes=", "-p", &pid.to_string()])
+        .await
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_running_services_and_install_times() {
+        let services = parse_services(
+            r#"[{"name":"postgresql@16","running":true,"pid":812},{"name":"redis","running":false,"pid":null}]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            services,
+            [RunningService {
+                name: "postgresql@16".to_string(),
+                pid: 812
+            }]
+        );
+
+        let info: Value = serde_json::from_str(
+            r#"{"formulae":[{"name":"postgresql@16","full_name":"postgresql@16","installed":[{"version":"16.2","time":1700000000},{"version":"16.3","time":1716000000}]}]}"#,
+        )
+        .unwrap();
+        assert_eq!(install_time(&info, "postgresql@16"), Some(1716000000));
+        assert_eq!(install_time(&info, "redis"), None);
+    }
+
+    #[test]
+    fn test_outdated_casks_with_their_apps() {
+        let outdated: Value = serde_json::from_str(
+            r#"{"formulae":[],"casks":[{"name":"visual-studio-code"},{"name":"orbstack"},{"name":"zoom"}]}"#,
+        )
+        .unwrap();
+        let info: Value = serde_json::from_str(
+            r#"{"formulae":[],"casks":[
+                {"token":"visual-studio-code","artifacts":[{"app":["Visual Studio Code.app"]},{"binary":["code"]}]},
+                {"token":"orbstack","artifacts":[{"app":["OrbStack.app",{"target":"Orb.app"}]},{"uninstall":[]}]},
+                {"token":"zoom","artifacts":[{"pkg":["zoomusInstallerFull.pkg"]}]}
+            ]}"#,
+        )
+        .unwrap();
+
+        let tokens = outdated_tokens(&outdated, &["zoom".to_string()]);
+        assert_eq!(tokens, ["visual-studio-code", "orbstack"]);
+        let cask = |token: &str, apps: &[&str]| OutdatedCask {
+            token: token.to_string(),
+            apps: apps.iter().map(|app| app.to_string()).collect(),
+        };
+        assert_eq!(
+            with_apps(outdated_tokens(&outdated, &[]), &info),
+            [
+                cask("visual-studio-code", &["Visual Studio Code"]),
+                cask("orbstack", &["OrbStack", "Orb"]),
+                cask("zoom", &[]),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_running_apps() {
+        let app = format!("Tide Test {}", std::process::id());
+        let dir = std::env::temp_dir()
+            .join(format!("{}.app", app))
+            .join("Contents")
+            .join("MacOS");
+        std::fs::create_dir_all(&dir).unwrap();
+        let binary = dir.join("tide-test");
+        std::fs::write(&binary, "sleep 30\n").unwrap();
+        let mut process = std::process::Command::new("sh")
+            .arg(&binary)
+            .spawn()
+            .unwrap();
+
+        let apps = [app.clone(), "Tide Not Running".to_string()];
+        assert_eq!(running_apps(&apps).await, apps[..1]);
+
+        process.kill().unwrap();
+        process.wait().unwrap();
+        assert!(running_apps(&apps).await.is_empty());
+        std::fs::remove_dir_all(std::env::temp_dir().join(format!("{}.app", app))).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_cached_outdated_casks() {
+        let cache = BrewCache::new();
+        let outdated: Value = serde_json::from_str(
+            r#"{"formulae":[{"name":"git"}],"casks":[{"name":"orbstack","installed_versions":["1.7.4"],"current_version":"1.8.0"}]}"#,
+        )
+        .unwrap();
+        *cache.outdated_greedy.lock().await = Some(Arc::new(outdated));
+
+        let cached = cache.outdated(true).await.unwrap();
+        assert_eq!(
+            outdated_cask_names(&cached).collect::<Vec<_>>(),
+            ["orbstack"]
+        );
+        cache.invalidate().await;
+        assert!(cache.outdated_greedy.lock().await.is_none());
+    }
+}
@@ -0,0 +1,165 @@
+use anyhow::Result;
+
+use super::brew::outdated_cask_names;
+use super::{BrewCache, IntegrationContext, capture, succeeds};
+use crate::keychain;
+
+/// Docker Desktop/OrbStack casks checked for pending updates
+const RUNTIME_CASKS: &[&str] = &["docker", "docker-desktop", "orbstack"];
+
+/// Prune unused Docker data, optionally remove dangling volumes and report
+/// disk usage before and after along with pending runtime updates
+pub async fn maintain(ctx: &IntegrationContext<'_>) -> Result<String> {
+    let options = ctx.task.docker.clone().unwrap_or_default();
+    if !succeeds("docker", &["info"]).await {
+        anyhow::bail!("Docker is not running (start Docker Desktop or OrbStack first)");
+    }
+
+    let mut report = Vec::new();
+    let context = capture("docker", &["context", "show"]).await.ok();
+    let before = disk_usage().await?;
+
+    capture("docker", &["system", "prune", "--force"]).await?;
+
+    let dangling: Vec<String> = capture(
+        "docker",
+        &["volume", "ls", "--quiet", "--filter", "dangling=true"],
+    )
+    .await?
+    .lines()
+    .map(str::trim)
+    .filter(|id| !id.is_empty())
+    .map(str::to_string)
+    .collect();
+    if !dangling.is_empty() {
+        let remove = options.prune_volumes
+            && ctx.confirm(
+                &format!(
+                    "Remove {} dangling Docker volume(s)? Data stored in them is lost.",
+                    dangling.len()
+                ),
+                false,
+            );
+        if remove {
+            let mut args = vec!["volume", "rm"];
+            args.extend(dangling.iter().map(String::as_str));
+            capture("docker", &args).await?;
+            report.push(format!("Removed {} dangling volume(s)", dangling.len()));
+        } else {
+            report.push(format!(
+                "Kept {} dangling volume(s){}",
+                dangling.len(),
+                if options.prune_volumes {
+                    ""
+                } else {
+                    " (set prune_volumes = true to remove them)"
+                }
+            ));
+        }
+    }
+
+    let after = disk_usage().await?;
+    report.insert(
+        0,
+        format!(
+            "Disk usage: {} → {} (freed {})",
+            format_bytes(before),
+            format_bytes(after),
+            format_bytes(before.saturating_sub(after))
+        ),
+    );
+
+    if options.check_updates {
+        if let Some(context) = context.as_deref().map(str::trim) {
+            report.push(format!("Runtime: {}", runtime_name(context)));
+        }
+        match outdated_runtimes(ctx.brew).await {
+            Some(outdated) if !outdated.is_empty() => {
+                report.push(format!("Update available: {}", outdated.join(", ")))
+            }
+            Some(_) => report.push("Docker runtime is up to date".to_string()),
+            None => {}
+        }
+    }
+
+    Ok(report.join("\n"))
+}
+
+/// Total size of images, containers, volumes and build cache in bytes
+async fn disk_usage() -> Result<u64> {
+    let sizes = capture("docker", &["system", "df", "--format", "{{.Size}}"]).await?;
+    Ok(sizes.lines().filter_map(parse_size).sum())
+}
+
+/// Outdated Docker Desktop/OrbStack casks, or `None` without Homebrew
+async fn outdated_runtimes(brew: &BrewCache) -> Option<Vec<String>> {
+    if !keychain::command_exists("brew") {
+        return None;
+    }
+    let outdated = brew.outdated(true).await.ok()?;
+    Some(
+        outdated_cask_names(&outdated)
+            .filter(|token| RUNTIME_CASKS.contains(token))
+            .map(str::to_string)
+            .collect(),
+    )
+}
+
+fn runtime_name(context: &str) -> &str {
+    match context {
+        "orbstack" => "OrbStack",
+        "desktop-linux" | "default" => "Docker Desktop",
+        other => other,
+    }
+}
+
+/// Parse Docker's human-readable sizes (`1.23GB`, `512kB`, `0B`); Docker
+/// uses decimal units
+fn parse_size(text: &str) -> Option<u64> {
+    let text = text.trim();
+    let split = text.find(|c: char| c.is_ascii_alphabetic())?;
+    let (number, unit) = text.split_at(split);
+    let value: f64 = number.trim().parse().ok()?;
+    let multiplier = match unit.to_ascii_uppercase().as_str() {
+        "B" => 1.0,
+        "KB" => 1e3,
+        "MB" => 1e6,
+        "GB" => 1e9,
+        "TB" => 1e12,
+        _ => return None,
+    };
+    Some((value * multiplier) as u64)
+}
+
+fn format_bytes(bytes: u64) -> String {
+    let mut value = bytes as f64;
+    let mut unit = "B";
+    for next in ["kB", "MB", "GB", "TB"] {
+        if value < 1000.0 {
+            break;
+        }
+        value /= 1000.0;
+        unit = next;
+    }
+    if unit == "B" {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, unit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_docker_sizes() {
+        assert_eq!(parse_size("1.5GB"), Some(1_500_000_000));
+        assert_eq!(parse_size("512kB"), Some(512_000));
+        assert_eq!(parse_size("0B"), Some(0));
+        assert_eq!(parse_size("n/a"), None);
+        assert_eq!(format_bytes(0), "0 B");
+        assert_eq!(format_bytes(4_200_000_000), "4.2 GB");
+        assert_eq!(runtime_name("orbstack"), "OrbStack");
+    }
+}
@@ -0,0 +1,116 @@
+//! Native implementations of built-in task types (`type = "..."` in config).
+
+mod brew;
+mod docker;
+mod node;
+mod python;
+mod xcode;
+
+use anyhow::{Context, Result};
+use dialoguer::{Confirm, theme::ColorfulTheme};
+use std::process::Stdio;
+use tokio::process::Command;
+
+use crate::config::{TaskConfig, TaskKind};
+use crate::controls::RunControls;
+use crate::orphans;
+
+pub use brew::BrewCache;
+
+/// Execution context handed to built-in task implementations
+pub struct IntegrationContext<'a> {
+    pub task: &'a TaskConfig,
+    pub controls: &'a RunControls,
+    pub brew: &'a BrewCache,
+}
+
+impl IntegrationContext<'_> {
+    /// Ask a yes/no question with progress spinners suspended; declined
+    /// when no terminal is attached
+    pub fn confirm(&self, prompt: &str, default: bool) -> bool {
+        if !self.controls.is_interactive() {
+            return false;
+        }
+        self.controls.suspend(|| {
+            Confirm::with_theme(&ColorfulTheme::default())
+                .with_prompt(prompt)
+                .default(default)
+                .interact()
+                .unwrap_or(false)
+        })
+    }
+}
+
+/// Run a built-in task type and return its report output
+pub async fn run(kind: TaskKind, ctx: &IntegrationContext<'_>) -> Result<String> {
+    match kind {
+        TaskKind::BrewCaskUpgrade => brew::upgrade_casks(ctx).await,
+        TaskKind::BrewServicesRestart => brew::restart_services(ctx).await,
+        TaskKind::DockerMaintenance => docker::maintain(ctx).await,
+        TaskKind::XcodeUpdate => xcode::update(ctx).await,
+        TaskKind::NodeUpdate => node::update(ctx).await,
+        TaskKind::PythonMaintenance => python::maintain(ctx).await,
+    }
+}
+
+/// Run a helper command and capture its stdout, failing on a non-zero exit
+pub(crate) async fn capture(program: &str, args: &[&str]) -> Result<String> {
+    let child = Command::new(program)
+        .args(args)
+        .process_group(0)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Failed to execute {}", program))?;
+    // Dropped unfinished when the task times out: end the command with it
+    let tracked = child
+        .id()
+        .map(|pid| orphans::track(pid, program).terminate_on_drop());
+    let output = child
+        .wait_with_output()
+        .await
+        .with_context(|| format!("Failed to execute {}", program))?;
+    if let Some(tracked) = tracked {
+        tracked.exited();
+    }
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    } else {
+        Err(anyhow::anyhow!(
+            "{} {} failed: {}",
+            program,
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+/// Run a privileged helper command through `sudo -n`.
+///
+/// Relies on the sudo timestamp cached before the run; never prompts.
+pub(crate) async fn capture_sudo(args: &[&str]) -> Result<String> {
+    if !succeeds("sudo", &["-n", "true"]).await {
+        anyhow::bail!(
+            "`{}` needs sudo, but no credentials are cached (run without --quiet so tide can authenticate first)",
+            args.join(" ")
+        );
+    }
+    let mut sudo_args = vec!["-n"];
+    sudo_args.extend_from_slice(args);
+    capture("sudo", &sudo_args).await
+}
+
+/// Run a helper command and report whether it exited successfully
+pub(crate) async fn succeeds(program: &str, args: &[&str]) -> bool {
+    Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .await
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
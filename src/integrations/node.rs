@@ -0,0 +1,298 @@
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::path::PathBuf;
+
+use super::{IntegrationContext, capture};
+use crate::config::NodeOptions;
+use crate::keychain;
+
+/// Global packages that ship with Node itself and are never reinstalled
+const BUNDLED_PACKAGES: &[&str] = &["npm", "corepack"];
+
+/// Node version managers the task knows how to drive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Manager {
+    Nvm,
+    Fnm,
+    Volta,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct Version {
+    major: u64,
+    minor: u64,
+    patch: u64,
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// An installed Node version and whether it is the manager's default
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Installed {
+    version: Version,
+    default: bool,
+}
+
+/// Update the Node versions managed by nvm, fnm and Volta to the latest
+/// release of each installed major line, moving global packages along with
+/// the default version
+pub async fn update(ctx: &IntegrationContext<'_>) -> Result<String> {
+    let options = ctx.task.node.clone().unwrap_or_default();
+    let managers = detect();
+    if managers.is_empty() {
+        anyhow::bail!("No Node version manager found (nvm, fnm or Volta)");
+    }
+
+    let mut report = Vec::new();
+    for manager in managers {
+        let lines = update_manager(manager, &options)
+            .await
+            .with_context(|| format!("{} update failed", manager.name()))?;
+        report.push(format!("{}: {}", manager.name(), lines.join("; ")));
+    }
+    Ok(report.join("\n"))
+}
+
+fn detect() -> Vec<Manager> {
+    let mut managers = Vec::new();
+    if nvm_script().is_some_and(|script| script.exists()) {
+        managers.push(Manager::Nvm);
+    }
+    if keychain::command_exists("fnm") {
+        managers.push(Manager::Fnm);
+    }
+    if keychain::command_exists("volta") {
+        managers.push(Manager::Volta);
+    }
+    managers
+}
+
+/// nvm is a shell function loaded from `$NVM_DIR/nvm.sh`
+fn nvm_script() -> Option<PathBuf> {
+    let dir = std::env::var_os("NVM_DIR")
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".nvm")))?;
+    Some(dir.join("nvm.sh"))
+}
+
+async fn update_manager(manager: Manager, options: &NodeOptions) -> Result<Vec<String>> {
+    let before = manager.installed().await?;
+    let majors: BTreeSet<u64> = before.iter().map(|i| i.version.major).collect();
+    if majors.is_empty() {
+        return Ok(vec!["no Node versions installed".to_string()]);
+    }
+
+    let mut changes = Vec::new();
+    for major in majors {
+        let old = latest_of(&before, major).context("installed version disappeared")?;
+        manager.install_major(major, old.default).await?;
+        let after = manager.installed().await?;
+        let Some(new) = latest_of(&after, major).map(|i| i.version) else {
+            continue;
+        };
+        if new <= old.version {
+            continue;
+        }
+
+        let mut change = format!("{} → {}", old.version, new);
+        if old.default {
+            manager.set_default(new).await?;
+            change.push_str(" (default)");
+            if options.reinstall_globals && manager != Manager::Volta {
+                let packages = manager.global_packages(old.version).await?;
+                if !packages.is_empty() {
+                    manager.install_globals(new, &packages).await?;
+                    change.push_str(&format!(", reinstalled {}", packages.join(", ")));
+                }
+            }
+        }
+        if options.remove_old && manager != Manager::Volta {
+            manager.uninstall(old.version).await?;
+            change.push_str(&format!(", removed {}", old.version));
+        }
+        changes.push(change);
+    }
+
+    if changes.is_empty() {
+        changes.push("all versions up to date".to_string());
+    }
+    Ok(changes)
+}
+
+impl Manager {
+    fn name(self) -> &'static str {
+        match self {
+            Manager::Nvm => "nvm",
+            Manager::Fnm => "fnm",
+            Manager::Volta => "Volta",
+        }
+    }
+
+    /// Run a manager subcommand; nvm needs a shell that sources it first
+    async fn run(self, args: &[&str]) -> Result<String> {
+        match self {
+            Manager::Nvm => {
+                let script = nvm_script().context("Cannot locate nvm.sh")?;
+                let command = format!(". \"{}\" && nvm {}", script.display(), args.join(" "));
+                capture("bash", &["-c", &command]).await
+            }
+            Manager::Fnm => capture("fnm", args).await,
+            Manager::Volta => capture("volta", args).await,
+        }
+    }
+
+    async fn installed(self) -> Result<Vec<Installed>> {
+        let listing = match self {
+            Manager::Nvm => self.run(&["ls", "--no-colors", "--no-alias"]).await?,
+            Manager::Fnm => self.run(&["list"]).await?,
+            Manager::Volta => self.run(&["list", "node", "--format", "plain"]).await?,
+        };
+        let mut installed = parse_listing(&listing);
+        if self == Manager::Nvm {
+            // `nvm ls --no-alias` marks the current version, not the default
+            let default = self.run(&["version", "default"]).await.ok();
+            let default = default.as_deref().and_then(parse_version);
+            for entry in &mut installed {
+                entry.default = Some(entry.version) == default;
+            }
+        }
+        Ok(installed)
+    }
+
+    /// Install the newest release of a major line
+    async fn install_major(self, major: u64, default: bool) -> Result<()> {
+        let major = major.to_string();
+        match self {
+            Manager::Nvm | Manager::Fnm => self.run(&["install", &major]).await?,
+            // `volta install` also makes the version the default, `fetch` does not
+            Manager::Volta if default => self.run(&["install", &format!("node@{}", major)]).await?,
+            Manager::Volta => self.run(&["fetch", &format!("node@{}", major)]).await?,
+        };
+        Ok(())
+    }
+
+    async fn set_default(self, version: Version) -> Result<()> {
+        let version = version.to_string();
+        match self {
+            Manager::Nvm => self.run(&["alias", "default", &version]).await?,
+            Manager::Fnm => self.run(&["default", &version]).await?,
+            Manager::Volta => return Ok(()),
+        };
+        Ok(())
+    }
+
+    async fn exec_npm(self, version: Version, npm_args: &[&str]) -> Result<String> {
+        let version = version.to_string();
+        let using = format!("--using={}", version);
+        let mut args = match self {
+            Manager::Nvm => vec!["exec", "--silent", version.as_str(), "npm"],
+            Manager::Fnm => vec!["exec", using.as_str(), "npm"],
+            Manager::Volta => vec!["run", "--node", version.as_str(), "npm"],
+        };
+        args.extend_from_slice(npm_args);
+        self.run(&args).await
+    }
+
+    async fn global_packages(self, version: Version) -> Result<Vec<String>> {
+        let json = self
+            .exec_npm(version, &["ls", "--global", "--depth=0", "--json"])
+            .await?;
+        parse_global_packages(&json)
+    }
+
+    async fn install_globals(self, version: Version, packages: &[String]) -> Result<()> {
+        let mut args = vec!["install", "--global"];
+        args.extend(packages.iter().map(String::as_str));
+        self.exec_npm(version, &args).await?;
+        Ok(())
+    }
+
+    async fn uninstall(self, version: Version) -> Result<()> {
+        self.run(&["uninstall", &version.to_string()]).await?;
+        Ok(())
+    }
+}
+
+fn latest_of(installed: &[Installed], major: u64) -> Option<&Installed> {
+    installed
+        .iter()
+        .filter(|i| i.version.major == major)
+        .max_by_key(|i| i.version)
+}
+
+/// Parse `v20.11.0`, `node@20.11.0` or `20.11.0`
+fn parse_version(text: &str) -> Option<Version> {
+    let text = text.trim();
+    let text = text.strip_prefix("node@").unwrap_or(text);
+    let text = text.strip_prefix('v').unwrap_or(text);
+    let mut parts = text.splitn(3, '.').map(|part| part.parse::<u64>().ok());
+    Some(Version {
+        major: parts.next()??,
+        minor: parts.next()??,
+        patch: parts.next()??,
+    })
+}
+
+/// Installed versions from `nvm ls`, `fnm list` or `volta list node` output
+fn parse_listing(listing: &str) -> Vec<Installed> {
+    listing
+        .lines()
+        .filter_map(|line| {
+            let version = line.split_whitespace().find_map(parse_version)?;
+            Some(Installed {
+                version,
+                default: line.contains("default"),
+            })
+        })
+        .collect()
+}
+
+/// Package names from `npm ls --global --depth=0 --json`
+fn parse_global_packages(json: &str) -> Result<Vec<String>> {
+    let tree: Value = serde_json::from_str(json).context("Failed to parse `npm ls` output")?;
+    Ok(tree["dependencies"]
+        .as_object()
+        .map(|deps| {
+            deps.keys()
+                .filter(|name| !BUNDLED_PACKAGES.contains(&name.as_str()))
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_node_manager_output() {
+        let fnm = parse_listing("* v18.19.0\n* v20.11.0 default\n* system\n");
+        assert_eq!(fnm.len(), 2);
+        assert_eq!(fnm[1].version.to_string(), "20.11.0");
+        assert!(fnm[1].default && !fnm[0].default);
+
+        let volta = parse_listing("runtime node@20.11.0 (default)\nruntime node@18.19.0\n");
+        assert!(volta[0].default);
+        assert_eq!(
+            latest_of(&volta, 18).unwrap().version.to_string(),
+            "18.19.0"
+        );
+
+        let nvm = parse_listing("->     v20.11.0 *\n       v20.9.0 *\n         system\n");
+        assert_eq!(latest_of(&nvm, 20).unwrap().version.to_string(), "20.11.0");
+        assert_eq!(parse_version("v20.x"), None);
+
+        let globals = parse_global_packages(
+            r#"{"dependencies":{"corepack":{},"npm":{},"pnpm":{"version":"9.0.0"},"typescript":{}}}"#,
+        )
+        .unwrap();
+        assert_eq!(globals, ["pnpm", "typescript"]);
+    }
+}
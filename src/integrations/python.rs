@@ -0,0 +1,183 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use super::{IntegrationContext, capture};
+use crate::keychain;
+
+/// A package whose installed version differs from the latest one
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PackageChange {
+    name: String,
+    from: String,
+    to: String,
+}
+
+/// Entry of `pip list --outdated --format=json`
+#[derive(Deserialize)]
+struct OutdatedPackage {
+    name: String,
+    version: String,
+    latest_version: String,
+}
+
+/// Upgrade pipx applications, report outdated packages in the configured
+/// virtualenvs and clear the uv and Poetry caches
+pub async fn maintain(ctx: &IntegrationContext<'_>) -> Result<String> {
+    let options = ctx.task.python.clone().unwrap_or_default();
+    let mut report = Vec::new();
+
+    if options.upgrade_pipx && keychain::command_exists("pipx") {
+        let before = pipx_versions().await?;
+        capture("pipx", &["upgrade-all"]).await?;
+        let after = pipx_versions().await?;
+        let upgraded = diff_versions(&before, &after);
+        if upgraded.is_empty() {
+            report.push("pipx: all applications up to date".to_string());
+        }
+        for change in upgraded {
+            report.push(format!(
+                "pipx: upgraded {} {} → {}",
+                change.name, change.from, change.to
+            ));
+        }
+    }
+
+    for venv in &options.venvs {
+        let dir = shellexpand::tilde(venv);
+        let python = Path::new(dir.as_ref()).join("bin/python");
+        if !python.exists() {
+            report.push(format!("{}: not a virtualenv (no bin/python)", venv));
+            continue;
+        }
+        let json = capture(
+            &python.to_string_lossy(),
+            &["-m", "pip", "list", "--outdated", "--format=json"],
+        )
+        .await?;
+        let outdated = parse_pip_outdated(&json)?;
+        if outdated.is_empty() {
+            report.push(format!("{}: all packages up to date", venv));
+        }
+        for change in outdated {
+            report.push(format!(
+                "{}: outdated {} {} → {}",
+                venv, change.name, change.from, change.to
+            ));
+        }
+    }
+
+    if options.clean_caches {
+        if keychain::command_exists("uv") {
+            capture("uv", &["cache", "clean"]).await?;
+            report.push("uv: cache cleaned".to_string());
+        }
+        if keychain::command_exists("poetry") {
+            let caches = capture("poetry", &["cache", "list"]).await?;
+            let caches: Vec<&str> = caches
+                .lines()
+                .map(str::trim)
+                .filter(|c| !c.is_empty())
+                .collect();
+            for cache in &caches {
+                capture(
+                    "poetry",
+                    &["cache", "clear", cache, "--all", "--no-interaction"],
+                )
+                .await?;
+            }
+            if !caches.is_empty() {
+                report.push(format!("poetry: cleared {}", caches.join(", ")));
+            }
+        }
+    }
+
+    if report.is_empty() {
+        anyhow::bail!("Nothing to do: pipx, uv and poetry are not installed and no venvs are set");
+    }
+    Ok(report.join("\n"))
+}
+
+/// Installed pipx applications and their versions
+async fn pipx_versions() -> Result<BTreeMap<String, String>> {
+    parse_pipx_list(&capture("pipx", &["list", "--json"]).await?)
+}
+
+/// `pipx list --json` nests versions under `venvs.<app>.metadata.main_package`
+fn parse_pipx_list(json: &str) -> Result<BTreeMap<String, String>> {
+    let list: Value = serde_json::from_str(json).context("Failed to parse `pipx list` output")?;
+    Ok(list["venvs"]
+        .as_object()
+        .map(|venvs| {
+            venvs
+                .iter()
+                .filter_map(|(name, venv)| {
+                    let version = venv["metadata"]["main_package"]["package_version"].as_str()?;
+                    Some((name.clone(), version.to_string()))
+                })
+                .collect()
+        })
+        .unwrap_or_default())
+}
+
+fn diff_versions(
+    before: &BTreeMap<String, String>,
+    after: &BTreeMap<String, String>,
+) -> Vec<PackageChange> {
+    after
+        .iter()
+        .filter_map(|(name, to)| {
+            let from = before.get(name)?;
+            (from != to).then(|| PackageChange {
+                name: name.clone(),
+                from: from.clone(),
+                to: to.clone(),
+            })
+        })
+        .collect()
+}
+
+fn parse_pip_outdated(json: &str) -> Result<Vec<PackageChange>> {
+    let outdated: Vec<OutdatedPackage> =
+        serde_json::from_str(json).context("Failed to parse `pip list --outdated` output")?;
+    Ok(outdated
+        .into_iter()
+        .map(|package| PackageChange {
+            name: package.name,
+            from: package.version,
+            to: package.latest_version,
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_python_tool_output() {
+        let before = parse_pipx_list(
+            r#"{"venvs":{"black":{"metadata":{"main_package":{"package_version":"23.1.0"}}},"ruff":{"metadata":{"main_package":{"package_version":"0.3.0"}}}}}"#,
+        )
+        .unwrap();
+        let mut after = before.clone();
+        after.insert("black".to_string(), "24.2.0".to_string());
+        assert_eq!(
+            diff_versions(&before, &after),
+            [PackageChange {
+                name: "black".to_string(),
+                from: "23.1.0".to_string(),
+                to: "24.2.0".to_string(),
+            }]
+        );
+
+        let outdated = parse_pip_outdated(
+            r#"[{"name":"requests","version":"2.31.0","latest_version":"2.32.3","latest_filetype":"wheel"}]"#,
+        )
+        .unwrap();
+        assert_eq!(outdated[0].name, "requests");
+        assert_eq!(outdated[0].to, "2.32.3");
+    }
+}
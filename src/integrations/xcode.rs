@@ -0,0 +1,149 @@
+use anyhow::Result;
+
+use super::{IntegrationContext, capture, capture_sudo, succeeds};
+use crate::keychain;
+
+/// Package receipt installed by the Command Line Tools
+const CLT_PACKAGE: &str = "com.apple.pkg.CLTools_Executables";
+
+/// Check Xcode and the Command Line Tools for updates, install CLT updates
+/// and finish pending first-launch and license steps
+pub async fn update(ctx: &IntegrationContext<'_>) -> Result<String> {
+    let options = ctx.task.xcode.clone().unwrap_or_default();
+    if !succeeds("xcode-select", &["--print-path"]).await {
+        anyhow::bail!(
+            "No developer tools installed (run `xcode-select --install` to install the Command Line Tools)"
+        );
+    }
+
+    let mut report = Vec::new();
+    let clt_version = capture("pkgutil", &[&format!("--pkg-info={}", CLT_PACKAGE)])
+        .await
+        .ok()
+        .and_then(|info| parse_pkg_version(&info));
+    let xcode_version = capture("xcodebuild", &["-version"])
+        .await
+        .ok()
+        .and_then(|version| parse_xcode_version(&version));
+
+    // Command Line Tools updates are delivered through softwareupdate
+    let listing = capture("softwareupdate", &["--list"]).await?;
+    let labels = parse_clt_labels(&listing);
+    if labels.is_empty() {
+        report.push(format!(
+            "Command Line Tools {}",
+            clt_version
+                .as_deref()
+                .map(|v| format!("{} is up to date", v))
+                .unwrap_or_else(|| "not installed".to_string())
+        ));
+    } else if options.install_updates {
+        for label in &labels {
+            capture_sudo(&["softwareupdate", "--install", label]).await?;
+        }
+        report.push(format!("Installed: {}", labels.join(", ")));
+    } else {
+        report.push(format!("Update available: {}", labels.join(", ")));
+    }
+
+    if let Some(installed) = &xcode_version {
+        let latest = if keychain::command_exists("xcodes") {
+            capture("xcodes", &["list"])
+                .await
+                .ok()
+                .and_then(|list| latest_xcodes_release(&list))
+        } else {
+            None
+        };
+        match latest {
+            Some(latest) if &latest != installed => report.push(format!(
+                "Xcode {} available (installed {}); run `xcodes install --latest`",
+                latest, installed
+            )),
+            _ => report.push(format!("Xcode {}", installed)),
+        }
+
+        if options.first_launch && !succeeds("xcodebuild", &["-checkFirstLaunchStatus"]).await {
+            capture_sudo(&["xcodebuild", "-runFirstLaunch"]).await?;
+            report.push("Ran first-launch setup".to_string());
+        }
+        if options.accept_license && !succeeds("xcodebuild", &["-license", "check"]).await {
+            capture_sudo(&["xcodebuild", "-license", "accept"]).await?;
+            report.push("Accepted the Xcode license".to_string());
+        }
+    }
+
+    Ok(report.join("\n"))
+}
+
+/// `pkgutil --pkg-info` prints `version: 15.3.0.0.1.1708646388` among other keys
+fn parse_pkg_version(info: &str) -> Option<String> {
+    info.lines()
+        .find_map(|line| line.strip_prefix("version:"))
+        .map(|version| version.trim().to_string())
+}
+
+/// `xcodebuild -version` prints `Xcode 15.4` followed by the build version
+fn parse_xcode_version(output: &str) -> Option<String> {
+    output
+        .lines()
+        .next()?
+        .strip_prefix("Xcode ")
+        .map(|version| version.trim().to_string())
+}
+
+/// Labels of pending Command Line Tools updates in `softwareupdate --list`
+/// output (`* Label: Command Line Tools for Xcode-15.3`)
+fn parse_clt_labels(listing: &str) -> Vec<String> {
+    listing
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("* Label:"))
+        .map(str::trim)
+        .filter(|label| label.starts_with("Command Line Tools"))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Newest stable release in `xcodes list` output, which is sorted oldest
+/// first with lines like `15.4 (15F31d) (Installed, Selected)`
+fn latest_xcodes_release(list: &str) -> Option<String> {
+    list.lines()
+        .map(str::trim)
+        .filter(|line| !line.contains("Beta") && !line.contains("Release Candidate"))
+        .filter_map(|line| line.split_whitespace().next())
+        .rfind(|version| version.chars().next().is_some_and(|c| c.is_ascii_digit()))
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_xcode_listings() {
+        let listing = "Software Update found the following new or updated software:\n\
+            * Label: Command Line Tools for Xcode-15.3\n\
+            \tTitle: Command Line Tools for Xcode, Version: 15.3, Size: 707501KiB, Recommended: YES,\n\
+            * Label: Safari17.4VenturaAuto-17.4\n";
+        assert_eq!(
+            parse_clt_labels(listing),
+            ["Command Line Tools for Xcode-15.3"]
+        );
+        assert_eq!(
+            parse_pkg_version(
+                "package-id: com.apple.pkg.CLTools_Executables\nversion: 15.1.0.0.1.1700200546\n"
+            ),
+            Some("15.1.0.0.1.1700200546".to_string())
+        );
+        assert_eq!(
+            parse_xcode_version("Xcode 15.4\nBuild version 15F31d\n"),
+            Some("15.4".to_string())
+        );
+        assert_eq!(
+            latest_xcodes_release(
+                "15.3 (15E204a)\n15.4 (15F31d) (Installed, Selected)\n16.0 Beta 2 (16A5171r)\n"
+            ),
+            Some("15.4".to_string())
+        );
+    }
+}
@@ -1,6 +1,8 @@
 use anyhow::Result;
 use std::process::{Command, Stdio};
 
+use crate::error::TideError;
+
 /// Check if a keychain entry exists
 pub fn entry_exists(label: &str) -> bool {
     Command::new("security")
@@ -21,7 +23,7 @@ pub fn get_password(label: &str) -> Result<String> {
     if output.status.success() {
         Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
     } else {
-        Err(anyhow::anyhow!("Keychain entry not found"))
+        Err(TideError::Keychain(format!("Keychain entry '{}' not found", label)).into())
     }
 }
 
@@ -42,7 +44,11 @@ pub fn save_password(label: &str, password: &str) -> Result<()> {
     if status.success() {
         Ok(())
     } else {
-        Err(anyhow::anyhow!("Failed to save password to keychain"))
+        Err(TideError::Keychain(format!(
+            "Failed to save password to keychain entry '{}'",
+            label
+        ))
+        .into())
     }
 }
 
@@ -0,0 +1,208 @@
+use anyhow::{Result, bail};
+use serde::Serialize;
+use std::fs::File;
+use std::io::Write;
+use std::os::fd::FromRawFd;
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::error::TideError;
+use crate::executor::{TaskResult, TaskStatus};
+use crate::history::RunRecord;
+use crate::ui;
+
+/// Script-filter document understood by Raycast and Alfred
+#[derive(Debug, Serialize)]
+pub struct ScriptFilter {
+    pub items: Vec<ScriptFilterItem>,
+}
+
+/// A single browsable entry; `arg` is the value passed to `tide run --groups`
+#[derive(Debug, Serialize)]
+pub struct ScriptFilterItem {
+    pub uid: String,
+    pub title: String,
+    pub subtitle: String,
+    pub arg: String,
+    pub autocomplete: String,
+    pub valid: bool,
+}
+
+/// List enabled groups and their tasks for launcher browsing.
+///
+/// Groups are actionable (running them triggers the group); tasks are shown
+/// beneath their group for context but resolve to the same group argument.
+pub fn script_filter(
+    config: &Config,
+    groups: Option<&[String]>,
+    skip_groups: Option<&[String]>,
+) -> ScriptFilter {
+    let mut items = Vec::new();
+    for group in config.groups.iter().filter(|g| g.enabled) {
+        if let Some(groups) = groups
+            && !groups.contains(&group.name)
+        {
+            continue;
+        }
+        if let Some(skip) = skip_groups
+            && skip.contains(&group.name)
+        {
+            continue;
+        }
+
+        let tasks: Vec<_> = group.tasks.iter().filter(|t| t.enabled).collect();
+        let subtitle = if group.description.is_empty() {
+            format!("{} task(s)", tasks.len())
+        } else {
+            format!("{} · {} task(s)", group.description, tasks.len())
+        };
+        items.push(ScriptFilterItem {
+            uid: group.name.clone(),
+            title: labelled(&group.icon, &group.name),
+            subtitle,
+            arg: group.name.clone(),
+            autocomplete: group.name.clone(),
+            valid: true,
+        });
+
+        for task in tasks {
+            items.push(ScriptFilterItem {
+                uid: format!("{}/{}", group.name, task.name),
+                title: labelled(&task.icon, &task.name),
+                subtitle: if task.description.is_empty() {
+                    group.name.clone()
+                } else {
+                    format!("{} › {}", group.name, task.description)
+                },
+                arg: group.name.clone(),
+                autocomplete: task.name.clone(),
+                valid: true,
+            });
+        }
+    }
+    ScriptFilter { items }
+}
+
+fn labelled(icon: &str, name: &str) -> String {
+    if icon.is_empty() {
+        name.to_string()
+    } else {
+        format!("{} {}", icon, name)
+    }
+}
+
+/// One-line result shown as a launcher toast (Raycast `compact` mode shows
+/// the last line of output)
+pub fn result_line(results: &[TaskResult], duration: Duration) -> String {
+    let failed: Vec<&str> = results
+        .iter()
+        .filter(|r| r.status == TaskStatus::Failed)
+        .map(|r| r.name.as_str())
+        .collect();
+    let succeeded = results
+        .iter()
+        .filter(|r| r.status == TaskStatus::Success)
+        .count();
+
+    if failed.is_empty() {
+        format!(
+            "✅ {} task(s) updated in {}",
+            succeeded,
+            ui::format_duration(duration)
+        )
+    } else {
+        format!(
+            "❌ {} of {} task(s) failed: {}",
+            failed.len(),
+            results.len(),
+            failed.join(", ")
+        )
+    }
+}
+
+/// Machine-readable result handed back to a Shortcuts.app workflow
+#[derive(Debug, Serialize)]
+pub struct ShortcutsReport {
+    pub success: bool,
+    pub summary: String,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub skipped: usize,
+    pub error: Option<ErrorReport>,
+    pub run: RunRecord,
+}
+
+/// Stable description of why a run did not succeed
+#[derive(Debug, Serialize)]
+pub struct ErrorReport {
+    pub code: i32,
+    pub kind: &'static str,
+    pub message: String,
+}
+
+impl From<&TideError> for ErrorReport {
+    fn from(error: &TideError) -> Self {
+        Self {
+            code: error.code(),
+            kind: error.kind(),
+            message: error.to_string(),
+        }
+    }
+}
+
+impl ShortcutsReport {
+    /// Build the report from the results of a finished run
+    pub fn new(
+        run: RunRecord,
+        results: &[TaskResult],
+        duration: Duration,
+        error: Option<&TideError>,
+    ) -> Self {
+        let count = |status| results.iter().filter(|r| r.status == status).count();
+        let failed = count(TaskStatus::Failed);
+        Self {
+            success: failed == 0,
+            summary: result_line(results, duration),
+            succeeded: count(TaskStatus::Success),
+            failed,
+            skipped: count(TaskStatus::Skipped),
+            error: error.map(ErrorReport::from),
+            run,
+        }
+    }
+}
+
+/// Write the report as a single JSON line to an already open file descriptor
+/// (e.g. `3>report.json` in the shell script the Shortcut runs)
+pub fn write_report(fd: i32, report: &ShortcutsReport) -> Result<()> {
+    // SAFETY: only probes whether the descriptor is open
+    if fd < 0 || unsafe { libc::fcntl(fd, libc::F_GETFD) } == -1 {
+        bail!("file descriptor {} is not open", fd);
+    }
+    // SAFETY: the descriptor is open and handed to us by the caller; it is
+    // closed once the report is written, which is what the wrapper expects
+    let mut file = unsafe { File::from_raw_fd(fd) };
+    writeln!(file, "{}", serde_json::to_string(report)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_script_filter_lists_groups_and_tasks() {
+        let config = Config::default();
+        let filter = script_filter(&config, None, Some(&["System Updates".to_string()]));
+
+        let titles: Vec<&str> = filter.items.iter().map(|i| i.title.as_str()).collect();
+        assert_eq!(
+            titles,
+            ["🍺 Homebrew", "📦 Update Formulae", "⬆️ Upgrade Packages"]
+        );
+        assert!(filter.items.iter().all(|i| i.arg == "Homebrew"));
+
+        let json = serde_json::to_value(&filter).unwrap();
+        assert_eq!(json["items"][1]["uid"], "Homebrew/Update Formulae");
+    }
+}
@@ -0,0 +1,95 @@
+//! Tide's update engine: configuration, planning, task execution and run
+//! history. The `tide` binary builds its runs from these pieces, adding run
+//! locking, preflights, parameter prompts and history recording on top;
+//! other tools can embed the same engine instead of shelling out to `tide`.
+//!
+//! A minimal programmatic run, without any of those steps:
+//!
+//! ```no_run
+//! use std::collections::HashMap;
+//! use std::sync::Arc;
+//! use tide_core::{Config, TaskExecutor, plan, runner};
+//!
+//! # async fn example() -> anyhow::Result<()> {
+//! let config = Config::load(None)?;
+//! let mut warnings = Vec::new();
+//! let tasks = plan::expand_matrices(plan::collect_tasks(&config, None, None), &mut warnings);
+//! let batches = plan::build_batches(tasks, &config.settings, 4);
+//! let executor = Arc::new(TaskExecutor::new(false, false, false, false, 0, None, None));
+//! let estimates = HashMap::new();
+//! let results =
+//!     runner::run_batches(&executor, batches, &config.settings, "tide-sudo", &estimates).await;
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod adaptive;
+pub mod appstore;
+pub mod attention;
+pub mod audit;
+pub mod backup;
+pub mod bench;
+pub mod catalog;
+pub mod config;
+pub mod controls;
+pub mod disk;
+pub mod email;
+pub mod envfile;
+pub mod error;
+pub mod executor;
+pub mod export;
+pub mod failure;
+pub mod format;
+pub mod harness;
+pub mod history;
+pub mod hyperlink;
+pub mod import;
+pub mod include;
+pub mod integrations;
+pub mod keychain;
+pub mod launcher;
+pub mod lint;
+pub mod lock;
+pub mod logger;
+pub mod logs;
+pub mod managed;
+pub mod matrix;
+pub mod network;
+pub mod notifications;
+pub mod orphans;
+pub mod outputs;
+pub mod overlay;
+pub mod palette;
+pub mod params;
+pub mod password;
+pub mod pending;
+pub mod plan;
+pub mod postrun;
+pub mod power;
+pub mod profile;
+pub mod progress;
+pub mod provenance;
+pub mod recipes;
+pub mod replay;
+pub mod report;
+pub mod runas;
+pub mod runenv;
+pub mod runner;
+pub mod sandbox;
+pub mod severity;
+pub mod stats;
+pub mod status;
+pub mod suggest;
+pub mod tail;
+pub mod thermal;
+pub mod trend;
+pub mod ui;
+pub mod validate;
+pub mod vars;
+pub mod why;
+pub mod widgets;
+pub mod yaml;
+
+pub use config::{Config, Settings, TaskConfig, TaskGroup};
+pub use executor::{TaskExecutor, TaskResult, TaskStatus};
+pub use plan::{Batch, BatchMode, PlannedTask};
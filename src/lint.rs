@@ -0,0 +1,276 @@
+use crate::config::TaskConfig;
+
+/// Shells whose `-c` argument is treated as a shell-string command
+const SHELLS: &[&str] = &["sh", "bash", "zsh", "fish", "dash", "ksh"];
+
+/// Category of a risky command pattern
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LintKind {
+    RecursiveDelete,
+    PipeToShell,
+    UnquotedVariable,
+    RelativeSudoPath,
+}
+
+impl LintKind {
+    /// Short human readable label
+    pub fn label(&self) -> &'static str {
+        match self {
+            LintKind::RecursiveDelete => "recursive delete of a root path",
+            LintKind::PipeToShell => "download piped into a shell",
+            LintKind::UnquotedVariable => "unquoted variable in shell string",
+            LintKind::RelativeSudoPath => "sudo with a relative path",
+        }
+    }
+}
+
+/// Inspect a single task command for dangerous patterns.
+pub fn lint_task(task: &TaskConfig) -> Vec<(LintKind, String)> {
+    let mut findings = Vec::new();
+    let cmd = &task.effective_command();
+    if cmd.is_empty() {
+        return findings;
+    }
+
+    let (elevated, program_idx) = if cmd[0] == "sudo" {
+        (true, sudo_program_index(cmd))
+    } else {
+        (task.sudo, Some(0))
+    };
+
+    if let Some(idx) = program_idx {
+        let program = &cmd[idx];
+        if elevated && is_relative_path(program) {
+            findings.push((
+                LintKind::RelativeSudoPath,
+                format!(
+                    "'{}' is resolved relative to the working directory",
+                    program
+                ),
+            ));
+        }
+
+        let words: Vec<&str> = cmd[idx..].iter().map(String::as_str).collect();
+        if let Some(target) = recursive_delete_target(&words) {
+            findings.push((LintKind::RecursiveDelete, format!("rm -rf {}", target)));
+        }
+
+        if let Some(script) = shell_script(&cmd[idx..]) {
+            findings.extend(lint_shell_script(script));
+        }
+    }
+
+    findings
+}
+
+fn lint_shell_script(script: &str) -> Vec<(LintKind, String)> {
+    let mut findings = Vec::new();
+
+    for segment in script.split([';', '&', '\n']) {
+        let words: Vec<&str> = segment.split_whitespace().collect();
+        if let Some(target) = recursive_delete_target(&words) {
+            findings.push((LintKind::RecursiveDelete, format!("rm -rf {}", target)));
+        }
+    }
+
+    for line in script.split([';', '\n']) {
+        let stages: Vec<&str> = line.split('|').map(str::trim).collect();
+        for pair in stages.windows(2) {
+            let source = pair[0].split_whitespace().next().unwrap_or_default();
+            let sink = pair[1]
+                .split_whitespace()
+                .find(|word| *word != "sudo")
+                .unwrap_or_default();
+            if matches!(base_name(source), "curl" | "wget") && SHELLS.contains(&base_name(sink)) {
+                findings.push((
+                    LintKind::PipeToShell,
+                    format!("{} | {}", source, base_name(sink)),
+                ));
+            }
+        }
+    }
+
+    for var in unquoted_variables(script) {
+        findings.push((
+            LintKind::UnquotedVariable,
+            format!("${} should be written as \"${}\"", var, var),
+        ));
+    }
+
+    findings
+}
+
+/// Index of the program executed by a `sudo ...` command line.
+fn sudo_program_index(cmd: &[String]) -> Option<usize> {
+    let mut idx = 1;
+    while idx < cmd.len() {
+        let arg = &cmd[idx];
+        if arg == "--" {
+            return (idx + 1 < cmd.len()).then_some(idx + 1);
+        }
+        if !arg.starts_with('-') {
+            return Some(idx);
+        }
+        // Options that consume a value
+        if matches!(arg.as_str(), "-u" | "-g" | "-C" | "-h" | "-p" | "-U") {
+            idx += 1;
+        }
+        idx += 1;
+    }
+    None
+}
+
+fn is_relative_path(program: &str) -> bool {
+    program.contains('/') && !program.starts_with('/') && !program.starts_with('~')
+}
+
+fn base_name(program: &str) -> &str {
+    program.rsplit('/').next().unwrap_or(program)
+}
+
+/// Return the script of a `sh -c "<script>"` style command.
+fn shell_script(cmd: &[String]) -> Option<&str> {
+    if !SHELLS.contains(&base_name(&cmd[0])) {
+        return None;
+    }
+    let pos = cmd
+        .iter()
+        .position(|arg| arg.starts_with('-') && !arg.starts_with("--") && arg.contains('c'))?;
+    cmd.get(pos + 1).map(String::as_str)
+}
+
+/// Detect `rm` invocations that recursively force-delete a root-like path.
+fn recursive_delete_target(words: &[&str]) -> Option<String> {
+    let rm_pos = words
+        .iter()
+        .position(|word| base_name(word.trim_matches(['"', '\''])) == "rm")?;
+    let args = &words[rm_pos + 1..];
+
+    let flags: String = args
+        .iter()
+        .filter(|arg| arg.starts_with('-') && !arg.starts_with("--"))
+        .map(|arg| arg.trim_start_matches('-'))
+        .collect();
+    let recursive = flags.contains('r') || flags.contains('R') || args.contains(&"--recursive");
+    let force = flags.contains('f') || args.contains(&"--force");
+    if !(recursive && force) {
+        return None;
+    }
+
+    args.iter()
+        .map(|arg| arg.trim_matches(['"', '\'']))
+        .find(|arg| {
+            matches!(
+                *arg,
+                "/" | "/*" | "~" | "~/" | "~/*" | "$HOME" | "${HOME}" | "$HOME/" | "$HOME/*"
+            )
+        })
+        .map(str::to_string)
+}
+
+/// Find `$VAR` / `${VAR}` expansions that are not inside double quotes.
+fn unquoted_variables(script: &str) -> Vec<String> {
+    let chars: Vec<char> = script.chars().collect();
+    let mut vars = Vec::new();
+    let mut in_single = false;
+    let mut in_double = false;
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            '\\' if !in_single => i += 1,
+            '\'' if !in_double => in_single = !in_single,
+            '"' if !in_single => in_double = !in_double,
+            '$' if !in_single && !in_double => {
+                // `${item}`, `${outputs.<name>}` and `${params.<name>}` are
+                // substituted by tide, not the shell
+                let rest: String = chars[i..].iter().take(10).collect();
+                if ["${item}", "${outputs.", "${params."]
+                    .iter()
+                    .any(|placeholder| rest.starts_with(placeholder))
+                {
+                    i += 2;
+                    continue;
+                }
+                let braced = chars.get(i + 1) == Some(&'{');
+                let start = if braced { i + 2 } else { i + 1 };
+                let mut end = start;
+                while end < chars.len() && (chars[end].is_ascii_alphanumeric() || chars[end] == '_')
+                {
+                    end += 1;
+                }
+                let name: String = chars[start..end].iter().collect();
+                let is_name = name
+                    .chars()
+                    .next()
+                    .is_some_and(|c| c.is_ascii_alphabetic() || c == '_');
+                if is_name && !vars.contains(&name) {
+                    vars.push(name);
+                }
+                i = end.max(i + 1);
+                continue;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+
+    vars
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Config, Shell};
+
+    fn task(command: &[&str], sudo: bool) -> TaskConfig {
+        let mut task = Config::default().groups[0].tasks[0].clone();
+        task.command = command.iter().map(|s| s.to_string()).collect();
+        task.sudo = sudo;
+        task
+    }
+
+    fn kinds(task: &TaskConfig) -> Vec<LintKind> {
+        lint_task(task).into_iter().map(|(kind, _)| kind).collect()
+    }
+
+    #[test]
+    fn test_flags_dangerous_patterns() {
+        assert_eq!(
+            kinds(&task(&["rm", "-rf", "/"], false)),
+            vec![LintKind::RecursiveDelete]
+        );
+        assert_eq!(
+            kinds(&task(
+                &["sh", "-c", "curl -fsSL https://x.sh | sudo bash"],
+                false
+            )),
+            vec![LintKind::PipeToShell]
+        );
+        assert_eq!(
+            kinds(&task(&["bash", "-c", "cd $DIR && make"], false)),
+            vec![LintKind::UnquotedVariable]
+        );
+        assert_eq!(
+            kinds(&task(&["./scripts/cleanup.sh"], true)),
+            vec![LintKind::RelativeSudoPath]
+        );
+
+        let mut script = task(&["cd ~/src &&", "rm -rf ~"], false);
+        script.shell = Some(Shell::Zsh);
+        script.login_shell = true;
+        assert_eq!(
+            script.effective_command(),
+            ["zsh", "-l", "-i", "-c", "cd ~/src && rm -rf ~"]
+        );
+        assert_eq!(kinds(&script), vec![LintKind::RecursiveDelete]);
+    }
+
+    #[test]
+    fn test_ignores_safe_commands() {
+        assert!(kinds(&task(&["rm", "-rf", "/tmp/cache"], false)).is_empty());
+        assert!(kinds(&task(&["bash", "-c", "cd \"$DIR\" && echo '$X'"], false)).is_empty());
+        assert!(kinds(&task(&["softwareupdate", "--install", "--all"], true)).is_empty());
+        assert!(kinds(&task(&["sh", "-c", "brew upgrade ${outputs.casks}"], false)).is_empty());
+    }
+}
@@ -0,0 +1,94 @@
+use anyhow::{Context, Result};
+use std::fs::{self, File, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::os::fd::AsRawFd;
+use std::path::{Path, PathBuf};
+
+/// Exclusive lock held for the whole of a run, so two runs never upgrade the
+/// same packages at once. It is an `flock` on the lock file, released by the
+/// kernel when tide exits however it ends, so a crashed run leaves no stale
+/// lock behind. The file holds the pid of the run for messages.
+#[derive(Debug)]
+pub struct RunLock {
+    _file: File,
+}
+
+/// Default lock location: `~/.local/state/tide/tide.lock`
+pub fn default_path() -> Result<PathBuf> {
+    Ok(dirs::home_dir()
+        .context("Could not determine home directory")?
+        .join(".local")
+        .join("state")
+        .join("tide")
+        .join("tide.lock"))
+}
+
+impl RunLock {
+    /// Take the lock unless another run holds it (`None`)
+    pub fn try_acquire(path: &Path) -> Result<Option<Self>> {
+        Self::lock(path, libc::LOCK_EX | libc::LOCK_NB)
+    }
+
+    /// Take the lock, waiting for the run holding it to finish
+    pub fn acquire(path: &Path) -> Result<Self> {
+        Self::lock(path, libc::LOCK_EX).map(|lock| lock.expect("blocking flock"))
+    }
+
+    fn lock(path: &Path, operation: libc::c_int) -> Result<Option<Self>> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        // Not truncated on open: the pid belongs to the holder until we own it
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)
+            .with_context(|| format!("Failed to open lock file {}", path.display()))?;
+        loop {
+            // SAFETY: flock on a descriptor owned by `file`
+            if unsafe { libc::flock(file.as_raw_fd(), operation) } == 0 {
+                break;
+            }
+            let err = std::io::Error::last_os_error();
+            match err.kind() {
+                ErrorKind::Interrupted => continue,
+                ErrorKind::WouldBlock => return Ok(None),
+                _ => {
+                    return Err(err).with_context(|| format!("Failed to lock {}", path.display()));
+                }
+            }
+        }
+        file.set_len(0)?;
+        write!(file, "{}", std::process::id())?;
+        Ok(Some(Self { _file: file }))
+    }
+}
+
+/// pid of the run holding the lock at `path`, as far as the file tells
+pub fn holder(path: &Path) -> Option<u32> {
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_lock() {
+        let dir = std::env::temp_dir().join(format!("tide-lock-{}", std::process::id()));
+        let path = dir.join("tide.lock");
+        let _ = fs::remove_dir_all(&dir);
+
+        let lock = RunLock::try_acquire(&path).unwrap().unwrap();
+        assert_eq!(holder(&path), Some(std::process::id()));
+        assert!(RunLock::try_acquire(&path).unwrap().is_none());
+        assert_eq!(holder(&path), Some(std::process::id()));
+
+        drop(lock);
+        assert!(RunLock::try_acquire(&path).unwrap().is_some());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,13 +1,74 @@
 use anyhow::{Context, Result};
-use chrono::Local;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
 use std::fs::{self, File, OpenOptions};
-use std::io::Write;
-use std::path::Path;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::executor::TaskStatus;
+use crate::export::slug;
+
+/// How entries of the log file are written (`log_format`)
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// Timestamped lines for reading
+    #[default]
+    Text,
+    /// One JSON object per event, for log shippers
+    Json,
+}
+
+/// Something worth a log entry; in the JSON format its fields become the
+/// record, tagged with `event`
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum LogEvent<'a> {
+    RunStarted {
+        run_id: &'a str,
+    },
+    TaskStarted {
+        group: &'a str,
+        task: &'a str,
+        command: &'a str,
+    },
+    TaskRetry {
+        group: &'a str,
+        task: &'a str,
+        attempt: u32,
+        attempts: u32,
+        error: &'a str,
+    },
+    Output {
+        group: &'a str,
+        task: &'a str,
+        output: &'a str,
+    },
+    TaskFinished {
+        group: &'a str,
+        task: &'a str,
+        status: TaskStatus,
+        duration_secs: f64,
+    },
+}
+
+#[derive(Serialize)]
+struct JsonRecord<'a> {
+    timestamp: DateTime<Local>,
+    #[serde(flatten)]
+    event: &'a LogEvent<'a>,
+}
 
 /// Simple thread-safe file logger for task execution traces.
 pub struct Logger {
-    file: Mutex<File>,
+    /// The file and the number of lines it holds
+    file: Mutex<(File, usize)>,
+    format: LogFormat,
+    /// Directory receiving a file per task (`log_dir`)
+    task_dir: Option<PathBuf>,
+    started: DateTime<Local>,
 }
 
 impl Logger {
@@ -24,30 +85,220 @@ impl Logger {
             .append(true)
             .open(path)
             .with_context(|| format!("Failed to open log file {}", path.display()))?;
+        let lines = BufReader::new(
+            File::open(path)
+                .with_context(|| format!("Failed to read log file {}", path.display()))?,
+        )
+        .split(b'\n')
+        .count();
 
         Ok(Self {
-            file: Mutex::new(file),
+            file: Mutex::new((file, lines)),
+            format: LogFormat::Text,
+            task_dir: None,
+            started: Local::now(),
         })
     }
 
-    /// Write a single log line with a timestamp prefix.
-    pub fn log_line(&self, message: &str) -> Result<()> {
+    /// Write entries in `format`
+    pub fn with_format(mut self, format: LogFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Also write the output of every task to its own file in `dir`
+    pub fn with_task_dir(mut self, dir: PathBuf) -> Self {
+        self.task_dir = Some(dir);
+        self
+    }
+
+    /// Open the log file of a task, e.g.
+    /// `2024-05-01_093000_homebrew_upgrade-packages.log`, named after the
+    /// start of the run. `None` unless a task directory is set.
+    pub fn task_log(&self, group: &str, task: &str) -> Result<Option<TaskLog>> {
+        let Some(dir) = &self.task_dir else {
+            return Ok(None);
+        };
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create log directory {}", dir.display()))?;
+        let path = dir.join(format!(
+            "{}_{}_{}.log",
+            self.started.format("%Y-%m-%d_%H%M%S"),
+            slug(group),
+            slug(task)
+        ));
+        let file = File::create(&path)
+            .with_context(|| format!("Failed to create task log {}", path.display()))?;
+        Ok(Some(TaskLog {
+            path,
+            file: Mutex::new(file),
+            captured: AtomicBool::new(false),
+        }))
+    }
+
+    /// Record an event, returning the 1-based line number of its entry.
+    /// The text format writes `text` instead, with the output of an
+    /// [`LogEvent::Output`] indented beneath it.
+    pub fn log_event(&self, event: &LogEvent, text: &str) -> Result<usize> {
+        match (self.format, event) {
+            (LogFormat::Json, event) => {
+                let record = JsonRecord {
+                    timestamp: Local::now(),
+                    event,
+                };
+                self.write_entry(&serde_json::to_string(&record)?)
+            }
+            (LogFormat::Text, LogEvent::Output { output, .. }) => self.log_block(text, output),
+            (LogFormat::Text, _) => self.log_line(text),
+        }
+    }
+
+    /// Write a single log line with a timestamp prefix, returning its
+    /// 1-based line number.
+    pub fn log_line(&self, message: &str) -> Result<usize> {
         let timestamp = Local::now().format("%Y-%m-%d %H:%M:%S");
+        self.write_entry(&format!("[{}] {}", timestamp, message))
+    }
+
+    fn write_entry(&self, entry: &str) -> Result<usize> {
         let mut guard = self
             .file
             .lock()
             .map_err(|_| anyhow::anyhow!("Failed to lock log file"))?;
-        writeln!(guard, "[{}] {}", timestamp, message)?;
-        Ok(())
+        let (file, lines) = &mut *guard;
+        writeln!(file, "{}", entry)?;
+        let number = *lines + 1;
+        *lines += entry.matches('\n').count() + 1;
+        Ok(number)
     }
 
-    /// Write a message followed by an indented multiline block.
-    pub fn log_block(&self, header: &str, body: &str) -> Result<()> {
-        self.log_line(header)?;
+    /// Write a message followed by an indented multiline block, returning
+    /// the line number of the message.
+    pub fn log_block(&self, header: &str, body: &str) -> Result<usize> {
+        let line = self.log_line(header)?;
         for line in body.lines() {
             let indent = format!("    {}", line);
             self.log_line(&indent)?;
         }
+        Ok(line)
+    }
+}
+
+/// The complete stdout and stderr of one task, in its own file
+pub struct TaskLog {
+    path: PathBuf,
+    file: Mutex<File>,
+    /// Whether the command's output was written line by line
+    captured: AtomicBool,
+}
+
+impl TaskLog {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append a line of output as the command prints it
+    pub fn write_line(&self, line: &str) -> io::Result<()> {
+        self.captured.store(true, Ordering::SeqCst);
+        let mut file = self.file.lock().unwrap_or_else(|p| p.into_inner());
+        file.write_all(line.as_bytes())?;
+        if !line.ends_with('\n') {
+            file.write_all(b"\n")?;
+        }
         Ok(())
     }
+
+    /// Close the log with the task's outcome. `output` is written first
+    /// when nothing was captured line by line, as for built-in tasks.
+    pub fn finish(&self, outcome: &str, output: Option<&str>) -> io::Result<()> {
+        if !self.captured.load(Ordering::SeqCst)
+            && let Some(output) = output.filter(|output| !output.trim().is_empty())
+        {
+            self.write_line(output)?;
+        }
+        let timestamp = Local::now().format("%Y-%m-%d %H:%M:%S");
+        let mut file = self.file.lock().unwrap_or_else(|p| p.into_inner());
+        writeln!(file, "[{}] {}", timestamp, outcome)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_task_log() {
+        let dir = std::env::temp_dir().join(format!("tide-logs-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let logger = Logger::new(dir.join("tide.log"))
+            .unwrap()
+            .with_task_dir(dir.clone());
+
+        let log = logger
+            .task_log("Homebrew", "Upgrade Packages")
+            .unwrap()
+            .unwrap();
+        let name = log.path().file_name().unwrap().to_string_lossy();
+        assert!(name.ends_with("_homebrew_upgrade-packages.log"));
+        log.write_line("==> Upgrading 2 outdated packages\n")
+            .unwrap();
+        log.write_line("Warning: git is pinned").unwrap();
+        log.finish("✓ SUCCESS (12s)", Some("ignored")).unwrap();
+        let content = fs::read_to_string(log.path()).unwrap();
+        assert!(content.starts_with("==> Upgrading 2 outdated packages\nWarning: git is pinned\n"));
+        assert!(content.trim_end().ends_with("✓ SUCCESS (12s)"));
+        assert!(!content.contains("ignored"));
+
+        let builtin = logger.task_log("Docker", "Prune").unwrap().unwrap();
+        builtin
+            .finish("✓ SUCCESS (1s)", Some("Reclaimed 2GB"))
+            .unwrap();
+        assert!(
+            fs::read_to_string(builtin.path())
+                .unwrap()
+                .starts_with("Reclaimed 2GB\n")
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_json_log_events() {
+        let path = std::env::temp_dir().join(format!("tide-log-{}.jsonl", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let logger = Logger::new(&path).unwrap().with_format(LogFormat::Json);
+
+        let started = LogEvent::TaskStarted {
+            group: "Homebrew",
+            task: "Upgrade Packages",
+            command: "brew upgrade",
+        };
+        assert_eq!(logger.log_event(&started, "▶ ignored").unwrap(), 1);
+        let output = LogEvent::Output {
+            group: "Homebrew",
+            task: "Upgrade Packages",
+            output: "==> Upgrading git\n==> Pouring git",
+        };
+        assert_eq!(logger.log_event(&output, "└ ignored").unwrap(), 2);
+        let finished = LogEvent::TaskFinished {
+            group: "Homebrew",
+            task: "Upgrade Packages",
+            status: TaskStatus::Success,
+            duration_secs: 12.5,
+        };
+        assert_eq!(logger.log_event(&finished, "✓ ignored").unwrap(), 3);
+
+        let records: Vec<serde_json::Value> = fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0]["event"], "task_started");
+        assert_eq!(records[0]["command"], "brew upgrade");
+        assert_eq!(records[1]["output"], "==> Upgrading git\n==> Pouring git");
+        assert_eq!(records[2]["status"], "success");
+        assert_eq!(records[2]["duration_secs"], 12.5);
+        assert!(records[2]["timestamp"].is_string());
+        fs::remove_file(&path).unwrap();
+    }
 }
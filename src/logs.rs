@@ -0,0 +1,166 @@
+use serde_json::Value;
+
+/// Picks the lines of the log to print for `tide logs`. It is fed one line
+/// at a time, so it works the same while following the file.
+///
+/// Lines of the text format look like `[2024-05-01 09:30:00] ✓ SUCCESS
+/// [🍺 Homebrew] 📦 Upgrade Packages (12s)`, with the output of a task
+/// indented beneath its `└ output` line; the JSON format has one record
+/// per line with `group` and `task` fields.
+#[derive(Debug)]
+pub struct LogFilter {
+    task: Option<String>,
+    /// Whether the entry that indented lines belong to was shown
+    showing: bool,
+}
+
+impl LogFilter {
+    /// Show every line, or only those of `task` (case-insensitive) and the
+    /// start of each run
+    pub fn new(task: Option<&str>) -> Self {
+        Self {
+            task: task.map(str::to_lowercase),
+            showing: true,
+        }
+    }
+
+    /// Whether `line` is printed
+    pub fn accepts(&mut self, line: &str) -> bool {
+        let Some(task) = &self.task else {
+            return true;
+        };
+        if let Some(record) = json_record(line) {
+            return run_started(line)
+                || record["task"]
+                    .as_str()
+                    .is_some_and(|name| name.to_lowercase() == *task);
+        }
+        let message = text_message(line);
+        if message.starts_with("    ") {
+            return self.showing;
+        }
+        self.showing = run_started(line)
+            || task_label(message).is_some_and(|label| {
+                let label = label.to_lowercase();
+                label == *task || label.ends_with(&format!(" {}", task))
+            });
+        self.showing
+    }
+}
+
+/// Byte offset of the line starting the last run in `content`, or 0
+pub fn last_run_start(content: &str) -> usize {
+    let mut offset = 0;
+    let mut start = 0;
+    for line in content.split_inclusive('\n') {
+        if run_started(line) {
+            start = offset;
+        }
+        offset += line.len();
+    }
+    start
+}
+
+fn json_record(line: &str) -> Option<Value> {
+    if !line.starts_with('{') {
+        return None;
+    }
+    serde_json::from_str::<Value>(line)
+        .ok()
+        .filter(|record| record["event"].is_string())
+}
+
+fn run_started(line: &str) -> bool {
+    match json_record(line) {
+        Some(record) => record["event"] == "run_started",
+        None => {
+            let message = text_message(line).trim_end();
+            message.starts_with("Run ") && message.ends_with(" started")
+        }
+    }
+}
+
+/// A text line without its `[timestamp] ` prefix
+fn text_message(line: &str) -> &str {
+    line.split_once("] ").map_or(line, |(_, message)| message)
+}
+
+/// Task label of a text entry: what follows its `[group]` up to the
+/// command, the retry note or the duration
+fn task_label(message: &str) -> Option<&str> {
+    let (_, rest) = message.split_once("] ")?;
+    let rest = rest.trim_end();
+    let label = if let Some((label, _)) = rest.split_once(" :: ") {
+        label
+    } else if let Some((label, _)) = rest.split_once(" transient network error") {
+        label
+    } else if message.starts_with("└ output") {
+        rest
+    } else {
+        rest.rsplit_once(" (").map_or(rest, |(label, _)| label)
+    };
+    Some(label)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = "\
+[2024-05-01 09:00:00] Run a1 started
+[2024-05-01 09:00:01] ▶ [🍺 Homebrew] 📦 Upgrade Packages :: brew upgrade
+[2024-05-01 09:00:09] ✗ FAILED [🍺 Homebrew] 📦 Upgrade Packages (8s)
+[2024-05-01 09:00:09] └ output [🍺 Homebrew] 📦 Upgrade Packages
+[2024-05-01 09:00:09]     Error: git is pinned
+[2024-05-01 09:00:10] ✓ SUCCESS [🍺 Homebrew] Cleanup (1s)
+[2024-05-01 09:00:10] └ output [🍺 Homebrew] Cleanup
+[2024-05-01 09:00:10]     Removing 3 files
+[2024-05-02 09:00:00] Run b2 started
+[2024-05-02 09:00:03] ✓ SUCCESS [🍺 Homebrew] 📦 Upgrade Packages (3s)
+";
+
+    fn shown(content: &str, task: Option<&str>) -> Vec<String> {
+        let mut filter = LogFilter::new(task);
+        content
+            .lines()
+            .filter(|line| filter.accepts(line))
+            .map(|line| text_message(line).to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_filter_text_log() {
+        assert_eq!(shown(TEXT, None).len(), 10);
+        assert_eq!(
+            shown(TEXT, Some("upgrade packages")),
+            [
+                "Run a1 started",
+                "▶ [🍺 Homebrew] 📦 Upgrade Packages :: brew upgrade",
+                "✗ FAILED [🍺 Homebrew] 📦 Upgrade Packages (8s)",
+                "└ output [🍺 Homebrew] 📦 Upgrade Packages",
+                "    Error: git is pinned",
+                "Run b2 started",
+                "✓ SUCCESS [🍺 Homebrew] 📦 Upgrade Packages (3s)",
+            ]
+        );
+        assert_eq!(shown(TEXT, Some("Cleanup")).len(), 5);
+
+        let last = &TEXT[last_run_start(TEXT)..];
+        assert!(last.starts_with("[2024-05-02 09:00:00] Run b2 started"));
+        assert_eq!(last_run_start("no runs\n"), 0);
+    }
+
+    #[test]
+    fn test_filter_json_log() {
+        let log = [
+            r#"{"timestamp":"2024-05-01T09:00:00+02:00","event":"run_started","run_id":"a1"}"#,
+            r#"{"timestamp":"2024-05-01T09:00:09+02:00","event":"task_finished","group":"Homebrew","task":"Upgrade Packages","status":"failed","duration_secs":8.0}"#,
+            r#"{"timestamp":"2024-05-01T09:00:10+02:00","event":"task_finished","group":"Homebrew","task":"Cleanup","status":"success","duration_secs":1.0}"#,
+        ]
+        .join("\n");
+        let mut filter = LogFilter::new(Some("Upgrade Packages"));
+        let shown: Vec<bool> = log.lines().map(|line| filter.accepts(line)).collect();
+        assert_eq!(shown, [true, true, false]);
+        assert_eq!(last_run_start(&log), 0);
+    }
+}
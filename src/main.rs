@@ -1,142 +1,649 @@
 mod cli;
-mod config;
-mod error;
-mod executor;
-mod keychain;
-mod logger;
-mod notifications;
-mod ui;
-
-use anyhow::Result;
-use clap::Parser;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
 use colored::Colorize;
-use dialoguer::{Confirm, theme::ColorfulTheme};
-use futures::future::join_all;
+use dialoguer::{Confirm, Input, MultiSelect, Select, theme::ColorfulTheme};
+use std::collections::{BTreeMap, HashMap};
 use std::env;
 use std::ffi::OsString;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
-use tokio::sync::Semaphore;
 
-use cli::Args;
-use config::{Config, Settings};
-use executor::{TaskExecutor, TaskResult, TaskStatus};
-use logger::Logger;
+use cli::{Args, AuditCommand, Commands, ConfigCommand, GroupCommand, ImportSource, TasksCommand};
+use tide_core::appstore::{self, SignIn, SignInPolicy};
+use tide_core::audit::{self, AuditLog};
+use tide_core::config::{Config, Settings, TaskGroup};
+use tide_core::error::{self, TideError};
+use tide_core::executor::{TaskExecutor, TaskResult, TaskStatus};
+use tide_core::failure::FailureClass;
+use tide_core::format::ConfigFormat;
+use tide_core::history::{History, RunRecord};
+use tide_core::lint::LintKind;
+use tide_core::logger::{LogEvent, Logger};
+use tide_core::logs::LogFilter;
+use tide_core::network::Connectivity;
+use tide_core::notifications::NotificationManager;
+use tide_core::palette::{self, Paint};
+use tide_core::plan::{Batch, BatchMode, PlannedTask, TaskForecast};
+use tide_core::provenance::{self, Approvals};
+use tide_core::runenv::RunEnv;
+use tide_core::severity::Severity;
+use tide_core::status::CheckFormat;
+use tide_core::{
+    backup, bench, catalog, controls, disk, export, harness, hyperlink, import, include, keychain,
+    launcher, lint, lock, logs, managed, network, orphans, overlay, params, pending, plan, postrun,
+    power, profile, recipes, replay, report, runner, severity, stats, status, suggest, thermal,
+    trend, ui, validate, why, widgets,
+};
 
 #[tokio::main]
-async fn main() -> Result<()> {
-    let args = Args::parse();
+async fn main() {
+    if let Err(err) = run().await {
+        eprintln!("Error: {:?}", err);
+        std::process::exit(error::exit_status(&err));
+    }
+}
+
+async fn run() -> Result<()> {
+    let mut args = Args::parse_merged();
+    if let Some(policy) = args.exit_code_policy {
+        error::set_exit_code_policy(policy);
+    }
+    let raycast_output = matches!(
+        args.command,
+        Some(Commands::Run {
+            raycast_output: true,
+            ..
+        })
+    );
+    if raycast_output || args.shortcuts {
+        // Launchers and Shortcuts cannot answer prompts, so run unattended
+        args.force = true;
+        args.quiet = true;
+    }
+    let json_output = args.run.output == report::OutputMode::Json;
+    if json_output {
+        // Only the JSON document may reach stdout
+        args.quiet = true;
+    }
+    if args.shortcuts || json_output {
+        colored::control::set_override(false);
+    }
 
+    if let Some(Commands::Init { format }) = args.command {
+        return init_config(args.config.as_ref(), format);
+    }
     if args.init {
-        return init_config(args.config.as_ref());
+        return init_config(args.config.as_ref(), args.format);
+    }
+
+    // Handled before loading so a broken config can still be restored
+    if let Some(Commands::Config { action }) = &args.command {
+        let config_path = Config::resolve_path(args.config.as_ref())?;
+        return manage_config(&config_path, action);
+    }
+
+    if let Some(Commands::Import { source }) = &args.command {
+        let config_path = Config::resolve_path(args.config.as_ref())?;
+        return match source {
+            ImportSource::Script { file, group } => {
+                import_script(&config_path, file, group.as_deref(), args.force)
+            }
+            ImportSource::Url { url, sha256 } => {
+                import_url(&config_path, url, sha256.as_deref(), args.force).await
+            }
+        };
+    }
+
+    if let Some(Commands::Tasks { action }) = &args.command {
+        return match action {
+            TasksCommand::Catalog => {
+                display_catalog();
+                Ok(())
+            }
+            TasksCommand::Add { id, group } => {
+                let config_path = Config::resolve_path(args.config.as_ref())?;
+                add_catalog_task(&config_path, id, group.as_deref(), args.force)
+            }
+        };
+    }
+
+    if let Some(Commands::Group { action }) = &args.command {
+        let config_path = Config::resolve_path(args.config.as_ref())?;
+        return match action {
+            GroupCommand::Export { name, file } => {
+                export_group(&config_path, name, file.as_deref(), args.force)
+            }
+            GroupCommand::Import { file } => import_group_file(&config_path, file, args.force),
+        };
+    }
+
+    if let Some(Commands::Audit {
+        action: AuditCommand::Verify { file },
+    }) = &args.command
+    {
+        let path = match file {
+            Some(file) => file.clone(),
+            None => {
+                let config_path = Config::resolve_path(args.config.as_ref())?;
+                let config = Config::load_profile(Some(&config_path), args.profile.as_deref())?;
+                let raw = config.settings.audit_log_path().ok_or_else(|| {
+                    anyhow::anyhow!("No audit log configured (set `audit_log` in [settings])")
+                })?;
+                resolve_settings_path(raw, &config_path)
+            }
+        };
+        return verify_audit_log(&path);
+    }
+
+    // Exporting only reads the config, so it works on any platform
+    if let Some(Commands::Export { format }) = args.command {
+        let config = Config::load_profile(args.config.as_ref(), args.profile.as_deref())?;
+        for names in [&args.groups, &args.skip_groups].into_iter().flatten() {
+            plan::check_group_names(&config, names)?;
+        }
+        print!(
+            "{}",
+            export::export(
+                &config,
+                format,
+                args.groups.as_deref(),
+                args.skip_groups.as_deref()
+            )
+        );
+        return Ok(());
+    }
+
+    // Validating and browsing the history only read files, so they work on
+    // any platform
+    if let Some(Commands::Validate) = args.command {
+        let config_path = Config::resolve_path(args.config.as_ref())?;
+        let config = Config::load_profile(Some(&config_path), args.profile.as_deref())?;
+        let filters = [&args.groups, &args.skip_groups].into_iter().flatten();
+        return validate_config(&config, &config_path, filters.flatten());
+    }
+
+    if let Some(Commands::History { last }) = args.command {
+        display_history(&History::open_default()?.recent_runs(last)?);
+        return Ok(());
+    }
+
+    if let Some(Commands::Logs { last, follow, task }) = &args.command {
+        let config_path = Config::resolve_path(args.config.as_ref())?;
+        let config = Config::load_profile(Some(&config_path), args.profile.as_deref())?;
+        let path = log_path(&config.settings, &config_path).ok_or_else(|| {
+            anyhow::anyhow!("No log configured (set `log_file` or `log_dir` in [settings])")
+        })?;
+        return show_logs(&path, *last, *follow, task.as_deref()).await;
+    }
+
+    if let Some(Commands::Stats { last }) = args.command {
+        let runs = History::open_default()?.recent_runs(last)?;
+        stats::display(&runs, &stats::analyze(&runs));
+        return Ok(());
+    }
+
+    // Explaining only reads the config and history, so it works on any platform
+    if let Some(Commands::Why { task }) = &args.command {
+        let config = Config::load_profile(args.config.as_ref(), args.profile.as_deref())?;
+        for names in [&args.groups, &args.skip_groups].into_iter().flatten() {
+            plan::check_group_names(&config, names)?;
+        }
+        let last = History::open_default()?.recent_runs(1)?.pop();
+        let explanations = why::explain(
+            &config,
+            task,
+            args.groups.as_deref(),
+            args.skip_groups.as_deref(),
+            last.as_ref(),
+        )?;
+        display_explanations(&explanations);
+        return Ok(());
     }
 
     if std::env::consts::OS != "macos" {
-        eprintln!("{}", "❌ This tool is for macOS only!".red().bold());
+        eprintln!("{}", "❌ This tool is for macOS only!".failure().bold());
         std::process::exit(1);
     }
 
     let config_path = Config::resolve_path(args.config.as_ref())?;
-    let config = Config::load(Some(&config_path))?;
+    let config = Config::load_profile(Some(&config_path), args.profile.as_deref())?;
+    palette::set_scheme(config.settings.color_scheme);
+    error::set_exit_code_policy(
+        args.exit_code_policy
+            .unwrap_or(config.settings.exit_code_policy),
+    );
+    if !args.quiet {
+        for notice in &config.notices {
+            eprintln!("{}", format!("🔒 {}", notice).warning());
+        }
+    }
+    for names in [&args.groups, &args.skip_groups].into_iter().flatten() {
+        plan::check_group_names(&config, names)?;
+    }
+    let single_task = match &args.run.task {
+        Some(_) if args.groups.is_some() || args.skip_groups.is_some() => {
+            return Err(TideError::Config(
+                "A single task cannot be combined with --groups or --skip-groups".to_string(),
+            )
+            .into());
+        }
+        Some(reference) => Some(plan::find_task(&config, reference)?),
+        None => None,
+    };
+
+    if let Some(Commands::List { raycast: true }) = args.command {
+        let filter =
+            launcher::script_filter(&config, args.groups.as_deref(), args.skip_groups.as_deref());
+        println!("{}", serde_json::to_string(&filter)?);
+        return Ok(());
+    }
 
-    if args.list {
+    if args.list || matches!(args.command, Some(Commands::List { .. })) {
         list_tasks(&config, &args);
-        display_config_path(&config_path)?;
+        display_config_path(&config_path, &config)?;
+        if let Some(profile) = &args.profile {
+            println!("{} {}", "With profile:".bright_blue().bold(), profile);
+        }
+        return Ok(());
+    }
+
+    if let Some(Commands::Plan) = args.command {
+        let tasks = expand_matrices(plan::collect_tasks(
+            &config,
+            args.groups.as_deref(),
+            args.skip_groups.as_deref(),
+        ));
+        display_batches(
+            &plan::build_batches(tasks, &config.settings, args.parallel),
+            &config.settings,
+        );
+        return Ok(());
+    }
+
+    if let Some(Commands::Check) = args.command {
+        let tasks =
+            plan::collect_tasks(&config, args.groups.as_deref(), args.skip_groups.as_deref());
+        let repos = pending::git_repos(tasks.iter().map(|planned| &planned.task));
+        let found = pending::discover(&repos).await;
+        pending::display(&found);
+        if let Some(summary) = pending::summary(&found) {
+            NotificationManager::new(config.settings.desktop_notifications)
+                .notify_pending_updates(&summary)?;
+        }
+        return Ok(());
+    }
+
+    if let Some(Commands::Test) = args.command {
+        if !harness::run_all(&config) {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    if let Some(Commands::Bench { runs, last }) = args.command {
+        if let Some(runs) = runs {
+            bench::invoke_runs(
+                runs,
+                &config_path,
+                args.groups.as_deref(),
+                args.skip_groups.as_deref(),
+                args.parallel,
+            )?;
+        }
+        let runs = History::open_default()?.recent_runs(runs.unwrap_or(last))?;
+        bench::display(&runs, &bench::analyze(&runs));
+        return Ok(());
+    }
+
+    if let Some(Commands::Status {
+        xbar,
+        check_format,
+        warn_hours,
+        crit_hours,
+    }) = args.command
+    {
+        if let Some(CheckFormat::Nagios) = check_format {
+            let runs = History::open_default()?.recent_runs(usize::MAX)?;
+            let limits = status::StalenessLimits {
+                warn_hours,
+                crit_hours,
+            };
+            let (state, line) = status::render_check(&runs, Local::now(), limits);
+            println!("{}", line);
+            std::process::exit(state.exit_code());
+        }
+        let last = History::open_default()?.recent_runs(1)?.pop();
+        if xbar {
+            let tide = env::current_exe()?;
+            print!(
+                "{}",
+                status::render_xbar(last.as_ref(), &config, &tide, &config_path)
+            );
+        } else {
+            status::display_status(last.as_ref(), &config);
+        }
         return Ok(());
     }
 
+    // Piped into `tee` or started by launchd nobody can answer a prompt,
+    // so run as with --force; prompts that cannot be skipped fail instead
+    let interactive = controls::terminal_attached();
+    if !interactive && !args.force {
+        args.force = true;
+        if !args.quiet {
+            println!(
+                "{}",
+                "No terminal attached: running non-interactively (as with --force)".dimmed()
+            );
+        }
+    }
+
     setup_environment();
 
-    let logger = match init_logger(&config.settings, &config_path)? {
+    let (logger, log_path) = match init_logger(&config.settings, &config_path)? {
         Some((logger, path)) => {
             if !args.quiet {
+                let per_task = config
+                    .settings
+                    .log_dir_path()
+                    .map(|dir| {
+                        format!(
+                            ", one file per task in {}",
+                            hyperlink::path(&resolve_settings_path(dir, &config_path))
+                        )
+                    })
+                    .unwrap_or_default();
                 println!(
                     "{}",
-                    format!("📝 Task output will be logged to {}", path.display()).dimmed()
+                    format!(
+                        "📝 Task output will be logged to {}{}",
+                        hyperlink::path(&path),
+                        per_task
+                    )
+                    .dimmed()
                 );
             }
-            Some(logger)
+            (Some(logger), Some(path))
         }
-        None => None,
+        None => (None, None),
     };
 
-    let weather_task = if !args.quiet && config.settings.show_weather {
-        Some(tokio::spawn(ui::fetch_weather()))
-    } else {
-        None
-    };
+    let dashboard = (!args.quiet).then(|| widgets::Dashboard::start(&config.settings));
 
     if !args.quiet && config.settings.show_banner {
         ui::print_banner();
     }
 
-    let mut all_tasks = Vec::new();
-    for group in &config.groups {
-        if !group.enabled {
-            continue;
+    let all_tasks = match single_task {
+        Some(planned) => vec![planned],
+        None if args.run.interactive => {
+            let picked = pick_tasks(&config, &args)?;
+            if picked.is_empty() {
+                println!("{}", "Nothing selected.".dimmed());
+                return Ok(());
+            }
+            picked
         }
-
-        if let Some(ref groups) = args.groups
-            && !groups.contains(&group.name)
-        {
-            continue;
+        None => plan::collect_tasks(&config, args.groups.as_deref(), args.skip_groups.as_deref()),
+    };
+    let all_tasks = review_dangerous_tasks(all_tasks, &args)?;
+    let all_tasks = if args.run.dry_run || args.run.replay.is_some() {
+        all_tasks
+    } else {
+        review_remote_tasks(all_tasks, &args)?
+    };
+    let mut skip_sudo = args.run.skip_sudo;
+    let all_tasks = if skip_sudo {
+        without_sudo_tasks(all_tasks, &args)
+    } else {
+        all_tasks
+    };
+    let mut all_tasks = expand_matrices(all_tasks);
+    if args.run.resume {
+        let runs = History::open_default()?.recent_runs(usize::MAX)?;
+        if runs.is_empty() {
+            return Err(TideError::Config("No previous run to resume".to_string()).into());
         }
-        if let Some(ref skip) = args.skip_groups
-            && skip.contains(&group.name)
-        {
-            continue;
+        all_tasks = plan::unfinished(all_tasks, &runs);
+        if all_tasks.is_empty() {
+            println!(
+                "{}",
+                "Nothing to resume: every task of the last run succeeded.".success()
+            );
+            return Ok(());
         }
-
-        for task in &group.tasks {
-            if task.enabled {
-                all_tasks.push((
-                    task.clone(),
-                    group.name.clone(),
-                    group.icon.clone(),
-                    group.parallel,
-                ));
-            }
+    }
+    // Encrypted env files travel with the config, so relative paths start there
+    for planned in &mut all_tasks {
+        if let Some(file) = &mut planned.task.env_file_encrypted {
+            *file = resolve_settings_path(file, &config_path)
+                .display()
+                .to_string();
         }
     }
 
     if all_tasks.is_empty() {
-        println!("{}", "No tasks to run!".yellow());
+        if json_output {
+            print_json_report(Local::now(), Duration::ZERO, &[], None)?;
+        } else {
+            println!("{}", "No tasks to run!".warning());
+        }
         return Ok(());
     }
 
+    // Dry runs and replays change nothing, so they may overlap a real run
+    let _run_lock = if args.run.dry_run || args.run.replay.is_some() {
+        None
+    } else {
+        Some(acquire_run_lock(&args)?)
+    };
+
+    orphans::install_handlers();
+    check_leftover_processes(&args)?;
+
+    let estimates = History::open_default()
+        .and_then(|history| history.duration_estimates())
+        .unwrap_or_default();
+
+    // Replays serve recorded outcomes whatever is installed now
+    let skips = if args.run.replay.is_some() {
+        vec![None; all_tasks.len()]
+    } else {
+        let mut skips = plan::preflight(&all_tasks).await;
+        // A task asked for by name runs whenever it last succeeded
+        if args.run.task.is_none() {
+            let last_successes = History::open_default()
+                .and_then(|history| history.last_successes())
+                .unwrap_or_default();
+            let recent = plan::recently_run(&all_tasks, &last_successes, Local::now());
+            for (skip, recent) in skips.iter_mut().zip(recent) {
+                *skip = skip.take().or(recent);
+            }
+        }
+        skips
+    };
+
     if !args.force && !args.quiet {
-        println!(
-            "\n{}",
-            format!("📦 Ready to run {} tasks", all_tasks.len()).bright_blue()
-        );
+        let forecasts = plan::forecast(&all_tasks, &skips, &estimates);
+        let sudo_count = forecasts
+            .iter()
+            .filter(|f| f.skip.is_none() && f.planned.task.needs_sudo())
+            .count();
+        display_plan(&forecasts);
+
+        if args.run.dry_run {
+            println!("{}", "🔸 DRY RUN MODE - No changes will be made".warning());
+        }
+        if let Some(path) = &args.run.replay {
+            println!(
+                "{}",
+                format!(
+                    "🔁 REPLAY MODE - Using recorded results from {}",
+                    path.display()
+                )
+                .warning()
+            );
+        }
 
-        if args.dry_run {
-            println!("{}", "🔸 DRY RUN MODE - No changes will be made".yellow());
+        match confirm_run(sudo_count)? {
+            RunChoice::All => {}
+            RunChoice::WithoutSudo => skip_sudo = true,
+            RunChoice::Cancel => {
+                println!("{}", "Cancelled by user".warning());
+                return Ok(());
+            }
         }
+    }
 
-        if !Confirm::with_theme(&ColorfulTheme::default())
-            .with_prompt("Continue?")
-            .default(true)
-            .interact()?
-        {
-            println!("{}", "Cancelled by user".yellow());
-            return Ok(());
+    // Tasks failing their preconditions are reported as skipped right away
+    // instead of occupying a slot in the run. They rejoin the plan when it
+    // is scheduled so tasks depending on them are skipped as well.
+    let (preflight_skipped, all_tasks): (Vec<PlannedTask>, Vec<PlannedTask>) = all_tasks
+        .into_iter()
+        .zip(skips)
+        .map(|(mut planned, skip)| {
+            planned.skip = skip;
+            planned
+        })
+        .partition(|planned| planned.skip.is_some());
+
+    let all_tasks = if skip_sudo && !args.run.skip_sudo {
+        without_sudo_tasks(all_tasks, &args)
+    } else {
+        all_tasks
+    };
+    if all_tasks.is_empty() && json_output {
+        let skipped: Vec<TaskResult> = preflight_skipped
+            .into_iter()
+            .filter_map(|planned| {
+                let reason = planned.skip?;
+                Some(TaskResult::precondition_skipped(
+                    planned.task.name,
+                    planned.group,
+                    planned.group_icon,
+                    &reason,
+                ))
+            })
+            .collect();
+        print_json_report(Local::now(), Duration::ZERO, &skipped, None)?;
+        return Ok(());
+    }
+    if all_tasks.is_empty() {
+        println!("{}", "No tasks to run!".warning());
+        for skipped in &preflight_skipped {
+            if let Some(reason) = &skipped.skip {
+                println!(
+                    "  {} {}",
+                    skipped.task.name,
+                    format!("({})", reason).dimmed()
+                );
+            }
         }
+        return Ok(());
     }
 
-    let show_progress = config.settings.show_progress && !args.quiet;
-    let executor = Arc::new(TaskExecutor::new(
-        args.dry_run,
-        args.verbose || config.settings.verbose,
+    let all_tasks = resolve_params(all_tasks, &config, &args)?;
+
+    // Replays serve recorded outcomes, so like dry runs they must not touch the system
+    let replay = args
+        .run
+        .replay
+        .as_deref()
+        .map(replay::Replay::load)
+        .transpose()?
+        .map(Arc::new);
+    let simulated = args.run.dry_run || replay.is_some();
+
+    let show_progress = config.settings.show_progress && !args.quiet && interactive;
+    let mut executor = TaskExecutor::new(
+        args.run.dry_run,
+        (args.verbose || config.settings.verbose) && !json_output,
         config.settings.desktop_notifications && !args.quiet,
         show_progress,
+        config.settings.retry_transient,
+        config.settings.attention_repeat(),
         logger.clone(),
-    ));
+    );
+    let run_env = RunEnv::new(args.run.dry_run, log_path.clone());
+    if let Some(logger) = &logger {
+        let _ = logger.log_event(
+            &LogEvent::RunStarted {
+                run_id: &run_env.run_id,
+            },
+            &format!("Run {} started", run_env.run_id),
+        );
+    }
+    executor.run_env = Arc::new(run_env);
+    executor.replay = replay;
+    if !config.notifications.is_empty() && !simulated {
+        executor.notifier = Arc::new(
+            NotificationManager::new(config.settings.desktop_notifications && !args.quiet)
+                .with_backends(&config.notifications),
+        );
+    }
+    executor.silent = json_output;
+    executor.output_tail_lines = config.settings.output_tail_lines;
+    executor.sudo_prompt_timeout = config.settings.sudo_prompt_timeout();
+    executor.sudo_password_attempts = config.settings.sudo_password_attempts;
+    executor.age_identity = config
+        .settings
+        .age_identity
+        .as_deref()
+        .map(|raw| resolve_settings_path(raw, &config_path));
+    // An unauditable run must not execute anything, least of all sudo commands
+    if let Some(raw) = config.settings.audit_log_path()
+        && !simulated
+    {
+        let path = resolve_settings_path(raw, &config_path);
+        let audit = AuditLog::open(&path)
+            .with_context(|| format!("Cannot open audit log {}", path.display()))?;
+        if !args.quiet {
+            println!(
+                "{}",
+                format!("🔏 Commands will be audited to {}", audit.path().display()).dimmed()
+            );
+        }
+        executor.audit = Some(Arc::new(audit));
+    }
+    let executor = Arc::new(executor);
+    // Hold a power assertion until the run finishes so long updates are not suspended
+    let sleep_guard = if config.settings.prevent_sleep && !simulated {
+        power::SleepGuard::acquire()
+    } else {
+        None
+    };
     let start_time = Instant::now();
+    let started_at = Local::now();
     let mut results = Vec::new();
 
+    let all_tasks = if simulated {
+        all_tasks
+    } else {
+        disk_space_preflight(all_tasks, &config.settings, &args)?
+    };
+
+    let all_tasks = if simulated {
+        all_tasks
+    } else {
+        thermal_preflight(all_tasks, &config.settings, &executor)
+    };
+
+    let all_tasks = if config.settings.connectivity_check && !simulated {
+        network_preflight(all_tasks, &config.settings, &args, &executor).await?
+    } else {
+        all_tasks
+    };
+
+    let all_tasks = if simulated {
+        all_tasks
+    } else {
+        app_store_preflight(all_tasks, &config.settings, &args, &executor).await?
+    };
+
     let keychain_label = config
         .settings
         .keychain_label
@@ -146,7 +653,7 @@ async fn main() -> Result<()> {
     // Pre-authenticate sudo to prevent tasks from hanging
     // This helps even if tasks don't have sudo: true but internally call sudo
     // We do this proactively unless in dry-run mode
-    if !args.dry_run && !args.quiet {
+    if !simulated && !args.quiet && !skip_sudo {
         // Only attempt if sudo is available and we're not running quietly
         if keychain::command_exists("sudo") {
             match executor.ensure_sudo_auth(keychain_label).await {
@@ -156,81 +663,90 @@ async fn main() -> Result<()> {
                 Err(e) => {
                     // Sudo auth failed - warn but don't exit
                     // Some tasks might not need sudo
+                    eprintln!("{}", format!("⚠️  {}", e).warning());
                     eprintln!(
                         "{}",
-                        format!("⚠️  Sudo authentication failed: {}", e).yellow()
-                    );
-                    eprintln!(
-                        "{}",
-                        "   Tasks requiring sudo may fail or timeout.".yellow()
+                        "   Tasks requiring sudo may fail or timeout.".warning()
                     );
                 }
             }
         }
     }
 
-    let mut sequential_tasks = Vec::new();
-    let mut parallel_tasks = Vec::new();
+    let controls = Arc::clone(&executor.controls);
+    if !args.quiet {
+        controls.start();
+    }
+    // Ctrl+C cancels the running tasks and still prints the summary
+    let interrupt_controls = Arc::downgrade(&controls);
+    orphans::set_interrupt_handler(move || {
+        interrupt_controls
+            .upgrade()
+            .is_some_and(|controls| controls.interrupt())
+    });
+
+    let mut planned = preflight_skipped;
+    planned.extend(all_tasks);
+    let batches = plan::build_batches(planned, &config.settings, args.parallel);
+    results.extend(
+        runner::run_batches(
+            &executor,
+            batches,
+            &config.settings,
+            keychain_label,
+            &estimates,
+        )
+        .await,
+    );
+
+    controls.stop();
+    orphans::clear_interrupt_handler();
+    drop(sleep_guard);
 
-    for (task, group, group_icon, is_parallel) in all_tasks {
-        if is_parallel || (config.settings.parallel_execution && !task.sudo) {
-            parallel_tasks.push((task, group, group_icon));
-        } else {
-            sequential_tasks.push((task, group, group_icon));
-        }
+    let total_duration = start_time.elapsed();
+    if !json_output {
+        display_results(&results, total_duration, &config, log_path.as_deref());
+        display_reports(&results, &config);
     }
 
-    for (task, group, group_icon) in sequential_tasks {
-        let pb = executor.new_spinner();
-        let result = executor
-            .execute_task(task, group, group_icon, pb, keychain_label)
-            .await;
+    if !args.quiet && config.settings.show_timeline {
+        ui::render_timeline(&results, start_time, total_duration);
+    }
 
-        if result.status == TaskStatus::Failed && config.settings.skip_optional_on_error {
-            println!(
+    if let Some(path) = &args.run.record {
+        match replay::write(path, &results) {
+            Ok(()) if !args.quiet => println!(
                 "{}",
-                "⚠️  Skipping remaining optional tasks due to failure".yellow()
-            );
-            break;
+                format!("🎞️  Run recorded to {}", hyperlink::path(path)).dimmed()
+            ),
+            Ok(()) => {}
+            Err(err) => eprintln!(
+                "{}",
+                format!("⚠️  Failed to write recording: {}", err).warning()
+            ),
         }
-
-        results.push(result);
     }
 
-    if !parallel_tasks.is_empty() {
-        let semaphore = Arc::new(Semaphore::new(
-            args.parallel.min(config.settings.parallel_limit),
-        ));
-        let mut handles = Vec::new();
-
-        for (task, group, group_icon) in parallel_tasks {
-            let executor_clone = Arc::clone(&executor);
-            let semaphore_clone = Arc::clone(&semaphore);
-            let keychain_label = keychain_label.to_string();
-            let group_clone = group.clone();
-            let icon_clone = group_icon.clone();
-
-            let handle = tokio::spawn(async move {
-                let _permit = semaphore_clone.acquire().await.unwrap();
-                let pb = executor_clone.new_spinner();
-                executor_clone
-                    .execute_task(task, group_clone, icon_clone, pb, &keychain_label)
-                    .await
-            });
-
-            handles.push(handle);
+    if !simulated {
+        let mut record = RunRecord::from_results(started_at, total_duration, &results);
+        record.resumed = args.run.resume;
+        if !args.quiet {
+            // Compared before recording so the run does not find itself
+            let runs = History::open_default()
+                .and_then(|history| history.recent_runs(usize::MAX))
+                .unwrap_or_default();
+            if let Some(previous) = trend::last_successful(&runs) {
+                trend::display(previous, &trend::compare(previous, &record));
+            }
         }
-
-        let parallel_results = join_all(handles).await;
-        for task_result in parallel_results.into_iter().flatten() {
-            results.push(task_result);
+        if let Err(err) = History::open_default().and_then(|history| history.record(&record)) {
+            eprintln!(
+                "{}",
+                format!("⚠️  Failed to record run history: {}", err).warning()
+            );
         }
     }
 
-    let total_duration = start_time.elapsed();
-    display_results(&results, total_duration);
-
-    // Send completion notification if all tasks succeeded
     let success_count = results
         .iter()
         .filter(|r| r.status == TaskStatus::Success)
@@ -240,58 +756,248 @@ async fn main() -> Result<()> {
         .filter(|r| r.status == TaskStatus::Failed)
         .count();
 
-    if failed_count == 0 && success_count > 0 {
+    let run_error = if controls.is_interrupted() {
+        Some(TideError::Cancelled("Run interrupted".to_string()))
+    } else {
+        TideError::from_results(&results).or_else(|| {
+            controls
+                .is_aborted()
+                .then(|| TideError::Cancelled("Run aborted by user".to_string()))
+        })
+    };
+
+    if !simulated && !config.post_run.is_empty() {
+        let summary = postrun::summary_text(&results, total_duration);
+        let problems = postrun::run(
+            &config.post_run,
+            run_error.is_none(),
+            &summary,
+            &executor.run_env,
+        );
+        for problem in problems {
+            eprintln!("{}", format!("⚠️  Post-run: {}", problem).warning());
+        }
+    }
+
+    let completion = config.settings.completion_notification;
+    if failed_count == 0 && success_count > 0 && completion.fires(true) {
         let _ = executor
             .notifier
             .notify_all_tasks_complete(success_count, total_duration.as_secs());
+    } else if failed_count > 0 && completion.fires(false) {
+        let failed: Vec<&str> = results
+            .iter()
+            .filter(|r| r.status == TaskStatus::Failed)
+            .map(|r| r.name.as_str())
+            .collect();
+        let _ = executor
+            .notifier
+            .notify_tasks_failed(&failed, results.len());
+    }
+    executor.notifier.notify_run_summary(
+        run_error.is_none(),
+        &postrun::summary_text(&results, total_duration),
+    );
+    if let Err(err) = executor.notifier.notify_run_complete(
+        &executor.run_env.run_id,
+        &report::RunReport::new(started_at, total_duration, &results, run_error.as_ref()),
+    ) {
+        eprintln!("{}", format!("⚠️  Webhook: {}", err).warning());
+    }
+    for problem in executor.notifier.flush().await {
+        eprintln!(
+            "{}",
+            format!("⚠️  Notification failed: {}", problem).warning()
+        );
     }
 
-    if !args.quiet && config.settings.show_system_info {
-        ui::display_system_info()?;
+    if let Some(dashboard) = dashboard {
+        dashboard.render().await;
     }
 
-    if let Some(handle) = weather_task {
-        let status = match handle.await {
-            Ok(status) => status,
-            Err(err) => ui::WeatherStatus::Error(format!("Runtime error: {err}")),
-        };
-        ui::render_weather(status);
+    if args.shortcuts {
+        let mut record = RunRecord::from_results(started_at, total_duration, &results);
+        record.resumed = args.run.resume;
+        let report =
+            launcher::ShortcutsReport::new(record, &results, total_duration, run_error.as_ref());
+        if let Err(err) = launcher::write_report(args.json_fd, &report) {
+            eprintln!("Failed to write JSON report: {}", err);
+        }
+    }
+
+    if json_output {
+        print_json_report(started_at, total_duration, &results, run_error.as_ref())?;
+    } else if raycast_output || args.shortcuts {
+        println!("{}", launcher::result_line(&results, total_duration));
+    }
+
+    // The summary is already printed, so only the exit status reports the failure.
+    // Monitoring thresholds replace the failure kinds with warning/critical.
+    let exit_code = if config.settings.failure_threshold.is_empty() || controls.is_aborted() {
+        run_error.map(|err| error::exit_code_policy().status(&err))
+    } else {
+        severity::evaluate(&config.settings.failure_threshold, &results).map(Severity::exit_code)
+    };
+    if let Some(code) = exit_code {
+        std::process::exit(code);
     }
 
     Ok(())
 }
 
-fn display_config_path(path: &Path) -> Result<()> {
+/// Print the outcome of the run as the only output of `--output json`
+fn print_json_report(
+    started_at: DateTime<Local>,
+    duration: Duration,
+    results: &[TaskResult],
+    error: Option<&TideError>,
+) -> Result<()> {
+    let report = report::RunReport::new(started_at, duration, results, error);
+    println!("{}", report.to_json()?);
+    Ok(())
+}
+
+fn display_config_path(path: &Path, config: &Config) -> Result<()> {
     println!(
         "{} {}",
         "Using config file:".bright_blue().bold(),
         path.display()
     );
+    for file in include::files(&config.include, path) {
+        println!("{} {}", "Including:".bright_blue().bold(), file.display());
+    }
+    if let Some(host) = overlay::host_name()
+        && let Some(section) = config
+            .host
+            .keys()
+            .find(|name| profile::host_matches(name, &host))
+    {
+        println!(
+            "{} [host.\"{}\"]",
+            "With host section:".bright_blue().bold(),
+            section
+        );
+    }
+    if let Some(host) = overlay::host_name()
+        && let host_overlay = overlay::host_path(path, &host)
+        && host_overlay.exists()
+    {
+        println!(
+            "{} {}",
+            "With host overlay:".bright_blue().bold(),
+            host_overlay.display()
+        );
+    }
+    let managed = managed::path();
+    if managed.exists() {
+        println!(
+            "{} {}",
+            "Merged over managed config:".bright_blue().bold(),
+            managed.display()
+        );
+    }
     Ok(())
 }
 
+/// Path of the combined log; with only `log_dir` set it is `tide.log` in there
+fn log_path(settings: &Settings, config_path: &Path) -> Option<PathBuf> {
+    match (settings.log_file_path(), settings.log_dir_path()) {
+        (Some(path), _) => Some(resolve_settings_path(path, config_path)),
+        (None, Some(dir)) => Some(resolve_settings_path(dir, config_path).join("tide.log")),
+        (None, None) => None,
+    }
+}
+
 fn init_logger(settings: &Settings, config_path: &Path) -> Result<Option<(Arc<Logger>, PathBuf)>> {
-    let raw_path = match settings.log_file_path() {
-        Some(path) => path,
-        None => return Ok(None),
+    let Some(resolved) = log_path(settings, config_path) else {
+        return Ok(None);
     };
 
-    let expanded = shellexpand::tilde(raw_path);
-    let mut resolved = PathBuf::from(expanded.as_ref());
-
-    if resolved.is_relative()
-        && let Some(parent) = config_path.parent()
-    {
-        resolved = parent.join(resolved);
+    let mut logger = Logger::new(&resolved)?.with_format(settings.log_format);
+    if let Some(dir) = settings.log_dir_path() {
+        logger = logger.with_task_dir(resolve_settings_path(dir, config_path));
     }
-
-    let logger = Arc::new(Logger::new(&resolved)?);
-    Ok(Some((logger, resolved)))
+    Ok(Some((Arc::new(logger), resolved)))
 }
 
-fn init_config(path: Option<&PathBuf>) -> Result<()> {
-    let config_dir = if let Some(p) = path {
-        p.parent().unwrap().to_path_buf()
+/// Print the log from the start of the last run with `last`, then with
+/// `follow` keep printing what is appended until interrupted
+async fn show_logs(path: &Path, last: bool, follow: bool, task: Option<&str>) -> Result<()> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    let content = fs::read(path).with_context(|| format!("No log at {}", path.display()))?;
+    let text = String::from_utf8_lossy(&content);
+    let start = if last { logs::last_run_start(&text) } else { 0 };
+    let mut filter = LogFilter::new(task);
+    for line in text[start..].lines() {
+        if filter.accepts(line) {
+            println!("{}", line);
+        }
+    }
+    if !follow {
+        return Ok(());
+    }
+
+    let mut offset = content.len() as u64;
+    let mut pending = Vec::new();
+    loop {
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        let len = fs::metadata(path).map_or(0, |meta| meta.len());
+        if len < offset {
+            // Truncated or replaced: start over
+            offset = 0;
+            pending.clear();
+        }
+        if len == offset {
+            continue;
+        }
+        let mut file = fs::File::open(path)?;
+        file.seek(SeekFrom::Start(offset))?;
+        offset += file.read_to_end(&mut pending)? as u64;
+        while let Some(end) = pending.iter().position(|&byte| byte == b'\n') {
+            let line: Vec<u8> = pending.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line[..end]);
+            if filter.accepts(&line) {
+                println!("{}", line);
+            }
+        }
+    }
+}
+
+/// Expand `~` and resolve relative paths against the config file's directory
+fn resolve_settings_path(raw_path: &str, config_path: &Path) -> PathBuf {
+    let expanded = shellexpand::tilde(raw_path);
+    let resolved = PathBuf::from(expanded.as_ref());
+    match config_path.parent() {
+        Some(parent) if resolved.is_relative() => parent.join(resolved),
+        _ => resolved,
+    }
+}
+
+fn verify_audit_log(path: &Path) -> Result<()> {
+    match audit::verify(path) {
+        Ok(count) => {
+            println!(
+                "{}",
+                format!(
+                    "✓ Audit log intact: {} entries in {}",
+                    count,
+                    path.display()
+                )
+                .success()
+            );
+            Ok(())
+        }
+        Err(err) => {
+            eprintln!("{}", format!("✗ {}", err).failure().bold());
+            std::process::exit(1);
+        }
+    }
+}
+
+fn init_config(path: Option<&PathBuf>, format: Option<ConfigFormat>) -> Result<()> {
+    let config_dir = if let Some(p) = path {
+        p.parent().unwrap().to_path_buf()
     } else {
         dirs::config_dir()
             .ok_or_else(|| anyhow::anyhow!("Could not determine config directory"))?
@@ -299,50 +1005,529 @@ fn init_config(path: Option<&PathBuf>) -> Result<()> {
     };
 
     fs::create_dir_all(&config_dir)?;
-    let config_path = config_dir.join("config.toml");
+    let format =
+        format.unwrap_or_else(|| path.map_or_else(ConfigFormat::default, |p| ConfigFormat::of(p)));
+    let config_path = config_dir.join(format!("config.{}", format.extension()));
 
-    if config_path.exists()
-        && !Confirm::with_theme(&ColorfulTheme::default())
+    if config_path.exists() {
+        managed::ensure_writable(&config_path)?;
+        if !Confirm::with_theme(&ColorfulTheme::default())
             .with_prompt("Config file already exists. Overwrite?")
             .default(false)
             .interact()?
-    {
-        return Ok(());
+        {
+            return Ok(());
+        }
+        if let Some(backup) = backup::create(&config_path)? {
+            println!(
+                "{}",
+                format!("💾 Previous config backed up to {}", backup.display()).dimmed()
+            );
+        }
     }
 
-    let default_config = Config::default();
-    let toml_str = toml::to_string_pretty(&default_config)?;
-    fs::write(&config_path, toml_str)?;
+    fs::write(&config_path, format.render(&Config::default())?)?;
 
     println!(
         "{}",
-        format!("✓ Config created: {}", config_path.display()).green()
+        format!("✓ Config created: {}", config_path.display()).success()
     );
+    let toml_path = config_dir.join("config.toml");
+    if format != ConfigFormat::Toml && path.is_none() && toml_path.exists() {
+        println!(
+            "{}",
+            format!(
+                "⚠ {} takes precedence; remove it to use the new config",
+                toml_path.display()
+            )
+            .warning()
+        );
+    }
     println!("Edit it with: nano {}", config_path.display());
 
     Ok(())
 }
 
-fn list_tasks(config: &Config, args: &Args) {
-    println!("{}", "📋 Configured Tasks".bright_blue().bold());
-    println!("{}", "═".repeat(60).bright_blue());
+fn manage_config(config_path: &Path, action: &ConfigCommand) -> Result<()> {
+    match action {
+        ConfigCommand::Backups => {
+            let backups = backup::list(config_path)?;
+            if backups.is_empty() {
+                println!("{}", "No config backups yet.".dimmed());
+            }
+            for backup in backups {
+                println!("{}", backup.display());
+            }
+        }
+        ConfigCommand::Restore { backup } => {
+            let restored = backup::restore(config_path, backup.as_deref())?;
+            println!(
+                "{}",
+                format!(
+                    "✓ Restored {} from {}",
+                    config_path.display(),
+                    restored.display()
+                )
+                .success()
+            );
+        }
+    }
+    Ok(())
+}
 
-    for group in &config.groups {
-        if let Some(ref groups) = args.groups
-            && !groups.contains(&group.name)
+fn import_script(
+    config_path: &Path,
+    script_path: &Path,
+    group_name: Option<&str>,
+    force: bool,
+) -> Result<()> {
+    let config = Config::load(Some(&config_path.to_path_buf()))?;
+    let script = fs::read_to_string(script_path)
+        .with_context(|| format!("Failed to read {}", script_path.display()))?;
+    let mut tasks = import::parse_script(&script);
+    if tasks.is_empty() {
+        return Err(
+            TideError::Config(format!("No commands found in {}", script_path.display())).into(),
+        );
+    }
+
+    let default_name = group_name
+        .map(str::to_string)
+        .unwrap_or_else(|| import::group_name_for(script_path));
+    let name = if force {
+        default_name
+    } else {
+        Input::<String>::with_theme(&ColorfulTheme::default())
+            .with_prompt("Group name")
+            .default(default_name)
+            .interact_text()?
+    };
+    if config.groups.iter().any(|g| g.name == name) {
+        return Err(TideError::Config(format!("Group '{}' already exists", name)).into());
+    }
+
+    println!(
+        "{}",
+        format!(
+            "📥 Proposed tasks from {} ({})",
+            script_path.display(),
+            tasks.len()
+        )
+        .bright_blue()
+        .bold()
+    );
+    for task in &tasks {
+        let sudo = if task.sudo { "🔒 " } else { "" };
+        println!("  {}{}", sudo, task.name.bold());
+        println!(
+            "    {}",
+            task.command.join(" ").replace('\n', "\n    ").dimmed()
+        );
+    }
+    println!();
+
+    if !force {
+        let labels: Vec<&str> = tasks.iter().map(|t| t.name.as_str()).collect();
+        let selected = MultiSelect::with_theme(&ColorfulTheme::default())
+            .with_prompt("Tasks to import (space toggles, enter confirms)")
+            .items(&labels)
+            .defaults(&vec![true; labels.len()])
+            .interact()?;
+        tasks = tasks
+            .into_iter()
+            .enumerate()
+            .filter(|(index, _)| selected.contains(index))
+            .map(|(_, task)| task)
+            .collect();
+        if tasks.is_empty() {
+            println!("{}", "Nothing selected; config unchanged.".dimmed());
+            return Ok(());
+        }
+        if !Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt(format!(
+                "Append group '{}' with {} task(s) to {}?",
+                name,
+                tasks.len(),
+                config_path.display()
+            ))
+            .default(true)
+            .interact()?
         {
-            continue;
+            return Ok(());
+        }
+    }
+
+    let group = TaskGroup {
+        name,
+        icon: "📜".to_string(),
+        enabled: true,
+        description: format!("Imported from {}", script_path.display()),
+        parallel: false,
+        needs_disk_space: false,
+        heavy: false,
+        min_interval: None,
+        tasks,
+    };
+    if let Some(backup) = import::append_group(config_path, &group)? {
+        println!(
+            "{}",
+            format!("💾 Previous config backed up to {}", backup.display()).dimmed()
+        );
+    }
+    println!(
+        "{}",
+        format!(
+            "✓ Added group '{}' with {} task(s). Review it with: tide list --groups \"{}\"",
+            group.name,
+            group.tasks.len(),
+            group.name
+        )
+        .success()
+    );
+    Ok(())
+}
+
+async fn import_url(
+    config_path: &Path,
+    url: &str,
+    sha256: Option<&str>,
+    force: bool,
+) -> Result<()> {
+    if force && sha256.is_none() {
+        return Err(TideError::Config(
+            "--force imports from a URL need --sha256 to pin the content".to_string(),
+        )
+        .into());
+    }
+
+    let content = import::fetch(url).await?;
+    match sha256 {
+        Some(expected) => {
+            import::verify_sha256(&content, expected)?;
+            println!("{}", "✓ SHA-256 checksum verified".success());
         }
-        if let Some(ref skip) = args.skip_groups
-            && skip.contains(&group.name)
+        None => println!(
+            "{}",
+            format!(
+                "⚠️  No --sha256 given; the content is unverified (SHA-256: {})",
+                import::sha256_hex(&content)
+            )
+            .warning()
+        ),
+    }
+
+    let groups = import::parse_snippet(&content)?;
+    let config = Config::load(Some(&config_path.to_path_buf()))?;
+    for shared in &groups {
+        if let Some(existing) = config.groups.iter().find(|g| g.name == shared.group.name)
+            && let Some(task) = shared
+                .group
+                .tasks
+                .iter()
+                .find(|task| existing.tasks.iter().any(|t| t.name == task.name))
         {
-            continue;
+            return Err(TideError::Config(format!(
+                "Group '{}' already has a task named '{}'",
+                existing.name, task.name
+            ))
+            .into());
         }
+    }
 
+    println!("{}", format!("🌐 Content of {}", url).bright_blue().bold());
+    for line in content.lines() {
+        println!("  {}", line.dimmed());
+    }
+    println!();
+    for shared in &groups {
+        let action = if config.groups.iter().any(|g| g.name == shared.group.name) {
+            "add to existing group"
+        } else {
+            "new group"
+        };
+        println!(
+            "  {} {}",
+            shared.group.name.bold(),
+            format!("({}, {} task(s))", action, shared.group.tasks.len()).dimmed()
+        );
+        for task in &shared.group.tasks {
+            let sudo = if task.sudo { "🔒 " } else { "" };
+            let action = task
+                .kind
+                .map(|kind| format!("type = {}", kind.as_str()))
+                .unwrap_or_else(|| task.effective_command().join(" "));
+            println!(
+                "    {}{} {}",
+                sudo,
+                task.name,
+                format!("— {}", action).dimmed()
+            );
+        }
+    }
+    println!();
+
+    if !force
+        && !Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt(format!("Merge into {}?", config_path.display()))
+            .default(false)
+            .interact()?
+    {
+        return Ok(());
+    }
+
+    if let Some(backup) = import::merge_snippet(config_path, &groups, url)? {
+        println!(
+            "{}",
+            format!("💾 Previous config backed up to {}", backup.display()).dimmed()
+        );
+    }
+    let tasks: usize = groups.iter().map(|g| g.group.tasks.len()).sum();
+    println!(
+        "{}",
+        format!("✓ Merged {} group(s) with {} task(s)", groups.len(), tasks).success()
+    );
+    Ok(())
+}
+
+fn export_group(config_path: &Path, name: &str, file: Option<&Path>, force: bool) -> Result<()> {
+    let config = Config::load(Some(&config_path.to_path_buf()))?;
+    let mut text = fs::read_to_string(config_path)
+        .with_context(|| format!("Failed to read {}", config_path.display()))?;
+    let format = ConfigFormat::of(config_path);
+    if format != ConfigFormat::Toml {
+        text = toml::to_string(&format.parse(&text)?)?;
+    }
+    let document = import::export_group(&text, name).ok_or_else(|| {
+        TideError::Config(format!(
+            "Unknown group '{}'{}",
+            name,
+            suggest::hint(name, config.groups.iter().map(|g| g.name.as_str()))
+        ))
+    })?;
+
+    let Some(file) = file else {
+        print!("{}", document);
+        return Ok(());
+    };
+    if file.exists()
+        && !force
+        && !Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt(format!("Overwrite {}?", file.display()))
+            .default(false)
+            .interact()?
+    {
+        return Ok(());
+    }
+    fs::write(file, &document).with_context(|| format!("Failed to write {}", file.display()))?;
+    println!(
+        "{}",
+        format!("✓ Exported group '{}' to {}", name, file.display()).success()
+    );
+    Ok(())
+}
+
+fn import_group_file(config_path: &Path, file: &Path, force: bool) -> Result<()> {
+    let content =
+        fs::read_to_string(file).with_context(|| format!("Failed to read {}", file.display()))?;
+    let groups = import::parse_snippet(&content)?;
+    if groups.is_empty() {
+        return Err(TideError::Config(format!("No [[groups]] found in {}", file.display())).into());
+    }
+    let config = Config::load(Some(&config_path.to_path_buf()))?;
+    if let Some(shared) = groups
+        .iter()
+        .find(|shared| config.groups.iter().any(|g| g.name == shared.group.name))
+    {
+        return Err(TideError::Config(format!(
+            "Group '{}' already exists; rename it in {} or remove the existing group first",
+            shared.group.name,
+            file.display()
+        ))
+        .into());
+    }
+
+    println!(
+        "{}",
+        format!("📥 Groups in {}", file.display())
+            .bright_blue()
+            .bold()
+    );
+    for shared in &groups {
+        println!(
+            "  {} {}",
+            shared.group.name.bold(),
+            format!("({} task(s))", shared.group.tasks.len()).dimmed()
+        );
+        for task in &shared.group.tasks {
+            let sudo = if task.sudo { "🔒 " } else { "" };
+            println!("    {}{}", sudo, task.name);
+        }
+    }
+    println!();
+
+    if !force
+        && !Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt(format!("Append to {}?", config_path.display()))
+            .default(true)
+            .interact()?
+    {
+        return Ok(());
+    }
+
+    if let Some(backup) = import::import_groups(config_path, &groups)? {
+        println!(
+            "{}",
+            format!("💾 Previous config backed up to {}", backup.display()).dimmed()
+        );
+    }
+    let tasks: usize = groups.iter().map(|g| g.group.tasks.len()).sum();
+    println!(
+        "{}",
+        format!(
+            "✓ Imported {} group(s) with {} task(s)",
+            groups.len(),
+            tasks
+        )
+        .success()
+    );
+    Ok(())
+}
+
+fn display_catalog() {
+    println!("{}", "📚 Task catalog".bright_blue().bold());
+    for entry in catalog::CATALOG {
+        println!(
+            "  {:<20} {} {}",
+            entry.id.bold(),
+            entry.summary,
+            format!("[{}]", entry.group).dimmed()
+        );
+    }
+    println!(
+        "\n{}",
+        "Add one with: tide tasks add <id> [--group NAME]".dimmed()
+    );
+}
+
+fn add_catalog_task(config_path: &Path, id: &str, group: Option<&str>, force: bool) -> Result<()> {
+    let entry = catalog::find(id).ok_or_else(|| {
+        TideError::Config(format!(
+            "Unknown catalog task '{}' (see `tide tasks catalog`)",
+            id
+        ))
+    })?;
+    let task = entry.task()?;
+    let group = group.unwrap_or(entry.group);
+
+    let config = Config::load(Some(&config_path.to_path_buf()))?;
+    if config
+        .groups
+        .iter()
+        .filter(|g| g.name == group)
+        .flat_map(|g| &g.tasks)
+        .any(|t| t.name == task.name)
+    {
+        return Err(TideError::Config(format!(
+            "Group '{}' already has a task named '{}'",
+            group, task.name
+        ))
+        .into());
+    }
+
+    println!(
+        "{}",
+        format!("📚 {} → group '{}'", entry.id, group)
+            .bright_blue()
+            .bold()
+    );
+    for line in entry.toml.lines() {
+        println!("  {}", line.dimmed());
+    }
+    println!();
+
+    if !force
+        && !Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt(format!("Add '{}' to {}?", task.name, config_path.display()))
+            .default(true)
+            .interact()?
+    {
+        return Ok(());
+    }
+
+    let source = format!("catalog:{}", entry.id);
+    if let Some(backup) =
+        import::insert_task(config_path, group, &import::tag_source(entry.toml, &source))?
+    {
+        println!(
+            "{}",
+            format!("💾 Previous config backed up to {}", backup.display()).dimmed()
+        );
+    }
+    println!(
+        "{}",
+        format!("✓ Added '{}' to group '{}'", task.name, group).success()
+    );
+    Ok(())
+}
+
+/// Recipes found in the justfiles/Taskfiles that tasks refer to, marking the
+/// ones not exposed as tasks yet
+fn display_recipe_sources(sources: &[recipes::RecipeSource], groups: &[&TaskGroup]) {
+    for source in sources {
+        let location = source.dir.as_deref().unwrap_or("the current directory");
+        println!(
+            "\n{}",
+            format!("🧰 {} recipes in {}", source.runner.program(), location)
+                .bright_white()
+                .bold()
+        );
+        for recipe in &source.recipes {
+            let exposed = groups.iter().flat_map(|g| &g.tasks).find(|task| {
+                task.recipe() == Some((source.runner, recipe.as_str()))
+                    && task.working_dir == source.dir
+            });
+            match exposed {
+                Some(task) => println!(
+                    "  {} {} {}",
+                    "✓".success(),
+                    recipe,
+                    format!("→ {}", task.name).dimmed()
+                ),
+                None => println!(
+                    "  {} {} {}",
+                    "+".dimmed(),
+                    recipe,
+                    format!("(add with {} = \"{}\")", source.runner.key(), recipe).dimmed()
+                ),
+            }
+        }
+    }
+}
+
+fn list_tasks(config: &Config, args: &Args) {
+    println!("{}", "📋 Configured Tasks".bright_blue().bold());
+    println!("{}", "═".repeat(60).bright_blue());
+
+    let listed: Vec<&TaskGroup> = config
+        .groups
+        .iter()
+        .filter(|group| {
+            args.groups
+                .as_ref()
+                .is_none_or(|groups| groups.contains(&group.name))
+                && args
+                    .skip_groups
+                    .as_ref()
+                    .is_none_or(|skip| !skip.contains(&group.name))
+        })
+        .collect();
+    let recipe_sources = recipes::discover(listed.iter().flat_map(|group| &group.tasks));
+
+    for group in &listed {
         let enabled_icon = if group.enabled {
-            "✓".green()
+            "✓".success()
         } else {
-            "✗".red()
+            "✗".failure()
         };
         println!(
             "\n{} {} {}",
@@ -356,9 +1541,9 @@ fn list_tasks(config: &Config, args: &Args) {
 
         for task in &group.tasks {
             let enabled_icon = if task.enabled {
-                "✓".green()
+                "✓".success()
             } else {
-                "✗".red()
+                "✗".failure()
             };
             let required_icon = if task.required { "🔴" } else { "⚪" };
             let sudo_icon = if task.sudo { "🔐" } else { "  " };
@@ -371,6 +1556,17 @@ fn list_tasks(config: &Config, args: &Args) {
                 task.icon,
                 task.name.bright_white()
             );
+            if let Some((runner, recipe)) = task.recipe()
+                && let Some(source) = recipe_sources
+                    .iter()
+                    .find(|s| s.runner == runner && s.dir == task.working_dir)
+                && !source.contains(recipe)
+            {
+                print!(
+                    " {}",
+                    format!("⚠ {} recipe '{}' not found", runner.key(), recipe).warning()
+                );
+            }
 
             if args.verbose && !task.description.is_empty() {
                 println!();
@@ -380,11 +1576,19 @@ fn list_tasks(config: &Config, args: &Args) {
             }
 
             if args.verbose {
-                println!("      Command: {}", task.command.join(" ").dimmed());
+                match task.kind {
+                    Some(kind) => println!("      Type: {}", kind.as_str().dimmed()),
+                    None => println!(
+                        "      Command: {}",
+                        task.effective_command().join(" ").dimmed()
+                    ),
+                }
             }
         }
     }
 
+    display_recipe_sources(&recipe_sources, &listed);
+
     println!("\n{}", "Legend:".dimmed());
     println!("  {} Enabled/Disabled", "✓/✗".dimmed());
     println!("  {} Required task", "🔴".dimmed());
@@ -393,7 +1597,894 @@ fn list_tasks(config: &Config, args: &Args) {
     println!();
 }
 
-fn display_results(results: &[TaskResult], total_duration: Duration) {
+/// Let the user check the tasks to run, starting from the ones the config
+/// enables; checked tasks run even when the config disables them
+fn pick_tasks(config: &Config, args: &Args) -> Result<Vec<PlannedTask>> {
+    if !controls::terminal_attached() {
+        return Err(TideError::Config("--interactive needs a terminal".to_string()).into());
+    }
+    let candidates =
+        plan::collect_candidates(config, args.groups.as_deref(), args.skip_groups.as_deref());
+    let labels: Vec<String> = candidates
+        .iter()
+        .map(|(planned, _)| {
+            format!(
+                "{} › {}",
+                format_group_display(&planned.group, &planned.group_icon),
+                planned.task.name
+            )
+        })
+        .collect();
+    let defaults: Vec<bool> = candidates.iter().map(|(_, enabled)| *enabled).collect();
+    let selected = MultiSelect::with_theme(&ColorfulTheme::default())
+        .with_prompt("Tasks to run (space toggles, enter confirms)")
+        .items(&labels)
+        .defaults(&defaults)
+        .max_length(20)
+        .interact()?;
+    Ok(candidates
+        .into_iter()
+        .enumerate()
+        .filter(|(index, _)| selected.contains(index))
+        .map(|(_, (mut planned, _))| {
+            planned.task.enabled = true;
+            planned
+        })
+        .collect())
+}
+
+/// Warn about tasks matching dangerous command patterns and drop them from
+/// the run unless the user explicitly confirms.
+fn review_dangerous_tasks(tasks: Vec<PlannedTask>, args: &Args) -> Result<Vec<PlannedTask>> {
+    let flagged: Vec<(usize, Vec<(LintKind, String)>)> = tasks
+        .iter()
+        .enumerate()
+        .filter(|(_, planned)| !planned.task.allow_dangerous)
+        .map(|(idx, planned)| (idx, lint::lint_task(&planned.task)))
+        .filter(|(_, findings)| !findings.is_empty())
+        .collect();
+
+    if flagged.is_empty() {
+        return Ok(tasks);
+    }
+
+    eprintln!(
+        "\n{}",
+        "⚠️  Potentially dangerous commands detected:"
+            .warning()
+            .bold()
+    );
+    for (idx, findings) in &flagged {
+        let planned = &tasks[*idx];
+        eprintln!(
+            "  {} {} {}",
+            "⚠".warning(),
+            planned.task.name.bright_white(),
+            format!(
+                "[{}]",
+                format_group_display(&planned.group, &planned.group_icon)
+            )
+            .dimmed()
+        );
+        for (kind, detail) in findings {
+            eprintln!("      {}: {}", kind.label(), detail.dimmed());
+        }
+    }
+    eprintln!(
+        "{}",
+        "   Set 'allow_dangerous = true' on a task to accept its command.".dimmed()
+    );
+
+    let confirmed = !args.force
+        && !args.quiet
+        && Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt(format!("Run {} flagged task(s) anyway?", flagged.len()))
+            .default(false)
+            .interact()?;
+
+    if confirmed {
+        return Ok(tasks);
+    }
+
+    eprintln!(
+        "{}",
+        format!("Skipping {} flagged task(s)", flagged.len()).warning()
+    );
+    Ok(tasks
+        .into_iter()
+        .enumerate()
+        .filter(|(idx, _)| !flagged.iter().any(|(flagged_idx, _)| flagged_idx == idx))
+        .map(|(_, task)| task)
+        .collect())
+}
+
+/// Show remotely sourced tasks that are new or whose command changed since
+/// they were last approved, and run only the ones the user approves again
+fn review_remote_tasks(tasks: Vec<PlannedTask>, args: &Args) -> Result<Vec<PlannedTask>> {
+    if !tasks
+        .iter()
+        .any(|planned| provenance::is_remote(&planned.task))
+    {
+        return Ok(tasks);
+    }
+    let mut approvals = Approvals::open_default()?;
+    let pending: Vec<usize> = tasks
+        .iter()
+        .enumerate()
+        .filter(|(_, planned)| {
+            provenance::is_remote(&planned.task)
+                && !approvals.is_approved(&planned.group, &planned.task)
+        })
+        .map(|(idx, _)| idx)
+        .collect();
+    if pending.is_empty() {
+        return Ok(tasks);
+    }
+
+    eprintln!(
+        "\n{}",
+        "🔏 Imported tasks that are new or changed since you last approved them:"
+            .warning()
+            .bold()
+    );
+    let mut rejected = Vec::new();
+    for &idx in &pending {
+        let planned = &tasks[idx];
+        let task = &planned.task;
+        eprintln!(
+            "  {} {}",
+            task.name.bright_white(),
+            format!(
+                "[{}] from {}",
+                format_group_display(&planned.group, &planned.group_icon),
+                task.source.as_deref().unwrap_or_default()
+            )
+            .dimmed()
+        );
+        let action = task
+            .kind
+            .map(|kind| format!("type = \"{}\"", kind.as_str()))
+            .unwrap_or_else(|| task.effective_command().join(" "));
+        let sudo = if task.sudo { "sudo " } else { "" };
+        eprintln!("      $ {}{}", sudo, action);
+
+        let approved = !args.force
+            && !args.quiet
+            && Confirm::with_theme(&ColorfulTheme::default())
+                .with_prompt(format!("Approve '{}'?", task.name))
+                .default(false)
+                .interact()?;
+        if approved {
+            approvals.approve(&planned.group, task);
+        } else {
+            rejected.push(idx);
+        }
+    }
+    approvals.save()?;
+
+    if !rejected.is_empty() {
+        eprintln!(
+            "{}",
+            format!(
+                "Skipping {} unapproved imported task(s); approve them in an interactive run",
+                rejected.len()
+            )
+            .warning()
+        );
+    }
+    Ok(tasks
+        .into_iter()
+        .enumerate()
+        .filter(|(idx, _)| !rejected.contains(idx))
+        .map(|(_, task)| task)
+        .collect())
+}
+
+/// Warn when `/` is low on free space and hold back groups flagged
+/// `needs_disk_space`, since running out mid-download is hard to recover from.
+fn disk_space_preflight(
+    tasks: Vec<PlannedTask>,
+    settings: &Settings,
+    args: &Args,
+) -> Result<Vec<PlannedTask>> {
+    if settings.min_free_disk_gb == 0 {
+        return Ok(tasks);
+    }
+    let Some(usage) = disk::root_usage() else {
+        return Ok(tasks);
+    };
+    let free_gb = usage.available_gb();
+    if free_gb >= settings.min_free_disk_gb as f64 {
+        return Ok(tasks);
+    }
+
+    eprintln!(
+        "\n{}",
+        format!(
+            "💾 Only {:.1} GB free on / (minimum {} GB)",
+            free_gb, settings.min_free_disk_gb
+        )
+        .warning()
+        .bold()
+    );
+
+    let affected = tasks
+        .iter()
+        .filter(|planned| planned.needs_disk_space)
+        .count();
+    if affected == 0 {
+        return Ok(tasks);
+    }
+
+    let skip = args.force
+        || args.quiet
+        || Select::with_theme(&ColorfulTheme::default())
+            .with_prompt(format!("{} task(s) need free disk space", affected))
+            .items(["Skip affected groups", "Continue anyway"])
+            .default(0)
+            .interact()?
+            == 0;
+
+    if !skip {
+        return Ok(tasks);
+    }
+
+    eprintln!(
+        "{}",
+        format!("Skipping {} task(s) that need free disk space", affected).warning()
+    );
+    Ok(tasks
+        .into_iter()
+        .filter(|planned| !planned.needs_disk_space)
+        .collect())
+}
+
+/// Check thermal pressure and load before heavy groups start, deferring them
+/// when `defer_when_hot` is set instead of piling onto an already busy Mac.
+fn thermal_preflight(
+    tasks: Vec<PlannedTask>,
+    settings: &Settings,
+    executor: &TaskExecutor,
+) -> Vec<PlannedTask> {
+    if !tasks.iter().any(|planned| planned.heavy) {
+        return tasks;
+    }
+    let Some(pressure) = thermal::current_pressure() else {
+        return tasks;
+    };
+
+    eprintln!(
+        "\n{}",
+        format!("🌡️  System is busy: {}", pressure).warning().bold()
+    );
+
+    if !settings.defer_when_hot {
+        eprintln!(
+            "{}",
+            "   Heavy groups will run anyway (set defer_when_hot = true to defer them).".warning()
+        );
+        return tasks;
+    }
+
+    let mut deferred: Vec<String> = Vec::new();
+    for planned in tasks.iter().filter(|planned| planned.heavy) {
+        if !deferred.contains(&planned.group) {
+            deferred.push(planned.group.clone());
+        }
+    }
+    eprintln!(
+        "{}",
+        format!("Deferring heavy groups: {}", deferred.join(", ")).warning()
+    );
+    let _ = executor
+        .notifier
+        .notify_deferred(&pressure.to_string(), &deferred);
+
+    tasks.into_iter().filter(|planned| !planned.heavy).collect()
+}
+
+/// Verify connectivity before network tasks run, pausing on captive portals
+/// or missing connectivity instead of letting every download fail.
+async fn network_preflight(
+    tasks: Vec<PlannedTask>,
+    settings: &Settings,
+    args: &Args,
+    executor: &TaskExecutor,
+) -> Result<Vec<PlannedTask>> {
+    let network_tasks = tasks
+        .iter()
+        .filter(|planned| planned.task.requires_network())
+        .count();
+    if network_tasks == 0 {
+        return Ok(tasks);
+    }
+
+    let url = settings
+        .connectivity_check_url
+        .as_deref()
+        .unwrap_or(network::DEFAULT_CHECK_URL);
+
+    loop {
+        let problem = match network::check(url).await {
+            Connectivity::Online => return Ok(tasks),
+            Connectivity::CaptivePortal => {
+                "Network access is intercepted by a captive portal (Wi-Fi login page)".to_string()
+            }
+            Connectivity::Offline(reason) => format!("No internet connection ({})", reason),
+        };
+
+        eprintln!("\n{}", format!("🌐 {}", problem).warning().bold());
+        let _ = executor.notifier.notify_network_problem(&problem);
+
+        let choice = if args.force || args.quiet {
+            1
+        } else {
+            Select::with_theme(&ColorfulTheme::default())
+                .with_prompt(format!("{} task(s) need the network", network_tasks))
+                .items(["Retry check", "Skip network tasks", "Continue anyway"])
+                .default(0)
+                .interact()?
+        };
+
+        match choice {
+            0 => continue,
+            1 => {
+                eprintln!(
+                    "{}",
+                    format!("Skipping {} network task(s)", network_tasks).warning()
+                );
+                return Ok(tasks
+                    .into_iter()
+                    .filter(|planned| !planned.task.requires_network())
+                    .collect());
+            }
+            _ => return Ok(tasks),
+        }
+    }
+}
+
+/// Verify the App Store sign-in before `mas` tasks run, so they are skipped
+/// with a clear reason instead of failing with a cryptic `mas` error.
+async fn app_store_preflight(
+    tasks: Vec<PlannedTask>,
+    settings: &Settings,
+    args: &Args,
+    executor: &TaskExecutor,
+) -> Result<Vec<PlannedTask>> {
+    let mas_tasks = tasks
+        .iter()
+        .filter(|planned| appstore::is_mas_task(&planned.task))
+        .count();
+    if mas_tasks == 0 || !keychain::command_exists("mas") {
+        return Ok(tasks);
+    }
+
+    loop {
+        if appstore::check().await != SignIn::SignedOut {
+            return Ok(tasks);
+        }
+
+        eprintln!(
+            "\n{}",
+            "🛍️  Not signed in to the App Store".warning().bold()
+        );
+
+        let choice = match settings.app_store_sign_in {
+            SignInPolicy::Prompt if !args.force && !args.quiet => {
+                Select::with_theme(&ColorfulTheme::default())
+                    .with_prompt(format!("{} task(s) use mas", mas_tasks))
+                    .items([
+                        "Open the App Store to sign in, then check again",
+                        "Skip App Store tasks",
+                        "Continue anyway",
+                    ])
+                    .default(0)
+                    .interact()?
+            }
+            SignInPolicy::Notify => {
+                let _ = executor.notifier.notify_app_store_sign_in(mas_tasks);
+                1
+            }
+            _ => 1,
+        };
+
+        match choice {
+            0 => {
+                if let Err(err) = appstore::open_app_store() {
+                    eprintln!(
+                        "{}",
+                        format!("⚠️  Failed to open the App Store: {}", err).warning()
+                    );
+                }
+                Confirm::with_theme(&ColorfulTheme::default())
+                    .with_prompt("Signed in? Press enter to check again")
+                    .default(true)
+                    .interact()?;
+            }
+            1 => {
+                eprintln!(
+                    "{}",
+                    format!(
+                        "Skipping {} App Store task(s): no Apple ID is signed in",
+                        mas_tasks
+                    )
+                    .warning()
+                );
+                return Ok(tasks
+                    .into_iter()
+                    .filter(|planned| !appstore::is_mas_task(&planned.task))
+                    .collect());
+            }
+            _ => return Ok(tasks),
+        }
+    }
+}
+
+/// Answer to the confirmation prompt
+enum RunChoice {
+    All,
+    WithoutSudo,
+    Cancel,
+}
+
+/// Ask whether to run; when tasks need sudo, also offer to leave them out
+fn confirm_run(sudo_count: usize) -> Result<RunChoice> {
+    if sudo_count == 0 {
+        let proceed = Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt("Continue?")
+            .default(true)
+            .interact()?;
+        return Ok(if proceed {
+            RunChoice::All
+        } else {
+            RunChoice::Cancel
+        });
+    }
+
+    let choices = [
+        "Run all tasks".to_string(),
+        format!("Run without the {} sudo task(s)", sudo_count),
+        "Cancel".to_string(),
+    ];
+    let choice = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("Continue?")
+        .items(&choices)
+        .default(0)
+        .interact_opt()?;
+    Ok(match choice {
+        Some(0) => RunChoice::All,
+        Some(1) => RunChoice::WithoutSudo,
+        _ => RunChoice::Cancel,
+    })
+}
+
+/// Hold the run lock, or fail when another run holds it and `--wait` was
+/// not given
+fn acquire_run_lock(args: &Args) -> Result<lock::RunLock> {
+    let path = lock::default_path()?;
+    if let Some(lock) = lock::RunLock::try_acquire(&path)? {
+        return Ok(lock);
+    }
+    let holder = lock::holder(&path)
+        .map(|pid| format!(" (pid {})", pid))
+        .unwrap_or_default();
+    if !args.run.wait {
+        return Err(TideError::AlreadyRunning(format!(
+            "another tide run{} is in progress; pass --wait to start once it finishes",
+            holder
+        ))
+        .into());
+    }
+    eprintln!(
+        "{}",
+        format!("⏳ Waiting for the tide run{} to finish…", holder).dimmed()
+    );
+    tokio::task::block_in_place(|| lock::RunLock::acquire(&path))
+}
+
+/// Report commands still running from a previous tide run that crashed or
+/// was killed, offering to terminate them
+fn check_leftover_processes(args: &Args) -> Result<()> {
+    for leftovers in orphans::leftovers() {
+        eprintln!(
+            "{}",
+            format!(
+                "⚠️  A previous run (pid {}) ended abnormally and left processes running:",
+                leftovers.tide_pid
+            )
+            .warning()
+        );
+        for child in &leftovers.children {
+            eprintln!("   {} (process group {})", child.program, child.pid);
+        }
+        if args.force || args.quiet {
+            continue;
+        }
+        if Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt("Terminate them?")
+            .default(false)
+            .interact()?
+        {
+            leftovers.terminate();
+        }
+        leftovers.dismiss()?;
+    }
+    Ok(())
+}
+
+/// [`plan::expand_matrices`], printing a warning for each skipped task
+fn expand_matrices(tasks: Vec<PlannedTask>) -> Vec<PlannedTask> {
+    let mut warnings = Vec::new();
+    let tasks = plan::expand_matrices(tasks, &mut warnings);
+    for warning in warnings {
+        eprintln!("{}", warning.warning());
+    }
+    tasks
+}
+
+/// Fill in task parameters from `--param`, a prompt or their defaults.
+/// Tasks left without a value are skipped; dry runs keep the placeholders.
+fn resolve_params(
+    tasks: Vec<PlannedTask>,
+    config: &Config,
+    args: &Args,
+) -> Result<Vec<PlannedTask>> {
+    let overrides = params::parse_overrides(&args.run.params)?;
+    let declared: Vec<&str> = config
+        .groups
+        .iter()
+        .flat_map(|group| &group.tasks)
+        .flat_map(|task| task.params.iter().map(|param| param.name.as_str()))
+        .collect();
+    for name in overrides.keys() {
+        if !declared.contains(&name.as_str()) {
+            return Err(TideError::Config(format!(
+                "Unknown param '{}'.{}",
+                name,
+                suggest::hint(name, declared.iter().copied())
+            ))
+            .into());
+        }
+    }
+
+    let ask = !args.force && !args.quiet && !args.run.dry_run;
+    let mut resolved = Vec::with_capacity(tasks.len());
+    'tasks: for mut planned in tasks {
+        let mut values = HashMap::new();
+        for param in &planned.task.params {
+            let default = param.default_value().and_then(Result::ok);
+            let value = match overrides.get(&param.name) {
+                Some(raw) => Some(param.kind.check(raw).map_err(|err| {
+                    TideError::Config(format!("--param {}: {}", param.name, err))
+                })?),
+                None if ask => {
+                    let theme = ColorfulTheme::default();
+                    let mut input = Input::<String>::with_theme(&theme)
+                        .with_prompt(format!("[{}] {}", planned.task.name, param.prompt_text()))
+                        .validate_with(|raw: &String| param.kind.check(raw).map(|_| ()));
+                    if let Some(default) = &default {
+                        input = input.default(default.clone());
+                    }
+                    let raw = input.interact_text()?;
+                    Some(param.kind.check(&raw).map_err(anyhow::Error::msg)?)
+                }
+                None => default,
+            };
+            match value {
+                Some(value) => {
+                    values.insert(param.name.clone(), value);
+                }
+                None if args.run.dry_run => {}
+                None => {
+                    eprintln!(
+                        "{}",
+                        format!(
+                            "Skipping '{}': no value for param '{}' (pass --param {}=<value>)",
+                            planned.task.name, param.name, param.name
+                        )
+                        .warning()
+                    );
+                    continue 'tasks;
+                }
+            }
+        }
+        params::apply(&mut planned.task, &values);
+        resolved.push(planned);
+    }
+    Ok(resolved)
+}
+
+/// Drop the tasks that need sudo, for `--skip-sudo` or the opt-out choice
+fn without_sudo_tasks(tasks: Vec<PlannedTask>, args: &Args) -> Vec<PlannedTask> {
+    let (sudo, rest): (Vec<_>, Vec<_>) = tasks
+        .into_iter()
+        .partition(|planned| planned.task.needs_sudo());
+    if !sudo.is_empty() && !args.quiet {
+        println!(
+            "{}",
+            format!("🔐 Skipping {} sudo task(s) for this run", sudo.len()).warning()
+        );
+    }
+    rest
+}
+
+/// Print the grouped run plan shown before the confirmation prompt
+fn display_plan(forecasts: &[TaskForecast]) {
+    let runnable: Vec<&TaskForecast> = forecasts.iter().filter(|f| f.skip.is_none()).collect();
+    let skipping = forecasts.len() - runnable.len();
+    let sudo_tasks: Vec<&str> = runnable
+        .iter()
+        .filter(|f| f.planned.task.needs_sudo())
+        .map(|f| f.planned.task.name.as_str())
+        .collect();
+    let estimated: Duration = runnable.iter().filter_map(|f| f.estimate).sum();
+    let unknown = runnable.iter().filter(|f| f.estimate.is_none()).count();
+
+    println!("\n{}", "📦 Run plan".bright_blue().bold());
+    println!("{}", "─".repeat(60).dimmed());
+
+    let mut current_group: Option<&str> = None;
+    for forecast in forecasts {
+        let planned = forecast.planned;
+        if current_group != Some(planned.group.as_str()) {
+            current_group = Some(planned.group.as_str());
+            println!(
+                "  {}",
+                format_group_display(&planned.group, &planned.group_icon)
+                    .bright_white()
+                    .bold()
+            );
+        }
+
+        let sudo_icon = if planned.task.needs_sudo() {
+            "🔐"
+        } else {
+            "  "
+        };
+        let label = format_group_display(&planned.task.name, &planned.task.icon);
+        match &forecast.skip {
+            Some(reason) => println!(
+                "    {} {} {}",
+                sudo_icon,
+                label.dimmed(),
+                format!("→ will skip ({})", reason).warning()
+            ),
+            None => {
+                let estimate = forecast
+                    .estimate
+                    .map(|d| format!("~{}", ui::format_duration(d)))
+                    .unwrap_or_else(|| "no history".to_string());
+                let label = if planned.task.needs_sudo() {
+                    label.bright_magenta()
+                } else {
+                    label.normal()
+                };
+                println!("    {} {} {}", sudo_icon, label, estimate.dimmed());
+            }
+        }
+    }
+
+    println!("{}", "─".repeat(60).dimmed());
+    let mut summary = format!("  {} task(s) will run", runnable.len());
+    if skipping > 0 {
+        summary.push_str(&format!(", {} predicted to skip", skipping));
+    }
+    println!("{}", summary.bright_blue());
+    if !sudo_tasks.is_empty() {
+        println!(
+            "  {}",
+            format!(
+                "🔐 {} task(s) require sudo: {}",
+                sudo_tasks.len(),
+                sudo_tasks.join(", ")
+            )
+            .bright_magenta()
+        );
+    }
+    if estimated > Duration::ZERO {
+        let mut line = format!(
+            "  ⏱️  Estimated duration: ~{}",
+            ui::format_duration(estimated)
+        );
+        if unknown > 0 {
+            line.push_str(&format!(" (+{} task(s) without history)", unknown));
+        }
+        println!("{}", line.dimmed());
+    }
+}
+
+/// Report what a loaded (and thus valid) config contains and the risky
+/// commands `tide run` would ask about
+fn validate_config<'a>(
+    config: &Config,
+    config_path: &Path,
+    group_filters: impl IntoIterator<Item = &'a String>,
+) -> Result<()> {
+    for notice in &config.notices {
+        println!("{}", format!("🔒 {}", notice).warning());
+    }
+    let environment =
+        validate::check_environment(config, group_filters, keychain::command_exists, |path| {
+            path.is_dir()
+        });
+    for warning in &environment.warnings {
+        println!("{} {}", "⚠".warning(), warning.dimmed());
+    }
+    for error in &environment.errors {
+        println!("{} {}", "✗".failure(), error);
+    }
+    if !environment.errors.is_empty() {
+        return Err(TideError::Config(format!(
+            "{} has {} problem(s)",
+            config_path.display(),
+            environment.errors.len()
+        ))
+        .into());
+    }
+    let mut flagged = 0;
+    for group in &config.groups {
+        for task in group.tasks.iter().filter(|task| !task.allow_dangerous) {
+            for (kind, detail) in lint::lint_task(task) {
+                flagged += 1;
+                println!(
+                    "{} {} {}: {}",
+                    "⚠".warning(),
+                    format!("{} › {}", group.name, task.name).bright_white(),
+                    kind.label(),
+                    detail.dimmed()
+                );
+            }
+        }
+    }
+    let tasks: usize = config.groups.iter().map(|group| group.tasks.len()).sum();
+    println!(
+        "{}",
+        format!(
+            "✓ {} is valid ({} groups, {} tasks{})",
+            config_path.display(),
+            config.groups.len(),
+            tasks,
+            if flagged > 0 {
+                format!(", {} flagged command(s)", flagged)
+            } else {
+                String::new()
+            }
+        )
+        .success()
+    );
+    Ok(())
+}
+
+fn display_history(runs: &[RunRecord]) {
+    println!("{}", "🕘 Recent Runs".bright_blue().bold());
+    println!("{}", "═".repeat(60).bright_blue());
+    if runs.is_empty() {
+        println!("{}", "No runs recorded yet.".dimmed());
+        return;
+    }
+    for run in runs.iter().rev() {
+        let count = |status| run.tasks.iter().filter(|t| t.status == status).count();
+        let failed = count(TaskStatus::Failed);
+        let icon = if failed > 0 {
+            "✗".failure()
+        } else {
+            "✓".success()
+        };
+        println!(
+            "  {} {}  {} ok  {} failed  {} skipped  {}",
+            icon,
+            run.started_at
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+                .bright_white(),
+            count(TaskStatus::Success),
+            failed,
+            count(TaskStatus::Skipped),
+            ui::format_duration(Duration::from_secs_f64(run.duration_secs)).dimmed()
+        );
+        for task in run.tasks.iter().filter(|t| t.status == TaskStatus::Failed) {
+            println!(
+                "      {} {}",
+                format!("✗ {} › {}", task.group, task.name).failure(),
+                task.reason.as_deref().unwrap_or_default().dimmed()
+            );
+        }
+    }
+}
+
+/// Print the `tide why` findings for each matching task
+fn display_explanations(explanations: &[why::Explanation]) {
+    for explanation in explanations {
+        println!(
+            "\n{} {}",
+            explanation.task.bright_white().bold(),
+            format!("[{}]", explanation.group).dimmed()
+        );
+        if explanation.blockers.is_empty() {
+            println!("  {} Nothing keeps it out of the next run.", "✓".success());
+        }
+        for blocker in &explanation.blockers {
+            println!("  {} {}", "✗".failure(), blocker);
+        }
+        for caveat in &explanation.caveats {
+            println!("  {} {}", "•".warning(), caveat);
+        }
+        println!("  {} {}", "↺".dimmed(), explanation.last_run);
+    }
+}
+
+/// Print the execution batches computed by the scheduler
+fn display_batches(batches: &[Batch], settings: &Settings) {
+    println!("{}", "🗺️  Execution Plan".bright_blue().bold());
+    println!("{}", "═".repeat(60).bright_blue());
+
+    if batches.is_empty() {
+        println!("{}", "No tasks to run!".warning());
+        return;
+    }
+
+    for (idx, batch) in batches.iter().enumerate() {
+        let mode = match batch.mode {
+            BatchMode::Sequential => "sequential, one at a time".to_string(),
+            BatchMode::Parallel { concurrency } => {
+                format!("parallel, up to {} concurrently", concurrency)
+            }
+            BatchMode::Graph { concurrency } => {
+                format!("dependency graph, up to {} concurrently", concurrency)
+            }
+        };
+        let dependencies = match batch.mode {
+            BatchMode::Graph { .. } => plan::dependencies(&batch.tasks, settings),
+            _ => vec![Vec::new(); batch.tasks.len()],
+        };
+        println!(
+            "\n{} {}",
+            format!("Batch {}", idx + 1).bright_white().bold(),
+            format!("({} task(s), {})", batch.tasks.len(), mode).dimmed()
+        );
+
+        for (position, planned) in batch.tasks.iter().enumerate() {
+            let marker = match batch.mode {
+                BatchMode::Sequential => format!("{:>3}.", position + 1),
+                BatchMode::Parallel { .. } | BatchMode::Graph { .. } => "   •".to_string(),
+            };
+            let after: Vec<&str> = dependencies[position]
+                .iter()
+                .map(|dep| batch.tasks[dep.task].task.name.as_str())
+                .collect();
+            let after = if after.is_empty() {
+                String::new()
+            } else {
+                format!(" after {}", after.join(", "))
+            };
+            let sudo_icon = if planned.task.needs_sudo() {
+                "🔐"
+            } else {
+                "  "
+            };
+            println!(
+                "  {} {} {} {}",
+                marker.dimmed(),
+                sudo_icon,
+                format_group_display(&planned.task.name, &planned.task.icon).bright_white(),
+                format!(
+                    "[{}]{}",
+                    format_group_display(&planned.group, &planned.group_icon),
+                    after
+                )
+                .dimmed()
+            );
+        }
+    }
+    println!();
+}
+
+fn display_results(
+    results: &[TaskResult],
+    total_duration: Duration,
+    config: &Config,
+    log_path: Option<&Path>,
+) {
     let success = results
         .iter()
         .filter(|r| r.status == TaskStatus::Success)
@@ -406,42 +2497,125 @@ fn display_results(results: &[TaskResult], total_duration: Duration) {
         .iter()
         .filter(|r| r.status == TaskStatus::Skipped)
         .count();
+    let cancelled = results
+        .iter()
+        .filter(|r| r.status == TaskStatus::Cancelled)
+        .count();
 
     println!("\n{}", "📊 Summary".bright_blue().bold());
     println!("{}", "─".repeat(60).dimmed());
 
     println!(
-        "  {} Success  {} Failed  {} Skipped  ⏱️  Total: {}",
-        format!("✓ {}", success).green(),
-        format!("✗ {}", failed).red(),
-        format!("○ {}", skipped).yellow(),
-        format_duration(total_duration).bright_white()
+        "  {} Success  {} Failed  {} Skipped{}  ⏱️  Total: {}",
+        format!("✓ {}", success).success(),
+        format!("✗ {}", failed).failure(),
+        format!("○ {}", skipped).warning(),
+        if cancelled > 0 {
+            format!("  {} Cancelled", format!("⊘ {}", cancelled).warning())
+        } else {
+            String::new()
+        },
+        ui::format_duration(total_duration).bright_white()
     );
 
     if let Some(longest_task) = results.iter().max_by_key(|r| r.duration) {
         let group_label = format_group_display(&longest_task.group, &longest_task.group_icon);
         println!(
             "  Longest task: {} [{} in {}]",
-            format_duration(longest_task.duration).bright_white(),
+            ui::format_duration(longest_task.duration).bright_white(),
             longest_task.name.bright_white(),
             group_label.dimmed()
         );
     }
 
     if failed > 0 {
-        println!("\n{}", "Failed tasks:".red().bold());
+        println!("\n{}", "Failed tasks:".failure().bold());
+
+        let mut by_class: BTreeMap<FailureClass, Vec<&TaskResult>> = BTreeMap::new();
         for result in results.iter().filter(|r| r.status == TaskStatus::Failed) {
-            let group_label = format_group_display(&result.group, &result.group_icon);
-            println!("  ✗ {} - {}", result.name.red(), group_label.dimmed());
-            if let Some(output) = &result.output
-                && !output.is_empty()
-            {
-                println!("    {}", output.dimmed());
+            by_class
+                .entry(result.failure.unwrap_or(FailureClass::NonZeroExit))
+                .or_default()
+                .push(result);
+        }
+
+        for (class, class_results) in by_class {
+            println!(
+                "\n  {} {} {}",
+                class.icon(),
+                class.label().bright_white().bold(),
+                format!("— {}", class.hint(class_results.len())).dimmed()
+            );
+            for result in class_results {
+                let group_label = format_group_display(&result.group, &result.group_icon);
+                let exit_label = result
+                    .exit_code
+                    .map(|code| format!(" (exit {})", code))
+                    .unwrap_or_default();
+                println!(
+                    "    ✗ {}{} - {}",
+                    result.name.failure(),
+                    exit_label.dimmed(),
+                    group_label.dimmed()
+                );
+                if let Some(output) = &result.output
+                    && !output.is_empty()
+                {
+                    println!("      {}", output.trim().dimmed());
+                }
+                if let Some(path) = log_path
+                    && let Some(line) = result.log_line
+                {
+                    println!(
+                        "      {} {}",
+                        "Log:".dimmed(),
+                        hyperlink::path_line(path, line)
+                    );
+                }
+                if let Some(url) = config
+                    .groups
+                    .iter()
+                    .filter(|g| g.name == result.group)
+                    .flat_map(|g| &g.tasks)
+                    .find(|t| t.name == result.name)
+                    .and_then(|t| t.help_url.as_deref())
+                {
+                    println!("      {} {}", "Help:".dimmed(), hyperlink::link(url, url));
+                }
             }
         }
     }
 }
 
+/// Print the reports of successful built-in tasks, e.g. reclaimed disk space
+fn display_reports(results: &[TaskResult], config: &Config) {
+    let reports: Vec<(&TaskResult, &str)> = results
+        .iter()
+        .filter(|r| r.status == TaskStatus::Success)
+        .filter_map(|r| {
+            let task = config
+                .groups
+                .iter()
+                .filter(|g| g.name == r.group)
+                .flat_map(|g| &g.tasks)
+                .find(|t| t.name == r.name)?;
+            let output = r.output.as_deref()?.trim();
+            (task.kind?.reports_in_summary() && !output.is_empty()).then_some((r, output))
+        })
+        .collect();
+    if reports.is_empty() {
+        return;
+    }
+
+    println!("\n{}", "Reports:".bright_white().bold());
+    for (result, output) in reports {
+        println!("  {}", result.name.bright_white());
+        for line in output.lines() {
+            println!("    {}", line.dimmed());
+        }
+    }
+}
+
 fn setup_environment() {
     if Path::new("/opt/homebrew/bin/brew").exists() {
         prepend_to_path("/opt/homebrew/bin");
@@ -475,15 +2649,6 @@ fn prepend_to_path<P: AsRef<Path>>(dir: P) {
     }
 }
 
-fn format_duration(d: Duration) -> String {
-    let secs = d.as_secs();
-    if secs < 60 {
-        format!("{}s", secs)
-    } else {
-        format!("{}m {}s", secs / 60, secs % 60)
-    }
-}
-
 fn format_group_display(name: &str, icon: &str) -> String {
     let icon = icon.trim();
     if icon.is_empty() {
@@ -0,0 +1,160 @@
+use anyhow::Result;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use toml::Table;
+
+use crate::config::Config;
+use crate::error::TideError;
+use crate::overlay;
+
+/// System-wide baseline installed by IT (e.g. through an MDM profile)
+pub const MANAGED_CONFIG_PATH: &str = "/Library/Application Support/tide/managed.toml";
+
+pub fn path() -> PathBuf {
+    PathBuf::from(MANAGED_CONFIG_PATH)
+}
+
+/// Merge the user config over the managed one.
+///
+/// The user config is applied as an [`overlay`]. The managed file may
+/// protect parts of itself:
+///
+/// - `[managed] locked_settings = ["..."]` keeps those settings at the
+///   managed value
+/// - `locked = true` on a group or task ignores the user's changes to it;
+///   groups holding locked tasks cannot be disabled
+///
+/// A notice is added for every change that was ignored.
+pub fn merge(managed: &str, user: Table, notices: &mut Vec<String>) -> Result<Table> {
+    let mut merged: Table = toml::from_str(managed)
+        .map_err(|err| TideError::Config(format!("Failed to parse managed config: {}", err)))?;
+    let locked_settings: Vec<String> = merged
+        .remove("managed")
+        .and_then(|section| section.get("locked_settings").cloned())
+        .map(|locked| locked.try_into())
+        .transpose()
+        .map_err(|_| {
+            TideError::Config("managed.locked_settings must be a list of setting names".to_string())
+        })?
+        .unwrap_or_default();
+    overlay::apply(&mut merged, user, &locked_settings, notices);
+    Ok(merged)
+}
+
+/// Fail with a clear message when tide cannot change `config_path`: it is
+/// the managed config itself or a file the user may not write
+pub fn ensure_writable(config_path: &Path) -> Result<()> {
+    if config_path == path() {
+        return Err(TideError::Config(format!(
+            "{} is the managed configuration installed by your organization and cannot be \
+             changed by tide.\nAdd your own tasks to {} instead.",
+            config_path.display(),
+            Config::default_config_path()?.display()
+        ))
+        .into());
+    }
+    match fs::OpenOptions::new().append(true).open(config_path) {
+        Err(err) if err.kind() == ErrorKind::PermissionDenied => Err(TideError::Config(format!(
+            "{} is read-only; it may be managed by your organization.\n\
+             Ask your administrator to change it, or pass --config with a file you can edit.",
+            config_path.display()
+        ))
+        .into()),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use toml::Value;
+
+    #[test]
+    fn test_merge_user_config_over_managed() {
+        let managed = r#"
+[managed]
+locked_settings = ["desktop_notifications"]
+
+[settings]
+desktop_notifications = true
+parallel_limit = 2
+
+[[groups]]
+name = "Security"
+icon = "🛡️"
+
+[[groups.tasks]]
+name = "Antivirus Definitions"
+command = ["av", "update"]
+locked = true
+
+[[groups.tasks]]
+name = "Report"
+command = ["inventory", "send"]
+
+[[groups]]
+name = "Compliance"
+locked = true
+
+[[groups.tasks]]
+name = "Check"
+command = ["compliance", "check"]
+"#;
+        let user = r#"
+[settings]
+desktop_notifications = false
+parallel_limit = 8
+
+[[groups]]
+name = "Security"
+enabled = false
+
+[[groups.tasks]]
+name = "Antivirus Definitions"
+enabled = false
+
+[[groups.tasks]]
+name = "Report"
+timeout = 60
+
+[[groups]]
+name = "Compliance"
+enabled = false
+
+[[groups]]
+name = "Homebrew"
+
+[[groups.tasks]]
+name = "Update"
+command = ["brew", "update"]
+"#;
+        let mut notices = Vec::new();
+        let merged = merge(managed, toml::from_str(user).unwrap(), &mut notices).unwrap();
+        let config: Config = Value::Table(merged).try_into().unwrap();
+        assert!(config.settings.desktop_notifications);
+        assert_eq!(config.settings.parallel_limit, 8);
+
+        let names: Vec<&str> = config.groups.iter().map(|g| g.name.as_str()).collect();
+        assert_eq!(names, ["Security", "Compliance", "Homebrew"]);
+        let security = &config.groups[0];
+        assert!(security.enabled);
+        assert_eq!(security.icon, "🛡️");
+        assert!(security.tasks[0].enabled);
+        assert_eq!(security.tasks[1].timeout, Some(60));
+        assert_eq!(security.tasks[1].command, ["inventory", "send"]);
+        assert!(config.groups[1].enabled);
+
+        assert_eq!(notices.len(), 4);
+        for expected in [
+            "Setting `desktop_notifications` is locked",
+            "\"Security\" holds locked tasks",
+            "\"Security › Antivirus Definitions\" is locked",
+            "Group \"Compliance\" is locked",
+        ] {
+            assert!(notices.iter().any(|n| n.contains(expected)), "{}", expected);
+        }
+
+        assert!(ensure_writable(&path()).is_err());
+    }
+}
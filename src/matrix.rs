@@ -0,0 +1,115 @@
+use anyhow::{Context, Result};
+use std::process::{Command, Stdio};
+
+use crate::config::{Matrix, TaskConfig};
+use crate::plan::PlannedTask;
+
+const PLACEHOLDER: &str = "${item}";
+
+/// The items a matrix expands over, running its command if it has one
+pub fn items(matrix: &Matrix) -> Result<Vec<String>> {
+    match matrix {
+        Matrix::Items(items) => Ok(items.clone()),
+        Matrix::Command { command } => {
+            let (program, args) = command.split_first().context("empty matrix command")?;
+            let output = Command::new(program)
+                .args(args)
+                .stdin(Stdio::null())
+                .output()
+                .with_context(|| format!("Failed to execute {}", program))?;
+            anyhow::ensure!(
+                output.status.success(),
+                "matrix command exited with {}: {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+            Ok(String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(str::to_string)
+                .collect())
+        }
+    }
+}
+
+/// Whether a task references `${item}` in its command, env or working_dir
+pub fn uses_item(task: &TaskConfig) -> bool {
+    task.command
+        .iter()
+        .chain(task.env.values())
+        .chain(&task.working_dir)
+        .any(|text| text.contains(PLACEHOLDER))
+}
+
+/// The task run for one item: `${item}` replaced and the item appended to
+/// the name so each execution is reported on its own
+pub fn instantiate(task: &TaskConfig, item: &str) -> TaskConfig {
+    let mut instance = task.clone();
+    instance.name = format!("{} ({})", task.name, item);
+    instance.matrix = None;
+    for text in instance
+        .command
+        .iter_mut()
+        .chain(instance.env.values_mut())
+        .chain(&mut instance.working_dir)
+    {
+        *text = text.replace(PLACEHOLDER, item);
+    }
+    instance
+}
+
+/// Replace a planned matrix task with one planned task per item
+pub fn expand(planned: &PlannedTask, items: &[String]) -> Vec<PlannedTask> {
+    items
+        .iter()
+        .map(|item| PlannedTask {
+            task: instantiate(&planned.task, item),
+            ..planned.clone()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_matrix_items() {
+        let task: TaskConfig = toml::from_str(
+            r#"
+name = "Pull Repos"
+command = ["git", "pull", "--ff-only"]
+working_dir = "${item}"
+matrix = ["~/code/tide", "~/code/dotfiles"]
+"#,
+        )
+        .unwrap();
+        assert!(uses_item(&task));
+        let repos = items(task.matrix.as_ref().unwrap()).unwrap();
+
+        let planned = PlannedTask {
+            task,
+            group: "Git".to_string(),
+            group_icon: String::new(),
+            parallel: true,
+            needs_disk_space: false,
+            heavy: false,
+            skip: None,
+        };
+        let expanded = expand(&planned, &repos);
+        assert_eq!(expanded.len(), 2);
+        assert_eq!(expanded[1].task.name, "Pull Repos (~/code/dotfiles)");
+        assert_eq!(
+            expanded[1].task.working_dir.as_deref(),
+            Some("~/code/dotfiles")
+        );
+        assert!(expanded[0].task.matrix.is_none() && expanded[0].parallel);
+
+        let listed = items(&Matrix::Command {
+            command: vec!["printf".to_string(), "a\\n\\nb\\n".to_string()],
+        })
+        .unwrap();
+        assert_eq!(listed, ["a", "b"]);
+    }
+}
@@ -0,0 +1,37 @@
+use std::time::Duration;
+
+/// Endpoint that answers `204 No Content` when the internet is reachable
+pub const DEFAULT_CHECK_URL: &str = "http://connectivitycheck.gstatic.com/generate_204";
+
+/// Outcome of the connectivity preflight
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Connectivity {
+    Online,
+    /// The check endpoint was intercepted, typically by a hotel/Wi-Fi login page
+    CaptivePortal,
+    Offline(String),
+}
+
+/// Probe the check endpoint without following redirects.
+///
+/// A `204` means we are online; any other response means something between
+/// us and the internet answered instead, which is how captive portals behave.
+pub async fn check(url: &str) -> Connectivity {
+    let client = match reqwest::Client::builder()
+        .timeout(Duration::from_secs(5))
+        .redirect(reqwest::redirect::Policy::none())
+        .user_agent(format!("tide-cli/{}", env!("CARGO_PKG_VERSION")))
+        .build()
+    {
+        Ok(client) => client,
+        Err(err) => return Connectivity::Offline(format!("HTTP client failed: {err}")),
+    };
+
+    match client.get(url).send().await {
+        Ok(response) if response.status() == reqwest::StatusCode::NO_CONTENT => {
+            Connectivity::Online
+        }
+        Ok(_) => Connectivity::CaptivePortal,
+        Err(err) => Connectivity::Offline(err.to_string()),
+    }
+}
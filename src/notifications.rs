@@ -1,15 +1,251 @@
 use anyhow::Result;
 use notify_rust::{Notification, Timeout};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::task::JoinHandle;
 
-/// Notification manager for desktop alerts
+use crate::config::PostRunTrigger;
+use crate::email::{self, EmailConfig};
+use crate::overlay;
+use crate::report::RunReport;
+
+/// Failed tasks named in the completion notification of a mixed run
+const NAMED_FAILURES: usize = 3;
+/// Longest error excerpt posted to Slack
+const SLACK_EXCERPT_CHARS: usize = 1500;
+/// Delay before the first retry of a webhook; doubled for each further one
+const WEBHOOK_RETRY_DELAY: Duration = Duration::from_secs(2);
+
+/// `[notifications]` table of the config
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct NotificationsConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slack: Option<SlackConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook: Option<WebhookConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<EmailConfig>,
+}
+
+impl NotificationsConfig {
+    pub fn is_empty(&self) -> bool {
+        self.slack.is_none() && self.webhook.is_none() && self.email.is_none()
+    }
+}
+
+/// `[notifications.slack]`: post to a channel through an incoming webhook
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SlackConfig {
+    pub webhook_url: String,
+    /// Which runs post their summary; failed tasks are posted regardless
+    #[serde(default = "default_always")]
+    pub summary: PostRunTrigger,
+}
+
+/// `[notifications.webhook]`: POST the outcome of every run as JSON
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct WebhookConfig {
+    pub url: String,
+    /// Which runs are posted
+    #[serde(default = "default_always")]
+    pub when: PostRunTrigger,
+    /// Further attempts after a network error or a 5xx response
+    #[serde(default = "default_webhook_retries")]
+    pub retries: u32,
+    #[serde(default = "default_webhook_timeout_secs")]
+    pub timeout_secs: u64,
+    /// Include what each task printed
+    #[serde(default)]
+    pub include_output: bool,
+}
+
+fn default_always() -> PostRunTrigger {
+    PostRunTrigger::Always
+}
+
+fn default_webhook_retries() -> u32 {
+    3
+}
+
+fn default_webhook_timeout_secs() -> u64 {
+    10
+}
+
+/// Body of the generic webhook
+#[derive(Serialize)]
+struct WebhookPayload<'a> {
+    run_id: &'a str,
+    host: &'a str,
+    #[serde(flatten)]
+    report: &'a RunReport,
+}
+
+/// Notification manager for desktop alerts, and the Slack, webhook and
+/// email backends when configured. Their posts are sent in the background, so a
+/// slow or unreachable server never holds up tasks; [`flush`](Self::flush)
+/// waits for them.
 pub struct NotificationManager {
     enabled: bool,
+    slack: Option<SlackConfig>,
+    webhook: Option<WebhookConfig>,
+    email: Option<EmailConfig>,
+    client: reqwest::Client,
+    host: String,
+    posts: Mutex<Vec<JoinHandle<Result<(), String>>>>,
 }
 
 impl NotificationManager {
     /// Create a new notification manager
     pub fn new(enabled: bool) -> Self {
-        Self { enabled }
+        Self {
+            enabled,
+            slack: None,
+            webhook: None,
+            email: None,
+            client: reqwest::Client::new(),
+            host: overlay::host_name().unwrap_or_else(|| "this Mac".to_string()),
+            posts: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Add the backends of the `[notifications]` table. They post whether or
+    /// not desktop notifications are enabled.
+    pub fn with_backends(mut self, config: &NotificationsConfig) -> Self {
+        self.slack = config.slack.clone();
+        self.webhook = config.webhook.clone();
+        self.email = config.email.clone();
+        self
+    }
+
+    fn spawn_post(&self, post: impl Future<Output = Result<(), String>> + Send + 'static) {
+        self.posts
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .push(tokio::spawn(post));
+    }
+
+    fn post_to_slack(&self, text: String) {
+        let Some(slack) = &self.slack else {
+            return;
+        };
+        let request = self
+            .client
+            .post(&slack.webhook_url)
+            .timeout(Duration::from_secs(10))
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(serde_json::json!({ "text": text }).to_string());
+        self.spawn_post(async move {
+            request
+                .send()
+                .await
+                .and_then(|response| response.error_for_status())
+                .map(drop)
+                .map_err(|err| format!("Slack: {}", err.without_url()))
+        });
+    }
+
+    /// Post the summary of a finished run to Slack, if its trigger fires
+    pub fn notify_run_summary(&self, success: bool, summary: &str) {
+        let Some(slack) = &self.slack else {
+            return;
+        };
+        if !slack.summary.fires(success) {
+            return;
+        }
+        let headline = if success {
+            format!(":white_check_mark: Tide run on `{}` succeeded", self.host)
+        } else {
+            format!(":x: Tide run on `{}` failed", self.host)
+        };
+        self.post_to_slack(format!("{}\n```{}```", headline, summary));
+    }
+
+    /// Report the outcome of a finished run to the webhook and by email,
+    /// each if its trigger fires
+    pub fn notify_run_complete(&self, run_id: &str, report: &RunReport) -> Result<()> {
+        if let Some(config) = self
+            .email
+            .clone()
+            .filter(|config| config.when.fires(report.success))
+        {
+            let subject = email::subject(&self.host, report);
+            let body = email::body(run_id, &self.host, report);
+            self.spawn_post(async move {
+                email::send(&config, subject, body)
+                    .await
+                    .map_err(|err| format!("Email: {:#}", err))
+            });
+        }
+        self.post_to_webhook(run_id, report)
+    }
+
+    /// POST the outcome of a run to the webhook, retrying network errors
+    /// and server errors
+    fn post_to_webhook(&self, run_id: &str, report: &RunReport) -> Result<()> {
+        let Some(webhook) = &self.webhook else {
+            return Ok(());
+        };
+        if !webhook.when.fires(report.success) {
+            return Ok(());
+        }
+        let mut payload = serde_json::to_value(WebhookPayload {
+            run_id,
+            host: &self.host,
+            report,
+        })?;
+        if !webhook.include_output
+            && let Some(tasks) = payload["tasks"].as_array_mut()
+        {
+            for task in tasks.iter_mut().filter_map(|task| task.as_object_mut()) {
+                task.remove("output");
+            }
+        }
+
+        let request = self
+            .client
+            .post(&webhook.url)
+            .timeout(Duration::from_secs(webhook.timeout_secs))
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(payload.to_string());
+        let retries = webhook.retries;
+        self.spawn_post(async move {
+            let mut attempt = 0;
+            loop {
+                let request = request.try_clone().expect("body is not a stream");
+                let result = request
+                    .send()
+                    .await
+                    .and_then(|response| response.error_for_status());
+                match result {
+                    Ok(_) => return Ok(()),
+                    Err(err)
+                        if attempt < retries
+                            && err.status().is_none_or(|status| status.is_server_error()) =>
+                    {
+                        tokio::time::sleep(WEBHOOK_RETRY_DELAY * 2u32.pow(attempt)).await;
+                        attempt += 1;
+                    }
+                    Err(err) => return Err(format!("Webhook: {}", err.without_url())),
+                }
+            }
+        });
+        Ok(())
+    }
+
+    /// Wait for the posts still in flight, returning why any failed
+    pub async fn flush(&self) -> Vec<String> {
+        let posts = std::mem::take(&mut *self.posts.lock().unwrap_or_else(|p| p.into_inner()));
+        let mut problems = Vec::new();
+        for post in posts {
+            match post.await {
+                Ok(Ok(())) => {}
+                Ok(Err(err)) => problems.push(err),
+                Err(err) => problems.push(err.to_string()),
+            }
+        }
+        problems
     }
 
     /// Send a notification that a task is waiting for interactive input
@@ -63,6 +299,13 @@ impl NotificationManager {
 
     /// Send a notification that a task failed
     pub fn notify_task_failed(&self, task_name: &str, group_name: &str, error: &str) -> Result<()> {
+        if self.slack.is_some() {
+            let excerpt: String = error.trim().chars().take(SLACK_EXCERPT_CHARS).collect();
+            self.post_to_slack(format!(
+                ":x: Task *{}* (group: {}) failed on `{}`:\n```{}```",
+                task_name, group_name, self.host, excerpt
+            ));
+        }
         if !self.enabled {
             return Ok(());
         }
@@ -102,6 +345,81 @@ impl NotificationManager {
         Ok(())
     }
 
+    /// Send a notification that the network is unavailable or intercepted
+    pub fn notify_network_problem(&self, message: &str) -> Result<()> {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        Notification::new()
+            .summary("🌐 Tide - Network Problem")
+            .body(&format!(
+                "{}\nCheck your terminal before network tasks start.",
+                message
+            ))
+            .icon("network-offline")
+            .timeout(Timeout::Milliseconds(10000))
+            .show()?;
+
+        Ok(())
+    }
+
+    /// Send a notification that App Store tasks were skipped for lack of a sign-in
+    pub fn notify_app_store_sign_in(&self, skipped: usize) -> Result<()> {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        Notification::new()
+            .summary("🛍️ Tide - App Store Sign-In Required")
+            .body(&format!(
+                "Skipped {} App Store task(s) because no Apple ID is signed in.\n\
+                 Sign in to the App Store and run Tide again.",
+                skipped
+            ))
+            .icon("dialog-warning")
+            .timeout(Timeout::Milliseconds(10000))
+            .show()?;
+
+        Ok(())
+    }
+
+    /// Send a notification that heavy groups were deferred
+    pub fn notify_deferred(&self, reason: &str, groups: &[String]) -> Result<()> {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        Notification::new()
+            .summary("🌡️ Tide - Heavy Tasks Deferred")
+            .body(&format!(
+                "Deferred {} due to {}.\nRun Tide again once the Mac has cooled down.",
+                groups.join(", "),
+                reason
+            ))
+            .icon("dialog-information")
+            .timeout(Timeout::Milliseconds(8000))
+            .show()?;
+
+        Ok(())
+    }
+
+    /// Send a notification summarizing updates found by `tide check`
+    pub fn notify_pending_updates(&self, summary: &str) -> Result<()> {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        Notification::new()
+            .summary("🔎 Tide - Updates Pending")
+            .body(&format!("{}\nRun Tide to install them.", summary))
+            .icon("software-update-available")
+            .timeout(Timeout::Milliseconds(10000))
+            .show()?;
+
+        Ok(())
+    }
+
     /// Send a notification that all tasks completed successfully
     pub fn notify_all_tasks_complete(
         &self,
@@ -124,6 +442,40 @@ impl NotificationManager {
 
         Ok(())
     }
+
+    /// Send a notification that a run finished with failed tasks, naming
+    /// the first few
+    pub fn notify_tasks_failed(&self, failed: &[&str], total: usize) -> Result<()> {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        Notification::new()
+            .summary("❌ Tide - Some Tasks Failed")
+            .body(&failure_summary(failed, total))
+            .icon("dialog-error")
+            .timeout(Timeout::Milliseconds(10000))
+            .show()?;
+
+        Ok(())
+    }
+}
+
+/// "2 of 7 tasks failed: A, B" or, past [`NAMED_FAILURES`], "…: A, B, C and 2 more"
+fn failure_summary(failed: &[&str], total: usize) -> String {
+    let named = failed[..failed.len().min(NAMED_FAILURES)].join(", ");
+    let more = failed.len().saturating_sub(NAMED_FAILURES);
+    format!(
+        "{} of {} tasks failed: {}{}",
+        failed.len(),
+        total,
+        named,
+        if more > 0 {
+            format!(" and {} more", more)
+        } else {
+            String::new()
+        }
+    )
 }
 
 #[cfg(test)]
@@ -140,4 +492,124 @@ mod tests {
                 .is_ok()
         );
     }
+
+    #[test]
+    fn test_failure_summary() {
+        assert_eq!(
+            failure_summary(&["Upgrade Packages", "Cleanup"], 7),
+            "2 of 7 tasks failed: Upgrade Packages, Cleanup"
+        );
+        assert_eq!(
+            failure_summary(&["a", "b", "c", "d", "e"], 9),
+            "5 of 9 tasks failed: a, b, c and 2 more"
+        );
+    }
+
+    /// Answer one request per response in `responses`, collecting the
+    /// request bodies
+    async fn serve(responses: &'static [&'static str]) -> (String, JoinHandle<Vec<String>>) {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let mut bodies = Vec::new();
+            for status in responses {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut request = Vec::new();
+                let mut buf = [0; 4096];
+                while !String::from_utf8_lossy(&request).ends_with('}') {
+                    let read = stream.read(&mut buf).await.unwrap();
+                    request.extend_from_slice(&buf[..read]);
+                }
+                let response = format!(
+                    "HTTP/1.1 {}\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+                    status
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+                let request = String::from_utf8_lossy(&request).to_string();
+                bodies.push(request.split("\r\n\r\n").nth(1).unwrap().to_string());
+            }
+            bodies
+        });
+        (url, server)
+    }
+
+    #[tokio::test]
+    async fn test_slack_posts() {
+        let (url, server) = serve(&["200 OK", "200 OK"]).await;
+        let manager = NotificationManager::new(false).with_backends(&NotificationsConfig {
+            slack: Some(SlackConfig {
+                webhook_url: url,
+                summary: PostRunTrigger::Failure,
+            }),
+            webhook: None,
+            email: None,
+        });
+        manager
+            .notify_task_failed("Upgrade Packages", "Homebrew", "Error: git is pinned")
+            .unwrap();
+        assert!(manager.flush().await.is_empty());
+        // Successful runs are not posted with the `failure` trigger
+        manager.notify_run_summary(true, "1 succeeded");
+        manager.notify_run_summary(false, "1 failed");
+        assert!(manager.flush().await.is_empty());
+
+        let bodies = server.await.unwrap();
+        let alert: serde_json::Value = serde_json::from_str(&bodies[0]).unwrap();
+        let text = alert["text"].as_str().unwrap();
+        assert!(text.contains("*Upgrade Packages* (group: Homebrew) failed"));
+        assert!(text.contains("```Error: git is pinned```"));
+        let summary: serde_json::Value = serde_json::from_str(&bodies[1]).unwrap();
+        assert!(
+            summary["text"]
+                .as_str()
+                .unwrap()
+                .contains("failed\n```1 failed```")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_webhook_retries() {
+        use crate::executor::{TaskResult, TaskStatus};
+        use chrono::Local;
+        use std::time::Instant;
+
+        let (url, server) = serve(&["503 Service Unavailable", "200 OK"]).await;
+        let manager = NotificationManager::new(false).with_backends(&NotificationsConfig {
+            slack: None,
+            webhook: Some(WebhookConfig {
+                url,
+                when: PostRunTrigger::Always,
+                retries: 1,
+                timeout_secs: 5,
+                include_output: false,
+            }),
+            email: None,
+        });
+        let results = [TaskResult {
+            name: "Upgrade Packages".to_string(),
+            group: "Homebrew".to_string(),
+            group_icon: "🍺".to_string(),
+            status: TaskStatus::Success,
+            started: Instant::now(),
+            duration: Duration::from_secs(12),
+            output: Some("Already up-to-date.".to_string()),
+            exit_code: Some(0),
+            failure: None,
+            log_line: None,
+        }];
+        let report = RunReport::new(Local::now(), Duration::from_secs(12), &results, None);
+        manager.notify_run_complete("run-1", &report).unwrap();
+        assert!(manager.flush().await.is_empty());
+
+        let bodies = server.await.unwrap();
+        assert_eq!(bodies.len(), 2);
+        let payload: serde_json::Value = serde_json::from_str(&bodies[1]).unwrap();
+        assert_eq!(payload["run_id"], "run-1");
+        assert_eq!(payload["success"], true);
+        assert_eq!(payload["tasks"][0]["name"], "Upgrade Packages");
+        assert_eq!(payload["tasks"][0]["duration_secs"], 12.0);
+        assert!(payload["tasks"][0].get("output").is_none());
+    }
 }
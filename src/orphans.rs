@@ -0,0 +1,471 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// A command spawned by tide, running as the leader of its own process group
+/// so it can be terminated together with everything it started
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Child {
+    pub pid: u32,
+    pub program: String,
+    /// When it was spawned, in seconds since the epoch. Tells the child apart
+    /// from an unrelated process that got its pid later.
+    pub started: i64,
+}
+
+/// What a run's state file records
+#[derive(Debug, Deserialize, Serialize)]
+struct State {
+    /// Boot time of the machine the children ran on; after a reboot none of
+    /// them can still be alive
+    boot_time: Option<i64>,
+    children: Vec<Child>,
+}
+
+/// How far the start time of a process may be off the recorded one
+const START_TOLERANCE_SECS: i64 = 2;
+
+/// How long a terminated process group gets to exit before SIGKILL
+pub const KILL_GRACE: Duration = Duration::from_secs(5);
+
+/// Children currently running, mirrored to the state file of this run
+static RUNNING: Mutex<Vec<Child>> = Mutex::new(Vec::new());
+
+type InterruptHandler = Box<dyn Fn() -> bool + Send + Sync>;
+
+/// Takes over SIGINT and SIGTERM while a run is in progress
+static INTERRUPT: Mutex<Option<InterruptHandler>> = Mutex::new(None);
+
+/// Keeps a child registered until it has exited
+pub struct Tracked {
+    pid: u32,
+    terminate_on_drop: bool,
+}
+
+impl Tracked {
+    /// Terminate the process group should this be dropped before
+    /// [`Tracked::exited`], i.e. when the future waiting for the child is
+    /// abandoned on timeout
+    pub fn terminate_on_drop(mut self) -> Self {
+        self.terminate_on_drop = true;
+        self
+    }
+
+    /// The child has exited; nothing is left to terminate
+    pub fn exited(mut self) {
+        self.terminate_on_drop = false;
+    }
+
+    /// Terminate the child with everything it started, see [`terminate_group`]
+    pub fn terminate(&self) {
+        terminate_group(self.pid);
+    }
+}
+
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        if self.terminate_on_drop {
+            terminate_group(self.pid);
+        }
+        let mut running = lock();
+        running.retain(|child| child.pid != self.pid);
+        persist(&running);
+    }
+}
+
+fn lock() -> std::sync::MutexGuard<'static, Vec<Child>> {
+    RUNNING.lock().unwrap_or_else(|p| p.into_inner())
+}
+
+/// Register a spawned child (its pid is also its process group id)
+pub fn track(pid: u32, program: &str) -> Tracked {
+    let mut running = lock();
+    running.push(Child {
+        pid,
+        program: program.to_string(),
+        started: now(),
+    });
+    persist(&running);
+    Tracked {
+        pid,
+        terminate_on_drop: false,
+    }
+}
+
+/// Terminate the process group of a child: SIGTERM first, then SIGKILL
+/// for whatever is still alive after [`KILL_GRACE`]
+pub fn terminate_group(pid: u32) {
+    let group = -(pid as libc::pid_t);
+    // SAFETY: signalling the process group of a child we spawned
+    unsafe { libc::kill(group, libc::SIGTERM) };
+    thread::spawn(move || {
+        thread::sleep(KILL_GRACE);
+        if alive(group) {
+            // SAFETY: as above; the group ignored SIGTERM
+            unsafe { libc::kill(group, libc::SIGKILL) };
+        }
+    });
+}
+
+/// Send `signal` to the process groups of all running children
+pub fn terminate_all(signal: libc::c_int) {
+    for child in lock().iter() {
+        // SAFETY: signalling process groups of children we spawned
+        unsafe { libc::kill(-(child.pid as libc::pid_t), signal) };
+    }
+}
+
+/// Let `handler` deal with SIGINT and SIGTERM, e.g. to wind a run down and
+/// print its summary. Tide keeps running while it returns `true`; once it
+/// returns `false` the signal ends tide as if no handler was set.
+pub fn set_interrupt_handler(handler: impl Fn() -> bool + Send + Sync + 'static) {
+    *INTERRUPT.lock().unwrap_or_else(|p| p.into_inner()) = Some(Box::new(handler));
+}
+
+/// Remove the handler set with [`set_interrupt_handler`]
+pub fn clear_interrupt_handler() {
+    *INTERRUPT.lock().unwrap_or_else(|p| p.into_inner()) = None;
+}
+
+fn interrupt_handled() -> bool {
+    INTERRUPT
+        .lock()
+        .unwrap_or_else(|p| p.into_inner())
+        .as_ref()
+        .is_some_and(|handler| handler())
+}
+
+/// Terminate the children when tide dies abnormally: on a panic of the main
+/// thread and on SIGINT, SIGTERM or SIGHUP (e.g. the terminal was closed).
+/// SIGINT and SIGTERM go to the [interrupt handler](set_interrupt_handler)
+/// first. SIGKILL cannot be caught; [`leftovers`] finds its orphans on the
+/// next run.
+pub fn install_handlers() {
+    // Looked up now rather than when the first command is tracked
+    boot_time();
+
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        // Panics of worker threads are caught by the runtime; tide lives on
+        if std::thread::current().name() == Some("main") {
+            terminate_all(libc::SIGTERM);
+        }
+        previous(info);
+    }));
+
+    use tokio::signal::unix::{SignalKind, signal};
+    for kind in [
+        SignalKind::interrupt(),
+        SignalKind::terminate(),
+        SignalKind::hangup(),
+    ] {
+        let Ok(mut stream) = signal(kind) else {
+            continue;
+        };
+        tokio::spawn(async move {
+            while stream.recv().await.is_some() {
+                let signal = kind.as_raw_value();
+                if signal != libc::SIGHUP && interrupt_handled() {
+                    continue;
+                }
+                terminate_all(signal);
+                // Children are gone; die the way the signal would have
+                // SAFETY: restoring the default disposition and re-raising
+                unsafe {
+                    libc::signal(signal, libc::SIG_DFL);
+                    libc::raise(signal);
+                }
+            }
+        });
+    }
+}
+
+fn state_dir() -> Option<PathBuf> {
+    Some(
+        dirs::home_dir()?
+            .join(".local")
+            .join("share")
+            .join("tide")
+            .join("children"),
+    )
+}
+
+/// Record the running children in `children/<tide pid>.json`, removing the
+/// file once none are left
+fn persist(running: &[Child]) {
+    let Some(dir) = state_dir() else {
+        return;
+    };
+    let path = dir.join(format!("{}.json", std::process::id()));
+    let state = State {
+        boot_time: boot_time(),
+        children: running.to_vec(),
+    };
+    if running.is_empty() {
+        let _ = fs::remove_file(path);
+    } else if fs::create_dir_all(&dir).is_ok()
+        && let Ok(json) = serde_json::to_string(&state)
+    {
+        let _ = fs::write(path, json);
+    }
+}
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs() as i64)
+}
+
+/// When the machine booted, in seconds since the epoch (looked up once)
+fn boot_time() -> Option<i64> {
+    static BOOT_TIME: OnceLock<Option<i64>> = OnceLock::new();
+    *BOOT_TIME.get_or_init(|| {
+        // macOS: "{ sec = 1715600000, usec = 0 } Mon May 13 …"
+        let sysctl = Command::new("sysctl")
+            .args(["-n", "kern.boottime"])
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .and_then(|output| {
+                let text = String::from_utf8_lossy(&output.stdout).to_string();
+                let (_, rest) = text.split_once("sec = ")?;
+                rest.split(',').next()?.trim().parse().ok()
+            });
+        sysctl.or_else(|| {
+            fs::read_to_string("/proc/stat")
+                .ok()?
+                .lines()
+                .find_map(|line| line.strip_prefix("btime "))?
+                .trim()
+                .parse()
+                .ok()
+        })
+    })
+}
+
+/// When the process `pid` started, in seconds since the epoch, or `None`
+/// when there is no such process
+fn process_start(pid: u32) -> Option<i64> {
+    let output = Command::new("ps")
+        .args(["-o", "etime=", "-p", &pid.to_string()])
+        .output()
+        .ok()?;
+    let elapsed = parse_elapsed(String::from_utf8_lossy(&output.stdout).trim())?;
+    Some(now() - elapsed)
+}
+
+/// Seconds of a `ps` elapsed time, `[[dd-]hh:]mm:ss`
+fn parse_elapsed(etime: &str) -> Option<i64> {
+    let (days, clock) = match etime.split_once('-') {
+        Some((days, clock)) => (days.parse::<i64>().ok()?, clock),
+        None => (0, etime),
+    };
+    let mut secs = 0;
+    for part in clock.split(':') {
+        secs = secs * 60 + part.parse::<i64>().ok()?;
+    }
+    Some(days * 86_400 + secs)
+}
+
+impl Child {
+    /// Whether the process group still belongs to this child: its leader is
+    /// the process spawned at the recorded time, or has exited while the
+    /// group lives on (the pid is not reused while the group exists)
+    fn survives(&self) -> bool {
+        if !alive(-(self.pid as i32)) {
+            return false;
+        }
+        match process_start(self.pid) {
+            Some(start) => (start - self.started).abs() <= START_TOLERANCE_SECS,
+            None => !alive(self.pid as i32),
+        }
+    }
+}
+
+/// Children of a previous tide run that ended without cleaning up
+#[derive(Debug)]
+pub struct Leftovers {
+    /// pid of the crashed tide process
+    pub tide_pid: u32,
+    /// Children whose process group still exists
+    pub children: Vec<Child>,
+    state_file: PathBuf,
+}
+
+impl Leftovers {
+    /// Terminate the leftover process groups that still belong to the
+    /// crashed run
+    pub fn terminate(&self) {
+        for child in self.children.iter().filter(|child| child.survives()) {
+            // SAFETY: signalling process groups a previous tide run spawned
+            unsafe { libc::kill(-(child.pid as libc::pid_t), libc::SIGTERM) };
+        }
+    }
+
+    /// Forget the crashed run
+    pub fn dismiss(&self) -> Result<()> {
+        fs::remove_file(&self.state_file)
+            .with_context(|| format!("Failed to remove {}", self.state_file.display()))
+    }
+}
+
+fn alive(pid: i32) -> bool {
+    // SAFETY: signal 0 only checks whether the process (group) exists
+    unsafe { libc::kill(pid, 0) == 0 }
+}
+
+/// State files of tide runs that are no longer alive, with the children
+/// they left running. Files of crashed runs without survivors, from before
+/// the last reboot or in an unknown format are removed.
+pub fn leftovers() -> Vec<Leftovers> {
+    state_dir().map_or_else(Vec::new, |dir| leftovers_in(&dir))
+}
+
+fn leftovers_in(dir: &Path) -> Vec<Leftovers> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut found = Vec::new();
+    for path in entries.flatten().map(|entry| entry.path()) {
+        let Some(tide_pid) = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .and_then(|stem| stem.parse::<u32>().ok())
+        else {
+            continue;
+        };
+        if tide_pid == std::process::id() {
+            continue;
+        }
+        let state: Option<State> = fs::read_to_string(&path)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok());
+        let children = match state {
+            Some(state) if state.boot_time == boot_time() => state.children,
+            _ => Vec::new(),
+        };
+        // The run is still going if its pid belongs to a process started
+        // no later than its children; otherwise the pid was reused
+        let first_spawn = children.iter().map(|child| child.started).min();
+        if let (Some(first_spawn), Some(start)) = (first_spawn, process_start(tide_pid))
+            && start <= first_spawn + START_TOLERANCE_SECS
+        {
+            continue;
+        }
+        let children: Vec<Child> = children
+            .into_iter()
+            .filter(|child| child.survives())
+            .collect();
+        if children.is_empty() {
+            let _ = fs::remove_file(&path);
+        } else {
+            found.push(Leftovers {
+                tide_pid,
+                children,
+                state_file: path,
+            });
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::process::CommandExt;
+    use std::process::Command;
+
+    #[test]
+    fn test_terminate_tracked_process_group() {
+        let mut child = Command::new("sleep")
+            .arg("30")
+            .process_group(0)
+            .spawn()
+            .unwrap();
+        let tracked = track(child.id(), "sleep");
+        assert!(alive(-(child.id() as i32)));
+
+        terminate_all(libc::SIGTERM);
+        let status = child.wait().unwrap();
+        assert!(!status.success());
+        drop(tracked);
+        assert!(lock().iter().all(|c| c.pid != child.id()));
+    }
+
+    #[test]
+    fn test_terminate_on_drop() {
+        let mut child = Command::new("sh")
+            .args(["-c", "sleep 30 & wait"])
+            .process_group(0)
+            .spawn()
+            .unwrap();
+        let tracked = track(child.id(), "sh").terminate_on_drop();
+        drop(tracked);
+        assert!(!child.wait().unwrap().success());
+
+        let mut child = Command::new("true").process_group(0).spawn().unwrap();
+        let tracked = track(child.id(), "true").terminate_on_drop();
+        assert!(child.wait().unwrap().success());
+        tracked.exited();
+        assert!(lock().iter().all(|c| c.pid != child.id()));
+    }
+
+    #[test]
+    fn test_leftovers_are_verified() {
+        let dir = std::env::temp_dir().join(format!("tide-orphans-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        // A tide pid that no longer runs
+        let mut exited = Command::new("true").spawn().unwrap();
+        exited.wait().unwrap();
+        let state_file = dir.join(format!("{}.json", exited.id()));
+
+        let mut sleep = Command::new("sleep")
+            .arg("30")
+            .process_group(0)
+            .spawn()
+            .unwrap();
+        let child = Child {
+            pid: sleep.id(),
+            program: "sleep".to_string(),
+            started: now(),
+        };
+        let write = |boot_time: Option<i64>, child: &Child| {
+            let state = State {
+                boot_time,
+                children: vec![child.clone()],
+            };
+            fs::write(&state_file, serde_json::to_string(&state).unwrap()).unwrap();
+        };
+
+        write(boot_time(), &child);
+        let found = leftovers_in(&dir);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].children.len(), 1);
+        assert_eq!(found[0].children[0], child);
+
+        // The pid now belongs to a process started long after the child
+        let reused = Child {
+            started: child.started - 3600,
+            ..child.clone()
+        };
+        write(boot_time(), &reused);
+        assert!(leftovers_in(&dir).is_empty());
+        assert!(!state_file.exists());
+
+        // Recorded before a reboot
+        write(boot_time().map(|boot| boot - 60), &child);
+        assert!(leftovers_in(&dir).is_empty());
+        assert!(!state_file.exists());
+
+        sleep.kill().unwrap();
+        sleep.wait().unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(parse_elapsed("1-02:03:04"), Some(93_784));
+        assert_eq!(parse_elapsed("00:07"), Some(7));
+    }
+}
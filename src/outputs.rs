@@ -0,0 +1,128 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::config::TaskConfig;
+
+const PREFIX: &str = "${outputs.";
+
+/// Names of the `${<namespace>.<name>}` placeholders in a string, in order
+pub fn placeholders<'a>(text: &'a str, namespace: &str) -> Vec<&'a str> {
+    let prefix = format!("${{{}.", namespace);
+    let mut names = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find(&prefix) {
+        rest = &rest[start + prefix.len()..];
+        let Some(end) = rest.find('}') else { break };
+        names.push(&rest[..end]);
+        rest = &rest[end + 1..];
+    }
+    names
+}
+
+/// Names of the `${outputs.<name>}` placeholders in a string, in order
+pub fn references(text: &str) -> Vec<&str> {
+    placeholders(text, "outputs")
+}
+
+/// Outputs a task consumes through its command or env values
+pub fn task_references(task: &TaskConfig) -> Vec<&str> {
+    task.command
+        .iter()
+        .chain(task.env.values())
+        .flat_map(|text| references(text))
+        .collect()
+}
+
+/// Whether a task produces or consumes outputs, which pins it to the
+/// sequential batch so producers finish before their consumers start
+pub fn participates(task: &TaskConfig) -> bool {
+    task.register.is_some() || !task_references(task).is_empty()
+}
+
+/// Stdout captured from tasks with `register`, shared across the run
+#[derive(Debug, Default)]
+pub struct Outputs {
+    values: Mutex<HashMap<String, String>>,
+}
+
+impl Outputs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Store a task's stdout, trimmed of surrounding whitespace
+    pub fn record(&self, name: &str, stdout: &str) {
+        self.values
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .insert(name.to_string(), stdout.trim().to_string());
+    }
+
+    /// Replace the placeholders in `text`, returning the first output that
+    /// has not been recorded (its placeholder is left in place)
+    pub fn interpolate(&self, text: &mut String) -> Result<(), String> {
+        let values = self
+            .values
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut missing = None;
+        for name in references(text) {
+            if !values.contains_key(name) && missing.is_none() {
+                missing = Some(name.to_string());
+            }
+        }
+        for (name, value) in values.iter() {
+            let placeholder = format!("{}{}}}", PREFIX, name);
+            if text.contains(&placeholder) {
+                *text = text.replace(&placeholder, value);
+            }
+        }
+        missing.map_or(Ok(()), Err)
+    }
+
+    /// Interpolate a task's command and env values
+    pub fn apply(&self, cmd: &mut [String], task: &mut TaskConfig) -> Result<(), String> {
+        let mut result = Ok(());
+        for text in cmd.iter_mut().chain(task.env.values_mut()) {
+            if let Err(name) = self.interpolate(text) {
+                result = result.and(Err(name));
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interpolate_outputs() {
+        assert_eq!(
+            references("upgrade ${outputs.casks} ${outputs.formulae}"),
+            ["casks", "formulae"]
+        );
+
+        let outputs = Outputs::new();
+        outputs.record("casks", "firefox\niterm2\n");
+        let mut cmd = vec![
+            "sh".to_string(),
+            "-c".to_string(),
+            "brew upgrade --cask ${outputs.casks}".to_string(),
+        ];
+        let mut task = TaskConfig::default();
+        task.env
+            .insert("PINNED".to_string(), "${outputs.pinned}".to_string());
+
+        assert_eq!(
+            outputs.apply(&mut cmd, &mut task),
+            Err("pinned".to_string())
+        );
+        assert_eq!(cmd[2], "brew upgrade --cask firefox\niterm2");
+        assert_eq!(task.env["PINNED"], "${outputs.pinned}");
+
+        outputs.record("pinned", "node");
+        assert_eq!(outputs.apply(&mut cmd, &mut task), Ok(()));
+        assert_eq!(task.env["PINNED"], "node");
+    }
+}
@@ -0,0 +1,217 @@
+use std::ffi::CStr;
+use std::path::{Path, PathBuf};
+use toml::{Table, Value};
+
+/// Short name of this machine (`studio` for `studio.local`), lowercased
+pub fn host_name() -> Option<String> {
+    let mut buf = [0 as libc::c_char; 256];
+    // SAFETY: the buffer is writable for its whole length and gethostname
+    // NUL-terminates names that fit
+    if unsafe { libc::gethostname(buf.as_mut_ptr(), buf.len() - 1) } != 0 {
+        return None;
+    }
+    // SAFETY: the last byte was never written, so the buffer is NUL-terminated
+    let name = unsafe { CStr::from_ptr(buf.as_ptr()) }.to_string_lossy();
+    let short = name.split('.').next().unwrap_or_default().to_lowercase();
+    (!short.is_empty()).then_some(short)
+}
+
+/// `config.<hostname>.toml` next to `config_path`, with the extension of
+/// `config_path` (`config.<hostname>.yaml` next to `config.yaml`)
+pub fn host_path(config_path: &Path, host: &str) -> PathBuf {
+    let stem = config_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy())
+        .unwrap_or_else(|| "config".into());
+    let extension = config_path
+        .extension()
+        .map(|ext| ext.to_string_lossy())
+        .unwrap_or_else(|| "toml".into());
+    config_path.with_file_name(format!("{}.{}.{}", stem, host, extension))
+}
+
+/// Overlay `layer` on `base`: settings, groups (matched by name) and tasks
+/// (matched by name within their group) are merged key by key, new groups and
+/// tasks are appended, `[[tests]]` are appended and other tables replaced.
+///
+/// Changes to `locked_settings` and to groups or tasks marked `locked = true`
+/// in `base` are ignored with a notice; groups holding locked tasks cannot be
+/// disabled.
+pub fn apply(
+    base: &mut Table,
+    layer: Table,
+    locked_settings: &[String],
+    notices: &mut Vec<String>,
+) {
+    for (key, value) in layer {
+        match (key.as_str(), value) {
+            ("settings", Value::Table(settings)) => {
+                let base = table_entry(base, "settings");
+                for (name, value) in settings {
+                    if locked_settings.contains(&name) {
+                        if base.get(&name) != Some(&value) {
+                            notices.push(format!(
+                                "Setting `{}` is locked by the managed configuration; your value is ignored",
+                                name
+                            ));
+                        }
+                    } else {
+                        base.insert(name, value);
+                    }
+                }
+            }
+            ("groups", Value::Array(groups)) => {
+                let base = array_entry(base, "groups");
+                for group in groups {
+                    apply_group(base, group, notices);
+                }
+            }
+            ("tests", Value::Array(tests)) => array_entry(base, "tests").extend(tests),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+fn table_entry<'a>(table: &'a mut Table, key: &str) -> &'a mut Table {
+    let entry = table
+        .entry(key)
+        .or_insert_with(|| Value::Table(Table::new()));
+    if !entry.is_table() {
+        *entry = Value::Table(Table::new());
+    }
+    entry.as_table_mut().expect("just made a table")
+}
+
+fn array_entry<'a>(table: &'a mut Table, key: &str) -> &'a mut Vec<Value> {
+    let entry = table.entry(key).or_insert_with(|| Value::Array(Vec::new()));
+    if !entry.is_array() {
+        *entry = Value::Array(Vec::new());
+    }
+    entry.as_array_mut().expect("just made an array")
+}
+
+fn name_of(value: &Value) -> Option<&str> {
+    value.get("name")?.as_str()
+}
+
+fn is_locked(value: &Value) -> bool {
+    value.get("locked").and_then(Value::as_bool) == Some(true)
+}
+
+fn apply_group(groups: &mut Vec<Value>, group: Value, notices: &mut Vec<String>) {
+    let Some(name) = name_of(&group).map(str::to_string) else {
+        groups.push(group);
+        return;
+    };
+    let Some(base) = groups.iter_mut().find(|g| name_of(g) == Some(&name)) else {
+        groups.push(group);
+        return;
+    };
+    if is_locked(base) {
+        notices.push(format!(
+            "Group \"{}\" is locked by the managed configuration; your changes to it are ignored",
+            name
+        ));
+        return;
+    }
+    let (Some(base), Value::Table(group)) = (base.as_table_mut(), group) else {
+        return;
+    };
+    let has_locked_tasks = base
+        .get("tasks")
+        .and_then(Value::as_array)
+        .is_some_and(|tasks| tasks.iter().any(is_locked));
+    for (key, value) in group {
+        match (key.as_str(), value) {
+            ("locked", _) => {}
+            ("enabled", Value::Boolean(false)) if has_locked_tasks => notices.push(format!(
+                "Group \"{}\" holds locked tasks of the managed configuration and cannot be disabled",
+                name
+            )),
+            ("tasks", Value::Array(tasks)) => {
+                let base = array_entry(base, "tasks");
+                for task in tasks {
+                    apply_task(&name, base, task, notices);
+                }
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+fn apply_task(group: &str, tasks: &mut Vec<Value>, task: Value, notices: &mut Vec<String>) {
+    let base = name_of(&task).and_then(|name| tasks.iter_mut().find(|t| name_of(t) == Some(name)));
+    let Some(base) = base else {
+        tasks.push(task);
+        return;
+    };
+    if is_locked(base) {
+        notices.push(format!(
+            "Task \"{} › {}\" is locked by the managed configuration; your changes to it are ignored",
+            group,
+            name_of(base).unwrap_or_default()
+        ));
+        return;
+    }
+    if let (Some(base), Value::Table(task)) = (base.as_table_mut(), task) {
+        for (key, value) in task {
+            if key != "locked" {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn test_host_overlay() {
+        assert_eq!(
+            host_path(Path::new("/Users/me/.config/tide/config.toml"), "studio"),
+            Path::new("/Users/me/.config/tide/config.studio.toml")
+        );
+        assert!(host_name().is_some_and(|name| !name.contains('.')));
+
+        let mut config: Table = toml::from_str(
+            r#"
+[settings]
+parallel_limit = 4
+
+[[groups]]
+name = "Xcode"
+icon = "🔨"
+
+[[groups.tasks]]
+name = "Clean DerivedData"
+command = ["rm", "-rf", "DerivedData"]
+"#,
+        )
+        .unwrap();
+        let host: Table = toml::from_str(
+            r#"
+[settings]
+parallel_limit = 8
+
+[[groups]]
+name = "Xcode"
+enabled = false
+"#,
+        )
+        .unwrap();
+        let mut notices = Vec::new();
+        apply(&mut config, host, &[], &mut notices);
+        let config: Config = Value::Table(config).try_into().unwrap();
+        assert!(notices.is_empty());
+        assert_eq!(config.settings.parallel_limit, 8);
+        assert!(!config.groups[0].enabled);
+        assert_eq!(config.groups[0].icon, "🔨");
+        assert_eq!(config.groups[0].tasks.len(), 1);
+    }
+}
@@ -0,0 +1,80 @@
+use colored::{ColoredString, Colorize};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Color presets for status output (`settings.color_scheme`)
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ColorScheme {
+    /// Green / red / yellow
+    #[default]
+    Default,
+    /// Blue / magenta / yellow, distinguishable with red-green color blindness
+    Deuteranopia,
+    /// Bold bright colors for low-contrast displays and themes
+    HighContrast,
+}
+
+static SCHEME: AtomicU8 = AtomicU8::new(ColorScheme::Default as u8);
+
+/// Select the scheme used by [`Paint`] for the rest of the process
+pub fn set_scheme(scheme: ColorScheme) {
+    SCHEME.store(scheme as u8, Ordering::Relaxed);
+}
+
+/// The currently selected scheme
+pub fn scheme() -> ColorScheme {
+    match SCHEME.load(Ordering::Relaxed) {
+        x if x == ColorScheme::Deuteranopia as u8 => ColorScheme::Deuteranopia,
+        x if x == ColorScheme::HighContrast as u8 => ColorScheme::HighContrast,
+        _ => ColorScheme::Default,
+    }
+}
+
+/// Semantic colors that follow the selected [`ColorScheme`]; use these
+/// instead of `green()`/`red()`/`yellow()` for status output.
+pub trait Paint: Colorize + Sized {
+    fn success(self) -> ColoredString {
+        match scheme() {
+            ColorScheme::Default => self.green(),
+            ColorScheme::Deuteranopia => self.bright_blue(),
+            ColorScheme::HighContrast => self.bright_green().bold(),
+        }
+    }
+
+    fn failure(self) -> ColoredString {
+        match scheme() {
+            ColorScheme::Default => self.red(),
+            ColorScheme::Deuteranopia => self.bright_magenta(),
+            ColorScheme::HighContrast => self.bright_red().bold(),
+        }
+    }
+
+    fn warning(self) -> ColoredString {
+        match scheme() {
+            ColorScheme::Default => self.yellow(),
+            ColorScheme::Deuteranopia => self.bright_yellow(),
+            ColorScheme::HighContrast => self.bright_yellow().bold(),
+        }
+    }
+}
+
+impl<T: Colorize> Paint for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_color_scheme_setting() {
+        #[derive(Deserialize)]
+        struct Wrapper {
+            color_scheme: ColorScheme,
+        }
+        let parsed: Wrapper = toml::from_str("color_scheme = \"high-contrast\"").unwrap();
+        assert_eq!(parsed.color_scheme, ColorScheme::HighContrast);
+        let parsed: Wrapper = toml::from_str("color_scheme = \"deuteranopia\"").unwrap();
+        assert_eq!(parsed.color_scheme, ColorScheme::Deuteranopia);
+        assert!(toml::from_str::<Wrapper>("color_scheme = \"sepia\"").is_err());
+    }
+}
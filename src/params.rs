@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+
+use crate::config::{ParamType, TaskConfig, TaskParam};
+use crate::error::TideError;
+use crate::outputs;
+
+impl ParamType {
+    /// Validate a raw value, returning it in canonical form
+    pub fn check(self, raw: &str) -> Result<String, String> {
+        let value = raw.trim();
+        match self {
+            ParamType::String => Ok(raw.to_string()),
+            ParamType::Integer => value
+                .parse::<i64>()
+                .map(|n| n.to_string())
+                .map_err(|_| format!("'{}' is not an integer", raw)),
+            ParamType::Boolean => match value.to_ascii_lowercase().as_str() {
+                "true" | "yes" | "y" | "1" => Ok("true".to_string()),
+                "false" | "no" | "n" | "0" => Ok("false".to_string()),
+                _ => Err(format!("'{}' is not a boolean (true/false)", raw)),
+            },
+        }
+    }
+}
+
+impl TaskParam {
+    /// The configured default as a checked string, if any
+    pub fn default_value(&self) -> Option<Result<String, String>> {
+        let raw = match self.default.as_ref()? {
+            toml::Value::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+        Some(self.kind.check(&raw))
+    }
+
+    /// Question shown when asking for the value
+    pub fn prompt_text(&self) -> &str {
+        self.prompt.as_deref().unwrap_or(&self.name)
+    }
+}
+
+/// Parse repeated `--param <name>=<value>` arguments
+pub fn parse_overrides(raw: &[String]) -> Result<HashMap<String, String>, TideError> {
+    raw.iter()
+        .map(|arg| match arg.split_once('=') {
+            Some((name, value)) if !name.trim().is_empty() => {
+                Ok((name.trim().to_string(), value.to_string()))
+            }
+            _ => Err(TideError::Config(format!(
+                "Invalid --param '{}', expected <name>=<value>",
+                arg
+            ))),
+        })
+        .collect()
+}
+
+/// Parameters a task references through its command or env values
+pub fn task_references(task: &TaskConfig) -> Vec<&str> {
+    task.command
+        .iter()
+        .chain(task.env.values())
+        .flat_map(|text| outputs::placeholders(text, "params"))
+        .collect()
+}
+
+/// Replace `${params.<name>}` in the task's command and env values
+pub fn apply(task: &mut TaskConfig, values: &HashMap<String, String>) {
+    for text in task.command.iter_mut().chain(task.env.values_mut()) {
+        for (name, value) in values {
+            let placeholder = format!("${{params.{}}}", name);
+            if text.contains(&placeholder) {
+                *text = text.replace(&placeholder, value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_params_check_and_apply() {
+        let task: TaskConfig = toml::from_str(
+            r#"
+name = "Delete Old Simulators"
+command = ["sh", "-c", "find ~/Library/Developer/CoreSimulator/Devices -maxdepth 1 -mtime +${params.days}"]
+params = [{ name = "days", type = "integer", default = 30, prompt = "Delete simulators unused for how many days?" }]
+"#,
+        )
+        .unwrap();
+        let param = &task.params[0];
+        assert_eq!(param.kind, ParamType::Integer);
+        assert_eq!(param.default_value(), Some(Ok("30".to_string())));
+        assert_eq!(
+            ParamType::Integer.check("x").unwrap_err(),
+            "'x' is not an integer"
+        );
+        assert_eq!(ParamType::Boolean.check("Yes"), Ok("true".to_string()));
+        assert_eq!(task_references(&task), ["days"]);
+
+        let overrides = parse_overrides(&["days=7".to_string()]).unwrap();
+        assert!(parse_overrides(&["days".to_string()]).is_err());
+
+        let mut task = task;
+        apply(&mut task, &overrides);
+        assert_eq!(
+            task.command[2],
+            "find ~/Library/Developer/CoreSimulator/Devices -maxdepth 1 -mtime +7"
+        );
+    }
+}
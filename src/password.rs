@@ -0,0 +1,94 @@
+use dialoguer::theme::{ColorfulTheme, Theme};
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::time::{Duration, Instant};
+
+/// Restores the terminal settings when the prompt ends, however it ends
+struct EchoGuard {
+    original: libc::termios,
+}
+
+impl EchoGuard {
+    fn disable() -> io::Result<Self> {
+        let mut termios = std::mem::MaybeUninit::<libc::termios>::uninit();
+        // SAFETY: tcgetattr initializes the struct when it returns 0
+        if unsafe { libc::tcgetattr(libc::STDIN_FILENO, termios.as_mut_ptr()) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let original = unsafe { termios.assume_init() };
+        let mut silent = original;
+        silent.c_lflag &= !libc::ECHO;
+        silent.c_lflag |= libc::ICANON;
+        // SAFETY: silent is a valid termios copied from the current settings
+        if unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &silent) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Self { original })
+    }
+}
+
+impl Drop for EchoGuard {
+    fn drop(&mut self) {
+        // SAFETY: original was obtained from tcgetattr
+        unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &self.original) };
+    }
+}
+
+/// Read a password without echo, styled like the other prompts. Returns
+/// `None` when no line was entered before the timeout (`None` waits forever).
+pub fn read_password(prompt: &str, timeout: Option<Duration>) -> io::Result<Option<String>> {
+    if !io::stdin().is_terminal() {
+        return Err(io::Error::other("stdin is not a terminal"));
+    }
+
+    let mut rendered = String::new();
+    ColorfulTheme::default()
+        .format_password_prompt(&mut rendered, prompt)
+        .map_err(io::Error::other)?;
+    let mut stderr = io::stderr();
+    write!(stderr, "{}", rendered)?;
+    stderr.flush()?;
+
+    let guard = EchoGuard::disable()?;
+    let ready = wait_for_line(timeout.map(|timeout| Instant::now() + timeout))?;
+    let mut line = String::new();
+    if ready {
+        io::stdin().lock().read_line(&mut line)?;
+    }
+    drop(guard);
+    writeln!(stderr)?;
+
+    Ok(ready.then(|| line.trim_end_matches(['\r', '\n']).to_string()))
+}
+
+/// Wait until a complete line can be read from stdin (the terminal is in
+/// canonical mode), or the deadline passes
+fn wait_for_line(deadline: Option<Instant>) -> io::Result<bool> {
+    loop {
+        let timeout_ms = match deadline {
+            Some(deadline) => {
+                let left = deadline.saturating_duration_since(Instant::now());
+                if left.is_zero() {
+                    return Ok(false);
+                }
+                left.as_millis().min(i32::MAX as u128) as i32
+            }
+            None => -1,
+        };
+        let mut fd = libc::pollfd {
+            fd: libc::STDIN_FILENO,
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        // SAFETY: fd points to a single valid pollfd
+        match unsafe { libc::poll(&mut fd, 1, timeout_ms) } {
+            -1 => {
+                let err = io::Error::last_os_error();
+                if err.kind() != io::ErrorKind::Interrupted {
+                    return Err(err);
+                }
+            }
+            0 => return Ok(false),
+            _ => return Ok(true),
+        }
+    }
+}
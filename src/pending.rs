@@ -0,0 +1,207 @@
+use colored::Colorize;
+use futures::future::join_all;
+use std::path::Path;
+
+use crate::config::TaskConfig;
+use crate::integrations::capture;
+use crate::keychain;
+
+/// Updates waiting in one source, found without installing anything
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pending {
+    pub source: &'static str,
+    pub items: Vec<String>,
+}
+
+/// Query every available source concurrently: Homebrew, macOS software
+/// updates, the App Store and how far the given git repositories are behind
+/// their upstream. Sources whose tool is missing or fails are left out.
+pub async fn discover(repos: &[String]) -> Vec<Pending> {
+    let (brew, macos, app_store, git) =
+        tokio::join!(homebrew(), macos(), app_store(), git_behind(repos));
+    [brew, macos, app_store, git]
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+/// Working directories of tasks that run `git`, whose repositories are
+/// checked for upstream commits
+pub fn git_repos<'a>(tasks: impl IntoIterator<Item = &'a TaskConfig>) -> Vec<String> {
+    let mut repos: Vec<String> = tasks
+        .into_iter()
+        .filter(|task| {
+            task.effective_command()
+                .first()
+                .and_then(|program| Path::new(program).file_name())
+                .is_some_and(|name| name == "git")
+        })
+        .filter_map(|task| task.working_dir.clone())
+        .collect();
+    repos.sort();
+    repos.dedup();
+    repos
+}
+
+async fn homebrew() -> Option<Pending> {
+    if !keychain::command_exists("brew") {
+        return None;
+    }
+    let json = capture("brew", &["outdated", "--json=v2"]).await.ok()?;
+    Some(Pending {
+        source: "Homebrew",
+        items: parse_brew_outdated(&json)?,
+    })
+}
+
+async fn macos() -> Option<Pending> {
+    if !keychain::command_exists("softwareupdate") {
+        return None;
+    }
+    let listing = capture("softwareupdate", &["--list"]).await.ok()?;
+    Some(Pending {
+        source: "macOS",
+        items: parse_software_updates(&listing),
+    })
+}
+
+async fn app_store() -> Option<Pending> {
+    if !keychain::command_exists("mas") {
+        return None;
+    }
+    let listing = capture("mas", &["outdated"]).await.ok()?;
+    Some(Pending {
+        source: "App Store",
+        items: parse_mas_outdated(&listing),
+    })
+}
+
+async fn git_behind(repos: &[String]) -> Option<Pending> {
+    if repos.is_empty() || !keychain::command_exists("git") {
+        return None;
+    }
+    let counts = join_all(repos.iter().map(|repo| async move {
+        let dir = shellexpand::tilde(repo).to_string();
+        capture("git", &["-C", &dir, "fetch", "--quiet"])
+            .await
+            .ok()?;
+        let count = capture(
+            "git",
+            &["-C", &dir, "rev-list", "--count", "HEAD..@{upstream}"],
+        )
+        .await
+        .ok()?;
+        let behind: u32 = count.trim().parse().ok()?;
+        (behind > 0).then(|| format!("{} ({} behind)", repo, behind))
+    }))
+    .await;
+    Some(Pending {
+        source: "Git",
+        items: counts.into_iter().flatten().collect(),
+    })
+}
+
+/// Formula and cask names in `brew outdated --json=v2` output
+fn parse_brew_outdated(json: &str) -> Option<Vec<String>> {
+    let outdated: serde_json::Value = serde_json::from_str(json).ok()?;
+    Some(
+        ["formulae", "casks"]
+            .iter()
+            .filter_map(|kind| outdated[kind].as_array())
+            .flatten()
+            .filter_map(|entry| entry["name"].as_str())
+            .map(str::to_string)
+            .collect(),
+    )
+}
+
+/// Labels in `softwareupdate --list` output (`* Label: macOS Sequoia 15.4`)
+fn parse_software_updates(listing: &str) -> Vec<String> {
+    listing
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("* Label:"))
+        .map(|label| label.trim().to_string())
+        .collect()
+}
+
+/// App names in `mas outdated` output (`497799835 Xcode (15.3 -> 15.4)`)
+fn parse_mas_outdated(listing: &str) -> Vec<String> {
+    listing
+        .lines()
+        .filter_map(|line| line.trim().split_once(' '))
+        .map(|(_, rest)| {
+            rest.rsplit_once(" (")
+                .map_or(rest, |(name, _)| name)
+                .trim()
+                .to_string()
+        })
+        .collect()
+}
+
+/// One line for the notification, e.g. `Homebrew 5 · macOS 1`; `None` when
+/// nothing is pending
+pub fn summary(pending: &[Pending]) -> Option<String> {
+    let parts: Vec<String> = pending
+        .iter()
+        .filter(|p| !p.items.is_empty())
+        .map(|p| format!("{} {}", p.source, p.items.len()))
+        .collect();
+    (!parts.is_empty()).then(|| parts.join(" · "))
+}
+
+/// Print the pending updates per source
+pub fn display(pending: &[Pending]) {
+    println!("{}", "🔎 Pending updates".bright_blue().bold());
+    if pending.is_empty() {
+        println!("{}", "No update sources available.".dimmed());
+    }
+    for source in pending {
+        if source.items.is_empty() {
+            println!("  {} {}", source.source.bold(), "up to date".dimmed());
+            continue;
+        }
+        println!("  {} ({})", source.source.bold(), source.items.len());
+        for item in &source.items {
+            println!("    {}", item);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pending_updates() {
+        let brew = r#"{"formulae":[{"name":"git","installed_versions":["2.44.0"],"current_version":"2.45.0"}],"casks":[{"name":"firefox"}]}"#;
+        assert_eq!(parse_brew_outdated(brew).unwrap(), ["git", "firefox"]);
+
+        let listing = "Software Update Tool\n\nFinding available software\n\
+            Software Update found the following new or updated software:\n\
+            * Label: macOS Sequoia 15.4-24E248\n\
+            \tTitle: macOS Sequoia 15.4, Version: 15.4, Size: 3034113KiB, Recommended: YES, Action: restart,\n";
+        assert_eq!(
+            parse_software_updates(listing),
+            ["macOS Sequoia 15.4-24E248"]
+        );
+
+        let mas = "497799835 Xcode (15.3 -> 15.4)\n1333542190 1Password 7 - Password Manager (7.9.10 -> 7.9.11)\n";
+        assert_eq!(
+            parse_mas_outdated(mas),
+            ["Xcode", "1Password 7 - Password Manager"]
+        );
+
+        let pending = [
+            Pending {
+                source: "Homebrew",
+                items: vec!["git".to_string(), "firefox".to_string()],
+            },
+            Pending {
+                source: "macOS",
+                items: Vec::new(),
+            },
+        ];
+        assert_eq!(summary(&pending).as_deref(), Some("Homebrew 2"));
+        assert_eq!(summary(&pending[1..]), None);
+    }
+}
@@ -0,0 +1,660 @@
+use chrono::{DateTime, Local};
+use futures::future::join_all;
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::config::{Config, Settings, TaskConfig, TaskGroup};
+use crate::error::TideError;
+use crate::executor::{self, SkipReason, TaskStatus};
+use crate::history::RunRecord;
+use crate::{matrix, outputs, suggest};
+
+/// A task selected for the current run together with its group context
+#[derive(Debug, Clone)]
+pub struct PlannedTask {
+    pub task: TaskConfig,
+    pub group: String,
+    pub group_icon: String,
+    pub parallel: bool,
+    pub needs_disk_space: bool,
+    pub heavy: bool,
+    /// Reported as skipped for this reason instead of running, e.g. after
+    /// failing preflight. The task stays in the plan so tasks depending on
+    /// it are skipped too.
+    pub skip: Option<SkipReason>,
+}
+
+/// Reject `--groups`/`--skip-groups` names that match no configured group,
+/// suggesting the closest name for typos
+pub fn check_group_names(config: &Config, names: &[String]) -> Result<(), TideError> {
+    for name in names {
+        if !config.groups.iter().any(|group| &group.name == name) {
+            return Err(TideError::Config(format!(
+                "Unknown group '{}'.{}",
+                name,
+                suggest::hint(name, config.groups.iter().map(|g| g.name.as_str()))
+            )));
+        }
+    }
+    Ok(())
+}
+
+impl PlannedTask {
+    /// `task` of `group`, with the group's scheduling properties
+    pub fn new(group: &TaskGroup, task: &TaskConfig) -> Self {
+        let mut task = task.clone();
+        if task.min_interval.is_none() {
+            task.min_interval = group.min_interval.clone();
+        }
+        PlannedTask {
+            task,
+            group: group.name.clone(),
+            group_icon: group.icon.clone(),
+            parallel: group.parallel,
+            needs_disk_space: group.needs_disk_space,
+            heavy: group.heavy,
+            skip: None,
+        }
+    }
+}
+
+/// Collect the enabled tasks of enabled groups, honoring group filters
+pub fn collect_tasks(
+    config: &Config,
+    groups: Option<&[String]>,
+    skip_groups: Option<&[String]>,
+) -> Vec<PlannedTask> {
+    collect_candidates(config, groups, skip_groups)
+        .into_iter()
+        .filter(|(_, enabled)| *enabled)
+        .map(|(planned, _)| planned)
+        .collect()
+}
+
+/// Every task of the groups passing the filters, disabled ones included,
+/// paired with whether the config enables both it and its group
+pub fn collect_candidates(
+    config: &Config,
+    groups: Option<&[String]>,
+    skip_groups: Option<&[String]>,
+) -> Vec<(PlannedTask, bool)> {
+    let mut tasks = Vec::new();
+    for group in &config.groups {
+        if let Some(groups) = groups
+            && !groups.contains(&group.name)
+        {
+            continue;
+        }
+        if let Some(skip) = skip_groups
+            && skip.contains(&group.name)
+        {
+            continue;
+        }
+
+        for task in &group.tasks {
+            tasks.push((PlannedTask::new(group, task), group.enabled && task.enabled));
+        }
+    }
+    tasks
+}
+
+/// The task named by a `<group>/<task>` reference, planned on its own. It
+/// is selected even when it or its group is disabled, since it was asked
+/// for by name.
+pub fn find_task(config: &Config, reference: &str) -> Result<PlannedTask, TideError> {
+    let Some((group_name, task_name)) = reference.split_once('/') else {
+        return Err(TideError::Config(format!(
+            "Task '{}' must be given as <group>/<task>",
+            reference
+        )));
+    };
+    let Some(group) = config.groups.iter().find(|g| g.name == group_name) else {
+        return Err(TideError::Config(format!(
+            "Unknown group '{}'.{}",
+            group_name,
+            suggest::hint(group_name, config.groups.iter().map(|g| g.name.as_str()))
+        )));
+    };
+    let Some(task) = group.tasks.iter().find(|t| t.name == task_name) else {
+        return Err(TideError::Config(format!(
+            "Unknown task '{}' in group '{}'.{}",
+            task_name,
+            group_name,
+            suggest::hint(task_name, group.tasks.iter().map(|t| t.name.as_str()))
+        )));
+    };
+    let mut planned = PlannedTask::new(group, task);
+    planned.task.enabled = true;
+    Ok(planned)
+}
+
+/// Pre-run prediction for a single planned task
+#[derive(Debug)]
+pub struct TaskForecast<'a> {
+    pub planned: &'a PlannedTask,
+    pub skip: Option<SkipReason>,
+    pub estimate: Option<Duration>,
+}
+
+/// Evaluate the preconditions of all tasks concurrently before the run,
+/// returning the skip reason of each task in order
+pub async fn preflight(tasks: &[PlannedTask]) -> Vec<Option<SkipReason>> {
+    let checks = tasks.iter().map(|planned| {
+        let task = planned.task.clone();
+        tokio::task::spawn_blocking(move || executor::check_preconditions(&task).err())
+    });
+    join_all(checks)
+        .await
+        .into_iter()
+        .map(|check| check.unwrap_or(None))
+        .collect()
+}
+
+/// Skip reasons of tasks whose `min_interval` has not passed since their
+/// last success, given as `(group, task)` → time
+pub fn recently_run(
+    tasks: &[PlannedTask],
+    last_successes: &HashMap<(String, String), DateTime<Local>>,
+    now: DateTime<Local>,
+) -> Vec<Option<SkipReason>> {
+    tasks
+        .iter()
+        .map(|planned| {
+            let interval = planned.task.min_interval()?;
+            let last = last_successes.get(&(planned.group.clone(), planned.task.name.clone()))?;
+            let ago = (now - *last).to_std().unwrap_or_default();
+            (ago < interval).then(|| SkipReason::RanRecently {
+                ago,
+                interval: planned.task.min_interval.clone().unwrap_or_default(),
+            })
+        })
+        .collect()
+}
+
+/// The tasks a `--resume` run still has to do: those that did not succeed
+/// in the last run of `runs` (oldest first), or in the runs before it that
+/// it resumed, including tasks that never started
+pub fn unfinished(tasks: Vec<PlannedTask>, runs: &[RunRecord]) -> Vec<PlannedTask> {
+    let mut statuses: HashMap<(&str, &str), TaskStatus> = HashMap::new();
+    for run in runs.iter().rev() {
+        for task in &run.tasks {
+            statuses
+                .entry((task.group.as_str(), task.name.as_str()))
+                .or_insert(task.status);
+        }
+        if !run.resumed {
+            break;
+        }
+    }
+    tasks
+        .into_iter()
+        .filter(|planned| {
+            statuses.get(&(planned.group.as_str(), planned.task.name.as_str()))
+                != Some(&TaskStatus::Success)
+        })
+        .collect()
+}
+
+/// Pair the preflight results with how long each task usually takes
+pub fn forecast<'a>(
+    tasks: &'a [PlannedTask],
+    skips: &[Option<SkipReason>],
+    estimates: &HashMap<(String, String), Duration>,
+) -> Vec<TaskForecast<'a>> {
+    tasks
+        .iter()
+        .zip(skips)
+        .map(|(planned, skip)| TaskForecast {
+            planned,
+            skip: skip.clone(),
+            estimate: estimates
+                .get(&(planned.group.clone(), planned.task.name.clone()))
+                .copied(),
+        })
+        .collect()
+}
+
+/// How the tasks of a batch are dispatched
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatchMode {
+    /// Tasks run one after another in configuration order
+    Sequential,
+    /// Tasks run concurrently, bounded by `concurrency`
+    Parallel { concurrency: usize },
+    /// Tasks run concurrently, bounded by `concurrency`, each starting once
+    /// its [`dependencies`] finished
+    Graph { concurrency: usize },
+}
+
+/// A wave of tasks executed before the next batch starts
+#[derive(Debug, Clone)]
+pub struct Batch {
+    pub mode: BatchMode,
+    pub tasks: Vec<PlannedTask>,
+}
+
+/// Replace matrix tasks with one task per item, running matrix commands
+/// for their items. Tasks whose matrix command fails are left out with a
+/// warning added to `warnings`.
+pub fn expand_matrices(tasks: Vec<PlannedTask>, warnings: &mut Vec<String>) -> Vec<PlannedTask> {
+    let mut expanded = Vec::with_capacity(tasks.len());
+    for planned in tasks {
+        let Some(spec) = &planned.task.matrix else {
+            expanded.push(planned);
+            continue;
+        };
+        match matrix::items(spec) {
+            Ok(items) => expanded.extend(matrix::expand(&planned, &items)),
+            Err(err) => warnings.push(format!("Skipping '{}': {:#}", planned.task.name, err)),
+        }
+    }
+    expanded
+}
+
+/// Split the selected tasks into the batches the runner executes.
+///
+/// Tasks of parallel groups (and, with `parallel_execution`, every task that
+/// does not need sudo) share one concurrent batch bounded by the smaller of
+/// `max_parallel` and `settings.parallel_limit`. Everything else runs
+/// sequentially first, as do tasks already marked to [skip](PlannedTask::skip).
+/// When a task declares `depends_on`, all tasks form a single
+/// [`BatchMode::Graph`] batch instead.
+pub fn build_batches(
+    tasks: Vec<PlannedTask>,
+    settings: &Settings,
+    max_parallel: usize,
+) -> Vec<Batch> {
+    let concurrency = max_parallel.min(settings.parallel_limit).max(1);
+    if tasks
+        .iter()
+        .any(|planned| !planned.task.depends_on.is_empty())
+    {
+        return vec![Batch {
+            mode: BatchMode::Graph { concurrency },
+            tasks,
+        }];
+    }
+
+    let mut sequential = Vec::new();
+    let mut parallel = Vec::new();
+
+    for planned in tasks {
+        if planned.skip.is_none() && runs_in_parallel(&planned, settings) {
+            parallel.push(planned);
+        } else {
+            sequential.push(planned);
+        }
+    }
+
+    let mut batches = Vec::new();
+    if !sequential.is_empty() {
+        batches.push(Batch {
+            mode: BatchMode::Sequential,
+            tasks: sequential,
+        });
+    }
+    if !parallel.is_empty() {
+        batches.push(Batch {
+            mode: BatchMode::Parallel { concurrency },
+            tasks: parallel,
+        });
+    }
+    batches
+}
+
+/// A task of a graph batch that another one waits for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Dependency {
+    /// Index of the task in the batch
+    pub task: usize,
+    /// The waiting task is skipped unless this one succeeds
+    pub required: bool,
+}
+
+/// Whether the `depends_on` entry `reference` of a task in `own_group`
+/// names the task `name` of `group`. Matrix instances (`name (item)`) match
+/// the name of their task.
+pub fn dependency_matches(reference: &str, own_group: &str, group: &str, name: &str) -> bool {
+    let task = match reference.split_once('/') {
+        Some((target, task)) if target == group => task,
+        _ if own_group == group => reference,
+        _ => return false,
+    };
+    name == task
+        || name
+            .strip_prefix(task)
+            .is_some_and(|rest| rest.starts_with(" (") && rest.ends_with(')'))
+}
+
+/// What each task of a graph batch waits for: the tasks named in its
+/// `depends_on` (those not selected for this run are ignored) and, for tasks
+/// that would otherwise run sequentially and are not marked to skip, the
+/// previous such task so they still run one at a time, in configuration
+/// order where `depends_on` allows. The latter only orders them unless
+/// `skip_optional_on_error` is set.
+pub fn dependencies(tasks: &[PlannedTask], settings: &Settings) -> Vec<Vec<Dependency>> {
+    let mut deps: Vec<Vec<Dependency>> = tasks
+        .iter()
+        .enumerate()
+        .map(|(index, planned)| {
+            tasks
+                .iter()
+                .enumerate()
+                .filter(|&(other, target)| {
+                    other != index
+                        && planned.task.depends_on.iter().any(|reference| {
+                            dependency_matches(
+                                reference,
+                                &planned.group,
+                                &target.group,
+                                &target.task.name,
+                            )
+                        })
+                })
+                .map(|(task, _)| Dependency {
+                    task,
+                    required: true,
+                })
+                .collect()
+        })
+        .collect();
+
+    // Chain the sequential tasks in the earliest order the declared
+    // dependencies allow, so the chain never waits against them
+    let mut placed = vec![false; tasks.len()];
+    let mut previous_sequential = None;
+    while let Some(index) = (0..tasks.len())
+        .find(|&i| !placed[i] && deps[i].iter().all(|dep| placed[dep.task]))
+        .or_else(|| placed.iter().position(|done| !done))
+    {
+        placed[index] = true;
+        if tasks[index].skip.is_some() || runs_in_parallel(&tasks[index], settings) {
+            continue;
+        }
+        if let Some(task) = previous_sequential
+            && deps[index].iter().all(|dep| dep.task != task)
+        {
+            deps[index].push(Dependency {
+                task,
+                required: settings.skip_optional_on_error,
+            });
+        }
+        previous_sequential = Some(index);
+    }
+    deps
+}
+
+/// Whether a task is dispatched to the concurrent batch. Tasks passing
+/// outputs stay sequential so they run in configuration order.
+pub fn runs_in_parallel(planned: &PlannedTask, settings: &Settings) -> bool {
+    (planned.parallel || (settings.parallel_execution && !planned.task.sudo))
+        && !outputs::participates(&planned.task)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Matrix, parse_interval};
+
+    #[test]
+    fn test_build_batches_splits_sudo_and_parallel_groups() {
+        let mut config = Config::default();
+        config.groups[1].parallel = true;
+        let settings = Settings {
+            parallel_limit: 2,
+            ..Settings::default()
+        };
+
+        let batches = build_batches(collect_tasks(&config, None, None), &settings, 4);
+
+        assert_eq!(batches.len(), 2);
+        assert_eq!(batches[0].mode, BatchMode::Sequential);
+        assert_eq!(batches[0].tasks[0].task.name, "macOS Updates");
+        assert_eq!(batches[1].mode, BatchMode::Parallel { concurrency: 2 });
+        assert_eq!(batches[1].tasks.len(), 2);
+    }
+
+    #[test]
+    fn test_dependency_graph() {
+        let mut config = Config::default();
+        config.groups[1].parallel = true;
+        config.groups[1].tasks[1].depends_on = vec!["Update Formulae".to_string()];
+        config.groups[0].tasks.push(TaskConfig {
+            name: "Reboot Check".to_string(),
+            command: vec!["true".to_string()],
+            depends_on: vec!["Homebrew/Upgrade Packages".to_string()],
+            ..TaskConfig::default()
+        });
+        assert!(crate::validate::validate(&config).is_empty());
+
+        let settings = Settings::default();
+        let batches = build_batches(collect_tasks(&config, None, None), &settings, 4);
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].mode, BatchMode::Graph { concurrency: 4 });
+        let names: Vec<&str> = batches[0]
+            .tasks
+            .iter()
+            .map(|p| p.task.name.as_str())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "macOS Updates",
+                "Reboot Check",
+                "Update Formulae",
+                "Upgrade Packages"
+            ]
+        );
+
+        let deps = dependencies(&batches[0].tasks, &settings);
+        let required = |task| Dependency {
+            task,
+            required: true,
+        };
+        assert_eq!(deps[0], []);
+        // Sequential tasks stay in order without failing together
+        assert_eq!(
+            deps[1],
+            [
+                required(3),
+                Dependency {
+                    task: 0,
+                    required: false
+                }
+            ]
+        );
+        assert_eq!(deps[2], []);
+        assert_eq!(deps[3], [required(2)]);
+
+        assert!(dependency_matches("Pull", "Git", "Git", "Pull (tide)"));
+        assert!(!dependency_matches("Pull", "Git", "Homebrew", "Pull"));
+
+        config.groups[1].tasks[0].depends_on = vec!["System Updates/Reboot Check".to_string()];
+        assert_eq!(
+            crate::validate::validate(&config),
+            [
+                "`depends_on` forms a cycle: System Updates/Reboot Check → Homebrew/Upgrade Packages → Homebrew/Update Formulae → System Updates/Reboot Check"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_preflight_checks_every_task_in_order() {
+        let mut tasks = collect_tasks(&Config::default(), None, None);
+        tasks[0].task.check_command = Some("tide-missing-command".to_string());
+        tasks[0].task.kind = None;
+        tasks[1].task.check_command = None;
+        tasks[1].task.check_path = Some("/".to_string());
+
+        let skips = preflight(&tasks).await;
+        assert_eq!(skips.len(), tasks.len());
+        assert_eq!(
+            skips[0],
+            Some(SkipReason::CommandNotFound(
+                "tide-missing-command".to_string()
+            ))
+        );
+        assert_eq!(skips[1], None);
+
+        let forecasts = forecast(&tasks, &skips, &HashMap::new());
+        assert!(forecasts[0].skip.is_some() && forecasts[1].skip.is_none());
+    }
+
+    #[test]
+    fn test_find_single_task() {
+        let mut config = Config::default();
+        config.groups[1].enabled = false;
+
+        let planned = find_task(&config, "Homebrew/Upgrade Packages").unwrap();
+        assert_eq!(planned.group, "Homebrew");
+        assert_eq!(planned.task.name, "Upgrade Packages");
+        assert!(planned.task.enabled);
+
+        let err = find_task(&config, "Homebrew/Upgrade Pakages").unwrap_err();
+        assert!(err.to_string().contains("Did you mean 'Upgrade Packages'?"));
+        assert!(find_task(&config, "Upgrade Packages").is_err());
+    }
+
+    #[test]
+    fn test_min_interval_skips_recent_successes() {
+        assert_eq!(parse_interval("24h"), Some(Duration::from_secs(86_400)));
+        assert_eq!(parse_interval("1d12h"), Some(Duration::from_secs(129_600)));
+        assert_eq!(parse_interval("90"), None);
+        assert_eq!(parse_interval("1y"), None);
+
+        let mut config = Config::default();
+        config.groups[1].min_interval = Some("24h".to_string());
+        config.groups[1].tasks[0].min_interval = Some("1h".to_string());
+        let tasks = collect_tasks(&config, Some(&["Homebrew".to_string()]), None);
+
+        let now = Local::now();
+        let last: HashMap<(String, String), DateTime<Local>> = tasks
+            .iter()
+            .map(|planned| {
+                (
+                    (planned.group.clone(), planned.task.name.clone()),
+                    now - chrono::Duration::hours(3),
+                )
+            })
+            .collect();
+        let skips = recently_run(&tasks, &last, now);
+        assert_eq!(skips[0], None);
+        assert!(matches!(
+            &skips[1],
+            Some(SkipReason::RanRecently { interval, .. }) if interval == "24h"
+        ));
+        assert_eq!(
+            skips[1].as_ref().unwrap().to_string(),
+            "Succeeded 3h 0m ago, within its min_interval of 24h"
+        );
+        assert!(
+            recently_run(&tasks, &HashMap::new(), now)
+                .iter()
+                .all(Option::is_none)
+        );
+    }
+
+    #[test]
+    fn test_unfinished_tasks_of_resumed_runs() {
+        use crate::history::TaskRecord;
+
+        let run = |tasks: &[(&str, TaskStatus)], resumed| RunRecord {
+            started_at: Local::now(),
+            duration_secs: 0.0,
+            tasks: tasks
+                .iter()
+                .map(|&(name, status)| TaskRecord {
+                    group: "Homebrew".to_string(),
+                    name: name.to_string(),
+                    status,
+                    duration_secs: 0.0,
+                    reason: None,
+                    started_at: None,
+                    output_hash: None,
+                })
+                .collect(),
+            resumed,
+        };
+        let config = Config::default();
+        let tasks = || collect_tasks(&config, Some(&["Homebrew".to_string()]), None);
+        let names = |tasks: Vec<PlannedTask>| -> Vec<String> {
+            tasks.into_iter().map(|planned| planned.task.name).collect()
+        };
+
+        let failed = run(
+            &[
+                ("Update Formulae", TaskStatus::Success),
+                ("Upgrade Packages", TaskStatus::Failed),
+            ],
+            false,
+        );
+        assert_eq!(
+            names(unfinished(tasks(), std::slice::from_ref(&failed))),
+            ["Upgrade Packages"]
+        );
+        let aborted = run(&[("Update Formulae", TaskStatus::Failed)], false);
+        assert_eq!(
+            names(unfinished(tasks(), &[aborted])),
+            ["Update Formulae", "Upgrade Packages"]
+        );
+
+        let resumed = run(&[("Upgrade Packages", TaskStatus::Success)], true);
+        assert!(unfinished(tasks(), &[failed.clone(), resumed]).is_empty());
+        let fresh = run(&[("Upgrade Packages", TaskStatus::Success)], false);
+        assert_eq!(
+            names(unfinished(tasks(), &[failed, fresh])),
+            ["Update Formulae"]
+        );
+    }
+
+    #[test]
+    fn test_expand_matrices() {
+        let mut config = Config::default();
+        config.groups[1].tasks[0].matrix =
+            Some(Matrix::Items(vec!["main".to_string(), "dev".to_string()]));
+        config.groups[1].tasks[1].matrix = Some(Matrix::Command {
+            command: vec!["false".to_string()],
+        });
+        let tasks = collect_tasks(&config, Some(&[config.groups[1].name.clone()]), None);
+        let name = config.groups[1].tasks[0].name.clone();
+
+        let mut warnings = Vec::new();
+        let expanded = expand_matrices(tasks, &mut warnings);
+        let names: Vec<&str> = expanded.iter().map(|p| p.task.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [format!("{} (main)", name), format!("{} (dev)", name)]
+        );
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].starts_with(&format!(
+            "Skipping '{}': matrix command exited",
+            config.groups[1].tasks[1].name
+        )));
+    }
+
+    #[test]
+    fn test_collect_candidates() {
+        let mut config = Config::default();
+        config.groups[1].enabled = false;
+        let group = config.groups[1].name.clone();
+
+        let candidates = collect_candidates(&config, None, None);
+        let total: usize = config.groups.iter().map(|g| g.tasks.len()).sum();
+        assert_eq!(candidates.len(), total);
+        assert!(
+            candidates
+                .iter()
+                .filter(|(planned, _)| planned.group == group)
+                .all(|(_, enabled)| !enabled)
+        );
+        assert_eq!(
+            collect_tasks(&config, None, None).len(),
+            candidates.iter().filter(|(_, enabled)| *enabled).count()
+        );
+        assert!(
+            collect_candidates(&config, None, Some(std::slice::from_ref(&group)))
+                .iter()
+                .all(|(planned, _)| planned.group != group)
+        );
+    }
+}
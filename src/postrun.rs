@@ -0,0 +1,142 @@
+use anyhow::{Context, Result};
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use crate::config::PostRun;
+use crate::executor::{TaskResult, TaskStatus};
+use crate::launcher;
+use crate::runenv::RunEnv;
+
+/// Plain-text run summary for the clipboard: the result line followed by
+/// one line per task
+pub fn summary_text(results: &[TaskResult], duration: Duration) -> String {
+    let mut text = launcher::result_line(results, duration);
+    for result in results {
+        let status = match result.status {
+            TaskStatus::Success => "✓",
+            TaskStatus::Failed => "✗",
+            TaskStatus::Skipped => "○",
+            TaskStatus::Cancelled => "⊘",
+        };
+        text.push_str(&format!(
+            "\n{} {} › {} ({}s)",
+            status,
+            result.group,
+            result.name,
+            result.duration.as_secs()
+        ));
+    }
+    text
+}
+
+/// Perform the `[post_run]` actions whose trigger matches the run outcome,
+/// returning a description of each action that failed. Commands see the run's
+/// `TIDE_*` variables.
+pub fn run(post_run: &PostRun, success: bool, summary: &str, run_env: &RunEnv) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    if post_run.copy_summary.fires(success)
+        && let Err(err) = copy_to_clipboard(summary)
+    {
+        problems.push(format!("Copying the summary failed: {}", err));
+    }
+
+    if post_run.open_log.fires(success) {
+        match &run_env.log_file {
+            Some(path) => {
+                if let Err(err) = open(path) {
+                    problems.push(format!("Opening {} failed: {}", path.display(), err));
+                }
+            }
+            None => problems.push("open_log is set but no log_file is configured".to_string()),
+        }
+    }
+
+    for hook in post_run
+        .commands
+        .iter()
+        .filter(|hook| hook.when.fires(success))
+    {
+        if let Err(err) = run_command(&hook.command, run_env) {
+            problems.push(format!("`{}` failed: {}", hook.command.join(" "), err));
+        }
+    }
+    problems
+}
+
+fn copy_to_clipboard(text: &str) -> Result<()> {
+    let mut child = Command::new("pbcopy")
+        .stdin(Stdio::piped())
+        .spawn()
+        .context("Failed to start pbcopy")?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(text.as_bytes())?;
+    }
+    child.wait()?;
+    Ok(())
+}
+
+fn open(path: &Path) -> Result<()> {
+    let status = Command::new("open").arg(path).status()?;
+    anyhow::ensure!(status.success(), "open exited with {}", status);
+    Ok(())
+}
+
+fn run_command(command: &[String], run_env: &RunEnv) -> Result<()> {
+    let (program, args) = command.split_first().context("empty command")?;
+    let status = Command::new(program)
+        .args(args)
+        .envs(run_env.vars())
+        .stdin(Stdio::null())
+        .status()
+        .with_context(|| format!("Failed to execute {}", program))?;
+    anyhow::ensure!(status.success(), "exited with {}", status);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Config, PostRunTrigger};
+    use std::time::Instant;
+
+    #[test]
+    fn test_post_run_triggers_and_summary() {
+        let config: Config = toml::from_str(
+            r#"
+[post_run]
+copy_summary = "failure"
+
+[[post_run.commands]]
+command = ["mackup", "backup"]
+when = "success"
+
+[[post_run.commands]]
+command = ["say", "done"]
+"#,
+        )
+        .unwrap();
+        let post_run = &config.post_run;
+        assert!(post_run.copy_summary.fires(false) && !post_run.copy_summary.fires(true));
+        assert_eq!(post_run.open_log, PostRunTrigger::Never);
+        assert!(!post_run.commands[0].when.fires(false));
+        assert_eq!(post_run.commands[1].when, PostRunTrigger::Always);
+
+        let results = [TaskResult {
+            name: "Update".to_string(),
+            group: "Homebrew".to_string(),
+            group_icon: String::new(),
+            status: TaskStatus::Failed,
+            started: Instant::now(),
+            duration: Duration::from_secs(4),
+            output: None,
+            exit_code: Some(1),
+            failure: None,
+            log_line: None,
+        }];
+        let summary = summary_text(&results, Duration::from_secs(4));
+        assert!(summary.ends_with("\n✗ Homebrew › Update (4s)"));
+    }
+}
@@ -0,0 +1,32 @@
+use std::process::{Child, Command, Stdio};
+
+/// Keeps the Mac awake for as long as the guard is alive.
+///
+/// Spawns `caffeinate -dimsu -w <pid>`, so the assertion is also released
+/// if Tide exits without dropping the guard.
+pub struct SleepGuard {
+    child: Child,
+}
+
+impl SleepGuard {
+    /// Take the power assertion; returns `None` if `caffeinate` is unavailable
+    pub fn acquire() -> Option<Self> {
+        Command::new("caffeinate")
+            .arg("-dimsu")
+            .arg("-w")
+            .arg(std::process::id().to_string())
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .ok()
+            .map(|child| Self { child })
+    }
+}
+
+impl Drop for SleepGuard {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
@@ -0,0 +1,250 @@
+use serde::{Deserialize, Serialize};
+use toml::{Table, Value};
+
+use crate::error::TideError;
+use crate::{overlay, suggest};
+
+/// Variation of the config: a `[profiles.<name>]` section selected with
+/// `--profile <name>`, or a `[host."<name>"]` section applied on that machine
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct Profile {
+    /// Groups switched on
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub enable_groups: Vec<String>,
+    /// Groups switched off
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub disable_groups: Vec<String>,
+    /// Tasks switched on, as `"<group>/<task>"`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub enable_tasks: Vec<String>,
+    /// Tasks switched off, as `"<group>/<task>"`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub disable_tasks: Vec<String>,
+    /// Settings replaced
+    #[serde(default, skip_serializing_if = "Table::is_empty")]
+    pub settings: Table,
+}
+
+impl Profile {
+    /// Group and task toggles with the state they set
+    pub fn toggles(&self) -> Vec<(Toggle<'_>, bool)> {
+        let mut toggles = Vec::new();
+        for (names, enabled) in [(&self.enable_groups, true), (&self.disable_groups, false)] {
+            toggles.extend(names.iter().map(|name| (Toggle::Group(name), enabled)));
+        }
+        for (references, enabled) in [(&self.enable_tasks, true), (&self.disable_tasks, false)] {
+            for reference in references {
+                let toggle = match reference.split_once('/') {
+                    Some((group, task)) => Toggle::Task(group, task),
+                    None => Toggle::Invalid(reference),
+                };
+                toggles.push((toggle, enabled));
+            }
+        }
+        toggles
+    }
+}
+
+/// What a profile switches on or off
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Toggle<'a> {
+    Group(&'a str),
+    Task(&'a str, &'a str),
+    /// A task reference without a `/`
+    Invalid(&'a str),
+}
+
+/// Apply the `[profiles.<name>]` section of a config table to it: its
+/// settings replace single keys and its groups and tasks are switched on or
+/// off
+pub fn apply(config: &mut Table, name: &str) -> Result<(), TideError> {
+    let profiles = config.get("profiles").and_then(Value::as_table);
+    let Some(profile) = profiles.and_then(|profiles| profiles.get(name)).cloned() else {
+        let known = profiles.into_iter().flat_map(|profiles| profiles.keys());
+        return Err(TideError::Config(format!(
+            "Unknown profile '{}'.{}",
+            name,
+            suggest::hint(name, known.map(String::as_str))
+        )));
+    };
+    apply_section(config, profile, &format!("Profile '{}'", name))
+}
+
+/// Apply the `[host."<name>"]` section naming this machine, if there is one
+pub fn apply_host(config: &mut Table, host: &str) -> Result<(), TideError> {
+    let section = config
+        .get("host")
+        .and_then(Value::as_table)
+        .and_then(|hosts| hosts.iter().find(|(name, _)| host_matches(name, host)))
+        .map(|(name, section)| (name.clone(), section.clone()));
+    match section {
+        Some((name, section)) => apply_section(config, section, &format!("Host '{}'", name)),
+        None => Ok(()),
+    }
+}
+
+/// Whether a `[host."<name>"]` section is meant for the machine with the
+/// short host name `host`; case and a `.local` suffix do not matter
+pub fn host_matches(section: &str, host: &str) -> bool {
+    let section = section.to_lowercase();
+    section.strip_suffix(".local").unwrap_or(&section) == host
+}
+
+fn apply_section(config: &mut Table, section: Value, label: &str) -> Result<(), TideError> {
+    let profile: Profile = section
+        .try_into()
+        .map_err(|err| TideError::Config(format!("{} is invalid: {}", label, err)))?;
+
+    let known: Vec<(String, Vec<String>)> = config
+        .get("groups")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|group| {
+            let tasks = group
+                .get("tasks")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(|task| Some(task.get("name")?.as_str()?.to_string()))
+                .collect();
+            Some((group.get("name")?.as_str()?.to_string(), tasks))
+        })
+        .collect();
+    let group_names = || known.iter().map(|(name, _)| name.as_str());
+
+    let mut groups: Vec<Table> = Vec::new();
+    for (toggle, enabled) in profile.toggles() {
+        let (group, task) = match toggle {
+            Toggle::Group(group) => (group, None),
+            Toggle::Task(group, task) => (group, Some(task)),
+            Toggle::Invalid(reference) => {
+                return Err(TideError::Config(format!(
+                    "{} refers to task '{}'; write it as \"<group>/<task>\"",
+                    label, reference
+                )));
+            }
+        };
+        let Some((_, tasks)) = known.iter().find(|(name, _)| name == group) else {
+            return Err(TideError::Config(format!(
+                "{} refers to unknown group '{}'.{}",
+                label,
+                group,
+                suggest::hint(group, group_names())
+            )));
+        };
+        if let Some(task) = task
+            && !tasks.iter().any(|name| name == task)
+        {
+            return Err(TideError::Config(format!(
+                "{} refers to unknown task '{}/{}'.{}",
+                label,
+                group,
+                task,
+                suggest::hint(task, tasks.iter().map(String::as_str))
+            )));
+        }
+
+        let position = match groups
+            .iter()
+            .position(|g| g["name"].as_str() == Some(group))
+        {
+            Some(position) => position,
+            None => {
+                let mut table = Table::new();
+                table.insert("name".to_string(), Value::String(group.to_string()));
+                groups.push(table);
+                groups.len() - 1
+            }
+        };
+        let entry = &mut groups[position];
+        match task {
+            None => {
+                entry.insert("enabled".to_string(), Value::Boolean(enabled));
+            }
+            Some(task) => {
+                let mut table = Table::new();
+                table.insert("name".to_string(), Value::String(task.to_string()));
+                table.insert("enabled".to_string(), Value::Boolean(enabled));
+                entry
+                    .entry("tasks")
+                    .or_insert_with(|| Value::Array(Vec::new()))
+                    .as_array_mut()
+                    .expect("tasks is an array")
+                    .push(Value::Table(table));
+            }
+        }
+    }
+
+    let mut layer = Table::new();
+    layer.insert("settings".to_string(), Value::Table(profile.settings));
+    layer.insert(
+        "groups".to_string(),
+        Value::Array(groups.into_iter().map(Value::Table).collect()),
+    );
+    overlay::apply(config, layer, &[], &mut Vec::new());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn test_apply_profile() {
+        let text = r#"
+[settings]
+parallel_limit = 4
+
+[[groups]]
+name = "Homebrew"
+
+[[groups.tasks]]
+name = "Update"
+command = ["brew", "update"]
+
+[[groups.tasks]]
+name = "Cleanup"
+command = ["brew", "cleanup"]
+
+[[groups]]
+name = "Work VPN"
+enabled = false
+
+[profiles.work]
+enable_groups = ["Work VPN"]
+disable_groups = ["Homebrew"]
+
+[profiles.work.settings]
+parallel_limit = 8
+
+[profiles.typo]
+enable_groups = ["Homebew"]
+
+[host."Studio.local"]
+disable_tasks = ["Homebrew/Cleanup"]
+"#;
+        let mut table: Table = toml::from_str(text).unwrap();
+        apply(&mut table, "work").unwrap();
+        let config: Config = Value::Table(table).try_into().unwrap();
+        assert_eq!(config.settings.parallel_limit, 8);
+        assert!(!config.groups[0].enabled);
+        assert!(config.groups[1].enabled);
+
+        let mut table: Table = toml::from_str(text).unwrap();
+        let err = apply(&mut table, "wrk").unwrap_err().to_string();
+        assert!(err.contains("Did you mean 'work'?"), "{}", err);
+        let err = apply(&mut table, "typo").unwrap_err().to_string();
+        assert!(err.contains("unknown group 'Homebew'"), "{}", err);
+
+        apply_host(&mut table, "laptop").unwrap();
+        apply_host(&mut table, "studio").unwrap();
+        let config: Config = Value::Table(table).try_into().unwrap();
+        assert!(config.groups[0].enabled);
+        assert!(config.groups[0].tasks[0].enabled);
+        assert!(!config.groups[0].tasks[1].enabled);
+        assert!(host_matches("my-macbook", "my-macbook"));
+        assert!(!host_matches("my-macbook-pro", "my-macbook"));
+    }
+}
@@ -0,0 +1,334 @@
+use indicatif::{MultiProgress, ProgressBar, ProgressState, ProgressStyle};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::executor::{self, TaskResult, TaskStatus};
+use crate::palette::Paint;
+use crate::plan::{Batch, BatchMode};
+
+/// A task that has not finished yet, with its historical duration if known
+#[derive(Debug, Clone)]
+struct PendingTask {
+    group: String,
+    name: String,
+    batch: usize,
+    estimate: Option<Duration>,
+}
+
+#[derive(Debug, Default)]
+struct Tracker {
+    pending: Vec<PendingTask>,
+    /// Concurrency per batch; 1 for sequential batches
+    concurrency: Vec<usize>,
+    finished: Vec<Duration>,
+    /// When the run is expected to end, if anything is known about it
+    expected_end: Option<Instant>,
+}
+
+impl Tracker {
+    /// Expected time left for the pending tasks.
+    ///
+    /// Tasks without history are assumed to take as long as the average
+    /// finished task; parallel batches are divided by their concurrency.
+    fn remaining(&self) -> Option<Duration> {
+        let average = (!self.finished.is_empty())
+            .then(|| self.finished.iter().sum::<Duration>() / self.finished.len() as u32);
+
+        let mut per_batch = vec![Duration::ZERO; self.concurrency.len()];
+        let mut known = false;
+        for task in &self.pending {
+            if let Some(estimate) = task.estimate.or(average) {
+                per_batch[task.batch] += estimate;
+                known = true;
+            }
+        }
+        known.then(|| {
+            per_batch
+                .iter()
+                .zip(&self.concurrency)
+                .map(|(total, concurrency)| *total / *concurrency as u32)
+                .sum()
+        })
+    }
+}
+
+/// Header bar of a group and the spinners of its started tasks
+struct GroupBars {
+    label: String,
+    total: u64,
+    failed: usize,
+    header: Option<ProgressBar>,
+    spinners: Vec<ProgressBar>,
+}
+
+/// Run-level progress display.
+///
+/// A pinned top-level bar ("Task 7/23 • 12m elapsed • ~18m remaining") sits
+/// above one header bar per group with its tasks' spinners indented beneath.
+/// A group collapses into its header once all of its tasks have finished.
+pub struct RunProgress {
+    multi: MultiProgress,
+    bar: ProgressBar,
+    tracker: Arc<Mutex<Tracker>>,
+    groups: Mutex<HashMap<String, GroupBars>>,
+}
+
+impl RunProgress {
+    /// Insert the overall bar above any spinners of `multi`
+    pub fn new(
+        multi: &MultiProgress,
+        batches: &[Batch],
+        estimates: &HashMap<(String, String), Duration>,
+    ) -> Self {
+        let mut tracker = Tracker::default();
+        let mut groups: HashMap<String, GroupBars> = HashMap::new();
+        for (index, batch) in batches.iter().enumerate() {
+            tracker.concurrency.push(match batch.mode {
+                BatchMode::Sequential => 1,
+                BatchMode::Parallel { concurrency } | BatchMode::Graph { concurrency } => {
+                    concurrency.max(1)
+                }
+            });
+            for planned in &batch.tasks {
+                groups
+                    .entry(planned.group.clone())
+                    .or_insert_with(|| GroupBars {
+                        label: group_label(&planned.group, &planned.group_icon),
+                        total: 0,
+                        failed: 0,
+                        header: None,
+                        spinners: Vec::new(),
+                    })
+                    .total += 1;
+                tracker.pending.push(PendingTask {
+                    group: planned.group.clone(),
+                    name: planned.task.name.clone(),
+                    batch: index,
+                    estimate: estimates
+                        .get(&(planned.group.clone(), planned.task.name.clone()))
+                        .copied(),
+                });
+            }
+        }
+        let total = tracker.pending.len() as u64;
+        tracker.expected_end = tracker.remaining().map(|left| Instant::now() + left);
+        let tracker = Arc::new(Mutex::new(tracker));
+
+        let remaining = Arc::clone(&tracker);
+        let style = ProgressStyle::with_template(
+            "{prefix:.bold} Task {pos}/{len} • {elapsed_short} elapsed{remaining}",
+        )
+        .unwrap()
+        .with_key(
+            "elapsed_short",
+            |state: &ProgressState, w: &mut dyn std::fmt::Write| {
+                let _ = w.write_str(&format_short(state.elapsed()));
+            },
+        )
+        .with_key(
+            "remaining",
+            move |_: &ProgressState, w: &mut dyn std::fmt::Write| {
+                let expected_end = remaining.lock().ok().and_then(|t| t.expected_end);
+                if let Some(end) = expected_end {
+                    let left = end.saturating_duration_since(Instant::now());
+                    let _ = write!(w, " • ~{} remaining", format_short(left));
+                }
+            },
+        );
+
+        let bar = multi.insert(0, ProgressBar::new(total));
+        bar.set_style(style);
+        bar.set_prefix("🌊");
+        bar.enable_steady_tick(Duration::from_secs(1));
+        Self {
+            multi: multi.clone(),
+            bar,
+            tracker,
+            groups: Mutex::new(groups),
+        }
+    }
+
+    /// Spinner for a task, indented beneath its group's header bar
+    pub fn task_spinner(&self, group: &str) -> ProgressBar {
+        let Ok(mut groups) = self.groups.lock() else {
+            return ProgressBar::hidden();
+        };
+        let Some(bars) = groups.get_mut(group) else {
+            return ProgressBar::hidden();
+        };
+
+        let header = bars.header.get_or_insert_with(|| {
+            let header = self.multi.add(ProgressBar::new(bars.total));
+            header.set_style(
+                ProgressStyle::with_template("{prefix}{msg:.bold} {pos}/{len}").unwrap(),
+            );
+            header.set_prefix("▾ ");
+            header.set_message(bars.label.clone());
+            header
+        });
+        let after = bars.spinners.last().unwrap_or(header).clone();
+        let pb = self.multi.insert_after(&after, ProgressBar::new_spinner());
+        pb.set_style(executor::spinner_style());
+        pb.set_prefix("   ");
+        pb.enable_steady_tick(Duration::from_millis(120));
+        bars.spinners.push(pb.clone());
+        pb
+    }
+
+    /// Count a finished (or skipped) task and re-estimate the time left
+    pub fn task_finished(&self, result: &TaskResult) {
+        if let Ok(mut tracker) = self.tracker.lock() {
+            if let Some(index) = tracker
+                .pending
+                .iter()
+                .position(|t| t.group == result.group && t.name == result.name)
+            {
+                tracker.pending.remove(index);
+            }
+            if result.duration > Duration::ZERO {
+                tracker.finished.push(result.duration);
+            }
+            tracker.expected_end = tracker.remaining().map(|left| Instant::now() + left);
+        }
+        self.bar.inc(1);
+        self.group_task_finished(result);
+    }
+
+    /// Advance the group's header and collapse the group when it is done
+    fn group_task_finished(&self, result: &TaskResult) {
+        let Ok(mut groups) = self.groups.lock() else {
+            return;
+        };
+        let Some(bars) = groups.get_mut(&result.group) else {
+            return;
+        };
+        if result.status == TaskStatus::Failed {
+            bars.failed += 1;
+        }
+        let Some(header) = &bars.header else {
+            return;
+        };
+
+        header.inc(1);
+        if header.position() < bars.total {
+            return;
+        }
+        for spinner in bars.spinners.drain(..) {
+            self.multi.remove(&spinner);
+        }
+        if bars.failed == 0 {
+            header.set_prefix(format!("{} ", "✓".success()));
+        } else {
+            header.set_prefix(format!("{} ", "✗".failure()));
+        }
+        header.finish();
+    }
+
+    /// Remove the bar once the run is over
+    pub fn finish(&self) {
+        self.bar.finish_and_clear();
+    }
+}
+
+fn group_label(name: &str, icon: &str) -> String {
+    let icon = icon.trim();
+    if icon.is_empty() {
+        name.to_string()
+    } else {
+        format!("{} {}", icon, name)
+    }
+}
+
+/// Compact duration for the overall bar: "45s", "12m", "1h 05m"
+fn format_short(d: Duration) -> String {
+    let secs = d.as_secs();
+    if secs < 60 {
+        format!("{}s", secs)
+    } else if secs < 3600 {
+        format!("{}m", secs / 60)
+    } else {
+        format!("{}h {:02}m", secs / 3600, (secs % 3600) / 60)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pending(batch: usize, estimate: Option<u64>) -> PendingTask {
+        PendingTask {
+            group: "Homebrew".to_string(),
+            name: "Update".to_string(),
+            batch,
+            estimate: estimate.map(Duration::from_secs),
+        }
+    }
+
+    #[test]
+    fn test_remaining_estimate() {
+        let mut tracker = Tracker {
+            pending: vec![
+                pending(0, Some(60)),
+                pending(1, Some(120)),
+                pending(1, None),
+            ],
+            concurrency: vec![1, 2],
+            ..Tracker::default()
+        };
+        // Unknown task contributes nothing until something has finished
+        assert_eq!(tracker.remaining(), Some(Duration::from_secs(120)));
+
+        tracker.finished.push(Duration::from_secs(40));
+        assert_eq!(tracker.remaining(), Some(Duration::from_secs(140)));
+
+        tracker.pending.clear();
+        assert_eq!(tracker.remaining(), None);
+
+        assert_eq!(format_short(Duration::from_secs(45)), "45s");
+        assert_eq!(format_short(Duration::from_secs(12 * 60 + 5)), "12m");
+        assert_eq!(format_short(Duration::from_secs(3900)), "1h 05m");
+    }
+
+    #[test]
+    fn test_group_collapses_when_finished() {
+        use crate::config::{Config, Settings};
+        use crate::plan;
+        use indicatif::ProgressDrawTarget;
+
+        let config = Config::default();
+        let batches = plan::build_batches(
+            plan::collect_tasks(&config, None, None),
+            &Settings::default(),
+            1,
+        );
+        let homebrew: Vec<_> = batches
+            .iter()
+            .flat_map(|b| &b.tasks)
+            .filter(|t| t.group == "Homebrew")
+            .collect();
+        assert!(homebrew.len() > 1);
+
+        let multi = MultiProgress::with_draw_target(ProgressDrawTarget::hidden());
+        let progress = RunProgress::new(&multi, &batches, &HashMap::new());
+        for planned in &homebrew {
+            progress.task_spinner(&planned.group);
+        }
+        let spinners =
+            |progress: &RunProgress| progress.groups.lock().unwrap()["Homebrew"].spinners.len();
+        assert_eq!(spinners(&progress), homebrew.len());
+
+        for planned in &homebrew {
+            progress.task_finished(&TaskResult::aborted(
+                planned.task.name.clone(),
+                planned.group.clone(),
+                planned.group_icon.clone(),
+            ));
+        }
+        assert_eq!(spinners(&progress), 0);
+        let groups = progress.groups.lock().unwrap();
+        assert!(groups["Homebrew"].header.as_ref().unwrap().is_finished());
+        assert!(groups["System Updates"].header.is_none());
+        assert_eq!(progress.bar.position(), homebrew.len() as u64);
+    }
+}
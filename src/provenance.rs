@@ -0,0 +1,130 @@
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::config::TaskConfig;
+use crate::import;
+
+/// Task fields that do not change what a task executes
+const COSMETIC_FIELDS: &[&str] = &[
+    "name",
+    "icon",
+    "description",
+    "enabled",
+    "required",
+    "timeout",
+    "log_output",
+];
+
+/// Fingerprints of remotely sourced tasks the user approved to run, so a
+/// changed command is confirmed again before it executes
+#[derive(Debug)]
+pub struct Approvals {
+    path: PathBuf,
+    approved: BTreeMap<String, String>,
+}
+
+impl Approvals {
+    /// Load the approvals file; a missing file means nothing is approved yet
+    pub fn load(path: PathBuf) -> Result<Self> {
+        let approved = match fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents)
+                .with_context(|| format!("Failed to parse {}", path.display()))?,
+            Err(_) => BTreeMap::new(),
+        };
+        Ok(Self { path, approved })
+    }
+
+    /// Open the approvals file at its default location
+    pub fn open_default() -> Result<Self> {
+        Self::load(Self::default_path()?)
+    }
+
+    /// Default location: `~/.local/share/tide/approved-tasks.json`
+    pub fn default_path() -> Result<PathBuf> {
+        Ok(dirs::home_dir()
+            .context("Could not determine home directory")?
+            .join(".local")
+            .join("share")
+            .join("tide")
+            .join("approved-tasks.json"))
+    }
+
+    /// Whether the task runs exactly what was approved last time
+    pub fn is_approved(&self, group: &str, task: &TaskConfig) -> bool {
+        self.approved.get(&key(group, task)) == Some(&fingerprint(task))
+    }
+
+    pub fn approve(&mut self, group: &str, task: &TaskConfig) {
+        self.approved.insert(key(group, task), fingerprint(task));
+    }
+
+    pub fn save(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        fs::write(&self.path, serde_json::to_string_pretty(&self.approved)?)
+            .with_context(|| format!("Failed to write {}", self.path.display()))
+    }
+}
+
+/// Whether the task was imported from a URL (catalog tasks ship with tide)
+pub fn is_remote(task: &TaskConfig) -> bool {
+    task.source
+        .as_deref()
+        .is_some_and(|source| source.starts_with("https://"))
+}
+
+fn key(group: &str, task: &TaskConfig) -> String {
+    format!("{}/{}", group, task.name)
+}
+
+/// SHA-256 over everything that determines what the task executes: the
+/// command, built-in type and its options, sudo, environment, working
+/// directory, preconditions and the source itself
+pub fn fingerprint(task: &TaskConfig) -> String {
+    let mut value = serde_json::to_value(task).unwrap_or(Value::Null);
+    if let Value::Object(fields) = &mut value {
+        for field in COSMETIC_FIELDS {
+            fields.remove(*field);
+        }
+    }
+    // JSON objects keep their keys sorted, so the environment hashes stably
+    import::sha256_hex(&value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_approval_tracks_command_changes() {
+        let path = std::env::temp_dir().join(format!("tide-approvals-{}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let mut task = TaskConfig {
+            name: "Cleanup".to_string(),
+            command: vec!["brew".to_string(), "cleanup".to_string()],
+            source: Some("https://example.com/recipes.toml".to_string()),
+            ..TaskConfig::default()
+        };
+        let mut approvals = Approvals::load(path.clone()).unwrap();
+        assert!(!approvals.is_approved("Homebrew", &task));
+        approvals.approve("Homebrew", &task);
+        approvals.save().unwrap();
+
+        let approvals = Approvals::load(path.clone()).unwrap();
+        assert!(is_remote(&task));
+        assert!(approvals.is_approved("Homebrew", &task));
+        task.description = "Reworded".to_string();
+        task.timeout = Some(60);
+        assert!(approvals.is_approved("Homebrew", &task));
+        task.command.push("--prune=all".to_string());
+        assert!(!approvals.is_approved("Homebrew", &task));
+
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,144 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::process::{Command, Stdio};
+
+use crate::config::TaskConfig;
+
+/// External task runners whose recipes can be exposed as tide tasks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum RecipeRunner {
+    /// `just = "recipe"` runs a recipe from a justfile
+    Just,
+    /// `taskfile = "name"` runs a task from a Taskfile via `task`
+    Taskfile,
+}
+
+impl RecipeRunner {
+    /// Executable that runs the recipes
+    pub fn program(self) -> &'static str {
+        match self {
+            RecipeRunner::Just => "just",
+            RecipeRunner::Taskfile => "task",
+        }
+    }
+
+    /// Config key naming the recipe
+    pub fn key(self) -> &'static str {
+        match self {
+            RecipeRunner::Just => "just",
+            RecipeRunner::Taskfile => "taskfile",
+        }
+    }
+}
+
+/// Recipes available from one runner in one directory
+#[derive(Debug, Clone)]
+pub struct RecipeSource {
+    pub runner: RecipeRunner,
+    /// The tasks' `working_dir`, or `None` for the current directory
+    pub dir: Option<String>,
+    pub recipes: Vec<String>,
+}
+
+impl RecipeSource {
+    pub fn contains(&self, recipe: &str) -> bool {
+        self.recipes.iter().any(|r| r == recipe)
+    }
+}
+
+/// Discover the recipes of every runner/directory pair the given tasks use.
+///
+/// Sources whose runner is not installed or has no recipe file are left out.
+pub fn discover<'a>(tasks: impl IntoIterator<Item = &'a TaskConfig>) -> Vec<RecipeSource> {
+    let mut keys: Vec<(RecipeRunner, Option<String>)> = tasks
+        .into_iter()
+        .filter_map(|task| {
+            task.recipe()
+                .map(|(runner, _)| (runner, task.working_dir.clone()))
+        })
+        .collect();
+    keys.sort();
+    keys.dedup();
+
+    keys.into_iter()
+        .filter_map(|(runner, dir)| {
+            list_recipes(runner, dir.as_deref())
+                .ok()
+                .map(|recipes| RecipeSource {
+                    runner,
+                    dir,
+                    recipes,
+                })
+        })
+        .collect()
+}
+
+/// Ask the runner for its recipes in `dir`
+pub fn list_recipes(runner: RecipeRunner, dir: Option<&str>) -> Result<Vec<String>> {
+    let mut command = Command::new(runner.program());
+    match runner {
+        RecipeRunner::Just => command.arg("--summary"),
+        RecipeRunner::Taskfile => command.args(["--list-all", "--json"]),
+    };
+    if let Some(dir) = dir {
+        command.current_dir(shellexpand::tilde(dir).as_ref());
+    }
+    let output = command
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .with_context(|| format!("Failed to run {}", runner.program()))?;
+    if !output.status.success() {
+        anyhow::bail!("{} could not list its recipes", runner.program());
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    match runner {
+        RecipeRunner::Just => Ok(parse_just_summary(&stdout)),
+        RecipeRunner::Taskfile => parse_taskfile_json(&stdout),
+    }
+}
+
+/// `just --summary` prints the recipe names separated by spaces
+fn parse_just_summary(summary: &str) -> Vec<String> {
+    summary.split_whitespace().map(str::to_string).collect()
+}
+
+#[derive(Deserialize)]
+struct TaskfileList {
+    #[serde(default)]
+    tasks: Vec<TaskfileTask>,
+}
+
+#[derive(Deserialize)]
+struct TaskfileTask {
+    name: String,
+}
+
+/// `task --list-all --json` prints `{"tasks": [{"name": …}, …]}`
+fn parse_taskfile_json(json: &str) -> Result<Vec<String>> {
+    let list: TaskfileList =
+        serde_json::from_str(json).context("Failed to parse `task --list-all --json` output")?;
+    Ok(list.tasks.into_iter().map(|task| task.name).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_recipe_listings() {
+        assert_eq!(
+            parse_just_summary("clean update-deps\nupgrade\n"),
+            ["clean", "update-deps", "upgrade"]
+        );
+        assert_eq!(
+            parse_taskfile_json(
+                r#"{"tasks":[{"name":"deps:update","desc":"Update"},{"name":"lint"}],"location":"Taskfile.yml"}"#
+            )
+            .unwrap(),
+            ["deps:update", "lint"]
+        );
+        assert!(parse_taskfile_json("not json").is_err());
+    }
+}
@@ -0,0 +1,132 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::executor::{TaskResult, TaskStatus};
+use crate::failure::FailureClass;
+
+/// Outcome of a single task as captured by `--record`
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct RecordedTask {
+    pub group: String,
+    pub name: String,
+    pub status: TaskStatus,
+    pub duration_secs: f64,
+    #[serde(default)]
+    pub output: Option<String>,
+    #[serde(default)]
+    pub exit_code: Option<i32>,
+    #[serde(default)]
+    pub failure: Option<FailureClass>,
+}
+
+impl From<&TaskResult> for RecordedTask {
+    fn from(result: &TaskResult) -> Self {
+        Self {
+            group: result.group.clone(),
+            name: result.name.clone(),
+            status: result.status,
+            duration_secs: result.duration.as_secs_f64(),
+            output: result.output.clone(),
+            exit_code: result.exit_code,
+            failure: result.failure,
+        }
+    }
+}
+
+impl RecordedTask {
+    /// Turn the recording back into a result as if the task had just run
+    pub fn into_result(self, group_icon: String) -> TaskResult {
+        TaskResult {
+            name: self.name,
+            group: self.group,
+            group_icon,
+            status: self.status,
+            started: Instant::now(),
+            duration: Duration::from_secs_f64(self.duration_secs),
+            output: self.output,
+            exit_code: self.exit_code,
+            failure: self.failure,
+            log_line: None,
+        }
+    }
+}
+
+/// Write the results of a run as JSON lines
+pub fn write(path: &Path, results: &[TaskResult]) -> Result<()> {
+    let mut contents = String::new();
+    for result in results {
+        contents.push_str(&serde_json::to_string(&RecordedTask::from(result))?);
+        contents.push('\n');
+    }
+    fs::write(path, contents)
+        .with_context(|| format!("Failed to write recording {}", path.display()))
+}
+
+/// Recorded outcomes served to the executor instead of running commands
+pub struct Replay {
+    tasks: Mutex<HashMap<(String, String), RecordedTask>>,
+}
+
+impl Replay {
+    /// Load a recording written by `--record`
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read recording {}", path.display()))?;
+        Self::parse(&contents).with_context(|| format!("Invalid recording {}", path.display()))
+    }
+
+    fn parse(contents: &str) -> Result<Self> {
+        let mut tasks = HashMap::new();
+        for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+            let task: RecordedTask = serde_json::from_str(line)?;
+            tasks.insert((task.group.clone(), task.name.clone()), task);
+        }
+        Ok(Self {
+            tasks: Mutex::new(tasks),
+        })
+    }
+
+    /// Take the recorded outcome of a task, if the recording contains it
+    pub fn take(&self, group: &str, name: &str) -> Option<RecordedTask> {
+        self.tasks
+            .lock()
+            .unwrap()
+            .remove(&(group.to_string(), name.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recording_round_trip() {
+        let result = TaskResult {
+            name: "Upgrade Packages".to_string(),
+            group: "Homebrew".to_string(),
+            group_icon: "🍺".to_string(),
+            status: TaskStatus::Failed,
+            started: Instant::now(),
+            duration: Duration::from_millis(1500),
+            output: Some("Command failed: curl: (6) Could not resolve host".to_string()),
+            exit_code: Some(1),
+            failure: Some(FailureClass::Network),
+            log_line: None,
+        };
+        let recorded = RecordedTask::from(&result);
+        let line = serde_json::to_string(&recorded).unwrap();
+
+        let replay = Replay::parse(&format!("{}\n\n", line)).unwrap();
+        assert_eq!(replay.take("Homebrew", "Upgrade Packages"), Some(recorded));
+        assert_eq!(replay.take("Homebrew", "Upgrade Packages"), None);
+
+        let replayed = RecordedTask::from(&result).into_result("🍺".to_string());
+        assert_eq!(replayed.duration, Duration::from_millis(1500));
+        assert_eq!(replayed.failure, Some(FailureClass::Network));
+    }
+}
@@ -0,0 +1,132 @@
+use chrono::{DateTime, Local};
+use clap::ValueEnum;
+use serde::Serialize;
+use std::time::Duration;
+
+use crate::error::TideError;
+use crate::executor::{TaskResult, TaskStatus};
+use crate::failure::FailureClass;
+use crate::launcher::ErrorReport;
+
+/// How the outcome of a run is printed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputMode {
+    /// Progress, summary and reports for a person at the terminal
+    #[default]
+    Human,
+    /// Nothing while running, then a single JSON document on stdout
+    Json,
+}
+
+/// Complete outcome of a run, printed by `--output json`
+#[derive(Debug, Serialize)]
+pub struct RunReport {
+    pub success: bool,
+    pub started_at: DateTime<Local>,
+    pub duration_secs: f64,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub skipped: usize,
+    pub cancelled: usize,
+    pub error: Option<ErrorReport>,
+    pub tasks: Vec<TaskReport>,
+}
+
+/// Outcome of one task, with everything it printed
+#[derive(Debug, Serialize)]
+pub struct TaskReport {
+    pub group: String,
+    pub name: String,
+    pub status: TaskStatus,
+    pub duration_secs: f64,
+    pub exit_code: Option<i32>,
+    pub failure: Option<FailureClass>,
+    pub output: Option<String>,
+}
+
+impl RunReport {
+    /// Build the report from the results of a finished run
+    pub fn new(
+        started_at: DateTime<Local>,
+        duration: Duration,
+        results: &[TaskResult],
+        error: Option<&TideError>,
+    ) -> Self {
+        let count = |status| results.iter().filter(|r| r.status == status).count();
+        let failed = count(TaskStatus::Failed);
+        Self {
+            success: failed == 0 && error.is_none(),
+            started_at,
+            duration_secs: duration.as_secs_f64(),
+            succeeded: count(TaskStatus::Success),
+            failed,
+            skipped: count(TaskStatus::Skipped),
+            cancelled: count(TaskStatus::Cancelled),
+            error: error.map(ErrorReport::from),
+            tasks: results
+                .iter()
+                .map(|result| TaskReport {
+                    group: result.group.clone(),
+                    name: result.name.clone(),
+                    status: result.status,
+                    duration_secs: result.duration.as_secs_f64(),
+                    exit_code: result.exit_code,
+                    failure: result.failure,
+                    output: result.output.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Pretty-printed JSON document
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    fn result(name: &str, status: TaskStatus, exit_code: Option<i32>) -> TaskResult {
+        TaskResult {
+            name: name.to_string(),
+            group: "Homebrew".to_string(),
+            group_icon: "🍺".to_string(),
+            status,
+            started: Instant::now(),
+            duration: Duration::from_millis(1500),
+            output: Some("Already up-to-date.\n".to_string()),
+            exit_code,
+            failure: (status == TaskStatus::Failed).then_some(FailureClass::NonZeroExit),
+            log_line: None,
+        }
+    }
+
+    #[test]
+    fn test_run_report_json() {
+        let results = [
+            result("Update Formulae", TaskStatus::Success, Some(0)),
+            result("Upgrade Packages", TaskStatus::Failed, Some(1)),
+        ];
+        let error = TideError::from_results(&results);
+        let report = RunReport::new(
+            Local::now(),
+            Duration::from_secs(3),
+            &results,
+            error.as_ref(),
+        );
+        let json: serde_json::Value = serde_json::from_str(&report.to_json().unwrap()).unwrap();
+        assert_eq!(json["success"], false);
+        assert_eq!(json["succeeded"], 1);
+        assert_eq!(json["failed"], 1);
+        assert_eq!(json["tasks"][0]["group"], "Homebrew");
+        assert_eq!(json["tasks"][0]["status"], "success");
+        assert_eq!(json["tasks"][0]["duration_secs"], 1.5);
+        assert_eq!(json["tasks"][0]["output"], "Already up-to-date.\n");
+        assert_eq!(json["tasks"][1]["exit_code"], 1);
+        assert_eq!(json["tasks"][1]["failure"], "non_zero_exit");
+        assert!(json["error"]["code"].is_number());
+    }
+}
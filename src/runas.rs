@@ -0,0 +1,106 @@
+use std::ffi::{CStr, CString};
+use std::path::PathBuf;
+
+/// Home directory of a local user from the user database, or `None` when
+/// there is no such user
+pub fn home_dir(user: &str) -> Option<PathBuf> {
+    let name = CString::new(user).ok()?;
+    // SAFETY: getpwnam returns a pointer into static storage that stays
+    // valid until the next call; the directory is copied out right away
+    unsafe {
+        let entry = libc::getpwnam(name.as_ptr());
+        if entry.is_null() || (*entry).pw_dir.is_null() {
+            return None;
+        }
+        let dir = CStr::from_ptr((*entry).pw_dir).to_string_lossy();
+        Some(PathBuf::from(dir.as_ref()))
+    }
+}
+
+/// `sudo` arguments running a command as `user` with their `HOME`, keeping
+/// the named variables of tide's environment
+pub fn sudo_args(user: &str, preserve_env: &[&str]) -> Vec<String> {
+    let mut args = vec![
+        "sudo".to_string(),
+        "-u".to_string(),
+        user.to_string(),
+        "-H".to_string(),
+    ];
+    args.extend(preserve_env_arg(preserve_env));
+    args.push("--".to_string());
+    args
+}
+
+/// `--preserve-env=<keys>`, so sudo keeps the named variables of tide's
+/// environment instead of resetting them
+fn preserve_env_arg(keys: &[&str]) -> Option<String> {
+    (!keys.is_empty()).then(|| format!("--preserve-env={}", keys.join(",")))
+}
+
+/// A `sudo …` command rebuilt to keep the named variables, ahead of any
+/// options the command gives sudo itself
+pub fn sudo_with_env(cmd: &[String], preserve_env: &[&str]) -> Vec<String> {
+    let mut args = vec!["sudo".to_string()];
+    args.extend(preserve_env_arg(preserve_env));
+    args.extend_from_slice(cmd.get(1..).unwrap_or_default());
+    args
+}
+
+/// The command run as `user`
+pub fn wrap(user: &str, cmd: Vec<String>) -> Vec<String> {
+    let mut wrapped = sudo_args(user, &[]);
+    wrapped.extend(cmd);
+    wrapped
+}
+
+/// Expand a leading `~` in a working directory against the user's home
+/// rather than the invoking user's
+pub fn working_dir(user: &str, dir: &str) -> PathBuf {
+    match (dir.strip_prefix('~'), home_dir(user)) {
+        (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with('/') => {
+            home.join(rest.trim_start_matches('/'))
+        }
+        _ => PathBuf::from(shellexpand::tilde(dir).as_ref()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_as_wrapping() {
+        let cmd = vec!["brew".to_string(), "cleanup".to_string()];
+        assert_eq!(
+            wrap("kids", cmd),
+            ["sudo", "-u", "kids", "-H", "--", "brew", "cleanup"]
+        );
+        assert_eq!(
+            sudo_args("kids", &["TIDE_RUN_ID", "HOMEBREW_NO_ANALYTICS"])[4],
+            "--preserve-env=TIDE_RUN_ID,HOMEBREW_NO_ANALYTICS"
+        );
+
+        let root = home_dir("root").unwrap();
+        assert_eq!(working_dir("root", "~/Library"), root.join("Library"));
+        assert_eq!(working_dir("root", "/tmp"), PathBuf::from("/tmp"));
+        assert!(home_dir("tide-no-such-user").is_none());
+    }
+
+    #[test]
+    fn test_sudo_keeps_env() {
+        let cmd: Vec<String> = ["sudo", "-n", "softwareupdate", "-l"]
+            .map(String::from)
+            .to_vec();
+        assert_eq!(
+            sudo_with_env(&cmd, &["TIDE_RUN_ID", "TOKEN"]),
+            [
+                "sudo",
+                "--preserve-env=TIDE_RUN_ID,TOKEN",
+                "-n",
+                "softwareupdate",
+                "-l"
+            ]
+        );
+        assert_eq!(sudo_with_env(&cmd, &[]), cmd);
+    }
+}
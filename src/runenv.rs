@@ -0,0 +1,77 @@
+use chrono::Local;
+use std::path::PathBuf;
+
+/// Metadata of the current run, exported to task commands as `TIDE_*`
+/// environment variables so scripts can adapt to how tide invoked them
+#[derive(Debug, Clone)]
+pub struct RunEnv {
+    /// Identifies the run in logs, e.g. `20261016T073000-4242`
+    pub run_id: String,
+    pub dry_run: bool,
+    pub log_file: Option<PathBuf>,
+}
+
+impl RunEnv {
+    /// Metadata for a run starting now, identified by its start time and pid
+    pub fn new(dry_run: bool, log_file: Option<PathBuf>) -> Self {
+        Self {
+            run_id: format!(
+                "{}-{}",
+                Local::now().format("%Y%m%dT%H%M%S"),
+                std::process::id()
+            ),
+            dry_run,
+            log_file,
+        }
+    }
+
+    /// Variables describing the run as a whole (`TIDE_LOG_FILE` only when
+    /// logging is enabled)
+    pub fn vars(&self) -> Vec<(&'static str, String)> {
+        let mut vars = vec![
+            ("TIDE_RUN_ID", self.run_id.clone()),
+            (
+                "TIDE_DRY_RUN",
+                if self.dry_run { "1" } else { "0" }.to_string(),
+            ),
+        ];
+        if let Some(path) = &self.log_file {
+            vars.push(("TIDE_LOG_FILE", path.display().to_string()));
+        }
+        vars
+    }
+
+    /// Run variables plus the group and name of the task being executed
+    pub fn task_vars(&self, group: &str, task: &str) -> Vec<(&'static str, String)> {
+        let mut vars = self.vars();
+        vars.push(("TIDE_GROUP", group.to_string()));
+        vars.push(("TIDE_TASK_NAME", task.to_string()));
+        vars
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_task_vars() {
+        let env = RunEnv::new(true, Some(PathBuf::from("/tmp/tide.log")));
+        let vars = env.task_vars("Homebrew", "Update Formulae");
+        let get = |key| {
+            vars.iter()
+                .find(|(k, _)| *k == key)
+                .map(|(_, v)| v.as_str())
+        };
+
+        assert_eq!(get("TIDE_RUN_ID"), Some(env.run_id.as_str()));
+        assert!(env.run_id.ends_with(&format!("-{}", std::process::id())));
+        assert_eq!(get("TIDE_DRY_RUN"), Some("1"));
+        assert_eq!(get("TIDE_LOG_FILE"), Some("/tmp/tide.log"));
+        assert_eq!(get("TIDE_GROUP"), Some("Homebrew"));
+        assert_eq!(get("TIDE_TASK_NAME"), Some("Update Formulae"));
+
+        let env = RunEnv::new(false, None);
+        assert!(!env.vars().iter().any(|(k, _)| *k == "TIDE_LOG_FILE"));
+    }
+}
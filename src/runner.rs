@@ -0,0 +1,349 @@
+use colored::Colorize;
+use futures::future::join_all;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{Semaphore, watch};
+use tokio::task::JoinHandle;
+
+use crate::adaptive::{self, AdaptiveLimit, Signals};
+use crate::config::Settings;
+use crate::executor::{SkipReason, TaskExecutor, TaskResult, TaskStatus};
+use crate::palette::Paint;
+use crate::plan::{self, Batch, BatchMode, PlannedTask};
+use crate::progress::RunProgress;
+
+/// Execute planned batches in order and collect the task results.
+///
+/// Sequential batches run one task at a time; parallel batches run up to
+/// their concurrency limit, and graph batches too while starting each task
+/// only after its dependencies finished (skipping it when a required one did
+/// not succeed). Tasks marked to [skip](PlannedTask::skip) and those that
+/// were not dispatched because the run was aborted are reported as skipped.
+/// With progress enabled, task spinners are nested under group headers below
+/// an overall bar whose time remaining is estimated from `estimates`.
+pub async fn run_batches(
+    executor: &Arc<TaskExecutor>,
+    batches: Vec<Batch>,
+    settings: &Settings,
+    keychain_label: &str,
+    estimates: &HashMap<(String, String), Duration>,
+) -> Vec<TaskResult> {
+    let mut results = Vec::new();
+    let overall = executor
+        .multi_progress
+        .as_ref()
+        .map(|multi| Arc::new(RunProgress::new(multi, &batches, estimates)));
+
+    for batch in batches {
+        match batch.mode {
+            BatchMode::Sequential => {
+                // Set once a task failed with `skip_optional_on_error`; the
+                // rest of the batch is then reported as skipped because of it
+                let mut failed: Option<String> = None;
+                for PlannedTask {
+                    task,
+                    group,
+                    group_icon,
+                    skip,
+                    ..
+                } in batch.tasks
+                {
+                    if let Some(reason) = skip {
+                        let result =
+                            TaskResult::precondition_skipped(task.name, group, group_icon, &reason);
+                        if let Some(overall) = &overall {
+                            overall.task_finished(&result);
+                        }
+                        results.push(result);
+                        continue;
+                    }
+                    if let Some(label) = &failed {
+                        let reason = SkipReason::DependencyUnmet(label.clone());
+                        let result =
+                            TaskResult::precondition_skipped(task.name, group, group_icon, &reason);
+                        if let Some(overall) = &overall {
+                            overall.task_finished(&result);
+                        }
+                        results.push(result);
+                        continue;
+                    }
+                    if !executor.controls.wait_for_dispatch().await {
+                        let result = TaskResult::aborted(task.name, group, group_icon);
+                        if let Some(overall) = &overall {
+                            overall.task_finished(&result);
+                        }
+                        results.push(result);
+                        continue;
+                    }
+                    let pb = match &overall {
+                        Some(overall) => overall.task_spinner(&group),
+                        None => executor.new_spinner(),
+                    };
+                    let label = format!("{}/{}", group, task.name);
+                    let result = executor
+                        .execute_task(task, group, group_icon, pb, keychain_label)
+                        .await;
+                    if let Some(overall) = &overall {
+                        overall.task_finished(&result);
+                    }
+
+                    if result.status == TaskStatus::Failed && settings.skip_optional_on_error {
+                        eprintln!(
+                            "{}",
+                            "⚠️  Skipping remaining optional tasks due to failure".warning()
+                        );
+                        failed = Some(label);
+                    }
+
+                    results.push(result);
+                }
+            }
+            BatchMode::Parallel { concurrency } | BatchMode::Graph { concurrency } => {
+                let dependencies = match batch.mode {
+                    BatchMode::Graph { .. } => plan::dependencies(&batch.tasks, settings),
+                    _ => vec![Vec::new(); batch.tasks.len()],
+                };
+                let labels: Vec<String> = batch
+                    .tasks
+                    .iter()
+                    .map(|planned| format!("{}/{}", planned.group, planned.task.name))
+                    .collect();
+                let (outcomes, watchers): (Vec<_>, Vec<_>) = batch
+                    .tasks
+                    .iter()
+                    .map(|_| watch::channel(None::<TaskStatus>))
+                    .unzip();
+
+                let semaphore = Arc::new(Semaphore::new(concurrency));
+                let adaptive = settings.adaptive_parallelism.then(|| {
+                    let limit = AdaptiveLimit::new(concurrency);
+                    let monitor = adapt_concurrency(executor, &limit, concurrency);
+                    (limit, monitor)
+                });
+                let mut handles = Vec::new();
+
+                for ((planned, outcome), deps) in
+                    batch.tasks.into_iter().zip(outcomes).zip(dependencies)
+                {
+                    let PlannedTask {
+                        task,
+                        group,
+                        group_icon,
+                        skip,
+                        ..
+                    } = planned;
+                    let waits: Vec<_> = deps
+                        .iter()
+                        .map(|dep| {
+                            (
+                                watchers[dep.task].clone(),
+                                dep.required,
+                                labels[dep.task].clone(),
+                            )
+                        })
+                        .collect();
+                    let executor_clone = Arc::clone(executor);
+                    let semaphore_clone = Arc::clone(&semaphore);
+                    let limit = adaptive.as_ref().map(|(limit, _)| Arc::clone(limit));
+                    let keychain_label = keychain_label.to_string();
+                    let overall = overall.clone();
+
+                    let handle = tokio::spawn(async move {
+                        let result = if let Some(reason) = skip {
+                            TaskResult::precondition_skipped(task.name, group, group_icon, &reason)
+                        } else if let Some(unmet) = wait_for_dependencies(waits).await {
+                            let reason = SkipReason::DependencyUnmet(unmet);
+                            executor_clone.controls.print(format!(
+                                "{}",
+                                format!("⏭  [{}] {} skipped: {}", group, task.name, reason)
+                                    .dimmed()
+                            ));
+                            TaskResult::precondition_skipped(task.name, group, group_icon, &reason)
+                        } else {
+                            let _permit = semaphore_clone.acquire().await.unwrap();
+                            let _slot = match &limit {
+                                Some(limit) => Some(limit.acquire().await),
+                                None => None,
+                            };
+                            if executor_clone.controls.wait_for_dispatch().await {
+                                let pb = match &overall {
+                                    Some(overall) => overall.task_spinner(&group),
+                                    None => executor_clone.new_spinner(),
+                                };
+                                executor_clone
+                                    .execute_task(task, group, group_icon, pb, &keychain_label)
+                                    .await
+                            } else {
+                                TaskResult::aborted(task.name, group, group_icon)
+                            }
+                        };
+                        if let Some(overall) = &overall {
+                            overall.task_finished(&result);
+                        }
+                        outcome.send_replace(Some(result.status));
+                        result
+                    });
+
+                    handles.push(handle);
+                }
+
+                let parallel_results = join_all(handles).await;
+                if let Some((_, monitor)) = adaptive {
+                    monitor.abort();
+                }
+                for task_result in parallel_results.into_iter().flatten() {
+                    results.push(task_result);
+                }
+            }
+        }
+    }
+
+    if let Some(overall) = &overall {
+        overall.finish();
+    }
+    results
+}
+
+/// Wait until every dependency finished; returns the first required one
+/// that did not succeed
+async fn wait_for_dependencies(
+    waits: Vec<(watch::Receiver<Option<TaskStatus>>, bool, String)>,
+) -> Option<String> {
+    let mut unmet = None;
+    for (mut outcome, required, label) in waits {
+        // A dependency whose task panicked never reports a status
+        let status = outcome
+            .wait_for(Option::is_some)
+            .await
+            .ok()
+            .and_then(|status| *status);
+        if required && status != Some(TaskStatus::Success) && unmet.is_none() {
+            unmet = Some(label);
+        }
+    }
+    unmet
+}
+
+/// Periodically move the batch's concurrency limit to what the machine can
+/// spare, announcing every change. Input from before the batch started is
+/// not counted as the user being active.
+fn adapt_concurrency(
+    executor: &Arc<TaskExecutor>,
+    limit: &Arc<AdaptiveLimit>,
+    max: usize,
+) -> JoinHandle<()> {
+    let limit = Arc::clone(limit);
+    let controls = Arc::clone(&executor.controls);
+    tokio::spawn(async move {
+        let started = Instant::now();
+        loop {
+            let Ok(signals) = tokio::task::spawn_blocking(Signals::sample).await else {
+                return;
+            };
+            let (target, reasons) = signals.since(started.elapsed()).target(max);
+            let current = limit.limit();
+            if target != current {
+                let why = if reasons.is_empty() {
+                    "machine idle".to_string()
+                } else {
+                    reasons.join(", ")
+                };
+                controls.print(format!(
+                    "{}",
+                    format!("⚙️  Parallel tasks {} → {} ({})", current, target, why).dimmed()
+                ));
+                limit.set(target);
+            }
+            tokio::time::sleep(adaptive::SAMPLE_INTERVAL).await;
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Config, TaskConfig};
+
+    #[tokio::test]
+    async fn test_dependents_of_preflight_skips_are_skipped() {
+        let group = &Config::default().groups[0];
+        let task = |name: &str, depends_on: &[&str]| TaskConfig {
+            name: name.to_string(),
+            command: vec!["true".to_string()],
+            depends_on: depends_on.iter().map(|name| name.to_string()).collect(),
+            ..TaskConfig::default()
+        };
+        let mut missing = PlannedTask::new(group, &task("Install Tool", &[]));
+        missing.skip = Some(SkipReason::CommandNotFound("tool".to_string()));
+        let tasks = vec![
+            missing,
+            PlannedTask::new(group, &task("Use Tool", &["Install Tool"])),
+            PlannedTask::new(group, &task("Unrelated", &[])),
+        ];
+        let settings = Settings::default();
+        let batches = plan::build_batches(tasks, &settings, 2);
+        let executor = Arc::new(TaskExecutor::new(false, false, false, false, 0, None, None));
+
+        let results =
+            run_batches(&executor, batches, &settings, "tide-test", &HashMap::new()).await;
+        let outcome = |name: &str| {
+            let result = results.iter().find(|r| r.name == name).unwrap();
+            (result.status, result.output.clone().unwrap_or_default())
+        };
+        assert_eq!(
+            outcome("Install Tool"),
+            (TaskStatus::Skipped, "Command 'tool' not found".to_string())
+        );
+        assert_eq!(
+            outcome("Use Tool"),
+            (
+                TaskStatus::Skipped,
+                format!("Dependency '{}/Install Tool' did not succeed", group.name)
+            )
+        );
+        assert_eq!(outcome("Unrelated").0, TaskStatus::Success);
+    }
+
+    #[tokio::test]
+    async fn test_failure_skips_rest_of_sequential_batch() {
+        let group = &Config::default().groups[0];
+        let task = |name: &str, command: &str| TaskConfig {
+            name: name.to_string(),
+            command: vec![command.to_string()],
+            ..TaskConfig::default()
+        };
+        let batches = vec![Batch {
+            mode: BatchMode::Sequential,
+            tasks: vec![
+                PlannedTask::new(group, &task("First", "true")),
+                PlannedTask::new(group, &task("Broken", "false")),
+                PlannedTask::new(group, &task("Last", "true")),
+            ],
+        }];
+        let settings = Settings {
+            skip_optional_on_error: true,
+            ..Settings::default()
+        };
+        let executor = Arc::new(TaskExecutor::new(false, false, false, false, 0, None, None));
+
+        let results =
+            run_batches(&executor, batches, &settings, "tide-test", &HashMap::new()).await;
+        let statuses: Vec<_> = results
+            .iter()
+            .map(|r| (r.name.as_str(), r.status))
+            .collect();
+        assert_eq!(
+            statuses,
+            [
+                ("First", TaskStatus::Success),
+                ("Broken", TaskStatus::Failed),
+                ("Last", TaskStatus::Skipped),
+            ]
+        );
+        assert_eq!(
+            results[2].output.as_deref(),
+            Some(format!("Dependency '{}/Broken' did not succeed", group.name).as_str())
+        );
+    }
+}
@@ -0,0 +1,92 @@
+use std::path::PathBuf;
+
+/// Seatbelt profile denying IP networking; local Unix sockets keep working
+const NO_NETWORK: &str = r#"(version 1)
+(allow default)
+(deny network-outbound (remote ip))
+(deny network-inbound (local ip))
+"#;
+
+/// Seatbelt profile making the home directory read-only
+const READONLY_HOME: &str = r#"(version 1)
+(allow default)
+(deny file-write* (subpath (param "HOME")))
+"#;
+
+/// Restriction applied to a task's command via `sandbox-exec`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Sandbox {
+    /// `sandbox = "no-network"`
+    NoNetwork,
+    /// `sandbox = "readonly-home"`
+    ReadonlyHome,
+    /// Path to a custom `.sb` profile (supports `~`)
+    Profile(PathBuf),
+}
+
+impl Sandbox {
+    /// Parse the `sandbox` value: a built-in profile name, or a path when it
+    /// contains `/` or ends in `.sb`
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "no-network" => Some(Sandbox::NoNetwork),
+            "readonly-home" => Some(Sandbox::ReadonlyHome),
+            path if path.contains('/') || path.ends_with(".sb") => Some(Sandbox::Profile(
+                PathBuf::from(shellexpand::tilde(path).as_ref()),
+            )),
+            _ => None,
+        }
+    }
+
+    /// Prefix the command with `sandbox-exec`, keeping a leading `sudo` first
+    pub fn wrap(&self, mut cmd: Vec<String>) -> Vec<String> {
+        let mut wrapper = vec!["sandbox-exec".to_string()];
+        match self {
+            Sandbox::NoNetwork => wrapper.extend(["-p".to_string(), NO_NETWORK.to_string()]),
+            Sandbox::ReadonlyHome => {
+                let home = dirs::home_dir().unwrap_or_default();
+                wrapper.extend([
+                    "-D".to_string(),
+                    format!("HOME={}", home.display()),
+                    "-p".to_string(),
+                    READONLY_HOME.to_string(),
+                ]);
+            }
+            Sandbox::Profile(path) => {
+                wrapper.extend(["-f".to_string(), path.display().to_string()]);
+            }
+        }
+        let at = usize::from(cmd.first().is_some_and(|program| program == "sudo"));
+        cmd.splice(at..at, wrapper);
+        cmd
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sandbox_wraps_command() {
+        assert_eq!(Sandbox::parse("no-network"), Some(Sandbox::NoNetwork));
+        assert_eq!(
+            Sandbox::parse("/etc/tide/strict.sb"),
+            Some(Sandbox::Profile(PathBuf::from("/etc/tide/strict.sb")))
+        );
+        assert_eq!(Sandbox::parse("no-netwrk"), None);
+
+        let cmd = vec![
+            "sudo".to_string(),
+            "brew".to_string(),
+            "cleanup".to_string(),
+        ];
+        let wrapped = Sandbox::Profile(PathBuf::from("/p.sb")).wrap(cmd);
+        assert_eq!(
+            wrapped,
+            ["sudo", "sandbox-exec", "-f", "/p.sb", "brew", "cleanup"]
+        );
+        let wrapped = Sandbox::NoNetwork.wrap(vec!["make".to_string()]);
+        assert_eq!(wrapped[0], "sandbox-exec");
+        assert_eq!(wrapped.last().map(String::as_str), Some("make"));
+    }
+}
@@ -0,0 +1,132 @@
+use serde::{Deserialize, Serialize};
+
+use crate::executor::{TaskResult, TaskStatus};
+use crate::failure::FailureClass;
+
+/// Severity reported to monitoring wrappers through the exit status
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Warning,
+    Critical,
+}
+
+impl Severity {
+    /// Exit status following the Nagios plugin convention
+    pub fn exit_code(self) -> i32 {
+        match self {
+            Severity::Warning => 1,
+            Severity::Critical => 2,
+        }
+    }
+}
+
+/// Task outcomes a threshold counts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ThresholdCondition {
+    /// Optional tasks whose command failed
+    OptionalFailures,
+    /// Required tasks that failed
+    RequiredFailures,
+    /// Tasks (required or optional) that timed out
+    Timeouts,
+    /// Tasks (required or optional) that failed with a network error
+    NetworkFailures,
+}
+
+/// `[[settings.failure_threshold]]` rule: at least `count` tasks matching
+/// `when` raise the run to `level`
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct FailureThreshold {
+    pub when: ThresholdCondition,
+    #[serde(default = "default_count")]
+    pub count: usize,
+    pub level: Severity,
+}
+
+fn default_count() -> usize {
+    1
+}
+
+impl ThresholdCondition {
+    fn matches(self, result: &TaskResult) -> bool {
+        match self {
+            ThresholdCondition::OptionalFailures => {
+                result.status == TaskStatus::Skipped && result.failure.is_some()
+            }
+            ThresholdCondition::RequiredFailures => result.status == TaskStatus::Failed,
+            ThresholdCondition::Timeouts => result.failure == Some(FailureClass::Timeout),
+            ThresholdCondition::NetworkFailures => result.failure == Some(FailureClass::Network),
+        }
+    }
+}
+
+/// Highest severity among the thresholds the run reached, if any
+pub fn evaluate(thresholds: &[FailureThreshold], results: &[TaskResult]) -> Option<Severity> {
+    thresholds
+        .iter()
+        .filter(|threshold| {
+            let count = results
+                .iter()
+                .filter(|result| threshold.when.matches(result))
+                .count();
+            count > 0 && count >= threshold.count
+        })
+        .map(|threshold| threshold.level)
+        .max()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Settings;
+    use std::time::{Duration, Instant};
+
+    fn result(status: TaskStatus, failure: Option<FailureClass>) -> TaskResult {
+        TaskResult {
+            name: "Task".to_string(),
+            group: "Group".to_string(),
+            group_icon: String::new(),
+            status,
+            started: Instant::now(),
+            duration: Duration::ZERO,
+            output: None,
+            exit_code: None,
+            failure,
+            log_line: None,
+        }
+    }
+
+    #[test]
+    fn test_thresholds_pick_highest_severity() {
+        let settings: Settings = toml::from_str(
+            r#"
+failure_threshold = [
+  { when = "optional-failures", level = "warning" },
+  { when = "required-failures", level = "critical" },
+  { when = "timeouts", count = 2, level = "critical" },
+]
+"#,
+        )
+        .unwrap();
+        let thresholds = &settings.failure_threshold;
+
+        let optional = result(TaskStatus::Skipped, Some(FailureClass::Timeout));
+        let precondition = result(TaskStatus::Skipped, None);
+        assert_eq!(evaluate(thresholds, &[precondition]), None);
+        assert_eq!(evaluate(thresholds, &[optional]), Some(Severity::Warning));
+
+        let timeouts = [
+            result(TaskStatus::Skipped, Some(FailureClass::Timeout)),
+            result(TaskStatus::Skipped, Some(FailureClass::Timeout)),
+        ];
+        assert_eq!(evaluate(thresholds, &timeouts), Some(Severity::Critical));
+
+        let required = result(TaskStatus::Failed, Some(FailureClass::NonZeroExit));
+        assert_eq!(
+            evaluate(thresholds, &[required]).map(Severity::exit_code),
+            Some(2)
+        );
+    }
+}
@@ -0,0 +1,218 @@
+use colored::Colorize;
+use std::collections::BTreeMap;
+
+use crate::bench::median;
+use crate::executor::TaskStatus;
+use crate::history::RunRecord;
+use crate::palette::Paint;
+
+/// Number of slowest tasks listed below the table
+pub const SLOWEST: usize = 5;
+/// Tasks failing at least this share of their executions are called out
+pub const UNRELIABLE_RATE: f64 = 0.25;
+
+/// How one task fared across the runs of the history
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaskStats {
+    pub group: String,
+    pub name: String,
+    /// Runs in which the task succeeded or failed; skips and cancellations
+    /// are not counted
+    pub executions: usize,
+    pub failures: usize,
+    /// Durations of successful executions, in seconds; `None` without any
+    pub average: Option<f64>,
+    pub median: Option<f64>,
+    pub last: Option<f64>,
+}
+
+impl TaskStats {
+    /// Share of executions that failed, from 0 to 1
+    pub fn failure_rate(&self) -> f64 {
+        if self.executions == 0 {
+            0.0
+        } else {
+            self.failures as f64 / self.executions as f64
+        }
+    }
+}
+
+/// Per-task statistics over the runs (oldest first), slowest on average first
+pub fn analyze(runs: &[RunRecord]) -> Vec<TaskStats> {
+    let mut tasks: BTreeMap<(String, String), (Vec<f64>, usize, usize)> = BTreeMap::new();
+    for run in runs {
+        for task in &run.tasks {
+            let (durations, executions, failures) = tasks
+                .entry((task.group.clone(), task.name.clone()))
+                .or_default();
+            match task.status {
+                TaskStatus::Success => {
+                    durations.push(task.duration_secs);
+                    *executions += 1;
+                }
+                TaskStatus::Failed => {
+                    *executions += 1;
+                    *failures += 1;
+                }
+                TaskStatus::Skipped | TaskStatus::Cancelled => {}
+            }
+        }
+    }
+
+    let mut stats: Vec<TaskStats> = tasks
+        .into_iter()
+        .map(
+            |((group, name), (durations, executions, failures))| TaskStats {
+                group,
+                name,
+                executions,
+                failures,
+                average: (!durations.is_empty())
+                    .then(|| durations.iter().sum::<f64>() / durations.len() as f64),
+                median: (!durations.is_empty()).then(|| median(&durations)),
+                last: durations.last().copied(),
+            },
+        )
+        .collect();
+    stats.sort_by(|a, b| {
+        b.average
+            .unwrap_or_default()
+            .total_cmp(&a.average.unwrap_or_default())
+    });
+    stats
+}
+
+/// Print the statistics table, the slowest tasks and the unreliable ones
+pub fn display(runs: &[RunRecord], stats: &[TaskStats]) {
+    println!("{}", "📈 Tide Stats".bright_blue().bold());
+    println!("{}", "═".repeat(60).bright_blue());
+
+    if runs.is_empty() {
+        println!("{}", "No runs recorded yet.".dimmed());
+        return;
+    }
+    println!("Over the last {} run(s)\n", runs.len());
+
+    let seconds = |value: Option<f64>| value.map_or("–".to_string(), |v| format!("{:.1}s", v));
+    println!(
+        "{:<32} {:>8} {:>8} {:>8} {:>9}",
+        "Task".bold(),
+        "average",
+        "median",
+        "last",
+        "failures"
+    );
+    for task in stats {
+        let failures = format!("{:.0}%", task.failure_rate() * 100.0);
+        println!(
+            "{:<32} {:>8} {:>8} {:>8} {:>9}",
+            format!("{} › {}", task.group, task.name),
+            seconds(task.average),
+            seconds(task.median),
+            seconds(task.last),
+            if task.failures > 0 {
+                failures.failure()
+            } else {
+                failures.normal()
+            }
+        );
+    }
+
+    let slowest: Vec<&TaskStats> = stats
+        .iter()
+        .filter(|task| task.average.is_some())
+        .take(SLOWEST)
+        .collect();
+    if !slowest.is_empty() {
+        println!("\n{}", "Slowest tasks:".bright_white().bold());
+        for task in slowest {
+            println!(
+                "  {} › {} {}",
+                task.group,
+                task.name,
+                seconds(task.average).dimmed()
+            );
+        }
+    }
+
+    let unreliable: Vec<&TaskStats> = stats
+        .iter()
+        .filter(|task| task.failure_rate() >= UNRELIABLE_RATE)
+        .collect();
+    if !unreliable.is_empty() {
+        println!("\n{}", "Often failing:".bright_white().bold());
+        for task in unreliable {
+            println!(
+                "  {}",
+                format!(
+                    "{} › {} failed {} of {} time(s)",
+                    task.group, task.name, task.failures, task.executions
+                )
+                .warning()
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::TaskRecord;
+    use chrono::Local;
+
+    fn run(tasks: &[(&str, f64, TaskStatus)]) -> RunRecord {
+        RunRecord {
+            started_at: Local::now(),
+            resumed: false,
+            duration_secs: tasks.iter().map(|(_, d, _)| d).sum(),
+            tasks: tasks
+                .iter()
+                .map(|(name, duration, status)| TaskRecord {
+                    group: "Homebrew".to_string(),
+                    name: name.to_string(),
+                    status: *status,
+                    duration_secs: *duration,
+                    reason: None,
+                    started_at: None,
+                    output_hash: None,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_task_stats() {
+        let runs = vec![
+            run(&[
+                ("update", 10.0, TaskStatus::Success),
+                ("upgrade", 60.0, TaskStatus::Success),
+                ("cleanup", 0.0, TaskStatus::Skipped),
+            ]),
+            run(&[
+                ("update", 20.0, TaskStatus::Success),
+                ("upgrade", 1.0, TaskStatus::Failed),
+            ]),
+            run(&[
+                ("update", 60.0, TaskStatus::Success),
+                ("upgrade", 80.0, TaskStatus::Success),
+            ]),
+        ];
+
+        let stats = analyze(&runs);
+        let names: Vec<&str> = stats.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["upgrade", "update", "cleanup"]);
+
+        let upgrade = &stats[0];
+        assert_eq!(upgrade.average, Some(70.0));
+        assert_eq!(upgrade.last, Some(80.0));
+        assert_eq!((upgrade.failures, upgrade.executions), (1, 3));
+
+        let update = &stats[1];
+        assert_eq!(update.average, Some(30.0));
+        assert_eq!(update.median, Some(20.0));
+        assert_eq!(update.failure_rate(), 0.0);
+
+        assert_eq!(stats[2].executions, 0);
+        assert_eq!(stats[2].average, None);
+    }
+}
@@ -0,0 +1,378 @@
+use chrono::{DateTime, Local};
+use clap::ValueEnum;
+use colored::Colorize;
+use std::fmt::Write;
+use std::path::Path;
+
+use crate::config::Config;
+use crate::executor::TaskStatus;
+use crate::history::{RunRecord, TaskRecord};
+use crate::palette::Paint;
+
+/// Per-group tally of a recorded run, in the order groups ran
+struct GroupSummary<'a> {
+    name: &'a str,
+    tasks: Vec<&'a TaskRecord>,
+}
+
+impl GroupSummary<'_> {
+    fn count(&self, status: TaskStatus) -> usize {
+        self.tasks.iter().filter(|t| t.status == status).count()
+    }
+}
+
+fn summarize(run: &RunRecord) -> Vec<GroupSummary<'_>> {
+    let mut groups: Vec<GroupSummary> = Vec::new();
+    for task in &run.tasks {
+        match groups.iter_mut().find(|g| g.name == task.group) {
+            Some(group) => group.tasks.push(task),
+            None => groups.push(GroupSummary {
+                name: &task.group,
+                tasks: vec![task],
+            }),
+        }
+    }
+    groups
+}
+
+fn status_icon(status: TaskStatus) -> &'static str {
+    match status {
+        TaskStatus::Success => "✅",
+        TaskStatus::Failed => "❌",
+        TaskStatus::Skipped => "⏭️",
+        TaskStatus::Cancelled => "⏹️",
+    }
+}
+
+/// Group name prefixed with its configured icon, if any
+fn group_label(config: &Config, group: &str) -> String {
+    match config.groups.iter().find(|g| g.name == group) {
+        Some(g) if !g.icon.is_empty() => format!("{} {}", g.icon, group),
+        _ => group.to_string(),
+    }
+}
+
+fn run_failed(run: &RunRecord) -> bool {
+    run.tasks.iter().any(|t| t.status == TaskStatus::Failed)
+}
+
+/// Print the outcome of the last recorded run
+pub fn display_status(last: Option<&RunRecord>, config: &Config) {
+    println!("{}", "🌊 Tide Status".bright_blue().bold());
+    println!("{}", "═".repeat(60).bright_blue());
+
+    let Some(run) = last else {
+        println!("{}", "No runs recorded yet.".dimmed());
+        return;
+    };
+
+    let outcome = if run_failed(run) {
+        "failed".failure().bold()
+    } else {
+        "succeeded".success().bold()
+    };
+    println!(
+        "Last run {} at {} ({:.0}s)\n",
+        outcome,
+        run.started_at.format("%Y-%m-%d %H:%M"),
+        run.duration_secs
+    );
+
+    for group in summarize(run) {
+        println!(
+            "{}: {} ok, {} failed, {} skipped",
+            group_label(config, group.name).bold(),
+            group.count(TaskStatus::Success),
+            group.count(TaskStatus::Failed),
+            group.count(TaskStatus::Skipped)
+        );
+    }
+}
+
+/// Render the SwiftBar/xbar plugin format: a title line, then the dropdown
+/// with per-group results and a "Run now" action invoking `tide`.
+pub fn render_xbar(
+    last: Option<&RunRecord>,
+    config: &Config,
+    tide: &Path,
+    config_path: &Path,
+) -> String {
+    let mut out = String::new();
+
+    match last {
+        None => {
+            out.push_str("🌊\n---\nNo runs recorded yet\n");
+        }
+        Some(run) => {
+            let title = if run_failed(run) {
+                "🌊❌"
+            } else {
+                "🌊✅"
+            };
+            let _ = writeln!(out, "{}\n---", title);
+            let _ = writeln!(
+                out,
+                "Last run: {} ({:.0}s) | color=gray",
+                run.started_at.format("%Y-%m-%d %H:%M"),
+                run.duration_secs
+            );
+            for group in summarize(run) {
+                let failed = group.count(TaskStatus::Failed);
+                let color = if failed > 0 { "red" } else { "green" };
+                let _ = writeln!(
+                    out,
+                    "{}: {}/{} ok | color={}",
+                    group_label(config, group.name),
+                    group.count(TaskStatus::Success),
+                    group.tasks.len(),
+                    color
+                );
+                for task in &group.tasks {
+                    let _ = writeln!(
+                        out,
+                        "--{} {} ({:.0}s)",
+                        status_icon(task.status),
+                        task.name,
+                        task.duration_secs
+                    );
+                }
+            }
+        }
+    }
+
+    let _ = writeln!(
+        out,
+        "---\nRun now | bash=\"{}\" param1=--config param2=\"{}\" terminal=true refresh=true",
+        tide.display(),
+        config_path.display()
+    );
+    out
+}
+
+/// Output formats of `tide status --check-format`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CheckFormat {
+    /// Nagios/Icinga plugin output (also ingested by Zabbix and Sensu)
+    Nagios,
+}
+
+/// Monitoring state of a check, in increasing order of severity
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckState {
+    Ok,
+    Warning,
+    Critical,
+    Unknown,
+}
+
+impl CheckState {
+    /// Exit status defined by the Nagios plugin API
+    pub fn exit_code(self) -> i32 {
+        match self {
+            CheckState::Ok => 0,
+            CheckState::Warning => 1,
+            CheckState::Critical => 2,
+            CheckState::Unknown => 3,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            CheckState::Ok => "OK",
+            CheckState::Warning => "WARNING",
+            CheckState::Critical => "CRITICAL",
+            CheckState::Unknown => "UNKNOWN",
+        }
+    }
+}
+
+/// Hours without a successful run before the check warns or goes critical
+#[derive(Debug, Clone, Copy)]
+pub struct StalenessLimits {
+    pub warn_hours: f64,
+    pub crit_hours: f64,
+}
+
+/// Render a single check line with perfdata from the recorded runs (oldest
+/// first). The last run failing is critical; so is going `crit_hours`
+/// without a successful run, while `warn_hours` only warns.
+pub fn render_check(
+    runs: &[RunRecord],
+    now: DateTime<Local>,
+    limits: StalenessLimits,
+) -> (CheckState, String) {
+    let Some(last) = runs.last() else {
+        return (
+            CheckState::Unknown,
+            "TIDE UNKNOWN - no runs recorded".to_string(),
+        );
+    };
+
+    let count = |status| last.tasks.iter().filter(|t| t.status == status).count();
+    let (succeeded, failed, skipped) = (
+        count(TaskStatus::Success),
+        count(TaskStatus::Failed),
+        count(TaskStatus::Skipped),
+    );
+    let hours_since_success = runs.iter().rev().find(|run| !run_failed(run)).map(|run| {
+        let finished =
+            run.started_at + chrono::Duration::milliseconds((run.duration_secs * 1000.0) as i64);
+        (now - finished).num_seconds().max(0) as f64 / 3600.0
+    });
+
+    let state = match hours_since_success {
+        _ if failed > 0 => CheckState::Critical,
+        None => CheckState::Critical,
+        Some(hours) if hours >= limits.crit_hours => CheckState::Critical,
+        Some(hours) if hours >= limits.warn_hours => CheckState::Warning,
+        Some(_) => CheckState::Ok,
+    };
+
+    let mut message = if failed > 0 {
+        let names: Vec<&str> = last
+            .tasks
+            .iter()
+            .filter(|t| t.status == TaskStatus::Failed)
+            .map(|t| t.name.as_str())
+            .collect();
+        format!(
+            "last run failed: {} of {} task(s) ({})",
+            failed,
+            last.tasks.len(),
+            names.join(", ")
+        )
+    } else {
+        format!(
+            "last run {} succeeded ({} ok, {} skipped)",
+            last.started_at.format("%Y-%m-%d %H:%M"),
+            succeeded,
+            skipped
+        )
+    };
+    match hours_since_success {
+        Some(hours) => {
+            let _ = write!(message, ", last success {:.1}h ago", hours);
+        }
+        None => message.push_str(", no successful run recorded"),
+    }
+
+    let mut line = format!(
+        "TIDE {} - {} | succeeded={};;;0 failed={};;;0 skipped={};;;0 duration={:.0}s;;;0",
+        state.label(),
+        message,
+        succeeded,
+        failed,
+        skipped,
+        last.duration_secs
+    );
+    if let Some(hours) = hours_since_success {
+        let _ = write!(
+            line,
+            " hours_since_success={:.1};{};{};0",
+            hours, limits.warn_hours, limits.crit_hours
+        );
+    }
+    (state, line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Local, TimeZone};
+
+    fn record(group: &str, name: &str, status: TaskStatus) -> TaskRecord {
+        TaskRecord {
+            group: group.to_string(),
+            name: name.to_string(),
+            status,
+            duration_secs: 12.0,
+            reason: None,
+            started_at: None,
+            output_hash: None,
+        }
+    }
+
+    #[test]
+    fn test_render_nagios_check() {
+        let limits = StalenessLimits {
+            warn_hours: 48.0,
+            crit_hours: 168.0,
+        };
+        let ok = RunRecord {
+            started_at: Local.with_ymd_and_hms(2026, 10, 16, 7, 30, 0).unwrap(),
+            resumed: false,
+            duration_secs: 1800.0,
+            tasks: vec![
+                record("Homebrew", "Update Formulae", TaskStatus::Success),
+                record("Homebrew", "Cleanup", TaskStatus::Skipped),
+            ],
+        };
+        let now = Local.with_ymd_and_hms(2026, 10, 16, 10, 0, 0).unwrap();
+        let (state, line) = render_check(std::slice::from_ref(&ok), now, limits);
+        assert_eq!(state, CheckState::Ok);
+        assert_eq!(
+            line,
+            "TIDE OK - last run 2026-10-16 07:30 succeeded (1 ok, 1 skipped), last success 2.0h ago \
+             | succeeded=1;;;0 failed=0;;;0 skipped=1;;;0 duration=1800s;;;0 \
+             hours_since_success=2.0;48;168;0"
+        );
+
+        let (state, _) = render_check(
+            std::slice::from_ref(&ok),
+            now + chrono::Duration::days(3),
+            limits,
+        );
+        assert_eq!(state, CheckState::Warning);
+
+        let failed = RunRecord {
+            tasks: vec![record("Homebrew", "Upgrade Packages", TaskStatus::Failed)],
+            ..ok.clone()
+        };
+        let (state, line) = render_check(&[ok, failed], now, limits);
+        assert_eq!(state.exit_code(), 2);
+        assert!(line.starts_with(
+            "TIDE CRITICAL - last run failed: 1 of 1 task(s) (Upgrade Packages), last success 2.0h ago"
+        ));
+
+        assert_eq!(render_check(&[], now, limits).0, CheckState::Unknown);
+    }
+
+    #[test]
+    fn test_render_xbar() {
+        let run = RunRecord {
+            started_at: Local.with_ymd_and_hms(2026, 10, 16, 7, 30, 0).unwrap(),
+            resumed: false,
+            duration_secs: 95.0,
+            tasks: vec![
+                record("Homebrew", "Update Formulae", TaskStatus::Success),
+                record("Homebrew", "Upgrade Packages", TaskStatus::Failed),
+                record("Languages", "Rustup", TaskStatus::Success),
+            ],
+        };
+        let out = render_xbar(
+            Some(&run),
+            &Config::default(),
+            Path::new("/opt/homebrew/bin/tide"),
+            Path::new("/Users/me/.config/tide/config.toml"),
+        );
+
+        assert_eq!(
+            out,
+            "🌊❌\n---\n\
+             Last run: 2026-10-16 07:30 (95s) | color=gray\n\
+             🍺 Homebrew: 1/2 ok | color=red\n\
+             --✅ Update Formulae (12s)\n\
+             --❌ Upgrade Packages (12s)\n\
+             Languages: 1/1 ok | color=green\n\
+             --✅ Rustup (12s)\n\
+             ---\n\
+             Run now | bash=\"/opt/homebrew/bin/tide\" param1=--config \
+             param2=\"/Users/me/.config/tide/config.toml\" terminal=true refresh=true\n"
+        );
+
+        assert!(
+            render_xbar(None, &Config::default(), Path::new("tide"), Path::new("c"))
+                .starts_with("🌊\n---\nNo runs recorded yet\n")
+        );
+    }
+}
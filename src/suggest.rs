@@ -0,0 +1,63 @@
+/// Closest candidate to a mistyped name, if any is close enough to be a typo
+pub fn did_you_mean<'a, I>(input: &str, candidates: I) -> Option<&'a str>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let needle = input.to_lowercase();
+    // Allow roughly one edit per three characters, but always at least one
+    let max_distance = (needle.chars().count() / 3).max(1);
+    candidates
+        .into_iter()
+        .map(|candidate| (levenshtein(&needle, &candidate.to_lowercase()), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Format a " Did you mean '…'?" suffix for error messages
+pub fn hint<'a, I>(input: &str, candidates: I) -> String
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    did_you_mean(input, candidates)
+        .map(|candidate| format!(" Did you mean '{}'?", candidate))
+        .unwrap_or_default()
+}
+
+/// Edit distance between two strings, counted in characters
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, ca) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_did_you_mean() {
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("", "abc"), 3);
+
+        let groups = ["Homebrew", "System Updates", "Package Managers"];
+        assert_eq!(did_you_mean("Homebew", groups), Some("Homebrew"));
+        assert_eq!(
+            did_you_mean("system updates", groups),
+            Some("System Updates")
+        );
+        assert_eq!(did_you_mean("Rust", groups), None);
+        assert_eq!(hint("Homebew", groups), " Did you mean 'Homebrew'?");
+        assert_eq!(hint("xyz", groups), "");
+    }
+}
@@ -0,0 +1,168 @@
+use colored::Colorize;
+use std::collections::VecDeque;
+use std::io;
+use std::process::Output;
+use std::sync::Mutex;
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::Child;
+
+const INDENT: &str = "      │ ";
+
+/// The last few lines of a running task's output, shown under its spinner
+#[derive(Debug)]
+pub struct OutputTail {
+    lines: Mutex<VecDeque<String>>,
+    limit: usize,
+}
+
+impl OutputTail {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            lines: Mutex::new(VecDeque::with_capacity(limit)),
+            limit,
+        }
+    }
+
+    /// Record a line of output, dropping the oldest beyond the limit
+    pub fn push(&self, line: &str) {
+        // Progress meters redraw with \r; only the latest state is interesting
+        let line = line.trim_end_matches(['\n', '\r']);
+        let line = strip_ansi(line.rsplit('\r').next().unwrap_or_default());
+        let line = line.trim_end();
+        if line.is_empty() {
+            return;
+        }
+        if let Ok(mut lines) = self.lines.lock() {
+            if lines.len() == self.limit {
+                lines.pop_front();
+            }
+            lines.push_back(line.to_string());
+        }
+    }
+
+    pub fn clear(&self) {
+        if let Ok(mut lines) = self.lines.lock() {
+            lines.clear();
+        }
+    }
+
+    /// Dimmed lines, each preceded by a newline, fitted to `width` columns
+    pub fn render(&self, width: usize) -> String {
+        let available = width.saturating_sub(INDENT.chars().count()).max(10);
+        let Ok(lines) = self.lines.lock() else {
+            return String::new();
+        };
+        lines
+            .iter()
+            .map(|line| {
+                let fitted: String = if line.chars().count() > available {
+                    let mut cut: String = line.chars().take(available - 1).collect();
+                    cut.push('…');
+                    cut
+                } else {
+                    line.clone()
+                };
+                format!("\n{}", format!("{}{}", INDENT, fitted).dimmed())
+            })
+            .collect()
+    }
+}
+
+/// Wait for a child with piped stdout/stderr, passing every line to
+/// `on_line` as it arrives while still capturing the complete output.
+pub async fn wait_with_lines(mut child: Child, on_line: impl Fn(&str)) -> io::Result<Output> {
+    let stdout = child.stdout.take();
+    let stderr = child.stderr.take();
+    let (status, stdout, stderr) = tokio::try_join!(
+        child.wait(),
+        collect(stdout, &on_line),
+        collect(stderr, &on_line)
+    )?;
+    Ok(Output {
+        status,
+        stdout,
+        stderr,
+    })
+}
+
+async fn collect(
+    reader: Option<impl AsyncRead + Unpin>,
+    on_line: &impl Fn(&str),
+) -> io::Result<Vec<u8>> {
+    let Some(reader) = reader else {
+        return Ok(Vec::new());
+    };
+    let mut reader = BufReader::new(reader);
+    let mut captured = Vec::new();
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        if reader.read_until(b'\n', &mut line).await? == 0 {
+            return Ok(captured);
+        }
+        on_line(&String::from_utf8_lossy(&line));
+        captured.extend_from_slice(&line);
+    }
+}
+
+/// Remove ANSI escape sequences so dimming applies to the whole line
+pub fn strip_ansi(text: &str) -> String {
+    let mut plain = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            if chars.next() == Some('[') {
+                for c in chars.by_ref() {
+                    if c.is_ascii_alphabetic() {
+                        break;
+                    }
+                }
+            }
+        } else {
+            plain.push(c);
+        }
+    }
+    plain
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tail_keeps_last_lines() {
+        let tail = OutputTail::new(2);
+        tail.push("==> Fetching one\n");
+        tail.push("\n");
+        tail.push("10%\r55%\r\x1b[32m100%\x1b[0m\n");
+        tail.push("==> Pouring two\r\n");
+        assert_eq!(
+            strip_ansi(&tail.render(80)),
+            format!("\n{}100%\n{}==> Pouring two", INDENT, INDENT)
+        );
+        assert_eq!(
+            strip_ansi(&tail.render(18)),
+            format!("\n{}100%\n{}==> Pouri…", INDENT, INDENT)
+        );
+        tail.clear();
+        assert_eq!(tail.render(80), "");
+    }
+
+    #[tokio::test]
+    async fn test_wait_with_lines() {
+        let child = tokio::process::Command::new("sh")
+            .args(["-c", "echo one; echo two >&2; echo three"])
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .spawn()
+            .unwrap();
+        let seen = Mutex::new(Vec::new());
+        let output = wait_with_lines(child, |line| seen.lock().unwrap().push(line.to_string()))
+            .await
+            .unwrap();
+        assert!(output.status.success());
+        assert_eq!(output.stdout, b"one\nthree\n");
+        assert_eq!(output.stderr, b"two\n");
+        assert_eq!(seen.lock().unwrap().len(), 3);
+    }
+}
@@ -0,0 +1,88 @@
+use std::process::Command;
+
+/// Reason the machine is considered too busy for heavy work
+#[derive(Debug, Clone, PartialEq)]
+pub enum Pressure {
+    /// `pmset -g therm` reports CPU throttling or a thermal warning
+    Thermal(String),
+    /// The 1-minute load average exceeds the number of CPUs
+    Load { load: f64, cpus: usize },
+}
+
+impl std::fmt::Display for Pressure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Pressure::Thermal(detail) => write!(f, "thermal pressure ({})", detail),
+            Pressure::Load { load, cpus } => {
+                write!(f, "load average {:.2} on {} CPUs", load, cpus)
+            }
+        }
+    }
+}
+
+/// Check thermal state and load; returns `None` when the machine is idle enough
+pub fn current_pressure() -> Option<Pressure> {
+    if let Ok(output) = Command::new("pmset").args(["-g", "therm"]).output()
+        && output.status.success()
+        && let Some(detail) = parse_therm(&String::from_utf8_lossy(&output.stdout))
+    {
+        return Some(Pressure::Thermal(detail));
+    }
+
+    let cpus = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let load = load_average()?;
+    (load > cpus as f64).then_some(Pressure::Load { load, cpus })
+}
+
+/// The 1-minute load average
+pub fn load_average() -> Option<f64> {
+    let mut loads = [0f64; 3];
+    // SAFETY: the buffer holds the single sample we ask for
+    let samples = unsafe { libc::getloadavg(loads.as_mut_ptr(), 1) };
+    (samples >= 1).then_some(loads[0])
+}
+
+fn parse_therm(output: &str) -> Option<String> {
+    for line in output.lines().map(str::trim) {
+        if let Some((key, value)) = line.split_once('=')
+            && key.trim() == "CPU_Speed_Limit"
+            && let Ok(limit) = value.trim().parse::<u32>()
+            && limit < 100
+        {
+            return Some(format!("CPU speed limited to {}%", limit));
+        }
+        if line.to_lowercase().contains("warning level set to") {
+            return Some(line.to_string());
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_therm() {
+        let idle = "\
+Note: No thermal warning level has been recorded
+Note: No performance warning level has been recorded
+Note: No CPU power status has been recorded
+";
+        assert_eq!(parse_therm(idle), None);
+
+        let throttled = "\
+CPU Power notify
+\tCPU_Scheduler_Limit \t= 100
+\tCPU_Available_CPUs \t= 10
+\tCPU_Speed_Limit \t= 72
+";
+        assert_eq!(
+            parse_therm(throttled).as_deref(),
+            Some("CPU speed limited to 72%")
+        );
+
+        let warning = "Thermal warning level set to 1.";
+        assert_eq!(parse_therm(warning).as_deref(), Some(warning));
+    }
+}
@@ -0,0 +1,173 @@
+use colored::Colorize;
+
+use crate::bench::{REGRESSION_FACTOR, REGRESSION_MIN_SECS};
+use crate::executor::TaskStatus;
+use crate::history::{RunRecord, TaskRecord};
+use crate::palette::Paint;
+
+/// A notable difference of one task against the previous successful run
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    Slower {
+        before: f64,
+        after: f64,
+    },
+    Faster {
+        before: f64,
+        after: f64,
+    },
+    NewlyFailing,
+    /// Ran before but was skipped this time
+    NewlySkipped,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaskChange {
+    pub group: String,
+    pub name: String,
+    pub change: Change,
+}
+
+/// The most recent recorded run without failures
+pub fn last_successful(runs: &[RunRecord]) -> Option<&RunRecord> {
+    runs.iter()
+        .rev()
+        .find(|run| run.tasks.iter().all(|t| t.status != TaskStatus::Failed))
+}
+
+/// Tasks whose status or duration changed notably since `previous`.
+/// Durations are compared the way `tide bench` flags regressions.
+pub fn compare(previous: &RunRecord, current: &RunRecord) -> Vec<TaskChange> {
+    current
+        .tasks
+        .iter()
+        .filter_map(|task| {
+            let before = previous
+                .tasks
+                .iter()
+                .find(|t| t.group == task.group && t.name == task.name)?;
+            let change = change(before, task)?;
+            Some(TaskChange {
+                group: task.group.clone(),
+                name: task.name.clone(),
+                change,
+            })
+        })
+        .collect()
+}
+
+fn change(before: &TaskRecord, after: &TaskRecord) -> Option<Change> {
+    match (before.status, after.status) {
+        (TaskStatus::Success, TaskStatus::Failed) => Some(Change::NewlyFailing),
+        (TaskStatus::Success, TaskStatus::Skipped) => Some(Change::NewlySkipped),
+        (TaskStatus::Success, TaskStatus::Success) => {
+            let (before, after) = (before.duration_secs, after.duration_secs);
+            if after > before * REGRESSION_FACTOR && after - before >= REGRESSION_MIN_SECS {
+                Some(Change::Slower { before, after })
+            } else if before > after * REGRESSION_FACTOR && before - after >= REGRESSION_MIN_SECS {
+                Some(Change::Faster { before, after })
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+impl TaskChange {
+    fn describe(&self) -> String {
+        let task = format!("{} › {}", self.group, self.name);
+        match self.change {
+            Change::Slower { before, after } => format!(
+                "{} took {:.1}x longer ({:.0}s → {:.0}s)",
+                task,
+                after / before.max(f64::EPSILON),
+                before,
+                after
+            ),
+            Change::Faster { before, after } => format!(
+                "{} was {:.1}x faster ({:.0}s → {:.0}s)",
+                task,
+                before / after.max(f64::EPSILON),
+                before,
+                after
+            ),
+            Change::NewlyFailing => format!("{} newly failing", task),
+            Change::NewlySkipped => format!("{} newly skipped", task),
+        }
+    }
+}
+
+/// Print the changes since the previous successful run, if there are any
+pub fn display(previous: &RunRecord, changes: &[TaskChange]) {
+    if changes.is_empty() {
+        return;
+    }
+    println!(
+        "\n{} {}",
+        "Since the last successful run".bright_white().bold(),
+        format!("({})", previous.started_at.format("%Y-%m-%d %H:%M")).dimmed()
+    );
+    for change in changes {
+        let line = change.describe();
+        match change.change {
+            Change::Slower { .. } => println!("  {} {}", "▲".warning(), line.warning()),
+            Change::Faster { .. } => println!("  {} {}", "▼".success(), line),
+            Change::NewlyFailing => println!("  {} {}", "✗".failure(), line.failure()),
+            Change::NewlySkipped => println!("  {} {}", "⏭".dimmed(), line.dimmed()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Local;
+
+    fn run(tasks: &[(&str, TaskStatus, f64)]) -> RunRecord {
+        RunRecord {
+            started_at: Local::now(),
+            resumed: false,
+            duration_secs: 0.0,
+            tasks: tasks
+                .iter()
+                .map(|&(name, status, duration_secs)| TaskRecord {
+                    group: "Homebrew".to_string(),
+                    name: name.to_string(),
+                    status,
+                    duration_secs,
+                    reason: None,
+                    started_at: None,
+                    output_hash: None,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_compare_with_last_successful_run() {
+        let good = run(&[
+            ("Upgrade", TaskStatus::Success, 60.0),
+            ("Cleanup", TaskStatus::Success, 20.0),
+            ("Doctor", TaskStatus::Success, 3.0),
+        ]);
+        let failed = run(&[("Upgrade", TaskStatus::Failed, 1.0)]);
+        let history = [good, failed];
+        let previous = last_successful(&history).unwrap();
+        assert_eq!(previous.tasks.len(), 3);
+
+        let current = run(&[
+            ("Upgrade", TaskStatus::Success, 190.0),
+            ("Cleanup", TaskStatus::Failed, 2.0),
+            ("Doctor", TaskStatus::Success, 7.0),
+            ("Autoremove", TaskStatus::Success, 30.0),
+        ]);
+        let changes = compare(previous, &current);
+        assert_eq!(changes.len(), 2);
+        assert_eq!(
+            changes[0].describe(),
+            "Homebrew › Upgrade took 3.2x longer (60s → 190s)"
+        );
+        assert_eq!(changes[1].change, Change::NewlyFailing);
+    }
+}
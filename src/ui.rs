@@ -1,9 +1,14 @@
-use anyhow::Result;
 use colored::Colorize;
 use std::process::Command;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+
+use crate::disk;
+use crate::executor::{TaskResult, TaskStatus};
+use crate::palette::Paint;
 
 const DIVIDER_WIDTH: usize = 60;
+const TIMELINE_LABEL_WIDTH: usize = 22;
+const TIMELINE_WIDTH: usize = 30;
 
 /// Print the Tide banner
 pub fn print_banner() {
@@ -28,27 +33,19 @@ pub fn print_banner() {
     println!("{}", banner.bright_cyan());
 }
 
-/// Display system information
-pub fn display_system_info() -> Result<()> {
-    println!("\n{}", "📊 System Information".bright_blue().bold());
-    println!("{}", "─".repeat(DIVIDER_WIDTH).dimmed());
+/// Lines of the system information widget: disk, power, macOS version
+/// and uptime, each left out when it cannot be determined
+pub fn system_info_lines() -> Vec<String> {
+    let mut lines = Vec::new();
 
     // Disk space
-    if let Ok(output) = Command::new("df").args(["-h", "/"]).output()
-        && output.status.success()
-    {
-        let lines = String::from_utf8_lossy(&output.stdout);
-        if let Some(line) = lines.lines().nth(1) {
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            if parts.len() >= 5 {
-                println!(
-                    "  💾 Disk: {} used of {} ({})",
-                    parts[2].bright_white(),
-                    parts[1].bright_white(),
-                    parts[4].bright_yellow()
-                );
-            }
-        }
+    if let Some(usage) = disk::root_usage() {
+        lines.push(format!(
+            "💾 Disk: {} used of {} ({})",
+            disk::format_kb(usage.used_kb).bright_white(),
+            disk::format_kb(usage.total_kb).bright_white(),
+            usage.capacity.bright_yellow()
+        ));
     }
 
     // Battery status
@@ -62,13 +59,13 @@ pub fn display_system_info() -> Result<()> {
         {
             let pct = &line[pct_start..pct_start + pct_end];
             let status = if line.contains("charging") {
-                "charging ⚡".yellow()
+                "charging ⚡".warning()
             } else if line.contains("charged") {
-                "charged ✅".green()
+                "charged ✅".success()
             } else {
                 "battery 🔋".normal()
             };
-            println!("  🔋 Power: {}% {}", pct.bright_white(), status);
+            lines.push(format!("🔋 Power: {}% {}", pct.bright_white(), status));
         }
     }
 
@@ -77,7 +74,7 @@ pub fn display_system_info() -> Result<()> {
         && output.status.success()
     {
         let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
-        println!("  🍎 macOS: {}", version.bright_white());
+        lines.push(format!("🍎 macOS: {}", version.bright_white()));
     }
 
     // Uptime
@@ -88,12 +85,90 @@ pub fn display_system_info() -> Result<()> {
         if let Some(up_pos) = uptime.find("up ") {
             let up_str = &uptime[up_pos + 3..];
             if let Some(comma_pos) = up_str.find(',') {
-                println!("  ⏱️  Uptime: {}", up_str[..comma_pos].bright_white());
+                lines.push(format!(
+                    "⏱️  Uptime: {}",
+                    up_str[..comma_pos].bright_white()
+                ));
             }
         }
     }
 
-    Ok(())
+    lines
+}
+
+/// Render a Gantt-style timeline of task start/end times relative to the run start
+pub fn render_timeline(results: &[TaskResult], run_start: Instant, total: Duration) {
+    if results.is_empty() {
+        return;
+    }
+
+    println!("\n{}", "📈 Timeline".bright_blue().bold());
+    println!("{}", "─".repeat(DIVIDER_WIDTH).dimmed());
+
+    let total_secs = total.as_secs_f64().max(f64::EPSILON);
+    let mut ordered: Vec<&TaskResult> = results.iter().collect();
+    ordered.sort_by_key(|result| result.started);
+
+    for result in ordered {
+        let offset = result
+            .started
+            .saturating_duration_since(run_start)
+            .as_secs_f64();
+        let end = offset + result.duration.as_secs_f64();
+        let first = ((offset / total_secs) * TIMELINE_WIDTH as f64).floor() as usize;
+        let last = ((end / total_secs) * TIMELINE_WIDTH as f64).ceil() as usize;
+        let first = first.min(TIMELINE_WIDTH - 1);
+        let last = last.clamp(first + 1, TIMELINE_WIDTH);
+
+        let bar = "█".repeat(last - first);
+        let bar = match result.status {
+            TaskStatus::Success => bar.success(),
+            TaskStatus::Failed => bar.failure(),
+            TaskStatus::Skipped | TaskStatus::Cancelled => bar.warning(),
+        };
+
+        println!(
+            "  {:<label_width$} │{}{}{}│ {}",
+            truncate_label(&result.name, TIMELINE_LABEL_WIDTH),
+            " ".repeat(first),
+            bar,
+            " ".repeat(TIMELINE_WIDTH - last),
+            format_duration(result.duration).dimmed(),
+            label_width = TIMELINE_LABEL_WIDTH
+        );
+    }
+
+    let axis = format!(
+        "{:<half$}{:>half$}",
+        "0s",
+        format_duration(total),
+        half = TIMELINE_WIDTH / 2
+    );
+    println!(
+        "  {:<label_width$}  {}",
+        "",
+        axis.dimmed(),
+        label_width = TIMELINE_LABEL_WIDTH
+    );
+}
+
+fn truncate_label(label: &str, width: usize) -> String {
+    if label.chars().count() <= width {
+        label.to_string()
+    } else {
+        let truncated: String = label.chars().take(width - 1).collect();
+        format!("{}…", truncated)
+    }
+}
+
+/// A duration as `42s` or `3m 5s`, as shown in summaries and spinners
+pub fn format_duration(d: Duration) -> String {
+    let secs = d.as_secs();
+    if secs < 60 {
+        format!("{}s", secs)
+    } else {
+        format!("{}m {}s", secs / 60, secs % 60)
+    }
 }
 
 /// Result of a weather lookup
@@ -143,18 +218,3 @@ pub async fn fetch_weather() -> WeatherStatus {
         WeatherStatus::Available(trimmed.to_string())
     }
 }
-
-/// Display weather information
-pub fn render_weather(status: WeatherStatus) {
-    println!("\n{}", "🌤️  Weather".bright_blue().bold());
-    println!("{}", "─".repeat(DIVIDER_WIDTH).dimmed());
-
-    match status {
-        WeatherStatus::Available(summary) => println!("  {}", summary.bright_white()),
-        WeatherStatus::NoData(message) => println!("  {}", message.dimmed()),
-        WeatherStatus::Error(reason) => println!(
-            "  {}",
-            format!("Unable to fetch weather data ({reason}).").dimmed()
-        ),
-    }
-}
@@ -0,0 +1,577 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::config::{Config, Matrix, Settings, TaskConfig, WidgetSpec, parse_interval};
+use crate::profile::{Profile, Toggle};
+use crate::sandbox::Sandbox;
+use crate::widgets::Widget;
+use crate::{matrix, outputs, params, plan, runas, suggest, vars};
+
+/// Check names and cross-references of a loaded config.
+///
+/// Returns every problem found rather than stopping at the first one, so a
+/// broken config can be fixed in a single pass before anything runs.
+pub fn validate(config: &Config) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    if config
+        .settings
+        .keychain_label
+        .as_deref()
+        .is_some_and(|label| label.trim().is_empty())
+    {
+        problems.push("settings.keychain_label must not be empty".to_string());
+    }
+
+    for widget in config.settings.widgets.iter().flatten() {
+        match widget {
+            WidgetSpec::Builtin(name) if Widget::parse(name).is_none() => {
+                problems.push(format!(
+                    "unknown widget '{}'.{}",
+                    name,
+                    suggest::hint(name, Widget::NAMES.iter().copied())
+                ));
+            }
+            WidgetSpec::Command { title, command, .. } if command.is_empty() => {
+                problems.push(format!("widget '{}' has an empty command", title));
+            }
+            _ => {}
+        }
+    }
+
+    let mut var_names: Vec<&String> = config.vars.keys().collect();
+    var_names.sort();
+    for name in var_names {
+        if vars::RESERVED.contains(&name.as_str()) {
+            problems.push(format!("vars.{} is reserved", name));
+        } else if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            problems.push(format!(
+                "vars.{} must only use letters, digits, `_` and `-`",
+                name
+            ));
+        }
+    }
+
+    let mut sections: Vec<(String, &Profile)> = config
+        .profiles
+        .iter()
+        .map(|(name, profile)| (format!("profile '{}'", name), profile))
+        .chain(
+            config
+                .host
+                .iter()
+                .map(|(name, section)| (format!("host '{}'", name), section)),
+        )
+        .collect();
+    sections.sort_by(|a, b| a.0.cmp(&b.0));
+    for (label, profile) in sections {
+        for (toggle, _) in profile.toggles() {
+            if let Some(problem) = check_toggle(config, toggle) {
+                problems.push(format!("{} {}", label, problem));
+            }
+        }
+        if let Err(err) = check_profile_settings(&config.settings, &profile.settings) {
+            problems.push(format!("{} has invalid settings: {}", label, err.message()));
+        }
+    }
+
+    for (index, hook) in config.post_run.commands.iter().enumerate() {
+        if hook.command.is_empty() {
+            problems.push(format!("post_run command #{} is empty", index + 1));
+        }
+    }
+
+    let mut group_names = HashSet::new();
+    let mut registered = HashSet::new();
+    for group in &config.groups {
+        if group.name.trim().is_empty() {
+            problems.push("a group has an empty name".to_string());
+        } else if !group_names.insert(group.name.as_str()) {
+            problems.push(format!("group '{}' is defined more than once", group.name));
+        }
+
+        if let Some(interval) = &group.min_interval
+            && parse_interval(interval).is_none()
+        {
+            problems.push(format!(
+                "group '{}' has invalid min_interval '{}' (use e.g. \"12h\", \"1d\" or \"30m\")",
+                group.name, interval
+            ));
+        }
+
+        let mut task_names = HashSet::new();
+        for task in &group.tasks {
+            if task.name.trim().is_empty() {
+                problems.push(format!(
+                    "group '{}' has a task with an empty name",
+                    group.name
+                ));
+                continue;
+            }
+            if !task_names.insert(task.name.as_str()) {
+                problems.push(format!(
+                    "task '{}' is defined more than once in group '{}'",
+                    task.name, group.name
+                ));
+            }
+            if let Some(sandbox) = &task.sandbox {
+                if Sandbox::parse(sandbox).is_none() {
+                    problems.push(format!(
+                        "task '{}' in group '{}' has unknown sandbox '{}' (use \"no-network\", \"readonly-home\" or a profile path)",
+                        task.name, group.name, sandbox
+                    ));
+                } else if task.kind.is_some() {
+                    problems.push(format!(
+                        "task '{}' in group '{}' cannot combine `sandbox` with a built-in `type`",
+                        task.name, group.name
+                    ));
+                }
+            }
+            for name in outputs::task_references(task) {
+                if !registered.contains(name) {
+                    problems.push(format!(
+                        "task '{}' in group '{}' uses output '{}' before any earlier task registers it",
+                        task.name, group.name, name
+                    ));
+                }
+            }
+            if let Some(interval) = &task.min_interval
+                && parse_interval(interval).is_none()
+            {
+                problems.push(format!(
+                    "task '{}' in group '{}' has invalid min_interval '{}' (use e.g. \"12h\", \"1d\" or \"30m\")",
+                    task.name, group.name, interval
+                ));
+            }
+            if (task.shell.is_some() || task.login_shell) && task.command.is_empty() {
+                problems.push(format!(
+                    "task '{}' in group '{}' sets `shell` or `login_shell` without a `command`",
+                    task.name, group.name
+                ));
+            }
+            if let Some(url) = &task.help_url
+                && !url.starts_with("https://")
+                && !url.starts_with("http://")
+            {
+                problems.push(format!(
+                    "task '{}' in group '{}' has a `help_url` that is not an http(s) URL",
+                    task.name, group.name
+                ));
+            }
+            if let Some(user) = &task.run_as {
+                if user.trim().is_empty() {
+                    problems.push(format!(
+                        "task '{}' in group '{}' has an empty `run_as`",
+                        task.name, group.name
+                    ));
+                } else if task.sudo || task.kind.is_some() || task.is_osascript() {
+                    problems.push(format!(
+                        "task '{}' in group '{}' cannot combine `run_as` with `sudo`, `type` or `osascript`",
+                        task.name, group.name
+                    ));
+                }
+            }
+            if task.env_file_encrypted.is_some() {
+                if task.command.is_empty() {
+                    problems.push(format!(
+                        "task '{}' in group '{}' sets `env_file_encrypted` without a `command`",
+                        task.name, group.name
+                    ));
+                } else if config.settings.age_identity.is_none() {
+                    problems.push(format!(
+                        "task '{}' in group '{}' sets `env_file_encrypted` but settings.age_identity is missing",
+                        task.name, group.name
+                    ));
+                }
+            }
+            match &task.matrix {
+                Some(_) if task.kind.is_some() || task.register.is_some() => {
+                    problems.push(format!(
+                        "task '{}' in group '{}' cannot combine `matrix` with `type` or `register`",
+                        task.name, group.name
+                    ));
+                }
+                Some(Matrix::Command { command }) if command.is_empty() => {
+                    problems.push(format!(
+                        "task '{}' in group '{}' has an empty matrix command",
+                        task.name, group.name
+                    ));
+                }
+                None if matrix::uses_item(task) => {
+                    problems.push(format!(
+                        "task '{}' in group '{}' uses ${{item}} without a `matrix`",
+                        task.name, group.name
+                    ));
+                }
+                _ => {}
+            }
+            let mut param_names = HashSet::new();
+            for param in &task.params {
+                if !param_names.insert(param.name.as_str()) {
+                    problems.push(format!(
+                        "task '{}' in group '{}' declares param '{}' more than once",
+                        task.name, group.name, param.name
+                    ));
+                }
+                if let Some(Err(err)) = param.default_value() {
+                    problems.push(format!(
+                        "task '{}' in group '{}' has an invalid default for param '{}': {}",
+                        task.name, group.name, param.name, err
+                    ));
+                }
+            }
+            for name in params::task_references(task) {
+                if !param_names.contains(name) {
+                    problems.push(format!(
+                        "task '{}' in group '{}' uses undeclared param '{}'",
+                        task.name, group.name, name
+                    ));
+                }
+            }
+            if let Some(name) = &task.register {
+                if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                    problems.push(format!(
+                        "task '{}' in group '{}' registers invalid output name '{}' (use letters, digits and _)",
+                        task.name, group.name, name
+                    ));
+                } else if !registered.insert(name.as_str()) {
+                    problems.push(format!("output '{}' is registered more than once", name));
+                }
+            }
+            if let Some(problem) = check_task_action(task) {
+                problems.push(format!(
+                    "task '{}' in group '{}' {}",
+                    task.name, group.name, problem
+                ));
+            }
+        }
+    }
+
+    for test in &config.tests {
+        let group = match &test.group {
+            Some(name) => match config.groups.iter().find(|g| &g.name == name) {
+                Some(group) => Some(group),
+                None => {
+                    problems.push(format!(
+                        "test '{}' references unknown group '{}'.{}",
+                        test.name,
+                        name,
+                        suggest::hint(name, config.groups.iter().map(|g| g.name.as_str()))
+                    ));
+                    continue;
+                }
+            },
+            None => None,
+        };
+
+        if let Some(name) = &test.task {
+            let found = config
+                .groups
+                .iter()
+                .filter(|g| group.is_none_or(|selected| selected.name == g.name))
+                .any(|g| g.tasks.iter().any(|t| &t.name == name));
+            if !found {
+                let candidates = config
+                    .groups
+                    .iter()
+                    .flat_map(|g| g.tasks.iter().map(|t| t.name.as_str()));
+                problems.push(format!(
+                    "test '{}' references unknown task '{}'.{}",
+                    test.name,
+                    name,
+                    suggest::hint(name, candidates)
+                ));
+            }
+        }
+    }
+
+    check_dependencies(config, &mut problems);
+    problems
+}
+
+/// `depends_on` entries must name existing tasks without forming a cycle
+fn check_dependencies(config: &Config, problems: &mut Vec<String>) {
+    let tasks: Vec<(&str, &TaskConfig)> = config
+        .groups
+        .iter()
+        .flat_map(|g| g.tasks.iter().map(move |t| (g.name.as_str(), t)))
+        .collect();
+    let mut edges = vec![Vec::new(); tasks.len()];
+    for (index, (group, task)) in tasks.iter().enumerate() {
+        for reference in &task.depends_on {
+            let targets: Vec<usize> = tasks
+                .iter()
+                .enumerate()
+                .filter(|(_, (other_group, other))| {
+                    plan::dependency_matches(reference, group, other_group, &other.name)
+                })
+                .map(|(target, _)| target)
+                .collect();
+            if targets.is_empty() {
+                problems.push(format!(
+                    "task '{}' in group '{}' depends on unknown task '{}'.{}",
+                    task.name,
+                    group,
+                    reference,
+                    suggest::hint(
+                        reference,
+                        tasks
+                            .iter()
+                            .filter(|(other_group, _)| other_group == group)
+                            .map(|(_, t)| t.name.as_str())
+                    )
+                ));
+            }
+            edges[index].extend(targets);
+        }
+    }
+
+    // Depth-first search; a task reached again while on the stack closes a cycle
+    fn visit(task: usize, edges: &[Vec<usize>], state: &mut [u8], stack: &mut Vec<usize>) -> bool {
+        match state[task] {
+            1 => {
+                stack.push(task);
+                return true;
+            }
+            2 => return false,
+            _ => {}
+        }
+        state[task] = 1;
+        stack.push(task);
+        for &next in &edges[task] {
+            if visit(next, edges, state, stack) {
+                return true;
+            }
+        }
+        stack.pop();
+        state[task] = 2;
+        false
+    }
+    let mut state = vec![0; tasks.len()];
+    for start in 0..tasks.len() {
+        let mut stack = Vec::new();
+        if visit(start, &edges, &mut state, &mut stack) {
+            let closing = *stack.last().expect("cycle ends on the stack");
+            let first = stack.iter().position(|&t| t == closing).unwrap_or(0);
+            let cycle: Vec<String> = stack[first..]
+                .iter()
+                .map(|&t| format!("{}/{}", tasks[t].0, tasks[t].1.name))
+                .collect();
+            problems.push(format!("`depends_on` forms a cycle: {}", cycle.join(" → ")));
+            return;
+        }
+    }
+}
+
+/// Problems that depend on this machine or the invocation rather than on the
+/// config itself, so they are only reported by `tide validate`
+#[derive(Debug, Default)]
+pub struct Environment {
+    /// Things that would make a run fail
+    pub errors: Vec<String>,
+    /// Things that make tasks skip on this machine
+    pub warnings: Vec<String>,
+}
+
+/// Check group filters against the config and the enabled tasks against this
+/// machine: missing `working_dir` directories are errors, missing
+/// `check_command` binaries only warnings since the task is skipped then
+pub fn check_environment<'a>(
+    config: &Config,
+    group_filters: impl IntoIterator<Item = &'a String>,
+    command_exists: impl Fn(&str) -> bool,
+    path_exists: impl Fn(&Path) -> bool,
+) -> Environment {
+    let mut environment = Environment::default();
+    for name in group_filters {
+        if !config.groups.iter().any(|group| &group.name == name) {
+            environment.errors.push(format!(
+                "unknown group '{}' in --groups/--skip-groups.{}",
+                name,
+                suggest::hint(name, config.groups.iter().map(|g| g.name.as_str()))
+            ));
+        }
+    }
+
+    for group in config.groups.iter().filter(|group| group.enabled) {
+        for task in group.tasks.iter().filter(|task| task.enabled) {
+            // Directories filled in from matrix items, params or outputs are
+            // only known at run time
+            if let Some(dir) = task
+                .working_dir
+                .as_deref()
+                .filter(|dir| !dir.contains("${"))
+            {
+                let path = match &task.run_as {
+                    Some(user) => runas::working_dir(user, dir),
+                    None => PathBuf::from(shellexpand::tilde(dir).as_ref()),
+                };
+                if !path_exists(&path) {
+                    environment.errors.push(format!(
+                        "task '{}' in group '{}' has a `working_dir` that does not exist: {}",
+                        task.name,
+                        group.name,
+                        path.display()
+                    ));
+                }
+            }
+            if let Some(check) = &task.check_command
+                && !command_exists(check)
+            {
+                environment.warnings.push(format!(
+                    "task '{}' in group '{}' will be skipped: `check_command` '{}' is not installed",
+                    task.name, group.name, check
+                ));
+            }
+        }
+    }
+    environment
+}
+
+/// Why a profile's group or task toggle does not apply, if it does not
+fn check_toggle(config: &Config, toggle: Toggle) -> Option<String> {
+    let group_names = || config.groups.iter().map(|g| g.name.as_str());
+    let (group, task) = match toggle {
+        Toggle::Group(group) => (group, None),
+        Toggle::Task(group, task) => (group, Some(task)),
+        Toggle::Invalid(reference) => {
+            return Some(format!(
+                "refers to task '{}'; write it as \"<group>/<task>\"",
+                reference
+            ));
+        }
+    };
+    let Some(found) = config.groups.iter().find(|g| g.name == group) else {
+        return Some(format!(
+            "refers to unknown group '{}'.{}",
+            group,
+            suggest::hint(group, group_names())
+        ));
+    };
+    let task = task?;
+    (!found.tasks.iter().any(|t| t.name == task)).then(|| {
+        format!(
+            "refers to unknown task '{}/{}'.{}",
+            group,
+            task,
+            suggest::hint(task, found.tasks.iter().map(|t| t.name.as_str()))
+        )
+    })
+}
+
+/// Whether the settings still deserialize with a profile's settings applied
+fn check_profile_settings(
+    settings: &Settings,
+    profile: &toml::Table,
+) -> Result<(), toml::de::Error> {
+    let mut merged = toml::Table::try_from(settings).expect("settings serialize to a table");
+    merged.extend(profile.clone());
+    toml::Value::Table(merged)
+        .try_into::<Settings>()
+        .map(|_| ())
+}
+
+/// A task must define exactly one way to run
+fn check_task_action(task: &TaskConfig) -> Option<&'static str> {
+    let actions = [
+        !task.command.is_empty(),
+        task.kind.is_some(),
+        task.osascript.is_some(),
+        task.applescript_file.is_some(),
+        task.just.is_some(),
+        task.taskfile.is_some(),
+    ]
+    .into_iter()
+    .filter(|set| *set)
+    .count();
+
+    match actions {
+        0 => {
+            Some("has no `command`, `type`, `osascript`, `applescript_file`, `just` or `taskfile`")
+        }
+        1 => None,
+        _ => Some(
+            "sets more than one of `command`, `type`, `osascript`, `applescript_file`, `just` and `taskfile`",
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ConfigTest, TaskExpectation};
+
+    #[test]
+    fn test_validate_reports_all_problems() {
+        let mut config = Config::default();
+        assert!(validate(&config).is_empty());
+
+        let duplicate = config.groups[1].clone();
+        config.groups.push(duplicate);
+        config.groups[0].tasks[0].command.clear();
+        config.tests.push(ConfigTest {
+            name: "casks".to_string(),
+            group: None,
+            task: Some("Upgrade Pakages".to_string()),
+            missing_commands: Vec::new(),
+            missing_paths: Vec::new(),
+            expect: Some(TaskExpectation::Run),
+            enabled_tasks: None,
+        });
+
+        let mut settings = toml::Table::new();
+        settings.insert("parallel_limit".to_string(), "eight".into());
+        config.profiles.insert(
+            "work".to_string(),
+            Profile {
+                enable_groups: vec!["Homebew".to_string()],
+                settings,
+                ..Profile::default()
+            },
+        );
+
+        assert_eq!(
+            validate(&config),
+            [
+                "profile 'work' refers to unknown group 'Homebew'. Did you mean 'Homebrew'?",
+                "profile 'work' has invalid settings: invalid type: string \"eight\", expected usize",
+                "task 'macOS Updates' in group 'System Updates' has no `command`, `type`, `osascript`, `applescript_file`, `just` or `taskfile`",
+                "group 'Homebrew' is defined more than once",
+                "test 'casks' references unknown task 'Upgrade Pakages'. Did you mean 'Upgrade Packages'?",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_environment() {
+        let mut config = Config::default();
+        config.groups[1].tasks[0].working_dir = Some("/Users/me/code".to_string());
+        config.groups[1].tasks[1].working_dir = Some("${item}".to_string());
+        let filters = ["Homebrew".to_string(), "Homebew".to_string()];
+
+        let environment = check_environment(
+            &config,
+            &filters,
+            |program| program != "brew",
+            |path| path != Path::new("/Users/me/code"),
+        );
+        assert_eq!(
+            environment.errors,
+            [
+                "unknown group 'Homebew' in --groups/--skip-groups. Did you mean 'Homebrew'?",
+                "task 'Update Formulae' in group 'Homebrew' has a `working_dir` that does not exist: /Users/me/code",
+            ]
+        );
+        assert!(
+            environment
+                .warnings
+                .iter()
+                .all(|warning| warning.contains("'brew' is not installed"))
+        );
+        assert!(!environment.warnings.is_empty());
+    }
+}
@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+
+use crate::config::Config;
+use crate::error::TideError;
+
+/// Names `[vars]` cannot use because their placeholders mean something else
+pub const RESERVED: [&str; 2] = ["HOME", "item"];
+
+/// Resolve the placeholders in the commands, working directories and env
+/// values of all tasks:
+///
+/// - `${HOME}` – the home directory
+/// - `${env:VAR}` – an environment variable, which must be set
+/// - `${<name>}` – a value of the `[vars]` table
+///
+/// Other `${...}` are left for the shell and for matrix items, params and
+/// outputs, which are filled in at run time; `$${` writes a literal `${`.
+/// Values in `[vars]` may use `${HOME}` and `${env:VAR}` themselves.
+pub fn apply(config: &mut Config) -> Result<(), TideError> {
+    apply_with(config, |name| std::env::var(name).ok())
+}
+
+fn apply_with(config: &mut Config, env: impl Fn(&str) -> Option<String>) -> Result<(), TideError> {
+    let none = HashMap::new();
+    let mut vars = HashMap::new();
+    for (name, value) in &config.vars {
+        let value = interpolate(value, &none, &env)
+            .map_err(|problem| TideError::Config(format!("vars.{}: {}", name, problem)))?;
+        vars.insert(name.clone(), value);
+    }
+
+    for group in &mut config.groups {
+        for task in &mut group.tasks {
+            for text in task
+                .command
+                .iter_mut()
+                .chain(task.working_dir.iter_mut())
+                .chain(task.env.values_mut())
+            {
+                *text = interpolate(text, &vars, &env).map_err(|problem| {
+                    TideError::Config(format!(
+                        "task '{}' in group '{}': {}",
+                        task.name, group.name, problem
+                    ))
+                })?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn interpolate(
+    text: &str,
+    vars: &HashMap<String, String>,
+    env: impl Fn(&str) -> Option<String>,
+) -> Result<String, String> {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("${") {
+        if rest[..start].ends_with('$') {
+            out.push_str(&rest[..start - 1]);
+            out.push_str("${");
+            rest = &rest[start + 2..];
+            continue;
+        }
+        out.push_str(&rest[..start]);
+        let Some(len) = rest[start..].find('}') else {
+            rest = &rest[start..];
+            break;
+        };
+        let placeholder = &rest[start..start + len + 1];
+        let name = &placeholder[2..placeholder.len() - 1];
+        if let Some(var) = name.strip_prefix("env:") {
+            let value =
+                env(var).ok_or_else(|| format!("environment variable {} is not set", var))?;
+            out.push_str(&value);
+        } else if name == "HOME" {
+            out.push_str(&env("HOME").ok_or("HOME is not set")?);
+        } else if let Some(value) = vars.get(name) {
+            out.push_str(value);
+        } else {
+            out.push_str(placeholder);
+        }
+        rest = &rest[start + len + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interpolate_vars_and_env() {
+        let mut config: Config = toml::from_str(
+            r#"
+[vars]
+code = "${HOME}/code"
+brewfile = "${env:DOTFILES}/Brewfile"
+
+[[groups]]
+name = "Dev"
+
+[[groups.tasks]]
+name = "Bundle"
+command = ["sh", "-c", "brew bundle --file ${brewfile} && for f in *; do echo ${f}; done"]
+working_dir = "${code}"
+env = { PRICE = "$${code}", PINNED = "${outputs.pinned}" }
+"#,
+        )
+        .unwrap();
+        let env = |name: &str| match name {
+            "HOME" => Some("/Users/me".to_string()),
+            "DOTFILES" => Some("/Users/me/dotfiles".to_string()),
+            _ => None,
+        };
+        apply_with(&mut config, env).unwrap();
+        let task = &config.groups[0].tasks[0];
+        assert_eq!(
+            task.command[2],
+            "brew bundle --file /Users/me/dotfiles/Brewfile && for f in *; do echo ${f}; done"
+        );
+        assert_eq!(task.working_dir.as_deref(), Some("/Users/me/code"));
+        assert_eq!(task.env["PRICE"], "${code}");
+        assert_eq!(task.env["PINNED"], "${outputs.pinned}");
+
+        config.groups[0].tasks[0].command = vec!["${env:MISSING}".to_string()];
+        let err = apply_with(&mut config, env).unwrap_err();
+        assert!(err.to_string().contains("MISSING is not set"));
+    }
+}
@@ -0,0 +1,228 @@
+use crate::config::{Config, TaskConfig, TaskGroup};
+use crate::error::TideError;
+use crate::executor::{self, TaskStatus};
+use crate::history::RunRecord;
+use crate::{lint, provenance, suggest};
+
+/// Why a task did or would not run, in plain language
+#[derive(Debug)]
+pub struct Explanation {
+    pub group: String,
+    pub task: String,
+    /// What keeps the task out of the next run (empty when nothing does)
+    pub blockers: Vec<String>,
+    /// Prompts or checks that may still hold the task back during the run
+    pub caveats: Vec<String>,
+    /// What happened to the task in the last recorded run
+    pub last_run: String,
+}
+
+/// Explain every task with the given name (case-insensitive), evaluating the
+/// group filters and preconditions now and looking the task up in the last run
+pub fn explain(
+    config: &Config,
+    name: &str,
+    groups: Option<&[String]>,
+    skip_groups: Option<&[String]>,
+    last_run: Option<&RunRecord>,
+) -> Result<Vec<Explanation>, TideError> {
+    let explanations: Vec<Explanation> = config
+        .groups
+        .iter()
+        .flat_map(|group| group.tasks.iter().map(move |task| (group, task)))
+        .filter(|(_, task)| task.name.eq_ignore_ascii_case(name))
+        .map(|(group, task)| Explanation {
+            group: group.name.clone(),
+            task: task.name.clone(),
+            blockers: blockers(group, task, groups, skip_groups),
+            caveats: caveats(group, task),
+            last_run: describe_last_run(group, task, last_run),
+        })
+        .collect();
+
+    if explanations.is_empty() {
+        let names = config
+            .groups
+            .iter()
+            .flat_map(|group| group.tasks.iter().map(|task| task.name.as_str()));
+        return Err(TideError::Config(format!(
+            "Unknown task '{}'.{}",
+            name,
+            suggest::hint(name, names)
+        )));
+    }
+    Ok(explanations)
+}
+
+fn blockers(
+    group: &TaskGroup,
+    task: &TaskConfig,
+    groups: Option<&[String]>,
+    skip_groups: Option<&[String]>,
+) -> Vec<String> {
+    let mut blockers = Vec::new();
+    if !group.enabled {
+        blockers.push(format!(
+            "Its group '{}' is disabled (`enabled = false`), so none of the group's tasks run.",
+            group.name
+        ));
+    }
+    if !task.enabled {
+        blockers.push("The task itself is disabled (`enabled = false`).".to_string());
+    }
+    if let Some(groups) = groups
+        && !groups.contains(&group.name)
+    {
+        blockers.push(format!(
+            "Its group '{}' is not among the selected --groups ({}).",
+            group.name,
+            groups.join(", ")
+        ));
+    }
+    if let Some(skip) = skip_groups
+        && skip.contains(&group.name)
+    {
+        blockers.push(format!(
+            "Its group '{}' is excluded by --skip-groups.",
+            group.name
+        ));
+    }
+    if let Err(reason) = executor::check_preconditions(task) {
+        blockers.push(format!("It would be skipped right now: {}.", reason));
+    }
+    blockers
+}
+
+fn caveats(group: &TaskGroup, task: &TaskConfig) -> Vec<String> {
+    let mut caveats = Vec::new();
+    if !task.allow_dangerous {
+        let findings = lint::lint_task(task);
+        if !findings.is_empty() {
+            let labels: Vec<&str> = findings.iter().map(|(kind, _)| kind.label()).collect();
+            caveats.push(format!(
+                "Its command looks dangerous ({}), so it must be confirmed before each run and is skipped with --force/--quiet.",
+                labels.join(", ")
+            ));
+        }
+    }
+    if provenance::is_remote(task) {
+        let approved = provenance::Approvals::open_default()
+            .map(|approvals| approvals.is_approved(&group.name, task))
+            .unwrap_or(false);
+        if !approved {
+            caveats.push(
+                "It was imported from a URL and its current command has not been approved yet; unattended runs skip it."
+                    .to_string(),
+            );
+        }
+    }
+    if let Some(interval) = task.min_interval.as_ref().or(group.min_interval.as_ref()) {
+        caveats.push(format!(
+            "It has a min_interval of {}, so it is skipped when it succeeded less than {} ago (unless run with --task).",
+            interval, interval
+        ));
+    }
+    if task.network {
+        caveats.push(
+            "It needs the network and is skipped when the connectivity check fails.".to_string(),
+        );
+    }
+    if task.needs_sudo() {
+        caveats.push(
+            "It needs sudo and fails without a cached session, Keychain password or someone to enter one (or is left out with --skip-sudo)."
+                .to_string(),
+        );
+    }
+    caveats
+}
+
+fn describe_last_run(group: &TaskGroup, task: &TaskConfig, last_run: Option<&RunRecord>) -> String {
+    let Some(run) = last_run else {
+        return "No run has been recorded yet.".to_string();
+    };
+    let when = run.started_at.format("%Y-%m-%d %H:%M");
+    let Some(record) = run
+        .tasks
+        .iter()
+        .find(|record| record.group == group.name && record.name == task.name)
+    else {
+        return format!(
+            "It was not part of the last run ({}): it was filtered out, held back by a preflight check or prompt, or the run stopped before reaching it.",
+            when
+        );
+    };
+    let reason = record
+        .reason
+        .as_deref()
+        .map(|reason| format!(": {}", reason))
+        .unwrap_or_default();
+    match record.status {
+        TaskStatus::Success => format!(
+            "It succeeded in the last run ({}) after {:.0}s.",
+            when, record.duration_secs
+        ),
+        TaskStatus::Failed => format!("It failed in the last run ({}){}", when, reason),
+        TaskStatus::Skipped => format!("It was skipped in the last run ({}){}", when, reason),
+        TaskStatus::Cancelled => format!(
+            "It was cancelled in the last run ({}) because tide was interrupted.",
+            when
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::TaskRecord;
+    use chrono::Local;
+
+    #[test]
+    fn test_explain_filtered_and_failed_task() {
+        let config: Config = toml::from_str(
+            r#"
+[[groups]]
+name = "Homebrew"
+[[groups.tasks]]
+name = "Update"
+command = ["brew", "update"]
+
+[[groups]]
+name = "Extras"
+enabled = false
+[[groups.tasks]]
+name = "Tidy"
+command = ["true"]
+enabled = false
+"#,
+        )
+        .unwrap();
+        let run = RunRecord {
+            started_at: Local::now(),
+            resumed: false,
+            duration_secs: 3.0,
+            tasks: vec![TaskRecord {
+                group: "Homebrew".to_string(),
+                name: "Update".to_string(),
+                status: TaskStatus::Failed,
+                duration_secs: 3.0,
+                reason: Some("Command failed: no network".to_string()),
+                started_at: None,
+                output_hash: None,
+            }],
+        };
+
+        let skip = ["Homebrew".to_string()];
+        let explained = explain(&config, "update", None, Some(&skip), Some(&run)).unwrap();
+        assert_eq!(explained.len(), 1);
+        assert!(explained[0].blockers[0].contains("--skip-groups"));
+        assert!(explained[0].last_run.contains("failed"));
+        assert!(explained[0].last_run.contains("no network"));
+
+        let explained = explain(&config, "Tidy", None, None, Some(&run)).unwrap();
+        assert_eq!(explained[0].blockers.len(), 2);
+        assert!(explained[0].last_run.contains("not part of the last run"));
+
+        let err = explain(&config, "Updte", None, None, None).unwrap_err();
+        assert!(err.to_string().contains("Did you mean 'Update'?"));
+    }
+}
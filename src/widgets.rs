@@ -0,0 +1,434 @@
+use colored::Colorize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::process::Command;
+use tokio::task::JoinHandle;
+
+use crate::config::{Settings, WidgetSpec};
+use crate::palette::Paint;
+use crate::ui::{self, WeatherStatus};
+
+const DIVIDER_WIDTH: usize = 60;
+const COMMAND_TIMEOUT_SECS: u64 = 10;
+const MAX_COMMAND_LINES: usize = 20;
+
+/// Built-in dashboard widgets
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Widget {
+    /// Disk, power, macOS version and uptime
+    System,
+    /// Current conditions from wttr.in
+    Weather,
+    /// Today's remaining events via icalBuddy
+    Calendar,
+    /// Incomplete reminders due today or earlier
+    Reminders,
+    /// Monitor states of an Uptime Kuma status page
+    UptimeKuma,
+}
+
+impl Widget {
+    /// Names accepted in `settings.widgets`
+    pub const NAMES: &[&str] = &["system", "weather", "calendar", "reminders", "uptime-kuma"];
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "system" => Some(Widget::System),
+            "weather" => Some(Widget::Weather),
+            "calendar" => Some(Widget::Calendar),
+            "reminders" => Some(Widget::Reminders),
+            "uptime-kuma" => Some(Widget::UptimeKuma),
+            _ => None,
+        }
+    }
+
+    fn title(self) -> &'static str {
+        match self {
+            Widget::System => "📊 System Information",
+            Widget::Weather => "🌤️  Weather",
+            Widget::Calendar => "📅 Today",
+            Widget::Reminders => "☑️  Reminders",
+            Widget::UptimeKuma => "🟢 Uptime Kuma",
+        }
+    }
+}
+
+/// What a widget has to show
+#[derive(Debug, PartialEq, Eq)]
+pub enum WidgetContent {
+    Lines(Vec<String>),
+    NoData(String),
+    Error(String),
+}
+
+enum Pending {
+    /// System information, gathered when rendering so it reflects the
+    /// state after the run
+    System,
+    Fetching(JoinHandle<WidgetContent>),
+    Ready(WidgetContent),
+}
+
+/// Widgets fetching in the background while the run is in progress, shown
+/// in configuration order once it finishes
+pub struct Dashboard {
+    blocks: Vec<(String, Pending)>,
+}
+
+impl Dashboard {
+    /// Start fetching every configured widget concurrently
+    pub fn start(settings: &Settings) -> Self {
+        let blocks = settings
+            .dashboard_widgets()
+            .into_iter()
+            .map(|spec| match spec {
+                WidgetSpec::Builtin(name) => match Widget::parse(&name) {
+                    Some(Widget::System) => (Widget::System.title().to_string(), Pending::System),
+                    Some(widget) => {
+                        let kuma_url = settings.uptime_kuma_url.clone();
+                        (
+                            widget.title().to_string(),
+                            Pending::Fetching(tokio::spawn(fetch(widget, kuma_url))),
+                        )
+                    }
+                    None => (
+                        name.clone(),
+                        Pending::Ready(WidgetContent::Error(format!("unknown widget '{}'", name))),
+                    ),
+                },
+                WidgetSpec::Command {
+                    title,
+                    icon,
+                    command,
+                    timeout,
+                } => {
+                    let title = if icon.is_empty() {
+                        title
+                    } else {
+                        format!("{} {}", icon, title)
+                    };
+                    let timeout = Duration::from_secs(timeout.unwrap_or(COMMAND_TIMEOUT_SECS));
+                    (
+                        title,
+                        Pending::Fetching(tokio::spawn(command_widget(command, timeout))),
+                    )
+                }
+            })
+            .collect();
+        Self { blocks }
+    }
+
+    /// Wait for the widgets and print their blocks
+    pub async fn render(self) {
+        for (title, pending) in self.blocks {
+            let content = match pending {
+                Pending::System => WidgetContent::Lines(ui::system_info_lines()),
+                Pending::Fetching(handle) => handle
+                    .await
+                    .unwrap_or_else(|err| WidgetContent::Error(format!("runtime error: {err}"))),
+                Pending::Ready(content) => content,
+            };
+
+            println!("\n{}", title.bright_blue().bold());
+            println!("{}", "─".repeat(DIVIDER_WIDTH).dimmed());
+            match content {
+                WidgetContent::Lines(lines) => {
+                    for line in lines {
+                        println!("  {}", line);
+                    }
+                }
+                WidgetContent::NoData(message) => println!("  {}", message.dimmed()),
+                WidgetContent::Error(reason) => {
+                    println!("  {}", format!("Unavailable ({reason}).").dimmed())
+                }
+            }
+        }
+    }
+}
+
+async fn fetch(widget: Widget, kuma_url: Option<String>) -> WidgetContent {
+    match widget {
+        Widget::System => WidgetContent::Lines(ui::system_info_lines()),
+        Widget::Weather => match ui::fetch_weather().await {
+            WeatherStatus::Available(summary) => {
+                WidgetContent::Lines(vec![summary.bright_white().to_string()])
+            }
+            WeatherStatus::NoData(message) => WidgetContent::NoData(message.to_string()),
+            WeatherStatus::Error(reason) => WidgetContent::Error(reason),
+        },
+        Widget::Calendar => calendar().await,
+        Widget::Reminders => reminders().await,
+        Widget::UptimeKuma => match kuma_url {
+            Some(url) => uptime_kuma(&url).await,
+            None => WidgetContent::NoData(
+                "Set uptime_kuma_url to the URL of a status page.".to_string(),
+            ),
+        },
+    }
+}
+
+/// Run a program and return its stdout, failing on errors and timeouts
+async fn run(program: &str, args: &[String], timeout: Duration) -> Result<String, String> {
+    let child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .kill_on_drop(true)
+        .output();
+    match tokio::time::timeout(timeout, child).await {
+        Err(_) => Err(format!(
+            "{} timed out after {}s",
+            program,
+            timeout.as_secs()
+        )),
+        Ok(Err(err)) => Err(format!("failed to run {}: {}", program, err)),
+        Ok(Ok(output)) if !output.status.success() => {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            Err(stderr
+                .lines()
+                .next()
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("{} exited with {}", program, output.status)))
+        }
+        Ok(Ok(output)) => Ok(String::from_utf8_lossy(&output.stdout).into_owned()),
+    }
+}
+
+fn non_empty_lines(text: &str) -> Vec<String> {
+    text.lines()
+        .map(str::trim_end)
+        .filter(|line| !line.trim().is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+async fn command_widget(command: Vec<String>, timeout: Duration) -> WidgetContent {
+    let Some((program, args)) = command.split_first() else {
+        return WidgetContent::Error("empty command".to_string());
+    };
+    match run(program, args, timeout).await {
+        Ok(stdout) => {
+            let lines: Vec<String> = non_empty_lines(&stdout)
+                .into_iter()
+                .take(MAX_COMMAND_LINES)
+                .collect();
+            if lines.is_empty() {
+                WidgetContent::NoData("No output.".to_string())
+            } else {
+                WidgetContent::Lines(lines)
+            }
+        }
+        Err(reason) => WidgetContent::Error(reason),
+    }
+}
+
+async fn calendar() -> WidgetContent {
+    if which::which("icalBuddy").is_err() {
+        return WidgetContent::NoData(
+            "Install icalBuddy (`brew install ical-buddy`) to show today's events.".to_string(),
+        );
+    }
+    let args: Vec<String> = [
+        "-n",
+        "-nc",
+        "-b",
+        "",
+        "-iep",
+        "datetime,title",
+        "-ps",
+        "| |",
+        "eventsToday",
+    ]
+    .iter()
+    .map(|arg| arg.to_string())
+    .collect();
+    match run(
+        "icalBuddy",
+        &args,
+        Duration::from_secs(COMMAND_TIMEOUT_SECS),
+    )
+    .await
+    {
+        Ok(stdout) => {
+            let events = non_empty_lines(&stdout);
+            if events.is_empty() {
+                WidgetContent::NoData("No more events today.".to_string())
+            } else {
+                WidgetContent::Lines(events)
+            }
+        }
+        Err(reason) => WidgetContent::Error(reason),
+    }
+}
+
+const REMINDERS_SCRIPT: &str = r#"tell application "Reminders"
+    set due to name of (reminders whose completed is false and due date < ((current date) + 1 * days))
+end tell
+set AppleScript's text item delimiters to linefeed
+return due as text"#;
+
+async fn reminders() -> WidgetContent {
+    let args = ["-e".to_string(), REMINDERS_SCRIPT.to_string()];
+    match run(
+        "osascript",
+        &args,
+        Duration::from_secs(COMMAND_TIMEOUT_SECS),
+    )
+    .await
+    {
+        Ok(stdout) => {
+            let due = non_empty_lines(&stdout);
+            if due.is_empty() {
+                WidgetContent::NoData("Nothing due today.".to_string())
+            } else {
+                WidgetContent::Lines(due.into_iter().map(|name| format!("☐ {}", name)).collect())
+            }
+        }
+        Err(reason) => WidgetContent::Error(reason),
+    }
+}
+
+/// The API endpoints behind a status page URL like
+/// `https://status.example.com/status/home`: page config and heartbeats
+fn uptime_kuma_endpoints(page_url: &str) -> Option<(String, String)> {
+    let (base, slug) = page_url
+        .trim()
+        .trim_end_matches('/')
+        .rsplit_once("/status/")?;
+    (!slug.is_empty() && !slug.contains('/')).then(|| {
+        (
+            format!("{}/api/status-page/{}", base, slug),
+            format!("{}/api/status-page/heartbeat/{}", base, slug),
+        )
+    })
+}
+
+/// Count monitors by their latest heartbeat, listing the ones that are down
+fn summarize_heartbeats(heartbeats: &Value, names: &HashMap<String, String>) -> WidgetContent {
+    let Some(monitors) = heartbeats.get("heartbeatList").and_then(Value::as_object) else {
+        return WidgetContent::Error("unexpected response".to_string());
+    };
+    let (mut up, mut pending) = (0, 0);
+    let mut down = Vec::new();
+    for (id, beats) in monitors {
+        let status = beats
+            .as_array()
+            .and_then(|beats| beats.last())
+            .and_then(|beat| beat.get("status"))
+            .and_then(Value::as_u64);
+        match status {
+            Some(1) | Some(3) => up += 1,
+            Some(0) => down.push(names.get(id).cloned().unwrap_or_else(|| format!("#{}", id))),
+            _ => pending += 1,
+        }
+    }
+    if monitors.is_empty() {
+        return WidgetContent::NoData("The status page has no monitors.".to_string());
+    }
+
+    let mut summary = format!("{} up", up).success().to_string();
+    if !down.is_empty() {
+        summary.push_str(&format!(", {}", format!("{} down", down.len()).failure()));
+    }
+    if pending > 0 {
+        summary.push_str(&format!(", {}", format!("{} pending", pending).warning()));
+    }
+    let mut lines = vec![summary];
+    down.sort();
+    lines.extend(down.into_iter().map(|name| format!("✗ {}", name)));
+    WidgetContent::Lines(lines)
+}
+
+async fn uptime_kuma(page_url: &str) -> WidgetContent {
+    let Some((page_api, heartbeat_api)) = uptime_kuma_endpoints(page_url) else {
+        return WidgetContent::Error(format!(
+            "'{}' is not a status page URL (…/status/<slug>)",
+            page_url
+        ));
+    };
+    let client = match reqwest::Client::builder()
+        .timeout(Duration::from_secs(5))
+        .user_agent(format!("tide-cli/{}", env!("CARGO_PKG_VERSION")))
+        .build()
+    {
+        Ok(client) => client,
+        Err(err) => return WidgetContent::Error(format!("HTTP client failed: {err}")),
+    };
+
+    let get = |url: String| {
+        let client = client.clone();
+        async move {
+            client
+                .get(url)
+                .send()
+                .await
+                .and_then(|response| response.error_for_status())
+                .map_err(|err| err.to_string())?
+                .text()
+                .await
+                .map_err(|err| err.to_string())
+                .and_then(|body| {
+                    serde_json::from_str::<Value>(&body).map_err(|err| err.to_string())
+                })
+        }
+    };
+    let (page, heartbeats) = tokio::join!(get(page_api), get(heartbeat_api));
+    let heartbeats = match heartbeats {
+        Ok(heartbeats) => heartbeats,
+        Err(reason) => return WidgetContent::Error(reason),
+    };
+
+    // Names are a nicety; without them down monitors are listed by id
+    let names = page
+        .ok()
+        .and_then(|page| page.get("publicGroupList").cloned())
+        .and_then(|groups| groups.as_array().cloned())
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|group| group.get("monitorList").and_then(Value::as_array))
+        .flatten()
+        .filter_map(|monitor| {
+            Some((
+                monitor.get("id")?.as_u64()?.to_string(),
+                monitor.get("name")?.as_str()?.to_string(),
+            ))
+        })
+        .collect();
+    summarize_heartbeats(&heartbeats, &names)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uptime_kuma_summary() {
+        assert_eq!(Widget::parse("Uptime-Kuma"), Some(Widget::UptimeKuma));
+        assert_eq!(Widget::parse("news"), None);
+        assert_eq!(
+            uptime_kuma_endpoints("https://status.example.com/status/home/"),
+            Some((
+                "https://status.example.com/api/status-page/home".to_string(),
+                "https://status.example.com/api/status-page/heartbeat/home".to_string()
+            ))
+        );
+        assert_eq!(uptime_kuma_endpoints("https://status.example.com"), None);
+
+        let heartbeats = serde_json::json!({
+            "heartbeatList": {
+                "1": [{ "status": 0 }, { "status": 1 }],
+                "2": [{ "status": 1 }, { "status": 0 }],
+                "3": [],
+            }
+        });
+        let names = HashMap::from([("2".to_string(), "NAS".to_string())]);
+        let WidgetContent::Lines(lines) = summarize_heartbeats(&heartbeats, &names) else {
+            panic!("expected a summary");
+        };
+        for count in ["1 up", "1 down", "1 pending"] {
+            assert!(lines[0].contains(count));
+        }
+        assert_eq!(lines[1..], ["✗ NAS"]);
+    }
+}
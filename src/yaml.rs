@@ -0,0 +1,252 @@
+use anyhow::{Result, bail};
+use serde_yaml_ng::Value as Yaml;
+use toml::{Table, Value};
+
+/// Parse a YAML document into a TOML table.
+///
+/// Anchors, aliases and `<<` merge keys are resolved; tags are ignored.
+/// `null` values are left out since TOML has no null.
+pub fn parse(text: &str) -> Result<Table> {
+    let mut document: Yaml = serde_yaml_ng::from_str(text)?;
+    document.apply_merge()?;
+    match document {
+        Yaml::Null => Ok(Table::new()),
+        Yaml::Mapping(_) => match into_toml(document)? {
+            Some(Value::Table(table)) => Ok(table),
+            _ => unreachable!("a mapping converts to a table"),
+        },
+        _ => bail!("the document must be a mapping of settings, groups and tasks"),
+    }
+}
+
+/// Render a TOML table as a YAML document
+pub fn to_string(table: &Table) -> String {
+    let mut out = String::new();
+    write_table(table, 0, &mut out);
+    out
+}
+
+fn into_toml(value: Yaml) -> Result<Option<Value>> {
+    Ok(Some(match value {
+        Yaml::Null => return Ok(None),
+        Yaml::Bool(flag) => Value::Boolean(flag),
+        Yaml::Number(number) => match number.as_i64() {
+            Some(integer) => Value::Integer(integer),
+            None => Value::Float(number.as_f64().unwrap_or(f64::NAN)),
+        },
+        Yaml::String(text) => Value::String(text),
+        Yaml::Sequence(items) => {
+            let mut array = Vec::with_capacity(items.len());
+            for item in items {
+                array.extend(into_toml(item)?);
+            }
+            Value::Array(array)
+        }
+        Yaml::Mapping(entries) => {
+            let mut table = Table::new();
+            for (key, value) in entries {
+                let key = match key {
+                    Yaml::String(key) => key,
+                    Yaml::Bool(flag) => flag.to_string(),
+                    Yaml::Number(number) => number.to_string(),
+                    key => bail!("unsupported mapping key {:?}", key),
+                };
+                if let Some(value) = into_toml(value)? {
+                    table.insert(key, value);
+                }
+            }
+            Value::Table(table)
+        }
+        Yaml::Tagged(tagged) => return into_toml(tagged.value),
+    }))
+}
+
+/// Whether `text` written as a plain scalar reads back as that string
+fn reads_as_string(text: &str) -> bool {
+    matches!(serde_yaml_ng::from_str(text), Ok(Yaml::String(parsed)) if parsed == text)
+}
+
+/// Names lead their group or task and nested lists close it, so a group
+/// reads top-down like its TOML counterpart
+fn key_rank(key: &str) -> u8 {
+    match key {
+        "name" | "settings" => 0,
+        "groups" | "tasks" | "tests" => 2,
+        _ => 1,
+    }
+}
+
+fn write_table(table: &Table, indent: usize, out: &mut String) {
+    let mut entries: Vec<(&String, &Value)> = table.iter().collect();
+    entries.sort_by_key(|(key, _)| key_rank(key));
+    for (key, value) in entries {
+        out.push_str(&" ".repeat(indent));
+        out.push_str(&render_key(key));
+        out.push(':');
+        match value {
+            Value::Table(table) if !table.is_empty() => {
+                out.push('\n');
+                write_table(table, indent + 2, out);
+            }
+            Value::Array(items) if !is_flow(items) => {
+                out.push('\n');
+                write_sequence(items, indent, out);
+            }
+            value => {
+                out.push(' ');
+                out.push_str(&render_flow(value));
+                out.push('\n');
+            }
+        }
+    }
+}
+
+fn write_sequence(items: &[Value], indent: usize, out: &mut String) {
+    for item in items {
+        match item {
+            Value::Table(table) if !table.is_empty() => {
+                let mut nested = String::new();
+                write_table(table, indent + 2, &mut nested);
+                out.push_str(&" ".repeat(indent));
+                out.push_str("- ");
+                out.push_str(&nested[indent + 2..]);
+            }
+            Value::Array(items) if !is_flow(items) => {
+                out.push_str(&" ".repeat(indent));
+                out.push_str("-\n");
+                write_sequence(items, indent + 2, out);
+            }
+            value => {
+                out.push_str(&" ".repeat(indent));
+                out.push_str("- ");
+                out.push_str(&render_flow(value));
+                out.push('\n');
+            }
+        }
+    }
+}
+
+/// Arrays of scalars are written inline as `[a, b]`
+fn is_flow(items: &[Value]) -> bool {
+    items
+        .iter()
+        .all(|item| !matches!(item, Value::Table(_) | Value::Array(_)))
+}
+
+fn render_flow(value: &Value) -> String {
+    match value {
+        Value::String(text) => render_string(text),
+        Value::Integer(number) => number.to_string(),
+        Value::Float(number) if number.is_nan() => ".nan".to_string(),
+        Value::Float(number) if number.is_infinite() => {
+            if *number > 0.0 { ".inf" } else { "-.inf" }.to_string()
+        }
+        Value::Float(number) => format!("{:?}", number),
+        Value::Boolean(flag) => flag.to_string(),
+        Value::Datetime(datetime) => render_string(&datetime.to_string()),
+        Value::Array(items) => format!(
+            "[{}]",
+            items.iter().map(render_flow).collect::<Vec<_>>().join(", ")
+        ),
+        Value::Table(table) => format!(
+            "{{{}}}",
+            table
+                .iter()
+                .map(|(key, value)| format!("{}: {}", render_key(key), render_flow(value)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    }
+}
+
+fn render_key(key: &str) -> String {
+    if !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        && reads_as_string(key)
+    {
+        key.to_string()
+    } else {
+        render_quoted(key)
+    }
+}
+
+/// Plain when it reads back as the same string, double-quoted otherwise
+fn render_string(text: &str) -> String {
+    let safe = text.starts_with(|c: char| c.is_ascii_alphanumeric() || c == '/' || c == '.')
+        && !text.ends_with(' ')
+        && text
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || " _./~+=-".contains(c))
+        && reads_as_string(text);
+    if safe {
+        text.to_string()
+    } else {
+        render_quoted(text)
+    }
+}
+
+fn render_quoted(text: &str) -> String {
+    // JSON strings are valid double-quoted YAML scalars
+    serde_json::to_string(text).expect("strings serialize")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn test_parse_yaml_config() {
+        let yaml = r#"
+# Shared by every brew task
+x-brew: &brew
+  check_command: brew
+  timeout: 600
+
+settings:
+  parallel_limit: 2
+  desktop_notifications: false   # quiet machine
+
+groups:
+- name: Homebrew
+  icon: "🍺"
+  tasks:
+    - name: Update Formulae
+      <<: *brew
+      command: [brew, update]
+    - name: Upgrade Packages
+      <<: *brew
+      timeout: 1800
+      command:
+        - brew
+        - upgrade
+    - name: Report
+      command: ["sh", "-c", 'echo "done: #1"']
+      description: >-
+        Summarize what
+        was upgraded
+      env: {HOMEBREW_NO_ANALYTICS: "1"}
+"#;
+        let table = parse(yaml).unwrap();
+        let config: Config = Value::Table(table.clone()).try_into().unwrap();
+        assert_eq!(config.settings.parallel_limit, 2);
+        assert!(!config.settings.desktop_notifications);
+        let tasks = &config.groups[0].tasks;
+        assert_eq!(config.groups[0].icon, "🍺");
+        assert_eq!(tasks[0].command, ["brew", "update"]);
+        assert_eq!(tasks[0].check_command.as_deref(), Some("brew"));
+        assert_eq!(tasks[0].timeout, Some(600));
+        assert_eq!(tasks[1].timeout, Some(1800));
+        assert_eq!(tasks[1].command, ["brew", "upgrade"]);
+        assert_eq!(tasks[2].command, ["sh", "-c", "echo \"done: #1\""]);
+        assert_eq!(tasks[2].description, "Summarize what was upgraded");
+
+        assert_eq!(parse(&to_string(&table)).unwrap(), table);
+        assert!(parse("groups:\n  - name: a\n   bad: 1\n").is_err());
+        assert!(parse("settings: *missing\n").is_err());
+        // YAML forbids tabs for indentation
+        assert!(parse("settings:\n\tverbose: true\n").is_err());
+    }
+}